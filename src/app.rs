@@ -1,55 +1,129 @@
-use crate::utils::Config;
-use rand::Rng;
-use std::collections::VecDeque;
-use std::time::{Duration, Instant};
+use ttypr::checkpoint::{load_checkpoint, remove_checkpoint, save_checkpoint, TestCheckpoint};
+use ttypr::clock::{Clock, MockClock, SystemClock};
+use ttypr::content::ContentInfo;
+use ttypr::csv::CsvDrill;
+use ttypr::dictation::Dictation;
+use ttypr::distraction::{DistractionReport, Distractions, DISTRACTOR_WORDS};
+use ttypr::engine::{hyphenate, random_chars_line, random_words_line, weighted_chars_line, with_capitals, CharState, TypedLine, TypingEngine};
+use ttypr::stats::{recent_wpm_trend, LifetimeTotals, PracticeBlock, Session, Stats};
+use ttypr::update::{is_newer, CURRENT_VERSION};
+use ttypr::utils::{Config, PlaylistSegment};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::ui::SpanCache;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Calculates and stores words per minute (WPM) data.
 ///
 /// This struct tracks the user's key presses, the time elapsed, and calculates
-/// the typing speed. The WPM is calculated after a pause in typing.
+/// the typing speed. The WPM is calculated after a pause in typing - or, with a
+/// rolling window (see `set_window`), every `update_interval` while typing, over
+/// the key presses of the last `window`.
 pub struct Wpm {
     pub timer: Option<Instant>,
     pub time_since_last_key_pressed: Option<Instant>,
     pub key_presses: usize,
     pub wpm: usize,
+    pub window: Duration, // (Rolling window) - The time the WPM is calculated over, zero - every burst of typing
+    pub update_interval: Duration, // (Rolling window) - How often the WPM is recalculated
+    key_press_times: VecDeque<Instant>, // (Rolling window) - The key presses within the window
+    updated_at: Option<Instant>, // (Rolling window) - When the WPM was last calculated
+    clock: Rc<dyn Clock>,
 }
 
 impl Wpm {
-    /// Creates a new `Wpm` instance with default values.
-    pub fn new() -> Wpm {
+    /// Creates a new `Wpm` instance with default values, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Wpm {
         Wpm {
             timer: None,
             time_since_last_key_pressed: None,
             key_presses: 0,
             wpm: 0,
+            window: Duration::ZERO,
+            update_interval: Duration::ZERO,
+            key_press_times: VecDeque::new(),
+            updated_at: None,
+            clock,
         }
     }
 
+    /// Calculates the WPM over a rolling window of the given size, every `update_interval`
+    /// while typing. A zero window calculates it over every burst of typing, after a pause.
+    pub fn set_window(&mut self, window: Duration, update_interval: Duration) {
+        self.window = window;
+        self.update_interval = update_interval;
+        self.reset();
+    }
+
+    /// Resets the timers and counters for the next measurement.
+    fn reset(&mut self) {
+        self.timer = None;
+        self.time_since_last_key_pressed = None;
+        self.key_presses = 0;
+        self.key_press_times.clear();
+        self.updated_at = None;
+    }
+
+    /// When the WPM is calculated next (`None` if nothing is being typed).
+    pub fn next_update(&self) -> Option<Instant> {
+        let paused_at = self.time_since_last_key_pressed? + WPM_PAUSE;
+        if self.window.is_zero() {
+            return Some(paused_at);
+        }
+        let updated_at = self.updated_at.or(self.timer)?;
+        Some(paused_at.min(updated_at + self.update_interval))
+    }
+
+    /// Whether key presses are being measured - typing started, and hasn't paused since.
+    pub fn is_measuring(&self) -> bool {
+        self.timer.is_some()
+    }
+
+    /// Forgets the WPM and the key presses measured (for the next session).
+    pub fn clear(&mut self) {
+        self.wpm = 0;
+        self.reset();
+    }
+
     /// Handles the logic for each key press.
     ///
     /// This function starts the main timer on the first key press, resets the
     /// pause timer, and increments the key press count.
     pub fn on_key_press(&mut self) {
-        if let None = self.timer {
-            self.timer = Some(Instant::now());
+        let now = self.clock.now();
+        if self.timer.is_none() {
+            self.timer = Some(now);
         }
-        self.time_since_last_key_pressed = Some(Instant::now());
+        self.time_since_last_key_pressed = Some(now);
         self.key_presses += 1;
+        if !self.window.is_zero() {
+            self.key_press_times.push_back(now);
+        }
     }
 
     /// Handles the logic for each application tick.
     ///
     /// This function checks if the user has paused typing (3 seconds). If so,
-    /// it calculates the WPM based on the key presses and elapsed time.
+    /// it calculates the WPM based on the key presses and elapsed time
+    /// (with a rolling window, see `on_tick_rolling`).
     ///
     /// Returns `true` if the WPM was calculated, indicating the UI needs
     /// to be updated.
     pub fn on_tick(&mut self) -> bool {
+        if !self.window.is_zero() {
+            return self.on_tick_rolling();
+        }
         if let Some(time_since_last_key_pressed) = self.time_since_last_key_pressed {
+            let now = self.clock.now();
             // If the user has paused for more than 3 seconds, calculate WPM
-            if time_since_last_key_pressed.elapsed() > Duration::from_secs(3) {
+            if now.duration_since(time_since_last_key_pressed) > WPM_PAUSE {
                 // Get the net typing time, excluding the 3-second pause
-                let time = self.timer.unwrap().elapsed().as_secs_f64() - 3.0;
+                let time = now.duration_since(self.timer.unwrap()).as_secs_f64() - 3.0;
                 
                 // If the net time is non-positive or too few keys were pressed, just reset
                 if time <= 0.0 || self.key_presses < 10 {
@@ -73,6 +147,427 @@ impl Wpm {
         }
         false
     }
+
+    /// (Rolling window) - Calculates the WPM over the key presses of the last `window`,
+    /// once every `update_interval` while typing. Stops once the user pauses typing.
+    fn on_tick_rolling(&mut self) -> bool {
+        let (Some(timer), Some(last_key_press)) = (self.timer, self.time_since_last_key_pressed) else {
+            return false;
+        };
+        let now = self.clock.now();
+        if now.duration_since(last_key_press) > WPM_PAUSE {
+            self.reset();
+            return false;
+        }
+        if now.duration_since(self.updated_at.unwrap_or(timer)) < self.update_interval {
+            return false;
+        }
+
+        while self.key_press_times.front().is_some_and(|pressed_at| now.duration_since(*pressed_at) > self.window) {
+            self.key_press_times.pop_front();
+        }
+        // The window is only partly filled when typing starts
+        let time = now.duration_since(timer).min(self.window).as_secs_f64();
+        if time <= 0.0 || self.key_press_times.len() < 10 {
+            return false;
+        }
+        self.wpm = ((self.key_press_times.len() as f64 / 5.0) / (time / 60.0)) as usize;
+        self.updated_at = Some(now);
+        true
+    }
+}
+
+/// Keeps count of the consecutive correctly typed characters (the combo).
+///
+/// The combo resets on an error, and reaching a milestone is
+/// highlighted for a moment.
+pub struct Combo {
+    pub current: usize,
+    pub best: usize, // The best combo of the session
+    pub milestone_reached_at: Option<Instant>,
+    clock: Rc<dyn Clock>,
+}
+
+impl Combo {
+    /// Creates a new `Combo` with nothing typed, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Combo {
+        Combo {
+            current: 0,
+            best: 0,
+            milestone_reached_at: None,
+            clock,
+        }
+    }
+
+    /// Extends the combo with a correctly typed character.
+    pub fn on_correct(&mut self) {
+        self.current += 1;
+        self.best = self.best.max(self.current);
+        if COMBO_MILESTONES.contains(&self.current) {
+            self.milestone_reached_at = Some(self.clock.now());
+        }
+    }
+
+    /// Breaks the combo.
+    pub fn on_error(&mut self) {
+        self.current = 0;
+        self.milestone_reached_at = None;
+    }
+
+    /// Whether a milestone was just reached (and is still highlighted).
+    pub fn at_milestone(&self) -> bool {
+        self.milestone_reached_at.is_some()
+    }
+
+    /// Stops highlighting the milestone after a moment.
+    ///
+    /// Returns `true` if the highlight ended, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        if let Some(reached_at) = self.milestone_reached_at
+            && self.clock.now().duration_since(reached_at) > COMBO_MILESTONE_DURATION
+        {
+            self.milestone_reached_at = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// (Forced pause) - Detects bursts of errors (`ERROR_BURST_LEN` of them within
+/// `ERROR_BURST_WINDOW`), and locks the input for a moment after one.
+pub struct SlowDown {
+    pub error_times: VecDeque<Instant>, // When the latest errors were typed
+    pub locked_until: Option<Instant>,
+    clock: Rc<dyn Clock>,
+}
+
+impl SlowDown {
+    /// Creates a new `SlowDown` with no errors, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> SlowDown {
+        SlowDown {
+            error_times: VecDeque::new(),
+            locked_until: None,
+            clock,
+        }
+    }
+
+    /// Records an error, and locks the input for `pause` if it completes a burst.
+    ///
+    /// Returns `true` if the input was locked.
+    pub fn on_error(&mut self, pause: Duration) -> bool {
+        let now = self.clock.now();
+        self.error_times.push_back(now);
+        while self.error_times.front().is_some_and(|typed_at| now.duration_since(*typed_at) > ERROR_BURST_WINDOW) {
+            self.error_times.pop_front();
+        }
+        if self.error_times.len() < ERROR_BURST_LEN {
+            return false;
+        }
+        self.error_times.clear();
+        self.locked_until = Some(now + pause);
+        true
+    }
+
+    /// Whether the input is locked.
+    pub fn is_locked(&self) -> bool {
+        self.locked_until.is_some_and(|until| self.clock.now() < until)
+    }
+
+    /// Unlocks the input once the pause is over.
+    ///
+    /// Returns `true` if it was unlocked, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        if self.locked_until.is_some() && !self.is_locked() {
+            self.locked_until = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// (Speed governor) - Holds the typing to `max_wpm` - a key pressed sooner after the last
+/// typed one than that pace allows is ignored, with a cue displayed for a moment.
+pub struct SpeedGovernor {
+    pub last_typed: Option<Instant>,
+    pub ignored_at: Option<Instant>, // When the last key too fast was ignored
+    clock: Rc<dyn Clock>,
+}
+
+impl SpeedGovernor {
+    /// Creates a governor with no key typed yet, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> SpeedGovernor {
+        SpeedGovernor {
+            last_typed: None,
+            ignored_at: None,
+            clock,
+        }
+    }
+
+    /// Whether a key can be typed at the pace of `max_wpm` (5 characters a word, 0 - any pace).
+    /// Records it as typed if it can, and as ignored if it can't.
+    pub fn allows(&mut self, max_wpm: u32) -> bool {
+        if max_wpm == 0 {
+            return true;
+        }
+        let now = self.clock.now();
+        let interval = Duration::from_secs_f64(12.0 / max_wpm as f64);
+        if self.last_typed.is_some_and(|typed_at| now.duration_since(typed_at) < interval) {
+            self.ignored_at = Some(now);
+            return false;
+        }
+        self.last_typed = Some(now);
+        true
+    }
+
+    /// Whether the cue of an ignored key is displayed.
+    pub fn shows_cue(&self) -> bool {
+        self.ignored_at.is_some_and(|ignored_at| self.clock.now().duration_since(ignored_at) < GOVERNOR_CUE_DURATION)
+    }
+
+    /// Hides the cue after a moment.
+    ///
+    /// Returns `true` if it was hidden, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        if self.ignored_at.is_some() && !self.shows_cue() {
+            self.ignored_at = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// (Pace caret) - A second caret moving through the lines at a steady speed,
+/// from where the first key of the session was pressed, to race against.
+pub struct PaceCaret {
+    pub started_at: Option<Instant>,
+    pub start: isize, // The position it started from (negative once its line is dropped)
+    pub wpm: f64,
+    pub drawn_at: Option<usize>, // The position it was last drawn at
+    clock: Rc<dyn Clock>,
+}
+
+impl PaceCaret {
+    /// Creates a new `PaceCaret` that isn't moving, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> PaceCaret {
+        PaceCaret {
+            started_at: None,
+            start: 0,
+            wpm: 0.0,
+            drawn_at: None,
+            clock,
+        }
+    }
+
+    /// Starts moving from the given position of the charset, at `wpm`.
+    pub fn start(&mut self, position: usize, wpm: f64) {
+        self.started_at = Some(self.clock.now());
+        self.start = position as isize;
+        self.wpm = wpm;
+    }
+
+    /// Stops (and hides) the caret.
+    pub fn stop(&mut self) {
+        self.started_at = None;
+        self.drawn_at = None;
+    }
+
+    /// The position of the charset the caret is at (`None` if it isn't moving).
+    pub fn position(&self) -> Option<usize> {
+        let elapsed = self.clock.now().duration_since(self.started_at?).as_secs_f64();
+        // A "word" is 5 characters, as for the WPM
+        let chars = (elapsed * self.wpm * 5.0 / 60.0) as isize;
+        usize::try_from(self.start + chars).ok()
+    }
+
+    /// Keeps the caret in place when the first line is dropped.
+    pub fn on_line_dropped(&mut self, line_len: usize) {
+        self.start -= line_len as isize;
+    }
+
+    /// Returns `true` if the caret moved since it was last drawn, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        let position = self.position();
+        if position == self.drawn_at {
+            return false;
+        }
+        self.drawn_at = position;
+        true
+    }
+}
+
+/// (Metronome) - A silent metronome - a dot in a corner of the typing screen, lit for a moment
+/// at every beat of `metronome_bpm`, to type in rhythm with (a beat for every key).
+pub struct Metronome {
+    pub started_at: Instant, // The beats are counted from here
+    pub drawn_lit: bool, // Whether the dot was lit when it was last drawn
+    clock: Rc<dyn Clock>,
+}
+
+impl Metronome {
+    /// Creates a new `Metronome`, beating from now, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Metronome {
+        Metronome { started_at: clock.now(), drawn_lit: false, clock }
+    }
+
+    /// The time from one beat to the next, and how long the dot is lit for at every beat
+    /// (`None` - no beats).
+    fn beat(bpm: u32) -> Option<(Duration, Duration)> {
+        let interval = Duration::from_secs(60).checked_div(bpm)?;
+        Some((interval, (interval / 2).min(METRONOME_FLASH)))
+    }
+
+    /// Whether the dot is lit - for a moment at every beat.
+    pub fn is_lit(&self, bpm: u32) -> bool {
+        let Some((interval, flash)) = Self::beat(bpm) else {
+            return false;
+        };
+        let elapsed = self.clock.now().duration_since(self.started_at);
+        elapsed.as_nanos() % interval.as_nanos() < flash.as_nanos()
+    }
+
+    /// When the dot is lit or dimmed next (`None` - no beats).
+    pub fn next_change(&self, bpm: u32) -> Option<Instant> {
+        let (interval, flash) = Self::beat(bpm)?;
+        let elapsed = self.clock.now().duration_since(self.started_at);
+        let beat_at = self.started_at + interval * (elapsed.as_nanos() / interval.as_nanos()) as u32;
+        Some(if self.is_lit(bpm) { beat_at + flash } else { beat_at + interval })
+    }
+
+    /// Returns `true` if the dot was lit or dimmed since it was last drawn, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self, bpm: u32) -> bool {
+        let lit = self.is_lit(bpm);
+        if lit == self.drawn_lit {
+            return false;
+        }
+        self.drawn_lit = lit;
+        true
+    }
+}
+
+/// (Practice schedule) - The phases of the schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulePhase {
+    Practice,
+    Rest,
+}
+
+/// (Practice schedule) - A Pomodoro-style schedule of practice blocks and rests
+/// (`practice_minutes` and `rest_minutes` in the config).
+///
+/// A practice block starts with the first key press after the previous rest (or the
+/// first one), and is followed by a rest once its time is up.
+pub struct PracticeSchedule {
+    pub started_at: Option<i64>, // When the first block started (seconds since the Unix epoch), identifies the schedule
+    pub block: u32, // The number of the current (or the last) practice block
+    pub phase: Option<(SchedulePhase, Instant)>, // The phase in progress and when it started (None - the next block is yet to start)
+    pub drawn_remaining: u64, // The remaining seconds of the phase last drawn
+    clock: Rc<dyn Clock>,
+}
+
+impl PracticeSchedule {
+    /// Creates a schedule with no block started, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> PracticeSchedule {
+        PracticeSchedule {
+            started_at: None,
+            block: 0,
+            phase: None,
+            drawn_remaining: 0,
+            clock,
+        }
+    }
+
+    /// Starts the next practice block.
+    pub fn start_block(&mut self) {
+        self.started_at.get_or_insert_with(|| chrono::Local::now().timestamp());
+        self.block += 1;
+        self.phase = Some((SchedulePhase::Practice, self.clock.now()));
+    }
+
+    /// The practice block in progress, as recorded with the sessions.
+    pub fn current_block(&self) -> Option<PracticeBlock> {
+        match self.phase {
+            Some((SchedulePhase::Practice, _)) => Some(PracticeBlock { schedule: self.started_at?, block: self.block }),
+            _ => None,
+        }
+    }
+
+    /// The phase in progress and its remaining time, given the lengths of the phases.
+    pub fn remaining(&self, practice: Duration, rest: Duration) -> Option<(SchedulePhase, Duration)> {
+        let (phase, started_at) = self.phase?;
+        let length = if phase == SchedulePhase::Practice { practice } else { rest };
+        Some((phase, length.saturating_sub(self.clock.now().duration_since(started_at))))
+    }
+
+    /// Moves on once the time of the phase is up - from a practice block to a rest, and from a rest
+    /// to waiting for the next block. Returns the phase that ended, if one did.
+    pub fn on_tick(&mut self, practice: Duration, rest: Duration) -> Option<SchedulePhase> {
+        let (phase, remaining) = self.remaining(practice, rest)?;
+        if !remaining.is_zero() {
+            return None;
+        }
+        self.phase = match phase {
+            SchedulePhase::Practice => Some((SchedulePhase::Rest, self.clock.now())),
+            SchedulePhase::Rest => None,
+        };
+        Some(phase)
+    }
+}
+
+/// (Line summary) - The speed and the errors of the line just typed, displayed next to it for a moment.
+pub struct LineSummary {
+    pub wpm: f64,
+    pub errors: usize,
+    pub shown_at: Instant,
+}
+
+/// (Accessibility) - Filters the typed characters, for users with tremor or sticky/slow keys
+/// and for keyboards with chatter - see `min_hold_ms` and `bounce_ms` in the config.
+pub struct KeyFilter {
+    pub last_typed: Option<(char, Instant)>, // The last character that was typed, for the bounce window
+    pub held: HashMap<char, Instant>, // The characters being held (with rollover, more than one), typed once they're released
+    clock: Rc<dyn Clock>,
+}
+
+impl KeyFilter {
+    /// Creates a new `KeyFilter` with nothing typed, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> KeyFilter {
+        KeyFilter {
+            last_typed: None,
+            held: HashMap::new(),
+            clock,
+        }
+    }
+
+    /// Whether a typed character is a bounce - the same character as the last one,
+    /// typed again within the window. Other characters are remembered as the last one.
+    pub fn is_bounce(&mut self, c: char, window: Duration) -> bool {
+        let now = self.clock.now();
+        let bounce = self.last_typed.is_some_and(|(last, typed_at)| last == c && now.duration_since(typed_at) < window);
+        if !bounce {
+            self.last_typed = Some((c, now));
+        }
+        bounce
+    }
+
+    /// Records a pressed character, to be typed once it's released. The repeats of a held
+    /// character keep the time it was first pressed at.
+    pub fn on_press(&mut self, c: char) {
+        self.held.entry(c).or_insert(self.clock.now());
+    }
+
+    /// Records a released character. Returns it if it was held for at least `min_hold`.
+    pub fn on_release(&mut self, c: char, min_hold: Duration) -> Option<char> {
+        let pressed_at = self.held.remove(&c)?;
+        (self.clock.now().duration_since(pressed_at) >= min_hold).then_some(c)
+    }
+}
+
+/// How loading the clipboard contents went - shown in the clipboard notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardLoad {
+    Loaded,
+    Empty, // Nothing to type in it
+    Failed, // It couldn't be read (no clipboard tool)
 }
 
 /// Manages the state and display timer for transient notifications in the UI.
@@ -84,12 +579,23 @@ pub struct Notifications {
     pub clear_mistyped: bool,
     pub wpm: bool,
     pub display_wpm: bool,
+    pub clipboard: Option<ClipboardLoad>,
+    pub goal: bool,
+    pub timed_test: bool,
+    pub time_up: bool,
+    pub banned: bool,
+    pub favorite: bool,
+    pub level_up: bool,
+    pub keyboard: bool,
+    pub repeat: bool,
     pub time_count: Option<Instant>,
+    pub muted: bool, // (Deterministic mode) - No notification is shown
+    clock: Rc<dyn Clock>,
 }
 
 impl Notifications {
-    /// Creates a new `Notifications` instance with all flags turned off.
-    pub fn new() -> Notifications {
+    /// Creates a new `Notifications` instance with all flags turned off, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Notifications {
         Notifications {
             mode: false,
             option: false,
@@ -98,18 +604,29 @@ impl Notifications {
             clear_mistyped: false,
             wpm: false,
             display_wpm: false,
+            clipboard: None,
+            goal: false,
+            timed_test: false,
+            time_up: false,
+            banned: false,
+            favorite: false,
+            level_up: false,
+            keyboard: false,
+            repeat: false,
             time_count: None,
+            muted: false,
+            clock,
         }
     }
 
     /// Call this on each application tick to manage notification visibility.
     /// Returns true if the UI needs to be updated.
     pub fn on_tick(&mut self) -> bool {
-        if let Some(shown_at) = self.time_count {
-            if shown_at.elapsed() > Duration::from_secs(2) {
-                self.hide_all();
-                return true; // Indicates an update is needed
-            }
+        if let Some(shown_at) = self.time_count
+            && self.clock.now().duration_since(shown_at) > NOTIFICATION_DURATION
+        {
+            self.hide_all();
+            return true; // Indicates an update is needed
         }
         false
     }
@@ -123,12 +640,25 @@ impl Notifications {
         self.clear_mistyped = false;
         self.wpm = false;
         self.display_wpm = false;
+        self.clipboard = None;
+        self.goal = false;
+        self.timed_test = false;
+        self.time_up = false;
+        self.banned = false;
+        self.favorite = false;
+        self.level_up = false;
+        self.keyboard = false;
+        self.repeat = false;
         self.time_count = None;
     }
 
     /// Starts the visibility timer for the currently active notification.
     fn trigger(&mut self) {
-        self.time_count = Some(Instant::now());
+        if self.muted {
+            self.hide_all();
+            return;
+        }
+        self.time_count = Some(self.clock.now());
     }
 
     /// Shows a notification indicating displaying WPM has been toggled.
@@ -172,6 +702,75 @@ impl Notifications {
         self.clear_mistyped = true;
         self.trigger();
     }
+
+    /// Shows a notification about loading the clipboard contents.
+    pub fn show_clipboard(&mut self, load: ClipboardLoad) {
+        self.clipboard = Some(load);
+        self.trigger();
+    }
+
+    /// Shows a notification that the daily goal has been reached.
+    pub fn show_goal(&mut self) {
+        self.goal = true;
+        self.trigger();
+    }
+
+    /// Shows a notification with the duration of the timed test (or that it's off).
+    pub fn show_timed_test(&mut self) {
+        self.timed_test = true;
+        self.trigger();
+    }
+
+    /// Shows a notification with the keyboard the sessions are tagged with.
+    pub fn show_keyboard(&mut self) {
+        self.keyboard = true;
+        self.trigger();
+    }
+
+    /// Shows a notification indicating repeat mode has been toggled.
+    pub fn show_repeat(&mut self) {
+        self.repeat = true;
+        self.trigger();
+    }
+
+    /// Shows a notification that the timed test is over.
+    pub fn show_time_up(&mut self) {
+        self.time_up = true;
+        self.trigger();
+    }
+
+    /// Shows a notification with the word that was just banned.
+    pub fn show_banned(&mut self) {
+        self.banned = true;
+        self.trigger();
+    }
+
+    /// Shows a notification that the word at the cursor was starred (or unstarred).
+    pub fn show_favorite(&mut self) {
+        self.favorite = true;
+        self.trigger();
+    }
+
+    /// Shows a notification that a new level was reached.
+    pub fn show_level_up(&mut self) {
+        self.level_up = true;
+        self.trigger();
+    }
+}
+
+/// The Text option content put aside while practicing an ad-hoc text
+/// (like the clipboard contents), restored once the ad-hoc session ends.
+pub struct StashedText {
+    pub text: Vec<String>,
+    pub chapters: Vec<usize>,
+    pub skip_len: usize,
+}
+
+/// (Playlist) - The playlist in progress - its segments (`playlist` in the config) are typed
+/// back-to-back, and summed up together once the last one is done.
+pub struct PlaylistRun {
+    pub segment: usize, // The segment being typed
+    pub sessions: Vec<Session>, // The sessions of the finished segments
 }
 
 /// Represents the main application state and logic.
@@ -182,14 +781,37 @@ impl Notifications {
 /// overall application lifecycle.
 pub struct App {
     pub running: bool,
-    pub needs_redraw: bool,
-    pub needs_clear: bool,
+    pub damage: Damage, // The regions of the screen to redraw with the next frame
     pub typed: bool,
-    pub charset: VecDeque<String>, // The ASCII/Words/Text character set (all are set of characters: ["a", "b", "c"])
-    pub input_chars: VecDeque<String>, // The characters user typed
-    pub ids: VecDeque<u8>, // Identifiers to display colored characters (0 - untyped, 1 - correct, 2 - incorrect)
+    pub engine: TypingEngine, // The ASCII/Words/Text lines, the typed characters and their scoring
+    pub rng: StdRng, // Generates the random ASCII/Words lines
+    pub test_started_at: Option<Instant>, // (Timed test) - When the first key of the test was pressed
+    pub test_penalty_secs: f64, // (Timed test) - The time added by errors (with `error_penalty_secs`)
+    pub test_drawn_remaining: u64, // (Timed test) - The remaining seconds last drawn (for the low-bandwidth mode)
+    pub checkpoint_dir: Option<PathBuf>, // (Timed test) - Where the running test is saved (None - it isn't, as in the demo)
+    pub interrupted_test: Option<TestCheckpoint>, // (Timed test) - The test interrupted at the last run, offered to be resumed
+    pub resumed_secs: Option<(f64, f64)>, // (Timed test) - The test and session time used up before the interruption, until the next key press
+    pub error_flash: Option<Instant>, // (Error flash) - When the last mistyped character was typed
+    pub slip_candidate: Option<(Instant, usize)>, // (Slips) - When and where the last mistyped character was typed
+    pub error_markers: Option<Instant>, // (Error markers) - When the last line was typed
+    pub line_started_at: Option<Instant>, // (Line summary) - When the first key of the line being typed was pressed
+    pub line_summary: Option<LineSummary>,
+    pub line_advance: Option<Instant>, // (Smooth line advance) - When the lines last scrolled
+    pub pace: PaceCaret,
+    pub metronome: Metronome,
+    pub schedule: PracticeSchedule,
+    pub session_block: Option<PracticeBlock>, // (Practice schedule) - The block the session in progress started in
+    pub ring_bell: bool, // The terminal bell is rung with the next draw
+    pub last_draw: Option<Instant>, // (Frame budget) - When the last frame was drawn
+    pub span_cache: RefCell<SpanCache>, // The styled lines of the last frame, rebuilt where they changed
+    pub update_check: Option<Receiver<String>>, // (Update check) - The latest version, once it's fetched in the background
+    pub available_update: Option<String>, // (Update check) - A newer version than this one, noticed in Menu mode
+    pub desktop_notification: Option<String>, // Sent (OSC 9) with the next draw
+    pub terminal_title: Option<String>, // The title last set, so it's only set again when it changes
+    pub playlist: Option<PlaylistRun>,
+    pub playlist_summary: Option<Vec<Session>>, // (Playlist) - The sessions of the finished playlist, for its summary page
+    pub composition: String, // (IME composition) - The characters typed for the current word, not compared yet
     pub line_len: usize,
-    pub lines_len: VecDeque<usize>, // Current length of lines in characters
     pub current_mode: CurrentMode,
     pub current_typing_option: CurrentTypingOption,
     pub words: Vec<String>,
@@ -197,9 +819,99 @@ pub struct App {
     pub notifications: Notifications,
     pub config: Config,
     pub show_help: bool,
+    pub help_scroll: usize, // How far the help popup is scrolled down
     pub show_mistyped: bool,
+    pub csv: Option<CsvDrill>, // (`ttypr csv`) - The data entry drill, typed on its page instead of the lines
+    pub dictation: Option<Dictation>, // The dictation drill - phrases typed from memory, on its page
+    pub show_summary: bool,
+    pub show_profile: bool,
+    pub show_results: bool, // The results page of the last session
+    pub show_attempts: bool, // (Repeat mode) - The page charting the attempts at the repeated material
+    pub repeat_text_start: Option<usize>, // (Repeat mode) - The position every attempt at the text starts at
+    pub note_input: Option<String>, // The note of the last session typed on the results page, while it's edited
+    pub targeted_drill: Option<String>, // (Targeted drill) - What became of the drill generated on the results page
+    pub show_scrollback: bool,
+    pub scrollback: VecDeque<TypedLine>, // The latest lines that were typed and scrolled away, for the review page
+    pub scrollback_offset: usize, // How many lines back from the latest one the review page is scrolled
+    pub summary_monthly: bool, // Whether the summary page shows months (or weeks)
+    pub summary_offset: usize, // How many periods back from the latest one the summary page shows
+    pub summary_keyboard: Option<String>, // The keyboard the summary page is filtered by
     pub first_text_gen_len: usize,
     pub wpm: Wpm,
+    pub combo: Combo,
+    pub slow_down: SlowDown, // (Forced pause) - The input lockout after a burst of errors
+    pub governor: SpeedGovernor, // (Speed governor) - The keys typed faster than `max_wpm` are ignored
+    pub distractions: Distractions, // (Distractor drill) - The prompts answered while typing
+    pub distraction_report: Option<DistractionReport>, // (Distractor drill) - How the last session held up under the prompts
+    pub key_filter: KeyFilter, // (Accessibility) - The minimum hold time and the bounce window of the typed keys
+    pub key_releases: bool, // Whether the terminal reports key releases (needed for the minimum hold time)
+    pub chapters: Vec<usize>, // Word positions at which the chapters of the text begin
+    pub stashed_text: Option<StashedText>, // The Text option content, while practicing an ad-hoc text
+    pub stats: Stats,
+    pub show_resume_prompt: bool, // (For the text option) - Asking whether to continue at the saved position
+    pub show_quit_prompt: bool, // (With `confirm_quit`) - Asking whether to quit
+    pub unfinished_test: Option<u64>, // The id of the session of a timed test left before the time was up
+    pub resume_asked: bool, // The resume prompt is only shown the first time the Text option is entered
+    pub goto_input: Option<String>, // (For the text option) - The position typed in the "go to" prompt, while it's open
+    pub text_finished: bool, // (For the text option) - The lines reached the end of the text
+    pub hyphenated_rest: Option<String>, // (For the text option) - The rest of the word hyphenated at the end of the last line
+    pub completed_text: Option<LifetimeTotals>, // The stats of the text just finished, shown on the completion page
+    pub text_choices: Vec<PathBuf>, // The texts that can be picked next on the completion page
+    pub text_choice: usize, // The picked choice on the completion page (0 - start over)
+    pub compared_profile: Option<(String, Stats)>, // The profile displayed next to the active one on the profile page
+    pub words_modified: Option<SystemTime>, // When the word list was modified, as loaded (to reload it once it's edited)
+    pub show_word_lists: bool,
+    pub word_list_choices: Vec<(String, ContentInfo)>, // The named word lists (and their metadata) for the picker page
+    pub word_list_choice: usize, // The picked choice of the shown ones on the word list picker page (0 - words.txt)
+    pub word_list_tag: Option<String>, // The tag the word list picker page is filtered by
+    pub demo: bool, // (`ttypr demo`) - Playing back the bundled demo session, nothing is recorded or saved
+    pub compact: bool, // (`--compact`) - A single line fitted to a small pane, without the notifications and the key hints
+    pub deterministic: bool, // (`--deterministic`) - The clock is stopped, so the frames are drawn without the frame budget
+    pub drill_chars: Option<Vec<String>>, // (`ttypr drill`) - The characters the ASCII option is generated from instead
+    clock: Rc<dyn Clock>, // Times the dictation drill (the other components keep their own)
+}
+
+/// A region of the screen, redrawn once it changed (see `Damage`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Typing, // The lines being typed
+    Hud, // The indicators around the lines (the timed test progress, the combo, the cues)
+    Notifications,
+    Screen, // All of it - a page opened or closed, or the layout changed (the screen is cleared first)
+}
+
+/// The regions of the screen that changed since the last frame.
+///
+/// Only `Region::Screen` clears the terminal before the frame - the other regions are drawn
+/// over the last frame, and only the cells that changed are written to the terminal, so a
+/// notification expiring or the timed test ticking down doesn't make the screen flicker.
+/// The typing lines are only restyled when `Region::Typing` changed (see `SpanCache`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Damage(u8);
+
+impl Damage {
+    /// Marks the region as changed.
+    pub fn add(&mut self, region: Region) {
+        self.0 |= 1 << region as u8;
+    }
+
+    /// Whether the region changed - every region did, if the whole screen did.
+    pub fn contains(&self, region: Region) -> bool {
+        self.0 & (1 << region as u8 | 1 << Region::Screen as u8) != 0
+    }
+
+    /// Whether nothing changed, and there's nothing to redraw.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<Region> for Damage {
+    fn from(region: Region) -> Damage {
+        let mut damage = Damage::default();
+        damage.add(region);
+        damage
+    }
 }
 
 /// Defines the major operational modes of the application.
@@ -218,32 +930,226 @@ pub enum CurrentTypingOption {
     Text,
 }
 
+impl CurrentTypingOption {
+    /// The name of the typing option, as displayed and stored in the stats.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurrentTypingOption::Ascii => "Ascii",
+            CurrentTypingOption::Words => "Words",
+            CurrentTypingOption::Text => "Text",
+        }
+    }
+
+    /// The typing option of the given name (in any case), `None` for unknown names.
+    pub fn from_name(name: &str) -> Option<CurrentTypingOption> {
+        match name.to_lowercase().as_str() {
+            "ascii" => Some(CurrentTypingOption::Ascii),
+            "words" => Some(CurrentTypingOption::Words),
+            "text" => Some(CurrentTypingOption::Text),
+            _ => None,
+        }
+    }
+}
+
 /// A constant array of ASCII characters used for generating lines of random ASCII characters.
 const ASCII_CHARSET: &[&str] = &["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z", "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "~", "`", "!", "@", "#", "$", "%", "^", "&", "*", "(", ")", "-", "_", "+", "=", "{", "}", "[", "]", "|", "\\", ":", ";", "\"", "'", "<", ">", ",", ".", "?", "/"];
 
+/// How long a pause in typing has to be, for the WPM to be calculated.
+const WPM_PAUSE: Duration = Duration::from_secs(3);
+
+/// (Live WPM) - The rolling window of the WPM displayed while typing, unless `wpm_window_secs` is set.
+const LIVE_WPM_WINDOW: Duration = Duration::from_secs(5);
+
+/// How long notifications are displayed for.
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(2);
+
+/// How long a combo milestone is highlighted for.
+const COMBO_MILESTONE_DURATION: Duration = Duration::from_secs(1);
+
+/// (Deterministic mode) - The seed the random lines are generated from.
+const DETERMINISTIC_SEED: u64 = 5;
+
+/// The combo counts at which the combo is highlighted for a moment.
+const COMBO_MILESTONES: [usize; 3] = [50, 100, 250];
+
+/// The durations of the timed test the user can switch between, in seconds (0 - untimed).
+const TEST_DURATIONS: [u64; 5] = [0, 15, 30, 60, 120];
+
+/// (Error flash) - How long a mistyped character is highlighted for.
+const ERROR_FLASH_DURATION: Duration = Duration::from_millis(100);
+
+/// (Error markers) - How long the errors of a typed line are marked for.
+const ERROR_MARKERS_DURATION: Duration = Duration::from_millis(1500);
+
+/// (Line summary) - How long the summary of a typed line is displayed for.
+const LINE_SUMMARY_DURATION: Duration = Duration::from_millis(1500);
+
+/// (Forced pause) - How many errors within `ERROR_BURST_WINDOW` lock the input.
+const ERROR_BURST_LEN: usize = 3;
+
+/// (Forced pause) - The time the errors of a burst are typed within.
+const ERROR_BURST_WINDOW: Duration = Duration::from_secs(1);
+
+/// (Speed governor) - How long the cue of a key typed too fast is displayed for.
+const GOVERNOR_CUE_DURATION: Duration = Duration::from_millis(300);
+
+/// (Compact mode) - The shortest the lines get, however narrow the pane.
+const MIN_COMPACT_LINE_LEN: usize = 10;
+
+/// How many typed lines are kept for the review page.
+const SCROLLBACK_LINES: usize = 200;
+
+/// (Smooth line advance) - How many rows the lines move up when they scroll.
+const LINE_ADVANCE_ROWS: u16 = 2;
+
+/// (Smooth line advance) - How long the lines stay at every intermediate row.
+const LINE_ADVANCE_STEP: Duration = Duration::from_millis(50);
+
+/// (Metronome) - How long the dot is lit for at every beat (at most half of the beat).
+const METRONOME_FLASH: Duration = Duration::from_millis(100);
+
+/// (Update check) - How often it's checked whether the latest version was fetched.
+const UPDATE_CHECK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// (Practice schedule) - How often the remaining time of the phase is checked, to redraw it every second.
+const SCHEDULE_REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The digits, for the ASCII option with a weight for them (`ascii_digit_weight`).
+const DIGITS: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+/// Cyrillic (Russian) letters and punctuation, for the ASCII option with `ascii_charset = "cyrillic"`.
+const CYRILLIC_CHARSET: &[&str] = &["а", "б", "в", "г", "д", "е", "ё", "ж", "з", "и", "й", "к", "л", "м", "н", "о", "п", "р", "с", "т", "у", "ф", "х", "ц", "ч", "ш", "щ", "ъ", "ы", "ь", "э", "ю", "я", "А", "Б", "В", "Г", "Д", "Е", "Ё", "Ж", "З", "И", "Й", "К", "Л", "М", "Н", "О", "П", "Р", "С", "Т", "У", "Ф", "Х", "Ц", "Ч", "Ш", "Щ", "Ъ", "Ы", "Ь", "Э", "Ю", "Я", "!", "?", ",", ".", ":", ";", "-", "(", ")", "\"", "'"];
+
+/// Greek letters and punctuation, for the ASCII option with `ascii_charset = "greek"`.
+const GREEK_CHARSET: &[&str] = &["α", "β", "γ", "δ", "ε", "ζ", "η", "θ", "ι", "κ", "λ", "μ", "ν", "ξ", "ο", "π", "ρ", "σ", "ς", "τ", "υ", "φ", "χ", "ψ", "ω", "Α", "Β", "Γ", "Δ", "Ε", "Ζ", "Η", "Θ", "Ι", "Κ", "Λ", "Μ", "Ν", "Ξ", "Ο", "Π", "Ρ", "Σ", "Τ", "Υ", "Φ", "Χ", "Ψ", "Ω", "!", "?", ",", ".", ":", ";", "-", "(", ")", "\"", "'"];
+
+/// Gets the characters the ASCII option is generated from, by the name set in the config.
+///
+/// Falls back to the Latin ASCII characters for unknown names.
+pub fn drill_charset(name: &str) -> &'static [&'static str] {
+    match name.to_lowercase().as_str() {
+        "cyrillic" => CYRILLIC_CHARSET,
+        "greek" => GREEK_CHARSET,
+        _ => ASCII_CHARSET,
+    }
+}
+
+/// Leaves out the characters that are excluded in the config (`excluded_chars`),
+/// unless all of them are.
+fn without_excluded<'a>(chars: Vec<&'a str>, excluded: &str) -> Vec<&'a str> {
+    let allowed: Vec<&str> = chars.iter().copied().filter(|c| !c.chars().any(|c| excluded.contains(c))).collect();
+    if allowed.is_empty() { chars } else { allowed }
+}
+
 impl App {
     /// Construct a new instance of App
     pub fn new() -> App {
+        App::with_clock(Rc::new(SystemClock))
+    }
+
+    /// Construct an instance of App for reproducible screenshots and recordings (`--deterministic`) -
+    /// the random lines are generated from a fixed seed, the clock is stopped and no notifications are shown.
+    pub fn deterministic() -> App {
+        let mut app = App::with_clock(Rc::new(MockClock::new()));
+        app.rng = StdRng::seed_from_u64(DETERMINISTIC_SEED);
+        app.notifications.muted = true;
+        app.deterministic = true;
+        app
+    }
+
+    /// Construct a new instance of App, with the notifications and the WPM
+    /// timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> App {
         App { 
             running: true, 
-            needs_redraw: true,
-            needs_clear: false,
+            damage: Damage::from(Region::Screen),
             typed: false,
-            charset: VecDeque::new(),
-            input_chars: VecDeque::new(),
-            ids: VecDeque::new(),
+            test_started_at: None,
+            test_penalty_secs: 0.0,
+            test_drawn_remaining: 0,
+            checkpoint_dir: None,
+            interrupted_test: None,
+            resumed_secs: None,
+            error_flash: None,
+            slip_candidate: None,
+            error_markers: None,
+            line_started_at: None,
+            line_summary: None,
+            line_advance: None,
+            composition: String::new(),
+            engine: TypingEngine::new(),
+            rng: StdRng::from_os_rng(),
             line_len: 50,
-            lines_len: VecDeque::new(),
             current_mode: CurrentMode::Menu,
             current_typing_option: CurrentTypingOption::Ascii,
             words: vec![],
             text: vec![],
-            notifications: Notifications::new(),
+            notifications: Notifications::with_clock(clock.clone()),
             config: Config::default(),
             show_help: false,
+            help_scroll: 0,
             show_mistyped: false,
+            csv: None,
+            dictation: None,
+            show_summary: false,
+            show_profile: false,
+            show_results: false,
+            targeted_drill: None,
+            show_attempts: false,
+            repeat_text_start: None,
+            note_input: None,
+            show_scrollback: false,
+            scrollback: VecDeque::new(),
+            scrollback_offset: 0,
+            summary_monthly: false,
+            summary_offset: 0,
+            summary_keyboard: None,
             first_text_gen_len: 0,
-            wpm: Wpm::new(),
+            wpm: Wpm::with_clock(clock.clone()),
+            combo: Combo::with_clock(clock.clone()),
+            slow_down: SlowDown::with_clock(clock.clone()),
+            governor: SpeedGovernor::with_clock(clock.clone()),
+            distractions: Distractions::with_clock(clock.clone()),
+            distraction_report: None,
+            pace: PaceCaret::with_clock(clock.clone()),
+            metronome: Metronome::with_clock(clock.clone()),
+            schedule: PracticeSchedule::with_clock(clock.clone()),
+            session_block: None,
+            ring_bell: false,
+            last_draw: None,
+            span_cache: RefCell::new(SpanCache::default()),
+            update_check: None,
+            available_update: None,
+            desktop_notification: None,
+            terminal_title: None,
+            playlist: None,
+            playlist_summary: None,
+            key_filter: KeyFilter::with_clock(clock.clone()),
+            clock,
+            key_releases: false,
+            chapters: vec![0],
+            stashed_text: None,
+            stats: Stats::default(),
+            show_resume_prompt: false,
+            show_quit_prompt: false,
+            unfinished_test: None,
+            resume_asked: false,
+            goto_input: None,
+            text_finished: false,
+            hyphenated_rest: None,
+            completed_text: None,
+            text_choices: vec![],
+            text_choice: 0,
+            compared_profile: None,
+            words_modified: None,
+            show_word_lists: false,
+            word_list_choices: vec![],
+            word_list_choice: 0,
+            word_list_tag: None,
+            demo: false,
+            compact: false,
+            deterministic: false,
+            drill_chars: None,
         }
     }
 
@@ -258,25 +1164,40 @@ impl App {
     /// responsible for persisting the application's state, such as saving the
     /// current configuration and adjusting any other relevant settings.
     pub fn on_exit(&mut self) {
-        use crate::utils::{get_config_dir, save_config};
+        use ttypr::utils::{get_config_dir, save_config};
+
+        // Nothing of the demo is kept
+        if self.demo {
+            return;
+        }
+
+        // Record the session if exited while typing - a timed test is saved to be resumed instead
+        if self.test_started_at.is_some() {
+            self.save_test_checkpoint();
+        } else {
+            self.finish_session();
+        }
 
         // (If exited the application while being the Text option)
-        // Subtract how many "words" there were on the first three lines
-        match self.current_typing_option {
-            CurrentTypingOption::Text => {
+        // Subtract how many "words" there were on the first generated lines
+        if let CurrentTypingOption::Text = self.current_typing_option {
+            if self.stashed_text.is_some() {
+                // The ad-hoc text position is not persisted
+                self.restore_stashed_text();
+            } else {
+                self.save_chapter_position();
                 if self.config.skip_len >= self.first_text_gen_len {
                     self.config.skip_len -= self.first_text_gen_len;
                 } else {
                     self.config.skip_len = 0;
                 }
             }
-            _ => {}
         }
 
         // Save config (for mistyped characters) before exiting
         if let Ok(config_dir) = get_config_dir() {
             save_config(&self.config, &config_dir).unwrap_or_else(|err| {
-                eprintln!("Failed to save config: {}", err);
+                ttypr::crash::log_error(&format!("Failed to save config: {}", err));
             });
         }
     }
@@ -286,57 +1207,495 @@ impl App {
     /// This function shows the WPM notification if a calculation is ready and also
     /// manages the lifecycle of notifications, clearing them after a timeout.
     pub fn on_tick(&mut self) {
-        if self.wpm.on_tick() {
+        let measuring = self.wpm.is_measuring();
+        let calculated = self.wpm.on_tick();
+        if self.config.live_wpm {
+            // (Live WPM) - Updated in its corner, and dimmed once the typing pauses
+            if self.live_wpm_shown() && (calculated || measuring != self.wpm.is_measuring()) {
+                self.damage.add(Region::Hud);
+            }
+        } else if calculated {
             self.notifications.show_wpm();
-            self.needs_redraw = true;
+            self.damage.add(Region::Notifications);
         }
         if self.notifications.on_tick() {
-            self.needs_clear = true;
-            self.needs_redraw = true;
+            self.damage.add(Region::Notifications);
         }
-    }
-
-    /// Initializes the application state at startup.
-    ///
-    /// This function is responsible for setting up the initial state of the
-    /// application. It loads the configuration, populates the initial character
-    /// sets for typing, and prepares the application to be run.
-    pub fn setup(&mut self) -> color_eyre::Result<()> {
-        use crate::utils::{
-            calculate_text_txt_hash, default_text, default_words, get_config_dir, load_config,
-            read_text_from_file, read_words_from_file,
-        };
-
-        // Get the config directory
-        let config_dir = get_config_dir()?;
+        if self.combo.on_tick() {
+            self.damage.add(Region::Hud);
+        }
+        if self.slow_down.on_tick() {
+            self.damage.add(Region::Hud);
+        }
+        if self.governor.on_tick() {
+            self.damage.add(Region::Hud);
+        }
+        // (Distractor drill) - Show the prompt once it's due, and the next one after it's missed
+        if self.config.distractor_secs > 0 && matches!(self.current_mode, CurrentMode::Typing) {
+            if self.distractions.is_due() {
+                let (word, ink) = (self.rng.random_range(0..DISTRACTOR_WORDS.len()), self.rng.random_range(0..DISTRACTOR_WORDS.len()));
+                self.distractions.show(word, ink);
+                self.damage.add(Region::Hud);
+            }
+            if self.distractions.on_tick() {
+                self.schedule_distractor();
+                self.damage.add(Region::Hud);
+            }
+        }
+        // End the timed test once the time is up, keep redrawing its progress bar until then
+        if let Some(elapsed) = self.test_elapsed_secs() {
+            if elapsed >= self.test_duration_secs() as f64 && self.playlist.is_some() {
+                self.next_playlist_segment();
+            } else if elapsed >= self.test_duration_secs() as f64 {
+                self.test_started_at = None;
+                self.current_mode = CurrentMode::Menu;
+                self.finish_session();
+                self.notifications.show_time_up();
+                self.damage.add(Region::Screen);
+            } else if let Some((_, remaining)) = self.test_progress() {
+                // (Low-bandwidth mode) - Only redraw once a second
+                if !self.config.low_bandwidth || remaining != self.test_drawn_remaining {
+                    self.test_drawn_remaining = remaining;
+                    self.damage.add(Region::Hud);
+                }
+            }
+        }
+        // (Playlist) - The next segment once the words of the segment are typed
+        if let Some(segment) = self.playlist_segment()
+            && segment.words > 0
+            && self.engine.session.correct + self.engine.session.errors >= segment.words * 5
+        {
+            self.next_playlist_segment();
+        }
+        // Stop highlighting the mistyped character after a moment
+        if let Some(flashed_at) = self.error_flash
+            && self.clock.now().duration_since(flashed_at) >= ERROR_FLASH_DURATION
+        {
+            self.error_flash = None;
+            self.damage.add(Region::Typing);
+        }
+        // Stop marking the errors of the typed line after a moment
+        if let Some(marked_at) = self.error_markers
+            && self.clock.now().duration_since(marked_at) >= ERROR_MARKERS_DURATION
+        {
+            self.error_markers = None;
+            self.damage.add(Region::Typing);
+        }
+        if self.line_summary.as_ref().is_some_and(|summary| self.clock.now().duration_since(summary.shown_at) >= LINE_SUMMARY_DURATION) {
+            self.line_summary = None;
+            self.damage.add(Region::Typing);
+        }
+        if self.pace.on_tick() {
+            self.damage.add(Region::Typing);
+        }
+        if self.metronome_active() && self.metronome.on_tick(self.config.metronome_bpm) {
+            self.damage.add(Region::Hud);
+        }
+        // (Practice schedule) - A rest once the practice block is over (the session is finished),
+        // and the next block once the rest is over
+        if self.config.practice_minutes > 0 {
+            let (practice, rest) = self.schedule_lengths();
+            if let Some(ended) = self.schedule.on_tick(practice, rest) {
+                if ended == SchedulePhase::Practice {
+                    self.playlist = None;
+                    self.test_started_at = None;
+                    self.current_mode = CurrentMode::Menu;
+                    self.finish_session();
+                }
+                self.ring_bell = self.config.schedule_bell;
+                self.damage.add(Region::Screen);
+            }
+            // Redraw the remaining time once a second
+            if let Some((_, remaining)) = self.schedule.remaining(practice, rest)
+                && remaining.as_secs() != self.schedule.drawn_remaining
+            {
+                self.schedule.drawn_remaining = remaining.as_secs();
+                self.damage.add(Region::Hud);
+            }
+        }
+        // (Dictation) - Hide the phrase once it was shown for long enough
+        if self.dictation.as_mut().is_some_and(Dictation::on_tick) {
+            self.damage.add(Region::Typing);
+        }
+        // (Update check) - A notice once a newer version is fetched
+        if let Some(update_check) = &self.update_check {
+            match update_check.try_recv() {
+                Ok(version) => {
+                    self.update_check = None;
+                    if is_newer(&version, CURRENT_VERSION) {
+                        self.available_update = Some(version);
+                        self.damage.add(Region::Hud);
+                    }
+                }
+                Err(TryRecvError::Disconnected) => self.update_check = None, // Failed (offline)
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        // Keep redrawing while the lines are moving into place
+        if let Some(started_at) = self.line_advance {
+            if self.clock.now().duration_since(started_at) >= LINE_ADVANCE_STEP * LINE_ADVANCE_ROWS as u32 {
+                self.line_advance = None;
+            }
+            self.damage.add(Region::Typing);
+        }
+    }
+
+    /// How long the main loop can wait for an event before something needs updating
+    /// (a notification to hide, the WPM to calculate, an animation frame...).
+    ///
+    /// Returns `None` if nothing does - there's no need to wake up until the next event.
+    pub fn tick_timeout(&self) -> Option<Duration> {
+        let now = self.clock.now();
+        let deadlines = [
+            self.wpm.next_update(),
+            self.notifications.time_count.map(|shown_at| shown_at + NOTIFICATION_DURATION),
+            self.combo.milestone_reached_at.map(|reached_at| reached_at + COMBO_MILESTONE_DURATION),
+            self.error_flash.map(|flashed_at| flashed_at + ERROR_FLASH_DURATION),
+            self.error_markers.map(|marked_at| marked_at + ERROR_MARKERS_DURATION),
+            self.line_summary.as_ref().map(|summary| summary.shown_at + LINE_SUMMARY_DURATION),
+            self.slow_down.locked_until,
+            self.governor.ignored_at.map(|ignored_at| ignored_at + GOVERNOR_CUE_DURATION),
+            self.distractions.next_deadline(),
+            self.line_advance.map(|_| now + LINE_ADVANCE_STEP),
+            self.pace.started_at.map(|_| now + self.tick_interval()),
+            self.metronome.next_change(self.config.metronome_bpm).filter(|_| self.metronome_active()),
+            self.dictation.as_ref().and_then(Dictation::hides_at),
+            self.schedule.phase.map(|_| now + SCHEDULE_REDRAW_INTERVAL),
+            self.update_check.as_ref().map(|_| now + UPDATE_CHECK_POLL_INTERVAL),
+            self.test_started_at.map(|_| now + self.tick_interval()),
+        ];
+        // (Frame budget) - The frame held back until the budget allows it
+        let next_frame = self.next_frame_at().filter(|_| !self.damage.is_empty());
+
+        // With a millisecond of slack, as the timers expire only after their duration has passed
+        deadlines
+            .into_iter()
+            .chain([next_frame])
+            .flatten()
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .min()
+            .map(|timeout| timeout + Duration::from_millis(1))
+    }
+
+    /// (Metronome) - Whether the metronome is beating - set, and in Typing mode.
+    pub fn metronome_active(&self) -> bool {
+        self.config.metronome_bpm > 0 && matches!(self.current_mode, CurrentMode::Typing)
+    }
+
+    /// (Live WPM) - Whether the live WPM is displayed - set, and not hidden with the WPM display
+    /// toggle (`a` in the menu).
+    pub fn live_wpm_shown(&self) -> bool {
+        self.config.live_wpm && self.config.show_wpm_notification
+    }
+
+    /// How often the countdowns and the animations are updated (`tick_ms` in the config) - the
+    /// progress bar of the timed test and the pace caret.
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_millis(self.config.tick_ms.max(1))
+    }
+
+    /// (Frame budget) - When the next frame can be drawn, with at most `max_fps` frames a second
+    /// (`None` - no limit, nothing was drawn yet, or the clock is stopped in deterministic mode).
+    fn next_frame_at(&self) -> Option<Instant> {
+        let budget = Duration::from_secs(1).checked_div(self.config.max_fps)?;
+        Some(self.last_draw? + budget).filter(|_| !self.deterministic)
+    }
+
+    /// (Frame budget) - Whether a frame can be drawn now. The key presses are still handled as
+    /// they come - only the drawing waits.
+    pub fn frame_due(&self) -> bool {
+        self.next_frame_at().is_none_or(|next_frame_at| self.clock.now() >= next_frame_at)
+    }
+
+    /// (Frame budget) - Notes that a frame was just drawn, for the budget of the next one.
+    pub fn mark_drawn(&mut self) {
+        self.last_draw = Some(self.clock.now());
+    }
+
+    /// (Update check) - Fetches the latest version in the background (see `on_tick`), so a slow
+    /// or missing connection doesn't hold up the start.
+    #[cfg(feature = "update-check")]
+    fn start_update_check(&mut self) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            if let Ok(version) = ttypr::update::fetch_latest_version() {
+                sender.send(version).ok();
+            }
+        });
+        self.update_check = Some(receiver);
+    }
+
+    /// (Update check) - Built without the `update-check` feature, there's nothing to check.
+    #[cfg(not(feature = "update-check"))]
+    fn start_update_check(&mut self) {}
+
+    /// (Distractor drill) - Schedules the next prompt, `distractor_secs` from now give or take a half.
+    pub fn schedule_distractor(&mut self) {
+        let secs = self.config.distractor_secs as f64 * self.rng.random_range(0.5..1.5);
+        self.distractions.schedule(Duration::from_secs_f64(secs));
+    }
+
+    /// (Distractor drill) - Answers the prompt shown with the word of F1-F4, and schedules the next one.
+    pub fn answer_distractor(&mut self, word: usize) {
+        if self.distractions.answer(word) {
+            self.schedule_distractor();
+            self.damage.add(Region::Typing);
+        }
+    }
+
+    /// (Practice schedule) - The lengths of the practice blocks and the rests.
+    pub fn schedule_lengths(&self) -> (Duration, Duration) {
+        (Duration::from_secs(self.config.practice_minutes * 60), Duration::from_secs(self.config.rest_minutes * 60))
+    }
+
+    /// Counts a key press of the typing session (for the WPM, the stats and the timed test).
+    ///
+    /// In untimed typing, a key press after an idle gap of `idle_split_minutes` finishes
+    /// the session and starts a new one - so the app sitting open all day doesn't make
+    /// a single session of it.
+    pub fn on_key_press(&mut self) {
+        if self.session_is_idle() {
+            self.finish_session();
+        }
+        // (Practice schedule) - The first key press after a rest starts the next block
+        if self.config.practice_minutes > 0 && self.schedule.phase.is_none() {
+            self.schedule.start_block();
+            self.damage.add(Region::Typing);
+        }
+        if self.engine.session.keystrokes == 0 {
+            self.session_block = self.schedule.current_block();
+        }
+        // (Timed test) - The resumed session carries on from the time typed before the interruption
+        let resumed = self.resumed_secs.take();
+        if let Some((_, session_secs)) = resumed {
+            self.engine.session.timer = self.clock.now().checked_sub(Duration::from_secs_f64(session_secs));
+        }
+        // (Distractor drill) - The prompts start with the session
+        if self.config.distractor_secs > 0 {
+            self.schedule_distractor();
+            self.distractions.on_key_press();
+        }
+        self.wpm.on_key_press();
+        self.engine.session.on_key_press(self.clock.now());
+        if self.line_started_at.is_none() {
+            self.line_started_at = Some(self.clock.now());
+        }
+        // (Pace caret) - It starts from where the first key of the session was pressed
+        if self.pace.started_at.is_none()
+            && let Some(wpm) = self.pace_target_wpm()
+        {
+            self.pace.start(self.engine.cursor().saturating_sub(1), wpm);
+        }
+        // The timed test starts with the first key press
+        if self.test_duration_secs() > 0 && self.test_started_at.is_none() {
+            self.test_started_at = Some(self.clock.now());
+            self.test_penalty_secs = resumed.map_or(0.0, |(test_secs, _)| test_secs);
+        }
+    }
+
+    /// The rolling window the WPM is calculated over - `wpm_window_secs`, or `LIVE_WPM_WINDOW`
+    /// for the live WPM (which has to be recalculated while typing).
+    pub fn wpm_window(&self) -> Duration {
+        match self.config.wpm_window_secs {
+            0 if self.config.live_wpm => LIVE_WPM_WINDOW,
+            secs => Duration::from_secs(secs),
+        }
+    }
+
+    /// The speed of the pace caret - `pace_wpm`, or the average WPM of the recent sessions
+    /// with `pace_average` in the config. `None` if there's no pace caret.
+    pub fn pace_target_wpm(&self) -> Option<f64> {
+        if self.config.pace_average {
+            return recent_wpm_trend(&self.stats.sessions).map(|(_, recent, _)| recent);
+        }
+        (self.config.pace_wpm > 0).then_some(self.config.pace_wpm as f64)
+    }
+
+    /// Whether the untimed session in progress has been idle for longer than `idle_split_minutes`.
+    fn session_is_idle(&self) -> bool {
+        let gap = Duration::from_secs(self.config.idle_split_minutes * 60);
+        self.config.idle_split_minutes > 0
+            && self.test_duration_secs() == 0
+            && self.engine.session.idle_time(self.clock.now()).is_some_and(|idle| idle > gap)
+    }
+
+    /// Switches to the next timed test duration (off, 15s, 30s, 60s, 120s).
+    pub fn cycle_test_duration(&mut self) {
+        let next = TEST_DURATIONS
+            .iter()
+            .position(|secs| *secs == self.config.test_duration_secs)
+            .map_or(0, |i| (i + 1) % TEST_DURATIONS.len());
+        self.config.test_duration_secs = TEST_DURATIONS[next];
+        self.test_started_at = None;
+    }
+
+    /// The length of the timed test in seconds (0 - untimed) - `test_duration_secs`, or that of
+    /// the playlist segment being typed.
+    pub fn test_duration_secs(&self) -> u64 {
+        match self.playlist_segment() {
+            Some(segment) => segment.secs,
+            None => self.config.test_duration_secs,
+        }
+    }
+
+    /// The terminal title - the WPM and the time of the session being typed (the time left of
+    /// a timed test), e.g. "ttypr – 74wpm – 00:42", or just "ttypr" outside of one.
+    pub fn title(&self) -> String {
+        let secs = match (&self.current_mode, self.test_progress(), self.engine.session.timer) {
+            (CurrentMode::Typing, Some((_, remaining)), _) => remaining,
+            (CurrentMode::Typing, None, Some(timer)) => self.clock.now().duration_since(timer).as_secs(),
+            _ => return "ttypr".to_string(),
+        };
+        format!("ttypr – {}wpm – {:02}:{:02}", self.wpm.wpm, secs / 60, secs % 60)
+    }
+
+    /// (Playlist) - The segment being typed, if a playlist is in progress.
+    pub fn playlist_segment(&self) -> Option<&PlaylistSegment> {
+        self.config.playlist.get(self.playlist.as_ref()?.segment)
+    }
+
+    /// (Playlist) - Starts typing the playlist from its first segment.
+    pub fn start_playlist(&mut self) {
+        if self.config.playlist.is_empty() {
+            return;
+        }
+        self.finish_session();
+        self.playlist = Some(PlaylistRun { segment: 0, sessions: vec![] });
+        self.start_playlist_segment(0);
+    }
+
+    /// (Playlist) - Finishes the segment being typed and starts the next one.
+    pub fn next_playlist_segment(&mut self) {
+        let recorded = self.stats.sessions.len();
+        self.test_started_at = None;
+        self.finish_session();
+        let Some(run) = self.playlist.as_mut() else {
+            return;
+        };
+        // Segments with too few key presses aren't recorded, nor summed up
+        if self.stats.sessions.len() > recorded {
+            run.sessions.extend(self.stats.sessions.last().cloned());
+        }
+        let next = run.segment + 1;
+        self.start_playlist_segment(next);
+    }
+
+    /// (Playlist) - Switches to the typing option of the segment and starts typing it - or of the
+    /// next one, if it has no limit, an unknown option or no words/text to type. The playlist is
+    /// finished after the last segment.
+    fn start_playlist_segment(&mut self, first: usize) {
+        for i in first..self.config.playlist.len() {
+            let segment = &self.config.playlist[i];
+            let Some(option) = CurrentTypingOption::from_name(&segment.option) else {
+                continue;
+            };
+            if segment.secs == 0 && segment.words == 0 {
+                continue;
+            }
+
+            // Switched the way `o` does, so the options keep their lines and positions
+            while self.current_typing_option.name() != option.name() {
+                self.switch_typing_option();
+            }
+            match self.current_typing_option {
+                CurrentTypingOption::Words if self.words.is_empty() => continue,
+                CurrentTypingOption::Text if self.text.is_empty() => continue,
+                _ => {}
+            }
+            self.show_resume_prompt = false;
+
+            if let Some(run) = self.playlist.as_mut() {
+                run.segment = i;
+            }
+            self.current_mode = CurrentMode::Typing;
+            self.damage.add(Region::Screen);
+            return;
+        }
+
+        // The summary page of the segments once they're all done
+        if let Some(run) = self.playlist.take() {
+            self.playlist_summary = Some(run.sessions);
+        }
+        self.current_mode = CurrentMode::Menu;
+        self.damage.add(Region::Screen);
+    }
+
+    /// The progress of the running timed test - the elapsed part of it (0 to 1)
+    /// and the seconds remaining.
+    pub fn test_progress(&self) -> Option<(f64, u64)> {
+        let duration = self.test_duration_secs() as f64;
+        let elapsed = self.test_elapsed_secs()?.min(duration);
+        Some((elapsed / duration, (duration - elapsed).ceil() as u64))
+    }
+
+    /// The time used up of the running timed test - including the penalties for errors.
+    fn test_elapsed_secs(&self) -> Option<f64> {
+        let started_at = self.test_started_at?;
+        Some(self.clock.now().duration_since(started_at).as_secs_f64() + self.test_penalty_secs)
+    }
+
+    /// How many rows below their position the lines are drawn, while they move into place
+    /// after scrolling (0 - no transition in progress).
+    pub fn line_advance_offset(&self) -> u16 {
+        match self.line_advance {
+            Some(started_at) => {
+                let steps = (self.clock.now().duration_since(started_at).as_millis() / LINE_ADVANCE_STEP.as_millis()) as u16;
+                LINE_ADVANCE_ROWS.saturating_sub(steps)
+            }
+            None => 0,
+        }
+    }
+
+    /// Initializes the application state at startup.
+    ///
+    /// This function is responsible for setting up the initial state of the
+    /// application. It loads the configuration, populates the initial character
+    /// sets for typing, and prepares the application to be run.
+    pub fn setup(&mut self) -> color_eyre::Result<()> {
+        use ttypr::stats::{load_stats, profile_dir};
+        use ttypr::utils::{
+            calculate_text_txt_hash, default_text, default_words, get_config_dir, load_config,
+            read_text_chapters, read_text_from_file, read_words_from_file,
+        };
+        use ttypr::wordlist::{word_list_modified, word_list_path};
+
+        // Get the config directory
+        let config_dir = get_config_dir()?;
 
         // Load config file or create it
         self.config = load_config(&config_dir).unwrap_or_else(|_err| Config::default());
+        ttypr::crash::log("Started");
+        if self.config.check_updates {
+            self.start_update_check();
+        }
+        self.wpm.set_window(self.wpm_window(), Duration::from_secs(self.config.wpm_update_secs));
+
+        // A timed test interrupted at the last run (crashed, or the terminal was closed) is offered to be resumed
+        self.interrupted_test = load_checkpoint(&config_dir);
+        self.checkpoint_dir = Some(config_dir.clone());
+
+        // Load the recorded typing history of the active profile
+        self.stats = load_stats(&profile_dir(&config_dir, &self.config.active_profile)).unwrap_or_default();
 
-        // (For the ASCII option) - Generate initial random charset and set all ids to 0
+        // (For the ASCII option) - Generate initial random charset (all untyped)
         // (This for block is here because the default typing option is Ascii)
-        for _ in 0..3 {
+        for _ in 0..self.lines_shown() {
             let one_line = self.gen_one_line_of_ascii();
-
-            let characters: Vec<char> = one_line.chars().collect();
-            self.lines_len.push_back(characters.len());
-            for char in characters {
-                self.charset.push_back(char.to_string());
-                self.ids.push_back(0);
-            }
+            self.engine.push_line(&one_line);
         }
 
         // (For the Words option) - Read the words from .config/ttypr/words.txt
         // If it doesn't exist, it will default to an empty vector.
-        self.words = read_words_from_file(&config_dir).unwrap_or_default();
+        self.words = read_words_from_file(&config_dir, &self.config, &mut self.rng).unwrap_or_default();
 
         // (For the Text option) - Read the text from .config/ttypr/text.txt
         // If it doesn't exist, it will default to an empty vector.
-        self.text = read_text_from_file(&config_dir).unwrap_or_default();
+        self.text = read_text_from_file(&config_dir, &self.config).unwrap_or_default();
 
         // If words file provided use that one instead of the default set
         if !self.words.is_empty() {
             self.config.use_default_word_set = false;
+            self.words_modified = word_list_modified(&word_list_path(&config_dir, self.config.word_list.as_deref()));
         }
 
         // Use the default word set if previously selected to use it
@@ -362,141 +1721,964 @@ impl App {
         // if block resets the position in the Text option to the beginning)
         if self.text.is_empty() && !self.config.use_default_text_set {
             self.config.skip_len = 0;
+            self.config.chapter_positions.clear();
+        }
+
+        // Use the default text set if previously selected to use it
+        if self.config.use_default_text_set {
+            self.text = default_text();
+        } else {
+            // Find where the chapters of the provided text begin
+            self.chapters = read_text_chapters(&config_dir).unwrap_or_else(|_| vec![0]);
+        }
+
+        // If the contents of the .config/ttypr/text.txt changed -
+        // reset the position to the beginning
+        if self.config.last_text_txt_hash != calculate_text_txt_hash(&config_dir).ok() {
+            self.config.skip_len = 0;
+            self.config.chapter_positions.clear();
+            self.config.text_name = None;
+        }
+
+        // Calculate the hash of the .config/ttypr/text.txt to
+        // compare to the previously generated one and determine
+        // whether the file contents have changed
+        self.config.last_text_txt_hash = calculate_text_txt_hash(&config_dir).ok();
+
+        Ok(())
+    }
+
+    /// (Accessibility) - Whether a typed character is ignored as a bounce (see `bounce_ms` in the config).
+    pub fn is_bounce(&mut self, c: char) -> bool {
+        self.config.bounce_ms > 0 && self.key_filter.is_bounce(c, Duration::from_millis(self.config.bounce_ms))
+    }
+
+    /// (Accessibility) - Whether the characters are typed once their keys are released, after being held
+    /// for `min_hold_ms` (only if the terminal reports key releases).
+    pub fn holds_keys(&self) -> bool {
+        self.config.min_hold_ms > 0 && self.key_releases
+    }
+
+    /// Constructs a line of random ASCII characters that fits within the configured line length.
+    ///
+    /// The characters are taken from the script set by `ascii_charset` in the config
+    /// (or the ones given to `ttypr drill`). If the config gives letters, digits and symbols
+    /// weights, every class is generated as often as its weight (`60`, `20`, `20`), otherwise
+    /// the characters are generated uniformly. The characters excluded in the config are
+    /// never generated, and `capital_letters_percent` sets how many of the letters are uppercase.
+    pub fn gen_one_line_of_ascii(&mut self) -> String {
+        let excluded = &self.config.excluded_chars;
+        if let Some(chars) = &self.drill_chars {
+            let chars = without_excluded(chars.iter().map(String::as_str).collect(), excluded);
+            return random_chars_line(&mut self.rng, &chars, self.line_len);
+        }
+
+        let charset = without_excluded(drill_charset(&self.config.ascii_charset).to_vec(), excluded);
+        let weights = [self.config.ascii_letter_weight, self.config.ascii_digit_weight, self.config.ascii_symbol_weight];
+        let line = if weights.iter().all(|weight| *weight == 0) {
+            random_chars_line(&mut self.rng, &charset, self.line_len)
+        } else {
+            let (letters, symbols): (Vec<&str>, Vec<&str>) = charset.into_iter().partition(|c| c.chars().all(char::is_alphabetic));
+            let digits = without_excluded(DIGITS.to_vec(), excluded);
+            weighted_chars_line(&mut self.rng, &[letters, digits, symbols], &weights, self.line_len)
+        };
+        match self.config.capital_letters_percent {
+            Some(percent) => with_capitals(&mut self.rng, &line, percent, &self.config.excluded_chars),
+            None => line,
+        }
+    }
+
+    /// Reads the word list again if it was modified since it was loaded (e.g. with `ttypr words`),
+    /// so the edits apply without restarting.
+    fn reload_words_if_changed(&mut self) {
+        use ttypr::utils::{get_config_dir, read_words_from_file};
+        use ttypr::wordlist::{word_list_modified, word_list_path};
+
+        let (Some(loaded), Ok(config_dir)) = (self.words_modified, get_config_dir()) else {
+            return;
+        };
+        let modified = word_list_modified(&word_list_path(&config_dir, self.config.word_list.as_deref()));
+        if modified.is_some_and(|modified| modified != loaded) {
+            if let Ok(words) = read_words_from_file(&config_dir, &self.config, &mut self.rng)
+                && !words.is_empty()
+            {
+                self.words = words;
+            }
+            self.words_modified = modified;
+        }
+    }
+
+    /// Opens the dictation drill page, with the first phrase to remember.
+    pub fn open_dictation(&mut self) {
+        let reveal = Duration::from_millis(self.config.dictation_reveal_ms);
+        let phrase = self.dictation_phrase();
+        self.dictation = Some(Dictation::with_clock(self.clock.clone(), phrase, reveal));
+        self.damage.add(Region::Screen);
+    }
+
+    /// Shows the next phrase of the dictation drill.
+    pub fn next_dictation_phrase(&mut self) {
+        let phrase = self.dictation_phrase();
+        if let Some(dictation) = self.dictation.as_mut() {
+            dictation.next_phrase(phrase);
+        }
+        self.damage.add(Region::Typing);
+    }
+
+    /// A phrase of the dictation drill - `dictation_words` random words (Words option), the words
+    /// from a random position of the text (Text option), or random characters (ASCII option).
+    pub fn dictation_phrase(&mut self) -> String {
+        let len = self.config.dictation_words.max(1);
+        match self.current_typing_option {
+            CurrentTypingOption::Words if !self.words.is_empty() => {
+                let mut words = vec![];
+                while words.len() < len {
+                    let line = self.gen_one_line_of_words();
+                    if line.is_empty() {
+                        break;
+                    }
+                    words.extend(line.split_whitespace().map(str::to_string));
+                }
+                words.truncate(len);
+                words.join(" ")
+            }
+            CurrentTypingOption::Text if !self.text.is_empty() => {
+                let start = self.rng.random_range(0..self.text.len());
+                self.text.iter().skip(start).take(len).cloned().collect::<Vec<_>>().join(" ")
+            }
+            _ => self.gen_one_line_of_ascii().chars().take(len).collect(),
+        }
+    }
+
+    /// Opens the word list picker page, with the active list picked.
+    pub fn open_word_list_picker(&mut self) {
+        use ttypr::content::load_content_info;
+        use ttypr::utils::get_config_dir;
+        use ttypr::drills::GENERATED_DRILLS;
+        use ttypr::wordlist::{list_word_lists, word_list_path, BUILTIN_WORD_LISTS};
+
+        self.word_list_choices = match get_config_dir() {
+            Ok(config_dir) => list_word_lists(&config_dir)
+                .into_iter()
+                .map(|name| {
+                    let info = load_content_info(&word_list_path(&config_dir, Some(&name)));
+                    (name, info)
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+        // The built-in keyword sets and the generated drills come after the user's lists
+        let builtin = BUILTIN_WORD_LISTS.iter().map(|list| (list, "programming"));
+        let generated = GENERATED_DRILLS.iter().map(|list| (list, "drill"));
+        for ((name, display_name), category) in builtin.chain(generated) {
+            let info = ContentInfo {
+                name: Some(display_name.to_string()),
+                category: Some(category.to_string()),
+                ..ContentInfo::default()
+            };
+            self.word_list_choices.push((name.to_string(), info));
+        }
+        self.word_list_tag = None;
+        self.word_list_choice = match &self.config.word_list {
+            Some(name) => self.word_list_choices.iter().position(|(choice, _)| choice == name).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        self.show_word_lists = true;
+        self.damage.add(Region::Screen);
+    }
+
+    /// Opens the word list picker filtered to the drills generated by ttypr (the drills submenu),
+    /// with the active drill (or the first one) picked.
+    pub fn open_drill_picker(&mut self) {
+        self.open_word_list_picker();
+        self.word_list_tag = Some("drill".to_string());
+        let shown = self.shown_word_lists();
+        let active = self.config.word_list.as_ref().and_then(|name| shown.iter().position(|(choice, _)| choice == name));
+        self.word_list_choice = active.unwrap_or(0) + 1;
+    }
+
+    /// The word lists shown on the picker page - the ones with the tag filtered by.
+    pub fn shown_word_lists(&self) -> Vec<&(String, ContentInfo)> {
+        self.word_list_choices
+            .iter()
+            .filter(|(_, info)| self.word_list_tag.as_ref().is_none_or(|tag| info.has_tag(tag)))
+            .collect()
+    }
+
+    /// Switches the tag the word list picker page is filtered by, to the next
+    /// (or the previous) one of the tags of the lists. Past the last one - no filter.
+    pub fn switch_word_list_tag(&mut self, next: bool) {
+        use ttypr::content::content_tags;
+
+        let tags = content_tags(self.word_list_choices.iter().map(|(_, info)| info));
+        // Position 0 is no filter, the tags come after it
+        let position = match &self.word_list_tag {
+            Some(tag) => tags.iter().position(|own| own == tag).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let position = if next { (position + 1) % (tags.len() + 1) } else { (position + tags.len()) % (tags.len() + 1) };
+        self.word_list_tag = position.checked_sub(1).map(|i| tags[i].clone());
+        self.word_list_choice = 0;
+    }
+
+    /// The accuracy of the last `progression_sessions` sessions, if the harder word lists are
+    /// locked until it reaches `progression_accuracy` (`None` if there's no such lock).
+    pub fn progression_accuracy(&self) -> Option<f64> {
+        use ttypr::stats::recent_accuracy;
+
+        if self.config.progression_accuracy <= 0.0 {
+            return None;
+        }
+        Some(recent_accuracy(&self.stats.sessions, self.config.progression_sessions).unwrap_or(0.0))
+    }
+
+    /// Whether a word list is locked - it's harder than the active list, and the accuracy
+    /// of the last sessions hasn't reached the configured one yet.
+    pub fn word_list_locked(&self, info: &ContentInfo) -> bool {
+        let Some(accuracy) = self.progression_accuracy() else {
+            return false;
+        };
+        // words.txt and the lists without a difficulty count as the easiest ones
+        let active_rank = self
+            .word_list_choices
+            .iter()
+            .find(|(name, _)| Some(name) == self.config.word_list.as_ref())
+            .and_then(|(_, info)| info.difficulty_rank())
+            .unwrap_or(0);
+        info.difficulty_rank().unwrap_or(0) > active_rank && accuracy < self.config.progression_accuracy
+    }
+
+    /// Makes the word list picked on the picker page the active one, and loads its words.
+    /// A locked list (see `word_list_locked`) isn't picked, the page stays open.
+    ///
+    /// If the Words option is the current one, its lines are generated again.
+    pub fn pick_word_list(&mut self) -> color_eyre::Result<()> {
+        use ttypr::utils::{default_words, get_config_dir, read_words_from_file};
+        use ttypr::wordlist::{word_list_modified, word_list_path};
+
+        let picked = self.word_list_choice.checked_sub(1).and_then(|i| self.shown_word_lists().get(i).copied());
+        if picked.is_some_and(|(_, info)| self.word_list_locked(info)) {
+            return Ok(());
+        }
+        let picked = picked.map(|(name, _)| name.clone());
+
+        self.show_word_lists = false;
+        self.damage.add(Region::Screen);
+
+        self.config.word_list = picked;
+        self.word_list_choices.clear();
+
+        let config_dir = get_config_dir()?;
+        self.words = read_words_from_file(&config_dir, &self.config, &mut self.rng).unwrap_or_default();
+        self.words_modified = None;
+        if !self.words.is_empty() {
+            self.config.use_default_word_set = false;
+            self.words_modified = word_list_modified(&word_list_path(&config_dir, self.config.word_list.as_deref()));
+        } else if self.config.use_default_word_set {
+            self.words = default_words();
+        }
+
+        if let CurrentTypingOption::Words = self.current_typing_option {
+            self.clear_typing_buffers();
+            if !self.words.is_empty() {
+                for _ in 0..self.lines_shown() {
+                    let one_line = self.gen_one_line_of_words();
+                    self.engine.push_line(&one_line);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Constructs a line of random words that fits within the configured line length.
+    ///
+    /// The words are sampled by their weights (see `word_weights`).
+    pub fn gen_one_line_of_words(&mut self) -> String {
+        let weights = self.word_weights();
+        random_words_line(&mut self.rng, &self.words, &weights, self.line_len)
+    }
+
+    /// Sampling weights of the words, for the Words option.
+    ///
+    /// Banned words and the words with excluded characters are left out (unless all of the
+    /// words are), and favorite words are `favorite_boost` times more likely to be generated.
+    pub fn word_weights(&self) -> Vec<u32> {
+        let excluded = &self.config.excluded_chars;
+        let left_out = |word: &String| self.config.banned_words.contains(word) || word.chars().any(|c| excluded.contains(c));
+        let skip_left_out = self.words.iter().any(|word| !left_out(word));
+
+        self.words
+            .iter()
+            .map(|word| {
+                if skip_left_out && left_out(word) {
+                    0
+                } else if self.config.favorite_words.contains(word) {
+                    self.config.favorite_boost.max(1)
+                } else {
+                    1
+                }
+            })
+            .collect()
+    }
+
+    /// Retrieves the next line of text from the source, respecting the configured line length.
+    ///
+    /// At the end of the text, starts over from the beginning - or, depending on
+    /// `end_of_text` in the config, stops there (the remaining lines are empty).
+    ///
+    /// With `hyphenate` in the config, a word that doesn't fit is split across the lines
+    /// (the line then ends with its first part instead of a space).
+    pub fn get_one_line_of_text(&mut self) -> String {
+        let mut line_of_text = vec![];
+        // (Hyphenation) - Start with the rest of the word split at the end of the last line
+        if let Some(rest) = self.hyphenated_rest.take() {
+            line_of_text.push(rest);
+        }
+        loop {
+            // If reached the end of the text - set position to 0, or stop
+            if self.config.skip_len == self.text.len() {
+                if !self.loops_at_end_of_text() {
+                    self.text_finished = true;
+                    break;
+                }
+                self.config.skip_len = 0;
+                self.config.text_started_at = None;
+            }
+
+            // Remember when the text was started, for the stats of the whole text
+            if self.config.text_started_at.is_none() {
+                self.config.text_started_at = Some(chrono::Local::now().timestamp());
+            }
+
+            line_of_text.push(self.text[self.config.skip_len].clone());
+            let current_line_len = line_of_text.join(" ").chars().count();
+            self.config.skip_len += 1;
+
+            if current_line_len > self.line_len {
+                let word = line_of_text.pop().unwrap_or_default();
+                self.config.skip_len -= 1;
+
+                // (Hyphenation) - Fill the rest of the line with the first part of the word
+                if self.config.hyphenate {
+                    let line_len = line_of_text.join(" ").chars().count();
+                    let room = self.line_len.saturating_sub(line_len + usize::from(!line_of_text.is_empty()));
+                    if let Some((first, rest)) = hyphenate(&word, room) {
+                        line_of_text.push(first);
+                        self.hyphenated_rest = Some(rest);
+                        self.config.skip_len += 1;
+                        return line_of_text.join(" ");
+                    }
+                }
+                break;
+            }
+        }
+
+        let mut current_line = line_of_text.join(" ");
+        if !current_line.is_empty() {
+            current_line.push(' ');
+        }
+        current_line
+    }
+
+    /// Whether the Text option starts over once the end of the text is reached
+    /// (`end_of_text` other than "stop" and "prompt").
+    fn loops_at_end_of_text(&self) -> bool {
+        !matches!(self.config.end_of_text.as_str(), "stop" | "prompt")
+    }
+
+    /// Ends the Text option session once the whole text was typed, and shows the
+    /// completion page with the stats of the whole text.
+    ///
+    /// The position is set back to the beginning of the text (an ad-hoc text is
+    /// put away). With `end_of_text = "prompt"`, the texts in the `texts` directory
+    /// are offered to be picked as the next one.
+    pub fn complete_text(&mut self) {
+        use ttypr::utils::{get_config_dir, list_texts};
+
+        self.current_mode = CurrentMode::Menu;
+        self.test_started_at = None;
+        self.composition.clear();
+        self.finish_session();
+
+        let since = self.config.text_started_at.take().unwrap_or(0);
+        self.completed_text = Some(self.stats.text_totals_since(since));
+        self.text_choice = 0;
+        self.text_choices = match (self.config.end_of_text.as_str(), get_config_dir()) {
+            ("prompt", Ok(config_dir)) => list_texts(&config_dir),
+            _ => vec![],
+        };
+
+        if self.stashed_text.is_some() {
+            self.restore_stashed_text();
+        } else {
+            self.config.skip_len = 0;
+            self.config.chapter_positions.clear();
+        }
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+    }
+
+    /// Closes the completion page, loading the picked text (if any).
+    ///
+    /// The picked text replaces `text.txt`, and is started from the beginning.
+    pub fn pick_text_choice(&mut self) -> color_eyre::Result<()> {
+        use ttypr::utils::{calculate_text_txt_hash, get_config_dir, read_text_chapters, read_text_from_file};
+
+        self.completed_text = None;
+        self.damage.add(Region::Screen);
+
+        let choices = std::mem::take(&mut self.text_choices);
+        let Some(path) = self.text_choice.checked_sub(1).and_then(|i| choices.get(i)) else {
+            return Ok(());
+        };
+
+        let config_dir = get_config_dir()?;
+        std::fs::copy(path, config_dir.join("text.txt"))?;
+        self.config.text_name = path.file_name().map(|name| name.to_string_lossy().into_owned());
+        self.text = read_text_from_file(&config_dir, &self.config)?;
+        self.chapters = read_text_chapters(&config_dir)?;
+        self.config.last_text_txt_hash = calculate_text_txt_hash(&config_dir).ok();
+        self.config.use_default_text_set = false;
+        self.config.skip_len = 0;
+        self.config.chapter_positions.clear();
+        self.config.text_started_at = None;
+
+        self.clear_typing_buffers();
+        if !self.text.is_empty() {
+            self.populate_text_lines();
+        }
+        Ok(())
+    }
+
+    /// Set the state of the last typed character to determine its color,
+    /// and record it if it was a mistype.
+    pub fn update_char_state(&mut self) {
+        use ttypr::utils::remove_mistake;
+
+        let position = self.engine.cursor() - 1;
+        if self.engine.check_last_char() != CharState::Incorrect {
+            self.combo.on_correct();
+
+            // (Slips) - A mistake right away followed by the correct next key is a slip, not an error
+            if let Some((mistyped_at, mistyped_position)) = self.slip_candidate.take()
+                && mistyped_position + 1 == position
+                && self.clock.now().duration_since(mistyped_at) <= Duration::from_millis(self.config.slip_window_ms)
+            {
+                let expected = self.engine.charset[mistyped_position].to_string();
+                self.engine.session.on_slip(&expected);
+                if self.config.save_mistyped {
+                    remove_mistake(&mut self.config.mistyped_chars, &expected);
+                }
+            }
+        } else {
+            self.combo.on_error();
+
+            if self.config.slip_window_ms > 0 {
+                self.slip_candidate = Some((self.clock.now(), position));
+            }
+
+            // (Error flash) - Highlight the mistyped character for a moment
+            if self.config.error_flash && !self.config.low_bandwidth && !self.config.silent_errors {
+                self.error_flash = Some(self.clock.now());
+            }
+
+            // (Forced pause) - Lock the input for a moment after a burst of errors (a negative, or
+            // not a number, pause from a hand-edited config is no pause)
+            if let Ok(pause) = Duration::try_from_secs_f64(self.config.forced_pause_secs)
+                && !pause.is_zero()
+                && self.slow_down.on_error(pause)
+            {
+                self.damage.add(Region::Typing);
+            }
+
+            // (Timed test) - With the penalty mode, errors use up time
+            if self.test_started_at.is_some() {
+                self.test_penalty_secs += self.config.error_penalty_secs;
+            }
+
+            // Add the mistyped character to mistyped characters list
+            if self.config.save_mistyped {
+                let expected = self.engine.charset[self.engine.cursor() - 1];
+                let count = self.config.mistyped_chars.entry(expected.to_string()).or_insert(0);
+                *count += 1;
+            }
+        }
+    }
+
+    /// Number of displayed lines - the typed lines kept above the line being typed, the line
+    /// itself and the upcoming lines (`history_lines` and `preview_lines` in the config).
+    pub fn lines_shown(&self) -> usize {
+        self.config.history_lines + 1 + self.config.preview_lines
+    }
+
+    /// Whether only the line being typed is displayed (`single_line`, or the compact mode).
+    pub fn single_line(&self) -> bool {
+        self.config.single_line || self.compact
+    }
+
+    /// (Compact mode) - Fits the lines to a small pane (a tmux popup) of the given width, with a
+    /// column of margin at each side, displays only the line being typed and mutes the notifications.
+    pub fn set_compact(&mut self, width: u16) {
+        self.compact = true;
+        self.line_len = self.line_len.min((width as usize).saturating_sub(2)).max(MIN_COMPACT_LINE_LEN);
+        self.notifications.muted = true;
+    }
+
+    /// Whether typed characters are composed into words before being compared.
+    ///
+    /// Enabled with `ime_composition` in the config, for the Words and Text options
+    /// (the ASCII option has no words to compose).
+    pub fn ime_composition_enabled(&self) -> bool {
+        self.config.ime_composition && !matches!(self.current_typing_option, CurrentTypingOption::Ascii)
+    }
+
+    /// Adds a typed character to the IME composition of the current word.
+    ///
+    /// Returns false if the character isn't part of a word (a space, or typed
+    /// where a space is expected), so it has to be handled as a regular keystroke.
+    /// The composition is compared once it's committed - by a space, or Enter - and not
+    /// once it's as long as the word (the reading of a word can be longer than the word).
+    pub fn compose(&mut self, c: char) -> bool {
+        if c == ' ' || self.engine.word_len_at_cursor() == 0 {
+            self.commit_composition();
+            return false;
+        }
+
+        self.composition.push(c);
+        true
+    }
+
+    /// Compares the composed string with the current word, at the word level.
+    ///
+    /// Every character of the word is compared with the composed character at the same
+    /// position. Missing characters are errors, and extra ones make the last character
+    /// of the word an error (both are recorded as `char::REPLACEMENT_CHARACTER`).
+    /// Intermediate composition states (edited with Backspace before this) are never
+    /// marked as errors.
+    pub fn commit_composition(&mut self) {
+        if self.composition.is_empty() {
+            return;
+        }
+
+        let word_len = self.engine.word_len_at_cursor();
+        let mut composed: Vec<char> = self.composition.chars().collect();
+        self.composition.clear();
+        if composed.len() > word_len {
+            composed.truncate(word_len);
+            composed[word_len - 1] = char::REPLACEMENT_CHARACTER;
+        }
+
+        for i in 0..word_len {
+            self.engine.input_chars.push_back(composed.get(i).copied().unwrap_or(char::REPLACEMENT_CHARACTER));
+            self.update_char_state();
+            self.update_lines();
+        }
+        self.damage.add(Region::Typing);
+    }
+
+    /// Manages the scrolling display by updating the character buffers.
+    ///
+    /// When the user finishes typing the second line, this function removes the
+    /// first line's data from the buffers and appends a new line, creating a
+    /// continuous scrolling effect.
+    pub fn update_lines(&mut self) {
+        // (For the text option) - If the whole text was typed
+        if self.text_finished && self.engine.input_chars.len() >= self.engine.charset.len() {
+            self.complete_text();
+            return;
+        }
+
+        if let Some(line) = self.engine.typed_line() {
+            // (Error markers) - Mark where the errors of the line just typed were, for a moment
+            if self.config.error_markers && !self.config.low_bandwidth && !self.config.silent_errors {
+                self.error_markers = Some(self.clock.now());
+            }
+            // (Line summary) - Display its speed and errors next to it, for a moment (not with the
+            // silent errors, which don't show the errors in the lines)
+            let started_at = self.line_started_at.take();
+            if self.config.line_summary && !self.config.low_bandwidth && !self.config.silent_errors
+                && let Some(started_at) = started_at
+            {
+                let errors = line
+                    .clone()
+                    .filter(|position| matches!(self.engine.char_states[*position], CharState::Incorrect | CharState::Corrected))
+                    .count();
+                let minutes = self.clock.now().duration_since(started_at).as_secs_f64() / 60.0;
+                let wpm = if minutes > 0.0 { (line.len() as f64 / 5.0) / minutes } else { 0.0 };
+                self.line_summary = Some(LineSummary { wpm, errors, shown_at: self.clock.now() });
+            }
+        }
+
+        // If reached the end of the second line
+        if self.engine.needs_new_line(self.config.history_lines) {
+            // Remove the first line from the buffers, keeping it for the review page
+            let dropped = self.engine.drop_first_line();
+            self.pace.on_line_dropped(dropped.chars.len());
+            self.scrollback.push_back(dropped);
+            if self.scrollback.len() > SCROLLBACK_LINES {
+                self.scrollback.pop_front();
+            }
+
+            // One line of ascii characters/words/text
+            let one_line = match self.current_typing_option {
+                CurrentTypingOption::Ascii => { self.gen_one_line_of_ascii() },
+                CurrentTypingOption::Words => { self.gen_one_line_of_words() },
+                CurrentTypingOption::Text => { self.get_one_line_of_text() },
+            };
+            self.engine.push_line(&one_line);
+
+            // (Smooth line advance) - Move the lines into place over a few frames
+            if self.config.smooth_line_advance && !self.single_line() && !self.config.low_bandwidth {
+                self.line_advance = Some(self.clock.now());
+            }
+
+            // (Timed test) - Saved with every line, to be resumed if it's interrupted
+            if self.test_started_at.is_some() {
+                self.save_test_checkpoint();
+            }
+        }
+    }
+
+    /// (Timed test) - Saves the running test, to be resumed at the next launch if it's interrupted.
+    pub fn save_test_checkpoint(&self) {
+        let (Some(dir), Some(elapsed)) = (&self.checkpoint_dir, self.test_elapsed_secs()) else {
+            return;
+        };
+        let option = self.current_typing_option.name();
+        if let Some(checkpoint) = TestCheckpoint::capture(&self.engine, option, self.test_duration_secs(), elapsed) {
+            save_checkpoint(&checkpoint, dir).unwrap_or_else(|err| {
+                ttypr::crash::log_error(&format!("Failed to save the timed test: {}", err));
+            });
+        }
+    }
+
+    /// (Timed test) - Resumes the test interrupted at the last run - its lines and session are
+    /// restored, and it carries on with the next key press.
+    pub fn resume_interrupted_test(&mut self) {
+        let Some(checkpoint) = self.interrupted_test.take() else {
+            return;
+        };
+        // Set directly - the lines come from the checkpoint, none are generated
+        if let Some(option) = CurrentTypingOption::from_name(&checkpoint.typing_option) {
+            if let CurrentTypingOption::Text = self.current_typing_option {
+                self.leave_text_option();
+            }
+            self.current_typing_option = option;
+        }
+        self.clear_typing_buffers();
+        self.show_resume_prompt = false;
+        self.config.test_duration_secs = checkpoint.test_secs;
+        checkpoint.restore(&mut self.engine);
+        self.resumed_secs = Some((checkpoint.elapsed_secs, checkpoint.session_secs));
+        self.current_mode = CurrentMode::Typing;
+        self.damage.add(Region::Screen);
+    }
+
+    /// (Timed test) - Drops the test interrupted at the last run, instead of resuming it.
+    pub fn discard_interrupted_test(&mut self) {
+        self.interrupted_test = None;
+        if let Some(dir) = &self.checkpoint_dir {
+            remove_checkpoint(dir);
         }
+        self.damage.add(Region::Screen);
+    }
 
-        // Use the default text set if previously selected to use it
-        if self.config.use_default_text_set {
-            self.text = default_text();
+    /// Ends the current typing session, recording it in the stats.
+    ///
+    /// Called when leaving Typing mode. Sessions with too few key presses are not recorded.
+    pub fn finish_session(&mut self) {
+        use ttypr::stats::{goal_reached, profile_dir, save_stats};
+        use ttypr::utils::get_config_dir;
+
+        // (Timed test) - The saved test is finished
+        if let Some(dir) = &self.checkpoint_dir
+            && self.interrupted_test.is_none()
+        {
+            remove_checkpoint(dir);
+        }
+        self.resumed_secs = None;
+
+        // The combo, the pace caret, the WPM and the line timing don't carry over to the next session
+        self.combo = Combo::with_clock(self.clock.clone());
+        self.wpm.clear();
+        self.pace.stop();
+        self.line_started_at = None;
+        let distraction_report = self.distractions.finish();
+
+        // The demo isn't recorded
+        if self.demo {
+            self.engine.session.finish(self.current_typing_option.name());
+            return;
         }
 
-        // If the contents of the .config/ttypr/text.txt changed -
-        // reset the position to the beginning
-        if self.config.last_text_txt_hash != calculate_text_txt_hash(&config_dir).ok() {
-            self.config.skip_len = 0;
+        let practice_block = self.session_block.take();
+        if let Some(mut session) = self.engine.session.finish(self.current_typing_option.name()) {
+            self.distraction_report = distraction_report;
+            // (Word stats) - The random characters of the ASCII option aren't words
+            if let CurrentTypingOption::Ascii = self.current_typing_option {
+                session.words.clear();
+            }
+            session.keyboard = self.config.active_keyboard.clone();
+            session.repeat_seed = self.config.repeat_seed;
+            session.practice_block = practice_block;
+            ttypr::crash::log(&format!("Finished a session ({}, {} keystrokes)", session.typing_option, session.keystrokes));
+            self.stats.sessions.push(session);
+
+            // A desktop notification, for when the terminal is in the background
+            if self.config.desktop_notifications
+                && let Some(session) = self.stats.sessions.last()
+            {
+                let finished = if self.stats.is_personal_best() { "New personal best" } else { "Session finished" };
+                self.desktop_notification = Some(format!(
+                    "ttypr: {} - {:.0} WPM, {:.1}% accuracy",
+                    finished,
+                    session.wpm(),
+                    session.accuracy()
+                ));
+            }
+
+            // Record reaching the daily goal (once a day)
+            let today = chrono::Local::now().date_naive();
+            let (secs, sessions) = self.stats.practiced_on(today);
+            if goal_reached(self.config.daily_goal_minutes, self.config.daily_goal_sessions, secs, sessions)
+                && self.stats.record_goal_completion(today)
+            {
+                self.notifications.show_goal();
+            }
+
+            // Record reaching a new level
+            if self.stats.record_level(chrono::Local::now().timestamp()).is_some() {
+                self.notifications.show_level_up();
+            }
+
+            if let Ok(config_dir) = get_config_dir() {
+                let stats_dir = profile_dir(&config_dir, &self.config.active_profile);
+                save_stats(&self.stats, &stats_dir).unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to save stats: {}", err));
+                });
+            }
         }
+    }
 
-        // Calculate the hash of the .config/ttypr/text.txt to
-        // compare to the previously generated one and determine
-        // whether the file contents have changed
-        self.config.last_text_txt_hash = calculate_text_txt_hash(&config_dir).ok();
+    /// Attaches a note to the last session (an empty one removes it), and saves the stats.
+    pub fn save_session_note(&mut self, note: &str) -> color_eyre::Result<()> {
+        use ttypr::stats::{profile_dir, save_stats};
+        use ttypr::utils::get_config_dir;
 
-        Ok(())
+        if !self.set_session_note(note) {
+            return Ok(());
+        }
+        let stats_dir = profile_dir(&get_config_dir()?, &self.config.active_profile);
+        save_stats(&self.stats, &stats_dir).map_err(|err| color_eyre::eyre::eyre!("{}", err))
     }
 
-    /// Constructs a line of random ASCII characters that fits within the configured line length.
-    pub fn gen_one_line_of_ascii(&mut self) -> String {
-        let mut line_of_ascii = vec![];
-        for _ in 0..self.line_len {
-            let index = rand::rng().random_range(0..ASCII_CHARSET.len());
-            let character = ASCII_CHARSET[index];
-            line_of_ascii.push(character.to_string())
+    /// (Targeted drill) - Generates a drill from the errors of the last session (see `targeted_drill`)
+    /// and saves it as a word list of its own, tagged as a drill. Returns its name (`None` - the
+    /// session had no errors to drill).
+    pub fn save_targeted_drill(&self) -> color_eyre::Result<Option<String>> {
+        use ttypr::drills::targeted_drill;
+        use ttypr::utils::{default_words, get_config_dir};
+        use ttypr::wordlist::{word_list_path, write_word_list};
+
+        let Some(session) = self.stats.sessions.last() else {
+            return Ok(None);
+        };
+        let words = if self.words.is_empty() { default_words() } else { self.words.clone() };
+        let drill = targeted_drill(session, &self.stats.sessions, &self.config.mistyped_chars, &words);
+        if drill.is_empty() {
+            return Ok(None);
         }
-        line_of_ascii.join("")
+
+        let started_at = session.started_at_local();
+        let path = word_list_path(&get_config_dir()?, Some(&format!("targeted-{}", started_at.format("%Y%m%d-%H%M"))));
+        let name = format!("Targeted drill {}", started_at.format("%Y-%m-%d %H:%M"));
+        write_word_list(&path, &drill)?;
+        std::fs::write(path.with_extension("toml"), format!("name = \"{}\"\ncategory = \"drill\"\n", name))?;
+        ttypr::crash::log(&format!("Saved a targeted drill of {} words", drill.len()));
+        Ok(Some(name))
     }
 
-    /// Constructs a line of random words that fits within the configured line length.
-    pub fn gen_one_line_of_words(&mut self) -> String {
-        let mut line_of_words = vec![];
-        loop {
-            let index = rand::rng().random_range(0..self.words.len());
-            let word = self.words[index].clone();
-            line_of_words.push(word);
+    /// Removes the session of the timed test left unfinished (quitting with `confirm_quit`), and saves the stats.
+    pub fn abandon_unfinished_test(&mut self) -> color_eyre::Result<()> {
+        use ttypr::stats::{profile_dir, save_stats};
+        use ttypr::utils::get_config_dir;
 
-            let current_line_len = line_of_words.join(" ").chars().count();
+        if !self.remove_unfinished_test() {
+            return Ok(());
+        }
+        let stats_dir = profile_dir(&get_config_dir()?, &self.config.active_profile);
+        save_stats(&self.stats, &stats_dir).map_err(|err| color_eyre::eyre::eyre!("{}", err))
+    }
 
-            if current_line_len > self.line_len {
-                line_of_words.pop();
-                let mut current_line = line_of_words.join(" ");
-                if !current_line.is_empty() {
-                    current_line.push(' ');
-                }
-                return current_line; 
-            };
+    /// Removes the session of the timed test left unfinished from the stats.
+    /// Returns `false` if there's none.
+    pub fn remove_unfinished_test(&mut self) -> bool {
+        let Some(id) = self.unfinished_test.take() else {
+            return false;
         };
+        let recorded = self.stats.sessions.len();
+        self.stats.sessions.retain(|session| session.id != id);
+        self.stats.sessions.len() < recorded
     }
 
-    /// Retrieves the next line of text from the source, respecting the configured line length.
-    pub fn get_one_line_of_text(&mut self) -> String {
-        let mut line_of_text = vec![];
-        loop {
-            // If reached the end of the text - set position to 0
-            if self.config.skip_len == self.text.len() { self.config.skip_len = 0 }
+    /// Attaches a note to the last session, trimmed (an empty one removes it).
+    /// Returns `false` if there's no session to attach it to.
+    pub fn set_session_note(&mut self, note: &str) -> bool {
+        let Some(session) = self.stats.sessions.last_mut() else {
+            return false;
+        };
+        let note = note.trim();
+        session.note = if note.is_empty() { None } else { Some(note.to_string()) };
+        true
+    }
 
-            line_of_text.push(self.text[self.config.skip_len].clone());
-            let current_line_len = line_of_text.join(" ").chars().count();
-            self.config.skip_len += 1;
+    /// Turns repeat mode on (with a new seed, for new material) or off.
+    pub fn toggle_repeat_mode(&mut self) {
+        self.config.repeat_seed = match self.config.repeat_seed {
+            Some(_) => None,
+            None => Some(self.rng.random()),
+        };
+        self.repeat_text_start = None;
+    }
 
-            if current_line_len > self.line_len {
-                line_of_text.pop();
-                self.config.skip_len -= 1;
+    /// (Repeat mode) - Starts an attempt at the repeated material - the ASCII/Words lines are
+    /// generated again from the same seed, and the text starts over at the same position.
+    pub fn start_repeat_attempt(&mut self) {
+        let Some(seed) = self.config.repeat_seed else {
+            return;
+        };
 
-                let mut current_line = line_of_text.join(" ");
-                if !current_line.is_empty() {
-                    current_line.push(' ');
+        match self.current_typing_option {
+            CurrentTypingOption::Text => {
+                if self.text.is_empty() {
+                    return;
+                }
+                let start = match self.repeat_text_start {
+                    Some(start) => start,
+                    None => *self.repeat_text_start.insert(self.text_position()),
+                };
+                self.config.skip_len = start;
+                self.clear_typing_buffers();
+                self.populate_text_lines();
+            }
+            CurrentTypingOption::Ascii | CurrentTypingOption::Words => {
+                self.rng = StdRng::seed_from_u64(seed);
+                self.clear_typing_buffers();
+                for _ in 0..self.lines_shown() {
+                    let one_line = match self.current_typing_option {
+                        CurrentTypingOption::Ascii => self.gen_one_line_of_ascii(),
+                        _ => self.gen_one_line_of_words(),
+                    };
+                    self.engine.push_line(&one_line);
                 }
-                return current_line;
             }
         }
+        self.damage.add(Region::Screen);
     }
 
-    /// Set the ID for the last typed character to determine its color,
-    /// and record it if it was a mistype.
-    pub fn update_id_field(&mut self) {
-        // Number of characters the user typed, to compare with the charset
-        let pos = self.input_chars.len() - 1;
-
-        // If the input character matches the characters in the
-        // charset replace the 0 in ids with 1 (correct), 2 (incorrect)
-        if self.input_chars[pos] == self.charset[pos] {
-            self.ids[pos] = 1;
-        } else {
-            self.ids[pos] = 2;
-            
-            // Add the mistyped character to mistyped characters list
-            if self.config.save_mistyped {
-                let count = self.config.mistyped_chars.entry(self.charset[pos].to_string()).or_insert(0);
-                *count += 1;
+    /// Switches the keyboard the sessions are tagged with to the next one of `keyboards`
+    /// in the config. Past the last one - untagged.
+    pub fn switch_keyboard(&mut self) {
+        let keyboards = &self.config.keyboards;
+        // Position 0 is untagged, the keyboards come after it
+        let position = match &self.config.active_keyboard {
+            Some(keyboard) => keyboards.iter().position(|own| own == keyboard).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let next = (position + 1) % (keyboards.len() + 1);
+        self.config.active_keyboard = next.checked_sub(1).map(|i| keyboards[i].clone());
+    }
+
+    /// Switches the keyboard the summary page is filtered by to the next one the sessions
+    /// were typed on. Past the last one - no filter.
+    pub fn switch_summary_keyboard(&mut self) {
+        use ttypr::stats::session_keyboards;
+
+        let keyboards = session_keyboards(&self.stats.sessions);
+        let position = match &self.summary_keyboard {
+            Some(keyboard) => keyboards.iter().position(|own| own == keyboard).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        let next = (position + 1) % (keyboards.len() + 1);
+        self.summary_keyboard = next.checked_sub(1).map(|i| keyboards[i].clone());
+        self.summary_offset = 0;
+    }
+
+    /// The weekly/monthly summaries of the sessions shown on the summary page
+    /// (the ones typed on the keyboard filtered by).
+    pub fn summaries(&self) -> Vec<ttypr::stats::PeriodSummary> {
+        use ttypr::stats::summarize_periods;
+
+        match &self.summary_keyboard {
+            Some(keyboard) => {
+                let typed_on: Vec<_> = self.stats.sessions.iter().filter(|s| s.keyboard.as_ref() == Some(keyboard)).cloned().collect();
+                summarize_periods(&typed_on, self.summary_monthly)
             }
+            None => summarize_periods(&self.stats.sessions, self.summary_monthly),
         }
     }
 
-    /// Manages the scrolling display by updating the character buffers.
-    ///
-    /// When the user finishes typing the second line, this function removes the
-    /// first line's data from the buffers and appends a new line, creating a
-    /// continuous scrolling effect.
-    pub fn update_lines(&mut self) {
-        // If reached the end of the second line
-        if self.input_chars.len() == self.lines_len[0] + self.lines_len[1] {
-            // Remove first line amount of characters from the character set, 
-            // the user inputted characters, and ids. 
-            for _ in 0..self.lines_len[0] {
-                self.charset.pop_front();
-                self.input_chars.pop_front();
-                self.ids.pop_front();
-            }
-        
-            // One line of ascii characters/words/text
-            let one_line = match self.current_typing_option {
-                CurrentTypingOption::Ascii => { self.gen_one_line_of_ascii() },
-                CurrentTypingOption::Words => { self.gen_one_line_of_words() },
-                CurrentTypingOption::Text => { self.get_one_line_of_text() },
-            };
-        
-            // Convert that line into characters
-            let characters: Vec<char> = one_line.chars().collect();
-        
-            // Remove the length of the first line of characters from the front, 
-            // and push the new one to the back.
-            self.lines_len.pop_front();
-            self.lines_len.push_back(characters.len());
-        
-            // Push new amount of characters (words) to charset, and that amount of 0's to ids
-            for char in characters {
-                self.charset.push_back(char.to_string());
-                self.ids.push_back(0);
+    /// Returns the names of all the profiles, the default one first.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names = vec!["default".to_string()];
+        for name in &self.config.profiles {
+            if !name.is_empty() && !names.contains(name) {
+                names.push(name.clone());
             }
         }
+        names
+    }
+
+    /// Switches to the next profile, loading its recorded typing history.
+    pub fn switch_profile(&mut self) {
+        use ttypr::stats::{load_stats, profile_dir};
+        use ttypr::utils::get_config_dir;
+
+        let names = self.profile_names();
+        let current = names.iter().position(|name| *name == self.config.active_profile).unwrap_or(0);
+        self.config.active_profile = names[(current + 1) % names.len()].clone();
+
+        self.stats = match get_config_dir() {
+            Ok(config_dir) => load_stats(&profile_dir(&config_dir, &self.config.active_profile)).unwrap_or_default(),
+            Err(_) => Stats::default(),
+        };
+        self.compared_profile = None;
+    }
+
+    /// Switches the profile compared to the active one to the next (or previous) other profile.
+    /// Does nothing if there are no other profiles.
+    pub fn switch_compared_profile(&mut self, forward: bool) {
+        use ttypr::stats::{load_stats, profile_dir};
+        use ttypr::utils::get_config_dir;
+
+        let others: Vec<String> = self
+            .profile_names()
+            .into_iter()
+            .filter(|name| *name != self.config.active_profile)
+            .collect();
+        if others.is_empty() {
+            return;
+        }
+
+        let next = match &self.compared_profile {
+            Some((name, _)) => {
+                let current = others.iter().position(|other| other == name).unwrap_or(0);
+                if forward {
+                    (current + 1) % others.len()
+                } else {
+                    (current + others.len() - 1) % others.len()
+                }
+            }
+            None => 0,
+        };
+
+        let name = others[next].clone();
+        let stats = match get_config_dir() {
+            Ok(config_dir) => load_stats(&profile_dir(&config_dir, &name)).unwrap_or_default(),
+            Err(_) => Stats::default(),
+        };
+        self.compared_profile = Some((name, stats));
     }
 
     /// Empties the buffers that store the character set, user input, IDs and line lengths.
@@ -504,10 +2686,10 @@ impl App {
     /// This is called when the typing option is switched - to reset the buffers for 
     /// the new content.
     pub fn clear_typing_buffers(&mut self) {
-        self.charset.clear();
-        self.input_chars.clear();
-        self.ids.clear();
-        self.lines_len.clear();
+        self.text_finished = false;
+        self.pace.stop();
+        self.line_started_at = None;
+        self.engine.clear();
     }
 
     /// Switches to the next typing option and generates the text.
@@ -516,7 +2698,7 @@ impl App {
     /// and prepares the application state for the new option. It clears the
     /// existing content in the buffers, generates new content, and signals to update the UI.
     pub(crate) fn switch_typing_option(&mut self) {
-        self.needs_clear = true;
+        self.damage.add(Region::Screen);
         self.notifications.show_option();
         self.clear_typing_buffers();
 
@@ -525,13 +2707,14 @@ impl App {
             // If ASCII - switch to Words
             CurrentTypingOption::Ascii => {
                 self.current_typing_option = CurrentTypingOption::Words;
+                self.reload_words_if_changed();
 
                 // Only generate the lines if the words file was provided or the default set was chosen
                 if !self.words.is_empty() {
-                    // Generate three lines of words
-                    for _ in 0..3 {
+                    // Generate the displayed lines of words
+                    for _ in 0..self.lines_shown() {
                         let one_line = self.gen_one_line_of_words();
-                        self.populate_charset_from_line(one_line);
+                        self.engine.push_line(&one_line);
                     }
                 }
             }
@@ -541,64 +2724,313 @@ impl App {
 
                 // Only generate the lines if the text file was provided or the default text was chosen
                 if !self.text.is_empty() {
-                    for _ in 0..3 {
-                        let one_line = self.get_one_line_of_text();
-                        // Count for how many "words" there were on the first three lines
-                        // to keep position on option switch and exit.
-                        // Otherwise would always skip 3 lines down.
-                        let first_text_gen_len: Vec<String> =
-                            one_line.split_whitespace().map(String::from).collect();
-                        self.first_text_gen_len += first_text_gen_len.len();
-
-                        self.populate_charset_from_line(one_line);
+                    self.populate_text_lines();
+
+                    // Ask whether to continue at the saved position, the first time
+                    if !self.resume_asked && self.text_position() > 0 {
+                        self.show_resume_prompt = true;
                     }
+                    self.resume_asked = true;
                 }
             }
             // If Text - switch to ASCII
             CurrentTypingOption::Text => {
-                // Subtract how many "words" there were on the first three lines
-                if self.config.skip_len >= self.first_text_gen_len {
-                    self.config.skip_len -= self.first_text_gen_len;
-                } else {
-                    self.config.skip_len = 0;
-                }
-                self.first_text_gen_len = 0;
-
+                self.leave_text_option();
                 self.current_typing_option = CurrentTypingOption::Ascii;
 
-                // Generate three lines worth of characters and ids
-                for _ in 0..3 {
+                // Generate the displayed lines worth of characters and their states
+                for _ in 0..self.lines_shown() {
                     let one_line = self.gen_one_line_of_ascii();
-                    self.populate_charset_from_line(one_line);
+                    self.engine.push_line(&one_line);
+                }
+            }
+        }
+        ttypr::crash::log(&format!("Switched to the {} option", self.current_typing_option.name()));
+    }
+
+    /// Gives the position in the text back the words of the generated lines (they weren't
+    /// typed), when leaving the Text option - or brings back the Text option content, when
+    /// leaving the ad-hoc text.
+    fn leave_text_option(&mut self) {
+        if self.stashed_text.is_some() {
+            self.restore_stashed_text();
+        } else {
+            if !self.text.is_empty() {
+                self.save_chapter_position();
+            }
+
+            // Subtract how many "words" there were on the first generated lines
+            if self.config.skip_len >= self.first_text_gen_len {
+                self.config.skip_len -= self.first_text_gen_len;
+            } else {
+                self.config.skip_len = 0;
+            }
+        }
+        self.first_text_gen_len = 0;
+    }
+
+    /// Starts (or starts over) the demo - the given text in the Text option, in Typing mode.
+    ///
+    /// The demo uses the default config, and nothing of it is recorded or saved.
+    pub fn start_demo(&mut self, text: &str) {
+        self.demo = true;
+        self.config.first_boot = false;
+        self.current_mode = CurrentMode::Typing;
+        self.current_typing_option = CurrentTypingOption::Text;
+        self.text = text.split_whitespace().map(String::from).collect();
+        self.config.skip_len = 0;
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+    }
+
+    /// Generates the displayed lines of the Text option starting from the current position.
+    ///
+    /// Keeps count of how many "words" there were on the generated lines, so the
+    /// position can be restored on option switch and exit.
+    pub(crate) fn populate_text_lines(&mut self) {
+        self.hyphenated_rest = None;
+        let mut generated = String::new();
+        for _ in 0..self.lines_shown() {
+            let one_line = self.get_one_line_of_text();
+            generated.push_str(&one_line);
+            self.engine.push_line(&one_line);
+        }
+        // (The parts of a hyphenated word are joined back together)
+        self.first_text_gen_len = generated.split_whitespace().count();
+    }
+
+    /// Returns the position (in "words") of the first displayed line of the Text option.
+    pub fn text_position(&self) -> usize {
+        if self.text.is_empty() {
+            return 0;
+        }
+
+        // The generated lines are ahead of the position by the amount of buffered words
+        let buffered: String = self.engine.charset.iter().collect();
+        let buffered_len = buffered.split_whitespace().count() % self.text.len();
+        (self.config.skip_len + self.text.len() - buffered_len) % self.text.len()
+    }
+
+    /// Returns the index of the chapter the current position is in.
+    pub fn current_chapter(&self) -> usize {
+        let position = self.text_position();
+        self.chapters
+            .iter()
+            .rposition(|&start| start <= position)
+            .unwrap_or(0)
+    }
+
+    /// Returns the current chapter number (starting from 1) and how far
+    /// into the whole text the current position is, in percent.
+    pub fn book_progress(&self) -> (usize, usize) {
+        let percent = if self.text.is_empty() {
+            0
+        } else {
+            self.text_position() * 100 / self.text.len()
+        };
+        (self.current_chapter() + 1, percent)
+    }
+
+    /// Remembers the current position as the resume position of the current chapter.
+    pub fn save_chapter_position(&mut self) {
+        let chapter = self.current_chapter();
+        if self.config.chapter_positions.len() < self.chapters.len() {
+            self.config.chapter_positions.resize(self.chapters.len(), 0);
+        }
+        self.config.chapter_positions[chapter] = self.text_position();
+    }
+
+    /// Jumps to the next (or previous) chapter of the text, resuming at the
+    /// position where that chapter was last left off.
+    pub fn jump_to_chapter(&mut self, forward: bool) {
+        if self.text.is_empty() || self.chapters.len() < 2 {
+            return;
+        }
+
+        self.save_chapter_position();
+
+        let current = self.current_chapter();
+        let target = if forward {
+            (current + 1) % self.chapters.len()
+        } else {
+            (current + self.chapters.len() - 1) % self.chapters.len()
+        };
+
+        // Resume inside the target chapter if a position within it was saved
+        let start = self.chapters[target];
+        let end = self.chapters.get(target + 1).copied().unwrap_or(self.text.len());
+        let saved = self.config.chapter_positions.get(target).copied().unwrap_or(0);
+        self.config.skip_len = if (start..end).contains(&saved) { saved } else { start };
+
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+    }
+
+
+    /// Bans the word at the cursor (Words option), so it's never generated again.
+    pub fn ban_word_at_cursor(&mut self) {
+        if !matches!(self.current_typing_option, CurrentTypingOption::Words) {
+            return;
+        }
+        if let Some(word) = self.engine.word_at_cursor() {
+            if !self.config.banned_words.contains(&word) {
+                self.config.banned_words.push(word);
+            }
+            self.notifications.show_banned();
+            self.damage.add(Region::Screen);
+        }
+    }
+
+    /// Stars the word at the cursor (Words option), so it's generated more often - or
+    /// unstars it, if it's starred already.
+    pub fn toggle_favorite_word_at_cursor(&mut self) {
+        if !matches!(self.current_typing_option, CurrentTypingOption::Words) {
+            return;
+        }
+        if let Some(word) = self.engine.word_at_cursor() {
+            match self.config.favorite_words.iter().position(|favorite| *favorite == word) {
+                Some(index) => {
+                    self.config.favorite_words.remove(index);
                 }
+                None => self.config.favorite_words.push(word),
             }
+            self.notifications.show_favorite();
+            self.damage.add(Region::Screen);
+        }
+    }
+
+    /// The name of the text of the Text option, as shown to the user.
+    pub fn text_name(&self) -> String {
+        if self.config.use_default_text_set {
+            "the default text".to_string()
+        } else {
+            self.config.text_name.clone().unwrap_or_else(|| "text.txt".to_string())
+        }
+    }
+
+    /// Starts the text over from the beginning, instead of the saved position.
+    pub fn restart_text(&mut self) {
+        self.config.skip_len = 0;
+        self.config.chapter_positions.clear();
+        self.config.text_started_at = None;
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+    }
+
+    /// Moves the Text option to the position typed in the "go to" prompt - a percentage
+    /// of the text ("37%") or a word index ("1200").
+    ///
+    /// Returns `false` if the position couldn't be parsed.
+    pub fn seek_text(&mut self, target: &str) -> bool {
+        if self.text.is_empty() {
+            return false;
         }
+
+        let target = target.trim();
+        let position = match target.strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<usize>() {
+                Ok(percent) => percent.min(100) * self.text.len() / 100,
+                Err(_) => return false,
+            },
+            None => match target.parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => return false,
+            },
+        };
+
+        self.save_chapter_position();
+        self.config.skip_len = position.min(self.text.len() - 1);
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+        true
     }
 
-    /// Populates the character set and related fields from a single line of text.
+    /// Loads the given contents as an ad-hoc Text option session.
     ///
-    /// This helper function takes a string, splits it into characters, and updates
-    /// the `charset`, `ids`, and `lines_len` fields of the `App` state. This is
-    /// used to prepare the text that the user will be prompted to type.
-    pub(crate) fn populate_charset_from_line(&mut self, one_line: String) {
-        // Push a line of characters and ids
-        let characters: Vec<char> = one_line.chars().collect();
-        self.lines_len.push_back(characters.len());
-        for char in characters {
-            self.charset.push_back(char.to_string());
-            self.ids.push_back(0);
+    /// The current Text option content and position are put aside (not
+    /// overwritten), and restored when switching away from the Text option or
+    /// exiting. Returns `false` if the contents have nothing to type.
+    pub fn load_adhoc_text(&mut self, contents: &str) -> bool {
+        let words = ttypr::utils::split_items(contents, &self.config);
+        if words.is_empty() {
+            return false;
+        }
+
+        // Put aside the Text option content, unless already practicing an ad-hoc text
+        if self.stashed_text.is_none() {
+            let mut skip_len = self.config.skip_len;
+            if let CurrentTypingOption::Text = self.current_typing_option
+                && !self.text.is_empty()
+            {
+                self.save_chapter_position();
+                skip_len = skip_len.saturating_sub(self.first_text_gen_len);
+            }
+            self.stashed_text = Some(StashedText {
+                text: std::mem::take(&mut self.text),
+                chapters: std::mem::take(&mut self.chapters),
+                skip_len,
+            });
+        }
+
+        self.current_typing_option = CurrentTypingOption::Text;
+        self.text = words;
+        self.chapters = vec![0];
+        self.config.skip_len = 0;
+        self.clear_typing_buffers();
+        self.populate_text_lines();
+        self.damage.add(Region::Screen);
+        true
+    }
+
+    /// Practices the clipboard contents that were read (as an ad-hoc Text option session), and
+    /// shows how it went - a clipboard that couldn't be read, or has nothing to type, leaves the
+    /// session as it is.
+    pub fn practice_clipboard(&mut self, contents: io::Result<String>) {
+        let load = match contents {
+            Err(_) => ClipboardLoad::Failed,
+            Ok(contents) if self.load_adhoc_text(&contents) => ClipboardLoad::Loaded,
+            Ok(_) => ClipboardLoad::Empty,
+        };
+        self.notifications.show_clipboard(load);
+        self.damage.add(Region::Screen);
+    }
+
+    /// Brings back the Text option content put aside by `load_adhoc_text`.
+    pub fn restore_stashed_text(&mut self) {
+        if let Some(stashed) = self.stashed_text.take() {
+            self.text = stashed.text;
+            self.chapters = stashed.chapters;
+            self.config.skip_len = stashed.skip_len;
+            self.first_text_gen_len = 0;
         }
     }
+
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::thread;
+    use ttypr::clock::MockClock;
+
+    /// A recorded session of the Words option - 300 keystrokes in a minute, without errors.
+    fn sample_session() -> ttypr::stats::Session {
+        ttypr::stats::Session {
+            duration_secs: 60.0,
+            typing_option: "Words".to_string(),
+            keystrokes: 300,
+            correct: 300,
+            ..ttypr::stats::Session::default()
+        }
+    }
 
     #[test]
     fn test_notifications_on_tick() {
-        let mut notifications = Notifications::new();
+        let clock = MockClock::new();
+        let mut notifications = Notifications::with_clock(Rc::new(clock.clone()));
 
         // Should return false when no notification is active
         assert!(!notifications.on_tick());
@@ -611,8 +3043,12 @@ mod tests {
         // Should still return false immediately after
         assert!(!notifications.on_tick());
 
+        // Still shown at exactly 2 seconds
+        clock.advance(NOTIFICATION_DURATION);
+        assert!(!notifications.on_tick());
+
         // Wait for more than 2 seconds
-        thread::sleep(Duration::from_secs(3));
+        clock.advance(Duration::from_millis(1));
 
         // Now on_tick should return true and hide notifications
         assert!(notifications.on_tick());
@@ -622,7 +3058,7 @@ mod tests {
 
     #[test]
     fn test_notifications_hide_all() {
-        let mut notifications = Notifications::new();
+        let mut notifications = Notifications::with_clock(Rc::new(MockClock::new()));
 
         // Show some notifications
         notifications.show_mode();
@@ -645,7 +3081,7 @@ mod tests {
 
     #[test]
     fn test_notifications_trigger() {
-        let mut notifications = Notifications::new();
+        let mut notifications = Notifications::with_clock(Rc::new(MockClock::new()));
 
         // Timer should not be set initially
         assert!(notifications.time_count.is_none());
@@ -659,7 +3095,7 @@ mod tests {
 
     #[test]
     fn test_notifications_show_methods() {
-        let mut notifications = Notifications::new();
+        let mut notifications = Notifications::with_clock(Rc::new(MockClock::new()));
 
         // Test show_mode
         notifications.show_mode();
@@ -713,6 +3149,52 @@ mod tests {
         app.line_len = 10;
         let line = app.gen_one_line_of_ascii();
         assert_eq!(line.chars().count(), 10);
+
+        // --- Other scripts ---
+        app.config.ascii_charset = "Greek".to_string();
+        let line = app.gen_one_line_of_ascii();
+        assert_eq!(line.chars().count(), 10);
+        assert!(line.chars().all(|c| GREEK_CHARSET.contains(&c.to_string().as_str())));
+
+        app.config.ascii_charset = "unknown".to_string();
+        assert_eq!(drill_charset(&app.config.ascii_charset), ASCII_CHARSET);
+
+        // --- Weighted classes ---
+        app.config.ascii_digit_weight = 20;
+        let line = app.gen_one_line_of_ascii();
+        assert!(line.chars().all(|c| c.is_ascii_digit()));
+
+        app.config.ascii_digit_weight = 0;
+        app.config.ascii_letter_weight = 60;
+        app.config.ascii_symbol_weight = 20;
+        app.line_len = 1000;
+        let line = app.gen_one_line_of_ascii();
+        let letters = line.chars().filter(|c| c.is_alphabetic()).count();
+        assert!(!line.chars().any(|c| c.is_ascii_digit()));
+        assert!((600..900).contains(&letters));
+
+        // Only letters weighted, and all of them excluded - the rest of the characters, uniformly
+        app.config.ascii_letter_weight = 1;
+        app.config.ascii_symbol_weight = 0;
+        app.config.excluded_chars = ('a'..='z').chain('A'..='Z').collect();
+        app.line_len = 100;
+        let line = app.gen_one_line_of_ascii();
+        assert_eq!(line.chars().count(), 100);
+        assert!(!line.chars().any(char::is_alphabetic));
+        app.config.excluded_chars.clear();
+
+        // --- Capital letters ---
+        app.config.capital_letters_percent = Some(0);
+        assert!(!app.gen_one_line_of_ascii().chars().any(char::is_uppercase));
+        app.config.capital_letters_percent = Some(100);
+        assert!(!app.gen_one_line_of_ascii().chars().any(char::is_lowercase));
+
+        // An excluded capital letter isn't generated from its lowercase letter
+        app.config.excluded_chars = "A".to_string();
+        app.line_len = 5000;
+        let line = app.gen_one_line_of_ascii();
+        assert!(line.contains('a') && !line.contains('A'));
+        assert!(!line.chars().any(|c| c.is_lowercase() && c != 'a'));
     }
 
     #[test]
@@ -740,10 +3222,37 @@ mod tests {
 
         // Test edge case where no words fit
         app.line_len = 2;
+        app.words.retain(|word| word.len() > 2);
         let line = app.gen_one_line_of_words();
         assert!(line.is_empty());
     }
 
+    #[test]
+    fn test_app_text_hyphenation() {
+        let mut app = App::new();
+        app.current_typing_option = CurrentTypingOption::Text;
+        app.line_len = 6;
+        app.config.hyphenate = true;
+        app.text = vec!["a".to_string(), "number".to_string(), "of".to_string(), "words".to_string()];
+        app.populate_text_lines();
+
+        // The word that doesn't fit is split across the first two lines
+        let lines: String = app.engine.charset.iter().collect();
+        assert_eq!(lines, "a num-ber of words ");
+        assert_eq!(app.engine.lines_len, VecDeque::from(vec![6, 7, 6]));
+
+        // Its parts count as one word for the position in the text
+        assert_eq!(app.first_text_gen_len, 4);
+        assert_eq!(app.text_position(), 0);
+
+        // Without hyphenation the word is moved to the next line
+        app.config.hyphenate = false;
+        app.clear_typing_buffers();
+        app.config.skip_len = 0;
+        app.populate_text_lines();
+        assert_eq!(app.engine.charset.iter().collect::<String>(), "a number of ");
+    }
+
     #[test]
     fn test_app_get_one_line_of_text() {
         let mut app = App::new();
@@ -771,29 +3280,122 @@ mod tests {
     }
 
     #[test]
-    fn test_app_update_id_field() {
+    fn test_app_update_char_state() {
         let mut app = App::new();
-        app.charset = VecDeque::from(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
-        app.ids = VecDeque::from(vec![0, 0, 0]);
+        app.engine.charset = VecDeque::from(vec!['a', 'b', 'c']);
+        app.engine.char_states = VecDeque::from(vec![CharState::Untyped; 3]);
         
         // --- Test 1: Correct character ---
-        app.input_chars.push_back("a".to_string());
-        app.update_id_field();
-        assert_eq!(app.ids[0], 1);
+        app.engine.input_chars.push_back('a');
+        app.update_char_state();
+        assert_eq!(app.engine.char_states[0], CharState::Correct);
 
         // --- Test 2: Incorrect character, without saving mistypes ---
         app.config.save_mistyped = false;
-        app.input_chars.push_back("x".to_string()); // Correct char is "b"
-        app.update_id_field();
-        assert_eq!(app.ids[1], 2);
+        app.engine.input_chars.push_back('x'); // Correct char is "b"
+        app.update_char_state();
+        assert_eq!(app.engine.char_states[1], CharState::Incorrect);
         assert!(app.config.mistyped_chars.is_empty()); // Should not record
 
         // --- Test 3: Incorrect character, with saving mistypes ---
         app.config.save_mistyped = true;
-        app.input_chars.push_back("y".to_string()); // Correct char is "c"
-        app.update_id_field();
-        assert_eq!(app.ids[2], 2);
+        app.engine.input_chars.push_back('y'); // Correct char is "c"
+        app.update_char_state();
+        assert_eq!(app.engine.char_states[2], CharState::Incorrect);
         assert_eq!(*app.config.mistyped_chars.get("c").unwrap(), 1); // "c" was mistyped once
+
+        // --- Test 4: Erased with Backspace and typed correctly ---
+        app.engine.input_chars.pop_back();
+        assert_eq!(app.engine.char_state(2), CharState::Untyped);
+        app.engine.input_chars.push_back('c');
+        app.update_char_state();
+        assert_eq!(app.engine.char_states[2], CharState::Corrected);
+        assert_eq!(app.engine.session.corrected, 1);
+        assert_eq!(app.engine.session.errors, 2);
+    }
+
+    #[test]
+    fn test_app_resume_interrupted_test() {
+        let mut interrupted = TypingEngine::new();
+        interrupted.push_line("abcdef");
+        "abx".chars().for_each(|c| {
+            interrupted.type_char(c, Instant::now());
+        });
+        interrupted.session.flag(ttypr::stats::IntegrityFlag::Paste);
+        let checkpoint = TestCheckpoint { session_secs: 10.0, ..TestCheckpoint::capture(&interrupted, "Ascii", 30, 12.0).unwrap() };
+
+        // Resumed from the Text option - the position in the text is left where it was
+        let mut app = App::new();
+        app.text = "one two three four five six".split_whitespace().map(String::from).collect();
+        app.current_typing_option = CurrentTypingOption::Text;
+        app.populate_text_lines();
+        assert!(app.config.skip_len > 0);
+        app.interrupted_test = Some(checkpoint);
+        app.resume_interrupted_test();
+        assert!(matches!(app.current_typing_option, CurrentTypingOption::Ascii));
+        assert_eq!((app.config.skip_len, app.first_text_gen_len), (0, 0));
+        assert!(matches!(app.current_mode, CurrentMode::Typing));
+        assert!(app.interrupted_test.is_none());
+        assert_eq!(app.engine.charset.iter().collect::<String>(), "abcdef");
+        assert_eq!((app.engine.cursor(), app.engine.session.errors), (3, 1));
+        assert_eq!(app.engine.session.flags, vec![ttypr::stats::IntegrityFlag::Paste]);
+        assert_eq!(app.config.test_duration_secs, 30);
+
+        // The test and the session carry on from the time used up before
+        app.on_key_press();
+        assert_eq!(app.test_progress().unwrap().1, 18);
+        assert!(app.engine.session.timer.unwrap().elapsed() >= Duration::from_secs(10));
+        assert!(app.resumed_secs.is_none());
+    }
+
+    #[test]
+    fn test_app_remove_unfinished_test() {
+        let session = |id: u64| {
+            let mut tracker = ttypr::stats::SessionTracker::new();
+            (0..10).for_each(|_| tracker.on_key_press(Instant::now()));
+            Session { id, ..tracker.finish("Words").unwrap() }
+        };
+        let mut app = App::new();
+        app.stats.sessions = vec![session(1), session(2)];
+        assert!(!app.remove_unfinished_test());
+
+        app.unfinished_test = Some(1);
+        assert!(app.remove_unfinished_test());
+        assert_eq!(app.stats.sessions.iter().map(|session| session.id).collect::<Vec<_>>(), vec![2]);
+        assert!(app.unfinished_test.is_none());
+    }
+
+    #[test]
+    fn test_app_slips() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.config.slip_window_ms = 150;
+        app.config.save_mistyped = true;
+        app.engine.push_line("abcde");
+
+        // A mistake right away followed by the correct next key
+        for c in ['x', 'b'] {
+            app.engine.input_chars.push_back(c);
+            app.update_char_state();
+        }
+        assert_eq!((app.engine.session.errors, app.engine.session.slips), (0, 1));
+        assert!(app.engine.session.mistyped_chars.is_empty() && app.config.mistyped_chars.is_empty());
+
+        // Not a slip - the next key came too late
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        clock.advance(Duration::from_millis(300));
+        app.engine.input_chars.push_back('d');
+        app.update_char_state();
+
+        // Not a slip - erased with Backspace and typed correctly
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        app.engine.backspace();
+        app.engine.input_chars.push_back('e');
+        app.update_char_state();
+        assert_eq!((app.engine.session.errors, app.engine.session.slips), (2, 1));
+        assert_eq!(app.config.mistyped_chars.get("c"), Some(&1));
     }
 
     #[test]
@@ -805,45 +3407,163 @@ mod tests {
         app.current_typing_option = CurrentTypingOption::Ascii;
         
         // Line 1: "aaaaa"
-        app.charset.extend(vec!["a".to_string(); 5]);
-        app.ids.extend(vec![1; 5]); // Simulate typed
-        app.input_chars.extend(vec!["a".to_string(); 5]);
-        app.lines_len.push_back(5);
+        app.engine.charset.extend(vec!['a'; 5]);
+        app.engine.char_states.extend(vec![CharState::Correct; 5]); // Simulate typed
+        app.engine.input_chars.extend(vec!['a'; 5]);
+        app.engine.lines_len.push_back(5);
 
         // Line 2: "bbbbb"
-        app.charset.extend(vec!["b".to_string(); 5]);
-        app.ids.extend(vec![1; 5]); // Simulate typed
-        app.input_chars.extend(vec!["b".to_string(); 5]);
-        app.lines_len.push_back(5);
+        app.engine.charset.extend(vec!['b'; 5]);
+        app.engine.char_states.extend(vec![CharState::Correct; 5]); // Simulate typed
+        app.engine.input_chars.extend(vec!['b'; 5]);
+        app.engine.lines_len.push_back(5);
 
         // Line 3: "ccccc" (not yet typed)
-        app.charset.extend(vec!["c".to_string(); 5]);
-        app.ids.extend(vec![0; 5]);
-        app.lines_len.push_back(5);
+        app.engine.charset.extend(vec!['c'; 5]);
+        app.engine.char_states.extend(vec![CharState::Untyped; 5]);
+        app.engine.lines_len.push_back(5);
 
         // At this point, input_chars length is 10, which equals lines_len[0] + lines_len[1]
-        assert_eq!(app.input_chars.len(), app.lines_len[0] + app.lines_len[1]);
+        assert_eq!(app.engine.input_chars.len(), app.engine.lines_len[0] + app.engine.lines_len[1]);
 
         // --- Call the function to test ---
         app.update_lines();
 
         // --- Assert the results ---
         // 1. First line's data should be removed from buffers
-        assert_eq!(app.input_chars.len(), 5);
-        assert_eq!(app.input_chars.front().unwrap(), "b");
+        assert_eq!(app.engine.input_chars.len(), 5);
+        assert_eq!(app.engine.input_chars.front().unwrap(), &'b');
         
         // 2. A new line should be generated and added
-        assert_eq!(app.lines_len.len(), 3); // Still 3 lines
-        assert_eq!(app.lines_len[0], 5); // Old line 2 is now line 1
-        assert_eq!(app.lines_len[1], 5); // Old line 3 is now line 2
-        assert_eq!(app.lines_len[2], 5); // New line 3 has been added
+        assert_eq!(app.engine.lines_len.len(), 3); // Still 3 lines
+        assert_eq!(app.engine.lines_len[0], 5); // Old line 2 is now line 1
+        assert_eq!(app.engine.lines_len[1], 5); // Old line 3 is now line 2
+        assert_eq!(app.engine.lines_len[2], 5); // New line 3 has been added
         
-        assert_eq!(app.charset.len(), 15); // Total chars should be back to 15
-        assert_eq!(app.ids.len(), 15);      // Total ids should be back to 15
+        assert_eq!(app.engine.charset.len(), 15); // Total chars should be back to 15
+        assert_eq!(app.engine.char_states.len(), 15);      // Total states should be back to 15
         
-        // 3. The newly added ids should be 0 (untyped)
-        // (Check the last 5 elements of the ids VecDeque)
-        assert!(app.ids.iter().skip(10).all(|&id| id == 0));
+        // 3. The newly added characters should be untyped
+        // (Check the last 5 elements of the char_states VecDeque)
+        assert!(app.engine.char_states.iter().skip(10).all(|&state| state == CharState::Untyped));
+    }
+
+    #[test]
+    fn test_app_history_and_preview_lines() {
+        let mut app = App::new();
+        app.line_len = 5;
+        app.config.history_lines = 2;
+        app.config.preview_lines = 0;
+        assert_eq!(app.lines_shown(), 3);
+
+        for line in ["aaaa ", "bbbb ", "cccc "] {
+            app.engine.push_line(line);
+        }
+
+        // With no preview, the lines only scroll once the last one is typed
+        for c in "aaaa bbbb ".chars() {
+            app.engine.input_chars.push_back(c);
+        }
+        app.update_lines();
+        assert_eq!(app.engine.lines_len.len(), 3);
+        assert_eq!(app.engine.active_line(), 2);
+
+        for c in "cccc ".chars() {
+            app.engine.input_chars.push_back(c);
+        }
+        app.update_lines();
+        assert_eq!(app.engine.lines_len.len(), 3);
+        assert_eq!(app.engine.input_chars.iter().collect::<String>(), "bbbb cccc ");
+        assert_eq!(app.engine.active_line(), 2);
+    }
+
+    #[test]
+    fn test_app_drill_chars() {
+        let mut app = App::new();
+        app.drill_chars = Some(vec!["[".to_string(), "]".to_string(), "{".to_string()]);
+
+        let line = app.gen_one_line_of_ascii();
+        assert_eq!(line.chars().count(), app.line_len);
+        assert!(line.chars().all(|c| "[]{".contains(c)));
+    }
+
+    #[test]
+    fn test_app_word_list_tags() {
+        let mut app = App::new();
+        let tagged = |language: &str, difficulty: &str| ContentInfo {
+            language: Some(language.to_string()),
+            difficulty: Some(difficulty.to_string()),
+            ..ContentInfo::default()
+        };
+        app.word_list_choices = vec![
+            ("english".to_string(), tagged("en", "easy")),
+            ("german".to_string(), tagged("de", "hard")),
+            ("medical".to_string(), tagged("en", "hard")),
+        ];
+        assert_eq!(app.shown_word_lists().len(), 3);
+
+        // The tags are sorted - de, easy, en, hard
+        app.switch_word_list_tag(true);
+        assert_eq!(app.word_list_tag.as_deref(), Some("de"));
+        app.switch_word_list_tag(true);
+        app.switch_word_list_tag(true);
+        let shown: Vec<&str> = app.shown_word_lists().iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(shown, vec!["english", "medical"]);
+
+        // Back past the first tag - no filter
+        app.switch_word_list_tag(false);
+        app.switch_word_list_tag(false);
+        app.switch_word_list_tag(false);
+        assert_eq!(app.word_list_tag, None);
+        app.switch_word_list_tag(false);
+        assert_eq!(app.word_list_tag.as_deref(), Some("hard"));
+
+        // The drills submenu - only the generated drills, the active one picked
+        app.config.word_list = Some("builtin:rolls".to_string());
+        app.open_drill_picker();
+        let shown = app.shown_word_lists();
+        assert!(shown.iter().all(|(_, info)| info.category.as_deref() == Some("drill")));
+        assert_eq!(shown[app.word_list_choice - 1].0, "builtin:rolls");
+    }
+
+    #[test]
+    fn test_app_progression_lock() {
+        use ttypr::stats::Session;
+
+        let mut app = App::new();
+        let with_difficulty = |difficulty: &str| ContentInfo { difficulty: Some(difficulty.to_string()), ..ContentInfo::default() };
+        app.word_list_choices = vec![
+            ("basics".to_string(), with_difficulty("easy")),
+            ("medical".to_string(), with_difficulty("hard")),
+        ];
+        let session = |correct: usize, errors: usize| Session { keystrokes: correct + errors, correct, errors, ..sample_session() };
+
+        // No lock configured
+        assert_eq!(app.progression_accuracy(), None);
+        assert!(!app.word_list_locked(&with_difficulty("hard")));
+
+        app.config.progression_accuracy = 95.0;
+        app.config.progression_sessions = 2;
+        app.stats.sessions = vec![session(90, 10)];
+        assert_eq!(app.progression_accuracy(), Some(0.0)); // Not enough sessions yet
+        assert!(app.word_list_locked(&with_difficulty("hard")));
+        assert!(!app.word_list_locked(&with_difficulty("easy")));
+
+        // A locked list isn't picked
+        app.show_word_lists = true;
+        app.word_list_choice = 2;
+        app.pick_word_list().unwrap();
+        assert!(app.show_word_lists);
+        assert_eq!(app.config.word_list, None);
+
+        app.stats.sessions.push(session(100, 0));
+        assert_eq!(app.progression_accuracy(), Some(95.0));
+        assert!(!app.word_list_locked(&with_difficulty("hard")));
+
+        // Lists no harder than the active one are never locked
+        app.stats.sessions.push(session(50, 50));
+        app.config.word_list = Some("medical".to_string());
+        assert!(!app.word_list_locked(&with_difficulty("hard")));
     }
 
     #[test]
@@ -851,25 +3571,25 @@ mod tests {
         let mut app = App::new();
 
         // Populate buffers with some data
-        app.charset.push_back("a".to_string());
-        app.input_chars.push_back("a".to_string());
-        app.ids.push_back(1);
-        app.lines_len.push_back(1);
+        app.engine.charset.push_back('a');
+        app.engine.input_chars.push_back('a');
+        app.engine.char_states.push_back(CharState::Correct);
+        app.engine.lines_len.push_back(1);
 
         // Ensure they are not empty before clearing
-        assert!(!app.charset.is_empty());
-        assert!(!app.input_chars.is_empty());
-        assert!(!app.ids.is_empty());
-        assert!(!app.lines_len.is_empty());
+        assert!(!app.engine.charset.is_empty());
+        assert!(!app.engine.input_chars.is_empty());
+        assert!(!app.engine.char_states.is_empty());
+        assert!(!app.engine.lines_len.is_empty());
 
         // Call the function
         app.clear_typing_buffers();
 
         // Assert that all buffers are empty
-        assert!(app.charset.is_empty());
-        assert!(app.input_chars.is_empty());
-        assert!(app.ids.is_empty());
-        assert!(app.lines_len.is_empty());
+        assert!(app.engine.charset.is_empty());
+        assert!(app.engine.input_chars.is_empty());
+        assert!(app.engine.char_states.is_empty());
+        assert!(app.engine.lines_len.is_empty());
     }
 
     #[test]
@@ -884,45 +3604,119 @@ mod tests {
         assert!(matches!(app.current_typing_option, CurrentTypingOption::Ascii));
         app.switch_typing_option();
         assert!(matches!(app.current_typing_option, CurrentTypingOption::Words));
-        assert!(!app.charset.is_empty()); // Should be populated with words
-        assert!(!app.lines_len.is_empty());
+        assert!(!app.engine.charset.is_empty()); // Should be populated with words
+        assert!(!app.engine.lines_len.is_empty());
 
         // --- 2. Switch from Words to Text ---
         app.switch_typing_option();
         assert!(matches!(app.current_typing_option, CurrentTypingOption::Text));
-        assert!(!app.charset.is_empty()); // Should be populated with text
+        assert!(!app.engine.charset.is_empty()); // Should be populated with text
         assert_ne!(app.first_text_gen_len, 0); // Should be tracking generated text length
 
         // --- 3. Switch from Text back to ASCII ---
         app.switch_typing_option();
         assert!(matches!(app.current_typing_option, CurrentTypingOption::Ascii));
-        assert!(!app.charset.is_empty()); // Should be populated with ASCII
+        assert!(!app.engine.charset.is_empty()); // Should be populated with ASCII
         assert_eq!(app.first_text_gen_len, 0); // Should be reset
     }
 
+    #[test]
+    fn test_app_jump_to_chapter() {
+        let mut app = App::new();
+        app.line_len = 12;
+        app.text = "one two three four five six seven eight nine ten eleven twelve"
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        app.chapters = vec![0, 4, 8];
+        app.populate_text_lines();
+        assert_eq!(app.text_position(), 0);
+        assert_eq!(app.book_progress(), (1, 0));
+
+        // --- Forward to the second chapter ---
+        app.jump_to_chapter(true);
+        assert_eq!(app.text_position(), 4);
+        assert_eq!(app.engine.charset.front().unwrap(), &'f');
+        assert_eq!(app.book_progress(), (2, 33));
+
+        // --- Back to the first one wraps from the front to the last ---
+        app.jump_to_chapter(false);
+        assert_eq!(app.current_chapter(), 0);
+        app.jump_to_chapter(false);
+        assert_eq!(app.current_chapter(), 2);
+
+        // --- Resumes at the position saved for a chapter ---
+        app.config.chapter_positions[1] = 6;
+        app.jump_to_chapter(false);
+        assert_eq!(app.text_position(), 6);
+        assert_eq!(app.current_chapter(), 1);
+    }
+
+    #[test]
+    fn test_app_load_adhoc_text() {
+        let mut app = App::new();
+        app.line_len = 20;
+        app.text = vec!["stored".to_string(), "text".to_string()];
+        app.config.skip_len = 1;
+
+        // --- Nothing to type ---
+        assert!(!app.load_adhoc_text("  \n "));
+        assert!(app.stashed_text.is_none());
+
+        // --- Loads the contents as a Text session ---
+        assert!(app.load_adhoc_text("copied words"));
+        assert!(matches!(app.current_typing_option, CurrentTypingOption::Text));
+        assert_eq!(app.text, vec!["copied", "words"]);
+        assert_eq!(app.engine.charset.front().unwrap(), &'c');
+
+        // --- The clipboard that couldn't be read (or is empty) is reported as such, even while
+        // practicing an ad-hoc text ---
+        app.practice_clipboard(Err(io::Error::new(io::ErrorKind::NotFound, "No clipboard tool found")));
+        assert_eq!(app.notifications.clipboard, Some(ClipboardLoad::Failed));
+        app.practice_clipboard(Ok(" ".to_string()));
+        assert_eq!(app.notifications.clipboard, Some(ClipboardLoad::Empty));
+        assert_eq!(app.text, vec!["copied", "words"]);
+
+        // --- Switching away restores the previous text and its position ---
+        app.switch_typing_option();
+        assert!(app.stashed_text.is_none());
+        assert_eq!(app.text, vec!["stored", "text"]);
+        assert_eq!(app.config.skip_len, 1);
+    }
+
+    #[test]
+    fn test_app_profile_names() {
+        let mut app = App::new();
+        assert_eq!(app.profile_names(), vec!["default"]);
+
+        app.config.profiles = vec!["kid".to_string(), "".to_string(), "default".to_string(), "kid".to_string()];
+        assert_eq!(app.profile_names(), vec!["default", "kid"]);
+    }
+
     #[test]
     fn test_app_populate_charset_from_line() {
         let mut app = App::new();
         let line = "hello".to_string();
         
-        app.populate_charset_from_line(line);
+        app.engine.push_line(&line);
 
         // Check lines_len
-        assert_eq!(app.lines_len.len(), 1);
-        assert_eq!(app.lines_len[0], 5);
+        assert_eq!(app.engine.lines_len.len(), 1);
+        assert_eq!(app.engine.lines_len[0], 5);
 
         // Check charset
-        let expected_charset = VecDeque::from(vec!["h".to_string(), "e".to_string(), "l".to_string(), "l".to_string(), "o".to_string()]);
-        assert_eq!(app.charset, expected_charset);
+        let expected_charset = VecDeque::from(vec!['h', 'e', 'l', 'l', 'o']);
+        assert_eq!(app.engine.charset, expected_charset);
 
-        // Check ids
-        assert_eq!(app.ids.len(), 5);
-        assert!(app.ids.iter().all(|&id| id == 0)); // All ids should be 0
+        // Check char_states
+        assert_eq!(app.engine.char_states.len(), 5);
+        assert!(app.engine.char_states.iter().all(|&state| state == CharState::Untyped)); // All should be untyped
     }
 
     #[test]
     fn test_wpm_logic() {
-        let mut wpm = Wpm::new();
+        let clock = MockClock::new();
+        let mut wpm = Wpm::with_clock(Rc::new(clock.clone()));
 
         // 1. Initial state check
         assert!(wpm.timer.is_none());
@@ -936,10 +3730,12 @@ mod tests {
         assert!(wpm.time_since_last_key_pressed.is_some());
         assert_eq!(wpm.key_presses, 1);
 
-        // 3. Subsequent key presses
+        // 3. Subsequent key presses (20 key presses over 6 seconds)
         for _ in 0..19 {
+            clock.advance(Duration::from_millis(300));
             wpm.on_key_press();
         }
+        clock.advance(Duration::from_millis(300));
         assert_eq!(wpm.key_presses, 20);
 
         // 4. Tick before pause timeout
@@ -947,11 +3743,11 @@ mod tests {
         assert_eq!(wpm.wpm, 0); // WPM should not be calculated yet
 
         // 5. Simulate pause and test WPM calculation
-        thread::sleep(Duration::from_secs(4)); // Wait for longer than the 3s pause
+        clock.advance(Duration::from_secs(3)); // Wait for longer than the 3s pause
         let wpm_updated = wpm.on_tick();
-        
+
         assert!(wpm_updated); // Should return true as WPM was calculated
-        assert_ne!(wpm.wpm, 0); // WPM should be a non-zero value
+        assert_eq!(wpm.wpm, 40); // 4 "words" in 6 seconds
         
         // Check if state is reset
         assert!(wpm.timer.is_none());
@@ -959,25 +3755,168 @@ mod tests {
         assert_eq!(wpm.key_presses, 0);
     }
 
+    #[test]
+    fn test_wpm_rolling_window() {
+        let clock = MockClock::new();
+        let mut wpm = Wpm::with_clock(Rc::new(clock.clone()));
+        wpm.set_window(Duration::from_secs(3), Duration::from_secs(1));
+
+        // 5 key presses a second - 60 WPM, calculated every second while typing
+        let type_for = |secs: u64, wpm: &mut Wpm| {
+            let mut updates = 0;
+            for _ in 0..secs * 5 {
+                wpm.on_key_press();
+                clock.advance(Duration::from_millis(200));
+                updates += wpm.on_tick() as usize;
+            }
+            updates
+        };
+        assert_eq!(type_for(4, &mut wpm), 3); // Not before there are 10 key presses
+        assert_eq!(wpm.wpm, 60);
+        assert_eq!(wpm.next_update(), Some(clock.now() + Duration::from_secs(1)));
+
+        // Only the last 3 seconds count
+        for _ in 0..3 {
+            assert_eq!(type_for(1, &mut wpm), 1);
+        }
+        assert_eq!(wpm.wpm, 60);
+        clock.advance(Duration::from_millis(800));
+        wpm.on_key_press();
+        clock.advance(Duration::from_millis(200));
+        assert!(wpm.on_tick());
+        assert!(wpm.wpm < 60);
+
+        // Stops after a pause
+        clock.advance(Duration::from_secs(4));
+        assert!(!wpm.on_tick());
+        assert!(wpm.next_update().is_none());
+    }
+
+    #[test]
+    fn test_app_practice_schedule() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.config.practice_minutes = 10;
+        app.config.rest_minutes = 2;
+        app.current_mode = CurrentMode::Typing;
+
+        // The first key press starts the first block
+        app.on_key_press();
+        let block = app.schedule.current_block().unwrap();
+        assert_eq!(block.block, 1);
+        assert_eq!(app.session_block, Some(block));
+        clock.advance(Duration::from_secs(9 * 60));
+        app.on_tick();
+        assert_eq!(app.schedule.remaining(Duration::from_secs(600), Duration::from_secs(120)).unwrap().1, Duration::from_secs(60));
+
+        // Then a rest - the session is finished
+        clock.advance(Duration::from_secs(60));
+        app.on_tick();
+        assert!(matches!(app.current_mode, CurrentMode::Menu));
+        assert!(app.ring_bell && app.session_block.is_none());
+        assert_eq!(app.schedule.phase.map(|(phase, _)| phase), Some(SchedulePhase::Rest));
+
+        // After the rest, the next block starts with the next key press
+        app.ring_bell = false;
+        clock.advance(Duration::from_secs(120));
+        app.on_tick();
+        assert!(app.ring_bell && app.schedule.phase.is_none());
+        app.on_key_press();
+        assert_eq!(app.schedule.current_block(), Some(PracticeBlock { block: 2, ..block }));
+    }
+
+    #[test]
+    fn test_app_playlist() {
+        let segment = |option: &str, secs, words| PlaylistSegment { option: option.to_string(), secs, words };
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.words = vec!["ab".to_string()];
+        app.config.playlist = vec![
+            segment("ascii", 0, 1),
+            segment("text", 60, 0), // Skipped - no text is loaded
+            segment("Words", 30, 0),
+            segment("numbers", 30, 0), // Skipped - no such option
+        ];
+        app.start_playlist();
+        assert!(matches!((&app.current_mode, &app.current_typing_option), (CurrentMode::Typing, CurrentTypingOption::Ascii)));
+        assert_eq!(app.test_duration_secs(), 0);
+
+        // The next segment once a word (5 characters) is typed
+        app.on_key_press();
+        app.engine.session.correct = 5;
+        app.on_tick();
+        assert!(matches!((&app.current_mode, &app.current_typing_option), (CurrentMode::Typing, CurrentTypingOption::Words)));
+        assert_eq!(app.playlist.as_ref().map(|run| run.segment), Some(2));
+
+        // A timed segment, timed like a timed test - the summary page after the last one
+        assert_eq!(app.test_duration_secs(), 30);
+        app.on_key_press();
+        clock.advance(Duration::from_secs(30));
+        app.on_tick();
+        assert!(matches!(app.current_mode, CurrentMode::Menu));
+        assert!(app.playlist.is_none() && app.test_started_at.is_none());
+        assert!(app.playlist_summary.is_some_and(|sessions| sessions.is_empty())); // Too few key presses to be recorded
+    }
+
+    #[test]
+    fn test_app_pace_caret() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        for line in ["abcdefghij", "klmnopqrst", "uvwxyz"] {
+            app.engine.push_line(line);
+        }
+
+        // Off by default
+        app.on_key_press();
+        assert!(app.pace.position().is_none());
+        app.finish_session();
+
+        // 60 WPM - 5 characters a second, from where the first key was pressed
+        app.config.pace_wpm = 60;
+        app.engine.input_chars.push_back('a');
+        app.on_key_press();
+        assert_eq!(app.pace.position(), Some(0));
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(app.pace.position(), Some(10));
+        assert!(app.pace.on_tick());
+        assert!(!app.pace.on_tick());
+
+        // It stays in place when a line is dropped
+        app.engine.input_chars.extend("bcdefghijklmnopqrst".chars());
+        app.update_lines();
+        assert_eq!(app.engine.charset.front(), Some(&'k'));
+        assert_eq!(app.pace.position(), Some(0));
+
+        // Racing the average
+        app.config.pace_average = true;
+        assert!(app.pace_target_wpm().is_none());
+
+        app.clear_typing_buffers();
+        assert!(app.pace.position().is_none());
+    }
+
     #[test]
     fn test_app_on_tick() {
-        let mut app = App::new();
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
 
         // --- Scenario 1: WPM update triggers notification ---
         // Manually set up the Wpm state to simulate a completed typing session
         app.wpm.key_presses = 15; // A realistic number of key presses
-        app.wpm.timer = Some(Instant::now() - Duration::from_secs(10)); // Timer started 10s ago
-        app.wpm.time_since_last_key_pressed = Some(Instant::now() - Duration::from_secs(4)); // Paused for 4s
+        app.wpm.timer = Some(clock.now());
+        app.wpm.time_since_last_key_pressed = Some(clock.now() + Duration::from_secs(6));
+        clock.advance(Duration::from_secs(10)); // Timer started 10s ago, paused for 4s
+        app.damage = Damage::default(); // Drawn
 
         app.on_tick();
 
         // Check that a WPM update occurred and triggered a notification
         assert!(app.notifications.wpm);
         assert!(app.notifications.time_count.is_some());
-        assert!(app.needs_redraw);
+        assert_eq!(app.damage, Damage::from(Region::Notifications));
 
         // Reset flags for the next scenario
-        app.needs_redraw = false;
+        app.damage = Damage::default();
         app.notifications.hide_all();
 
         // --- Scenario 2: Notification timeout clears flags ---
@@ -985,14 +3924,915 @@ mod tests {
         assert!(app.notifications.mode);
 
         // Wait for the notification to time out
-        thread::sleep(Duration::from_secs(3));
+        clock.advance(Duration::from_secs(3));
 
         app.on_tick();
 
-        // Check that the notification timeout has set the appropriate flags
-        assert!(app.needs_clear);
-        assert!(app.needs_redraw);
+        // Check that the notification timeout has set the appropriate flags - its region is
+        // redrawn, without clearing the screen
+        assert!(app.damage.contains(Region::Notifications));
+        assert!(!app.damage.contains(Region::Screen));
         // The notification's own on_tick should have hidden it
         assert!(!app.notifications.mode);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_app_ime_composition() {
+        let mut app = App::new();
+        app.config.ime_composition = true;
+        app.current_typing_option = CurrentTypingOption::Words;
+        app.line_len = 40;
+        app.words = vec!["日本語".to_string()];
+        for _ in 0..3 {
+            let one_line = app.gen_one_line_of_words();
+            app.engine.push_line(&one_line);
+        }
+
+        // Intermediate states aren't compared - not even a reading longer than the word
+        for c in "にほんご".chars() {
+            assert!(app.compose(c));
+        }
+        assert!(app.engine.input_chars.is_empty());
+        for _ in 0..4 {
+            app.composition.pop(); // Backspace
+        }
+        for c in "日本語".chars() {
+            assert!(app.compose(c));
+        }
+        assert!(app.engine.input_chars.is_empty());
+        assert_eq!(app.engine.char_states[0], CharState::Untyped);
+
+        // Compared once committed - the space is then a regular keystroke
+        assert!(!app.compose(' '));
+        assert!(app.composition.is_empty());
+        assert_eq!(app.engine.char_states.iter().take(3).collect::<Vec<_>>(), vec![&CharState::Correct; 3]);
+        app.engine.input_chars.push_back(' ');
+        app.update_char_state();
+
+        // A shorter composition, committed with Enter - the missing characters are errors
+        assert!(app.compose('日'));
+        app.commit_composition();
+        assert_eq!(app.engine.char_states.iter().skip(4).take(3).collect::<Vec<_>>(), vec![&CharState::Correct, &CharState::Incorrect, &CharState::Incorrect]);
+        assert_eq!(app.engine.input_chars.len(), 7);
+
+        // Not used for the ASCII option
+        app.current_typing_option = CurrentTypingOption::Ascii;
+        assert!(!app.ime_composition_enabled());
+    }
+
+    #[test]
+    fn test_app_smooth_line_advance() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.config.smooth_line_advance = true;
+        app.line_len = 10;
+        for _ in 0..3 {
+            let one_line = app.gen_one_line_of_ascii();
+            app.engine.push_line(&one_line);
+        }
+        assert_eq!(app.line_advance_offset(), 0);
+
+        // Type the first two lines - the lines scroll
+        for _ in 0..20 {
+            app.engine.input_chars.push_back('a');
+            app.update_lines();
+        }
+        assert_eq!(app.line_advance_offset(), LINE_ADVANCE_ROWS);
+
+        // In place once the transition is over
+        clock.advance(LINE_ADVANCE_STEP * 3);
+        assert_eq!(app.line_advance_offset(), 0);
+        app.on_tick();
+        assert!(app.line_advance.is_none());
+    }
+
+    #[test]
+    fn test_app_idle_split() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.demo = true; // Not to record the sessions
+        for _ in 0..3 {
+            app.on_key_press();
+        }
+        assert!(!app.session_is_idle());
+        assert_eq!(app.engine.session.keystrokes, 3);
+
+        // A key press after the idle gap starts a new session
+        clock.advance(Duration::from_secs(6 * 60));
+        assert!(app.session_is_idle());
+        app.on_key_press();
+        assert_eq!(app.engine.session.keystrokes, 1);
+
+        // Not in timed tests, or with the splitting turned off
+        clock.advance(Duration::from_secs(6 * 60));
+        app.config.test_duration_secs = 30;
+        assert!(!app.session_is_idle());
+        app.config.test_duration_secs = 0;
+        app.config.idle_split_minutes = 0;
+        assert!(!app.session_is_idle());
+    }
+
+    #[test]
+    fn test_app_timed_test() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        assert!(app.test_progress().is_none());
+
+        // Switching the duration
+        app.cycle_test_duration();
+        assert_eq!(app.config.test_duration_secs, 15);
+        app.config.test_duration_secs = 120;
+        app.cycle_test_duration();
+        assert_eq!(app.config.test_duration_secs, 0);
+
+        // Untimed - no test is started
+        app.on_key_press();
+        assert!(app.test_started_at.is_none());
+
+        // Started with the first key press
+        app.config.test_duration_secs = 30;
+        app.current_mode = CurrentMode::Typing;
+        app.on_key_press();
+        let (elapsed, remaining) = app.test_progress().unwrap();
+        assert_eq!(elapsed, 0.0);
+        assert_eq!(remaining, 30);
+
+        // Errors use up time, with the penalty mode
+        app.engine.push_line("abc");
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        assert_eq!(app.test_penalty_secs, 0.0);
+        app.config.error_penalty_secs = 2.0;
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        assert_eq!(app.test_penalty_secs, 2.0);
+        assert_eq!(app.test_progress().unwrap().1, 28);
+
+        // Back to Menu mode once the time is up
+        clock.advance(Duration::from_secs(28));
+        app.on_tick();
+        assert!(app.test_started_at.is_none());
+        assert!(matches!(app.current_mode, CurrentMode::Menu));
+        assert!(app.notifications.time_up);
+    }
+
+    #[test]
+    fn test_app_end_of_text() {
+        let mut app = App::new();
+        app.current_typing_option = CurrentTypingOption::Text;
+        app.line_len = 20;
+        app.text = "one two three four five six seven".split(' ').map(String::from).collect();
+
+        // --- Loop (the default) ---
+        app.populate_text_lines();
+        app.get_one_line_of_text();
+        assert!(!app.text_finished);
+        assert!(app.config.text_started_at.is_some());
+
+        // --- Stop ---
+        app.config.end_of_text = "stop".to_string();
+        app.config.skip_len = 0;
+        app.clear_typing_buffers();
+        app.populate_text_lines();
+        assert!(app.text_finished);
+        assert_eq!(app.engine.lines_len.iter().sum::<usize>(), app.engine.charset.len());
+
+        // Typing the whole text shows the completion page and starts over
+        app.current_mode = CurrentMode::Typing;
+        let total = app.engine.charset.len();
+        for i in 0..total {
+            let character = app.engine.charset[app.engine.input_chars.len()];
+            app.engine.input_chars.push_back(character);
+            app.update_char_state();
+            app.update_lines();
+            if i < total - 1 {
+                assert!(app.completed_text.is_none());
+            }
+        }
+        assert!(app.completed_text.is_some());
+        assert!(matches!(app.current_mode, CurrentMode::Menu));
+        assert!(app.engine.input_chars.is_empty());
+        assert_eq!(app.text_position(), 0);
+
+        // Closing the page with "start over" picked
+        app.pick_text_choice().unwrap();
+        assert!(app.completed_text.is_none());
+        assert_eq!(app.text_position(), 0);
+    }
+
+    #[test]
+    fn test_app_seek_text() {
+        let mut app = App::new();
+        app.current_typing_option = CurrentTypingOption::Text;
+        app.line_len = 20;
+        app.text = (0..200).map(|i| format!("w{}", i)).collect();
+        app.populate_text_lines();
+
+        assert!(app.seek_text("50%"));
+        assert_eq!(app.text_position(), 100);
+        assert_eq!(app.engine.charset.iter().take(4).collect::<String>(), "w100");
+
+        assert!(app.seek_text(" 12 "));
+        assert_eq!(app.text_position(), 12);
+
+        // Clamped to the text
+        assert!(app.seek_text("100%"));
+        assert_eq!(app.text_position(), 199);
+        assert!(app.seek_text("5000"));
+        assert_eq!(app.text_position(), 199);
+
+        // Not a position
+        assert!(!app.seek_text("abc"));
+        assert!(!app.seek_text("%"));
+        assert_eq!(app.text_position(), 199);
+    }
+
+    #[test]
+    fn test_app_resume_prompt() {
+        let mut app = App::new();
+        app.line_len = 20;
+        app.text = (0..200).map(|i| format!("w{}", i)).collect();
+
+        // --- Saved position - asked the first time the Text option is entered ---
+        app.config.skip_len = 50;
+        app.current_typing_option = CurrentTypingOption::Words;
+        app.switch_typing_option();
+        assert!(app.show_resume_prompt);
+        assert_eq!(app.text_name(), "text.txt");
+
+        // Starting over
+        app.show_resume_prompt = false;
+        app.restart_text();
+        assert_eq!(app.text_position(), 0);
+
+        // --- Not asked again ---
+        app.config.skip_len = 50;
+        app.current_typing_option = CurrentTypingOption::Words;
+        app.switch_typing_option();
+        assert!(!app.show_resume_prompt);
+    }
+
+    #[test]
+    fn test_app_ban_word() {
+        let mut app = App::new();
+        app.current_typing_option = CurrentTypingOption::Words;
+        app.engine.push_line("hello world ");
+
+        // The word at the cursor
+        assert_eq!(app.engine.word_at_cursor().unwrap(), "hello");
+        app.engine.input_chars.extend(['h', 'e']);
+        assert_eq!(app.engine.word_at_cursor().unwrap(), "hello");
+        // On a space - the word just typed
+        app.engine.input_chars.extend(['l', 'l', 'o']);
+        assert_eq!(app.engine.word_at_cursor().unwrap(), "hello");
+        app.engine.input_chars.push_back(' ');
+        assert_eq!(app.engine.word_at_cursor().unwrap(), "world");
+
+        // Banned words are not generated
+        app.ban_word_at_cursor();
+        app.ban_word_at_cursor();
+        assert_eq!(app.config.banned_words, vec!["world".to_string()]);
+        app.words = vec!["hello".to_string(), "world".to_string()];
+        app.line_len = 50;
+        assert!(!app.gen_one_line_of_words().contains("world"));
+
+        // Unless all of them are
+        app.config.banned_words.push("hello".to_string());
+        assert!(!app.gen_one_line_of_words().is_empty());
+    }
+
+    #[test]
+    fn test_app_excluded_chars() {
+        let mut app = App::new();
+        app.config.excluded_chars = "`~a".to_string();
+        app.line_len = 500;
+        let line = app.gen_one_line_of_ascii();
+        assert!(!line.contains(['`', '~', 'a']));
+
+        // With the weighted classes, and the characters given to `ttypr drill`
+        app.config.ascii_symbol_weight = 1;
+        assert!(!app.gen_one_line_of_ascii().contains(['`', '~']));
+        app.drill_chars = Some(vec!["a".to_string(), "b".to_string()]);
+        assert!(app.gen_one_line_of_ascii().chars().all(|c| c == 'b'));
+
+        // Unless all of them are excluded
+        app.drill_chars = Some(vec!["a".to_string()]);
+        assert!(app.gen_one_line_of_ascii().chars().all(|c| c == 'a'));
+
+        // The words with them are left out
+        app.words = vec!["tilde~".to_string(), "cat".to_string(), "dog".to_string()];
+        assert_eq!(app.word_weights(), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_app_favorite_words() {
+        let mut app = App::new();
+        app.current_typing_option = CurrentTypingOption::Words;
+        app.words = vec!["hello".to_string(), "world".to_string(), "junk".to_string()];
+        app.config.banned_words = vec!["junk".to_string()];
+        app.engine.push_line("world ");
+
+        // Starring and unstarring
+        app.toggle_favorite_word_at_cursor();
+        assert_eq!(app.config.favorite_words, vec!["world".to_string()]);
+        assert_eq!(app.word_weights(), vec![1, 3, 0]);
+        app.toggle_favorite_word_at_cursor();
+        assert!(app.config.favorite_words.is_empty());
+        assert_eq!(app.word_weights(), vec![1, 1, 0]);
+
+        // The boost is configurable
+        app.config.favorite_words = vec!["hello".to_string()];
+        app.config.favorite_boost = 10;
+        assert_eq!(app.word_weights(), vec![10, 1, 0]);
+    }
+
+    #[test]
+    fn test_app_session_note() {
+        let mut app = App::new();
+        assert!(!app.set_session_note("tired"));
+
+        app.stats.sessions.push(sample_session());
+        assert!(app.set_session_note("  new keyboard "));
+        assert_eq!(app.stats.sessions[0].note.as_deref(), Some("new keyboard"));
+        assert!(app.set_session_note(""));
+        assert_eq!(app.stats.sessions[0].note, None);
+    }
+
+    #[test]
+    fn test_app_targeted_drill() {
+        let mut app = App::new();
+        app.config.mistyped_chars.clear();
+        assert_eq!(app.save_targeted_drill().ok(), Some(None));
+
+        // Nothing to drill after a session without errors (and nothing is saved)
+        app.stats.sessions.push(sample_session());
+        assert_eq!(app.save_targeted_drill().ok(), Some(None));
+    }
+
+    #[test]
+    fn test_app_keyboards() {
+        let mut app = App::new();
+        // No keyboards configured - stays untagged
+        app.switch_keyboard();
+        assert_eq!(app.config.active_keyboard, None);
+
+        app.config.keyboards = vec!["laptop".to_string(), "ergo split".to_string()];
+        app.switch_keyboard();
+        assert_eq!(app.config.active_keyboard.as_deref(), Some("laptop"));
+        app.switch_keyboard();
+        assert_eq!(app.config.active_keyboard.as_deref(), Some("ergo split"));
+        app.switch_keyboard();
+        assert_eq!(app.config.active_keyboard, None);
+
+        // The summary page is filtered by the keyboards the sessions were typed on
+        let session = |keyboard: Option<&str>| ttypr::stats::Session {
+            started_at: 1_700_000_000,
+            keyboard: keyboard.map(String::from),
+            ..sample_session()
+        };
+        app.stats.sessions = vec![session(Some("laptop")), session(None), session(Some("laptop"))];
+        assert_eq!(app.summaries()[0].sessions, 3);
+        app.switch_summary_keyboard();
+        assert_eq!(app.summary_keyboard.as_deref(), Some("laptop"));
+        assert_eq!(app.summaries()[0].sessions, 2);
+        app.switch_summary_keyboard();
+        assert_eq!(app.summary_keyboard, None);
+    }
+
+    #[test]
+    fn test_speed_governor() {
+        let clock = MockClock::new();
+        let mut governor = SpeedGovernor::with_clock(Rc::new(clock.clone()));
+        assert!(governor.allows(0) && governor.allows(0)); // Off
+
+        // 60 WPM - a key every 200ms
+        assert!(governor.allows(60));
+        clock.advance(Duration::from_millis(150));
+        assert!(!governor.allows(60));
+        assert!(governor.shows_cue());
+        clock.advance(Duration::from_millis(50));
+        assert!(governor.allows(60)); // Timed from the last typed key, not the ignored one
+        assert!(!governor.on_tick());
+
+        clock.advance(GOVERNOR_CUE_DURATION);
+        assert!(governor.on_tick());
+        assert!(!governor.shows_cue());
+    }
+
+    #[test]
+    fn test_app_title() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        assert_eq!(app.title(), "ttypr");
+        app.current_mode = CurrentMode::Typing;
+        assert_eq!(app.title(), "ttypr");
+
+        app.on_key_press();
+        app.wpm.wpm = 74;
+        clock.advance(Duration::from_secs(42));
+        assert_eq!(app.title(), "ttypr – 74wpm – 00:42");
+
+        // The time left of a timed test
+        app.config.test_duration_secs = 60;
+        app.on_key_press();
+        clock.advance(Duration::from_millis(30_500));
+        assert_eq!(app.title(), "ttypr – 74wpm – 00:30");
+    }
+
+    #[test]
+    fn test_app_live_wpm() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.current_mode = CurrentMode::Typing;
+        assert_eq!(app.wpm_window(), Duration::ZERO);
+        app.config.wpm_window_secs = 10;
+        app.config.live_wpm = true;
+        assert_eq!(app.wpm_window(), Duration::from_secs(10));
+        app.config.wpm_window_secs = 0;
+        assert_eq!(app.wpm_window(), LIVE_WPM_WINDOW);
+        app.wpm.set_window(app.wpm_window(), Duration::from_secs(1));
+
+        // A key every 100ms (120 WPM) - recalculated while typing, in its corner instead of a notification
+        for _ in 0..20 {
+            app.on_key_press();
+            clock.advance(Duration::from_millis(100));
+        }
+        app.damage = Damage::default();
+        app.on_tick();
+        assert_eq!(app.wpm.wpm, 120);
+        assert!(app.wpm.is_measuring() && !app.notifications.wpm);
+        assert_eq!(app.damage, Damage::from(Region::Hud));
+
+        // Paused - kept, and dimmed
+        app.damage = Damage::default();
+        clock.advance(WPM_PAUSE + Duration::from_secs(1));
+        app.on_tick();
+        assert_eq!(app.wpm.wpm, 120);
+        assert!(!app.wpm.is_measuring());
+        assert_eq!(app.damage, Damage::from(Region::Hud));
+
+        // Hidden with the WPM display toggle - not redrawn, and no notification instead
+        app.config.show_wpm_notification = false;
+        for _ in 0..20 {
+            app.on_key_press();
+            clock.advance(Duration::from_millis(100));
+        }
+        app.damage = Damage::default();
+        app.on_tick();
+        assert!(app.wpm.is_measuring() && !app.notifications.wpm);
+        assert_eq!(app.damage, Damage::default());
+
+        // Not carried over to the next session
+        app.engine.session = ttypr::stats::SessionTracker::new(); // Not recorded
+        app.finish_session();
+        assert_eq!(app.wpm.wpm, 0);
+    }
+
+    #[test]
+    fn test_app_metronome() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.current_mode = CurrentMode::Typing;
+        assert!(!app.metronome_active());
+        assert_eq!(app.metronome.next_change(0), None);
+
+        // 300 BPM (60 WPM) - a beat every 200ms, lit for 100ms of it
+        app.config.metronome_bpm = 300;
+        app.damage = Damage::default();
+        app.on_tick();
+        assert!(app.metronome.is_lit(300));
+        assert_eq!(app.damage, Damage::from(Region::Hud));
+        assert_eq!(app.metronome.next_change(300), Some(clock.now() + METRONOME_FLASH));
+
+        app.damage = Damage::default();
+        clock.advance(Duration::from_millis(50));
+        app.on_tick();
+        assert!(app.damage.is_empty()); // Still lit
+        clock.advance(Duration::from_millis(50));
+        app.on_tick();
+        assert!(!app.metronome.is_lit(300));
+        assert_eq!(app.damage, Damage::from(Region::Hud));
+        assert_eq!(app.metronome.next_change(300), Some(clock.now() + Duration::from_millis(100)));
+        clock.advance(Duration::from_millis(100));
+        assert!(app.metronome.is_lit(300)); // The next beat
+
+        // Only while typing
+        app.current_mode = CurrentMode::Menu;
+        assert!(!app.metronome_active());
+    }
+
+    #[test]
+    fn test_app_distractor_drill() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.config.distractor_secs = 2;
+        app.current_mode = CurrentMode::Typing;
+
+        // Scheduled with the first key press, 1-3 seconds ahead
+        app.on_key_press();
+        let next_at = app.distractions.next_at.unwrap();
+        assert!(next_at >= clock.now() + Duration::from_secs(1) && next_at <= clock.now() + Duration::from_secs(3));
+        app.on_tick();
+        assert!(app.distractions.prompt.is_none());
+
+        clock.advance(Duration::from_secs(3));
+        app.on_tick();
+        let prompt = app.distractions.prompt.unwrap();
+
+        // Answered, and the next one scheduled
+        app.answer_distractor(prompt.word);
+        assert!(app.distractions.prompt.is_none() && app.distractions.next_at.is_some());
+        assert_eq!(app.distractions.acknowledged, 1);
+
+        // Missed
+        clock.advance(Duration::from_secs(3));
+        app.on_tick();
+        assert!(app.distractions.prompt.is_some());
+        clock.advance(Duration::from_secs(3));
+        app.on_tick();
+        assert_eq!(app.distractions.missed, 1);
+    }
+
+    #[test]
+    fn test_slow_down() {
+        let clock = MockClock::new();
+        let mut slow_down = SlowDown::with_clock(Rc::new(clock.clone()));
+        let pause = Duration::from_millis(1500);
+
+        // Errors more than a second apart aren't a burst
+        assert!(!slow_down.on_error(pause));
+        assert!(!slow_down.on_error(pause));
+        clock.advance(Duration::from_millis(1200));
+        assert!(!slow_down.on_error(pause));
+        assert!(!slow_down.is_locked());
+
+        // The third error within a second locks the input
+        clock.advance(Duration::from_millis(300));
+        assert!(!slow_down.on_error(pause));
+        clock.advance(Duration::from_millis(300));
+        assert!(slow_down.on_error(pause));
+        assert!(slow_down.is_locked());
+        assert!(!slow_down.on_tick());
+
+        clock.advance(pause);
+        assert!(!slow_down.is_locked());
+        assert!(slow_down.on_tick());
+        assert_eq!(slow_down.locked_until, None);
+        // The burst doesn't carry over
+        assert!(!slow_down.on_error(pause));
+
+        // An invalid pause in the config is no pause
+        for secs in [-1.0, f64::NAN, f64::INFINITY] {
+            let mut app = App::with_clock(Rc::new(clock.clone()));
+            app.config.forced_pause_secs = secs;
+            app.engine.push_line("abc ");
+            for _ in 0..3 {
+                app.engine.input_chars.push_back('x');
+                app.update_char_state();
+            }
+            assert!(!app.slow_down.is_locked());
+        }
+    }
+
+    #[test]
+    fn test_combo() {
+        let clock = MockClock::new();
+        let mut combo = Combo::with_clock(Rc::new(clock.clone()));
+        for _ in 0..49 {
+            combo.on_correct();
+        }
+        assert_eq!(combo.current, 49);
+        assert!(!combo.at_milestone());
+
+        // Milestone
+        combo.on_correct();
+        assert!(combo.at_milestone());
+        assert!(!combo.on_tick());
+        clock.advance(Duration::from_secs(2));
+        assert!(combo.on_tick());
+        assert!(!combo.at_milestone());
+
+        // Broken by an error, the best one is kept
+        combo.on_correct();
+        combo.on_error();
+        assert_eq!(combo.current, 0);
+        assert_eq!(combo.best, 51);
+    }
+
+    #[test]
+    fn test_app_error_flash() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.engine.push_line("abc");
+
+        // Off by default
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        assert!(app.error_flash.is_none());
+
+        app.config.error_flash = true;
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        assert!(app.error_flash.is_some());
+
+        // Over after a moment
+        clock.advance(ERROR_FLASH_DURATION);
+        app.on_tick();
+        assert!(app.error_flash.is_none());
+
+        // Not in the low-bandwidth mode
+        app.config.low_bandwidth = true;
+        app.engine.input_chars.push_back('x');
+        app.update_char_state();
+        assert!(app.error_flash.is_none());
+    }
+
+    #[test]
+    fn test_app_error_markers() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.engine.push_line("ab ");
+        app.engine.push_line("cd ");
+        let type_line = |app: &mut App| {
+            for c in "ax ".chars() {
+                assert!(app.error_markers.is_none());
+                app.engine.input_chars.push_back(c);
+                app.update_char_state();
+                app.update_lines();
+            }
+        };
+
+        // Off by default
+        type_line(&mut app);
+        assert!(app.error_markers.is_none());
+
+        // Marked once the line is typed
+        app.config.error_markers = true;
+        app.engine.input_chars.clear();
+        type_line(&mut app);
+        assert!(app.error_markers.is_some());
+
+        // Over after a moment
+        clock.advance(ERROR_MARKERS_DURATION);
+        app.on_tick();
+        assert!(app.error_markers.is_none());
+    }
+
+    #[test]
+    fn test_app_line_summary() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+        app.config.line_summary = true;
+        app.engine.push_line("abcde ");
+        app.engine.push_line("fghij ");
+
+        for c in "abxde".chars() {
+            app.engine.input_chars.push_back(c);
+            app.on_key_press();
+            app.update_char_state();
+            app.update_lines();
+        }
+        assert!(app.line_summary.is_none());
+
+        // Started 6 seconds ago - 6 characters in 6 seconds is 12 WPM
+        clock.advance(Duration::from_secs(6));
+        app.engine.input_chars.push_back(' ');
+        app.update_char_state();
+        app.update_lines();
+        let summary = app.line_summary.as_ref().unwrap();
+        assert_eq!((summary.wpm.round(), summary.errors), (12.0, 1));
+        assert!(app.line_started_at.is_none());
+
+        // Over after a moment
+        clock.advance(LINE_SUMMARY_DURATION);
+        app.on_tick();
+        assert!(app.line_summary.is_none());
+
+        // Not with the silent errors
+        app.config.silent_errors = true;
+        for c in "fxhij ".chars() {
+            app.engine.input_chars.push_back(c);
+            app.on_key_press();
+            app.update_char_state();
+            app.update_lines();
+        }
+        assert!(app.line_summary.is_none());
+    }
+
+    #[test]
+    fn test_app_dictation_phrase() {
+        let mut app = App::new();
+        app.config.dictation_words = 3;
+        app.words = ["red", "fox", "jumps"].iter().map(|word| word.to_string()).collect();
+        app.text = app.words.clone();
+
+        app.current_typing_option = CurrentTypingOption::Words;
+        assert_eq!(app.dictation_phrase().split(' ').count(), 3);
+
+        // Consecutive words of the text, from a random position
+        app.current_typing_option = CurrentTypingOption::Text;
+        let phrase = app.dictation_phrase();
+        assert!(["red fox jumps", "fox jumps", "jumps"].contains(&phrase.as_str()));
+
+        app.current_typing_option = CurrentTypingOption::Ascii;
+        assert_eq!(app.dictation_phrase().chars().count(), 3);
+
+        app.open_dictation();
+        assert!(app.dictation.as_ref().unwrap().hides_at().is_some());
+    }
+
+    #[test]
+    fn test_app_tick_timeout() {
+        let clock = MockClock::new();
+        let mut app = App::with_clock(Rc::new(clock.clone()));
+
+        // Idle - nothing to wake up for
+        assert!(app.tick_timeout().is_none());
+
+        // The closest timer
+        app.notifications.show_mode();
+        let timeout = app.tick_timeout().unwrap();
+        assert!(timeout > Duration::from_secs(1) && timeout <= NOTIFICATION_DURATION + Duration::from_millis(1));
+
+        app.error_flash = Some(clock.now());
+        assert!(app.tick_timeout().unwrap() <= ERROR_FLASH_DURATION + Duration::from_millis(1));
+
+        // Expired timers are due right away
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(app.tick_timeout().unwrap(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn test_app_update_check() {
+        let mut app = App::new();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        app.update_check = Some(receiver);
+        app.on_tick();
+        assert!(app.update_check.is_some() && app.tick_timeout().is_some()); // Still fetching
+
+        // A newer version is noticed
+        sender.send("99.0.0".to_string()).unwrap();
+        app.on_tick();
+        assert_eq!(app.available_update.as_deref(), Some("99.0.0"));
+        assert!(app.update_check.is_none());
+
+        // This one isn't, and a failed check is given up on
+        let (sender, receiver) = std::sync::mpsc::channel();
+        app.available_update = None;
+        app.update_check = Some(receiver);
+        sender.send(CURRENT_VERSION.to_string()).unwrap();
+        app.on_tick();
+        assert_eq!(app.available_update, None);
+        let (sender, receiver) = std::sync::mpsc::channel::<String>();
+        app.update_check = Some(receiver);
+        drop(sender);
+        app.on_tick();
+        assert!(app.update_check.is_none());
+    }
+
+    #[test]
+    fn test_damage() {
+        let mut damage = Damage::default();
+        assert!(damage.is_empty());
+        damage.add(Region::Notifications);
+        damage.add(Region::Hud);
+        assert!(damage.contains(Region::Hud) && !damage.contains(Region::Typing) && !damage.contains(Region::Screen));
+
+        // The whole screen contains every region
+        let damage = Damage::from(Region::Screen);
+        assert!(damage.contains(Region::Typing) && damage.contains(Region::Notifications));
+    }
+
+    #[test]
+    fn test_app_tick_rate_and_frame_budget() {
+        let clock = Rc::new(MockClock::new());
+        let mut app = App::with_clock(clock.clone());
+        app.config.tick_ms = 200;
+        app.test_started_at = Some(clock.now());
+        assert_eq!(app.tick_timeout(), Some(Duration::from_millis(201)));
+
+        // No limit by default
+        app.mark_drawn();
+        app.damage.add(Region::Typing);
+        assert!(app.frame_due());
+
+        // The frame waits for the budget, and the loop wakes up for it
+        app.config.max_fps = 20;
+        assert!(!app.frame_due());
+        assert_eq!(app.tick_timeout(), Some(Duration::from_millis(51)));
+        clock.advance(Duration::from_millis(49));
+        assert!(!app.frame_due());
+        clock.advance(Duration::from_millis(1));
+        assert!(app.frame_due());
+
+        // Not on the stopped clock of the deterministic mode
+        let mut app = App::deterministic();
+        app.config.max_fps = 20;
+        app.mark_drawn();
+        assert!(app.frame_due());
+    }
+
+    #[test]
+    fn test_app_deterministic() {
+        let mut first = App::deterministic();
+        let mut second = App::deterministic();
+        assert_eq!(first.gen_one_line_of_ascii(), second.gen_one_line_of_ascii());
+
+        // The clock is stopped, and no notifications are shown
+        let started = first.notifications.clock.now();
+        first.notifications.show_mode();
+        assert!(!first.notifications.mode);
+        assert!(first.notifications.time_count.is_none());
+        assert_eq!(first.notifications.clock.now(), started);
+
+        // The timers run on the stopped clock too - they don't expire as the real time passes,
+        // and the loop doesn't spin on them
+        first.config.error_flash = true;
+        first.engine.push_line("abc");
+        first.engine.input_chars.push_back('x');
+        first.on_key_press();
+        first.update_char_state();
+        std::thread::sleep(ERROR_FLASH_DURATION * 2);
+        assert!(first.tick_timeout().is_some_and(|timeout| timeout > ERROR_FLASH_DURATION / 2));
+        first.on_tick();
+        assert!(first.error_flash.is_some());
+        assert_eq!(first.engine.session.timer, Some(started));
+    }
+
+    #[test]
+    fn test_app_repeat_mode() {
+        let mut app = App::new();
+        app.config.history_lines = 0;
+        app.config.preview_lines = 2;
+        app.start_repeat_attempt(); // Off - nothing changes
+        assert!(app.engine.charset.is_empty());
+
+        app.toggle_repeat_mode();
+        assert!(app.config.repeat_seed.is_some());
+        let mut attempt = || {
+            app.start_repeat_attempt();
+            app.engine.charset.iter().collect::<String>()
+        };
+        let first = attempt();
+        assert_eq!(first.chars().count(), 3 * 50);
+        assert_eq!(attempt(), first);
+
+        // The text starts over at the same position
+        app.current_typing_option = CurrentTypingOption::Text;
+        app.text = (0..100).map(|i| i.to_string()).collect();
+        app.config.skip_len = 10;
+        app.engine.clear();
+        app.start_repeat_attempt();
+        assert_eq!(app.repeat_text_start, Some(10));
+        app.engine.clear();
+        app.config.skip_len = 40;
+        app.start_repeat_attempt();
+        assert_eq!(app.engine.charset.iter().take(3).collect::<String>(), "10 ");
+
+        app.toggle_repeat_mode();
+        assert_eq!((app.config.repeat_seed, app.repeat_text_start), (None, None));
+    }
+
+    #[test]
+    fn test_key_filter() {
+        let clock = MockClock::new();
+        let mut filter = KeyFilter::with_clock(Rc::new(clock.clone()));
+        let window = Duration::from_millis(100);
+
+        // The same character again within the window is a bounce, others aren't
+        assert!(!filter.is_bounce('a', window));
+        clock.advance(Duration::from_millis(30));
+        assert!(filter.is_bounce('a', window));
+        assert!(!filter.is_bounce('b', window));
+        assert!(!filter.is_bounce('a', window));
+        clock.advance(Duration::from_millis(150));
+        assert!(!filter.is_bounce('a', window));
+
+        // Released too soon, released after the minimum hold time, a different key released
+        let min_hold = Duration::from_millis(200);
+        filter.on_press('x');
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(filter.on_release('x', min_hold), None);
+        filter.on_press('x');
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(filter.on_release('y', min_hold), None);
+        assert_eq!(filter.on_release('x', min_hold), Some('x'));
+        assert_eq!(filter.on_release('x', min_hold), None);
+
+        // Overlapping presses (rollover) - each key is timed from its own press, and a repeat
+        // of a held key doesn't restart it
+        filter.on_press('a');
+        clock.advance(Duration::from_millis(150));
+        filter.on_press('b');
+        filter.on_press('a'); // Repeat
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(filter.on_release('a', min_hold), Some('a'));
+        assert_eq!(filter.on_release('b', min_hold), None);
+        filter.on_press('c');
+        filter.on_press('d');
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(filter.on_release('d', min_hold), Some('d'));
+        assert_eq!(filter.on_release('c', min_hold), Some('c'));
+        assert!(filter.held.is_empty());
+    }
+}
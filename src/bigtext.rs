@@ -0,0 +1,144 @@
+/// The width of a large character, in cells (with a column of space after the glyph).
+pub const BIG_CHAR_WIDTH: usize = 4;
+
+/// The height of a large character, in rows.
+pub const BIG_CHAR_HEIGHT: usize = 3;
+
+/// The 3x5 pixel glyphs of the printable ASCII characters (from the space), a row per byte -
+/// `0b100` is the left pixel.
+const GLYPHS: [[u8; 5]; 95] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // ' '
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b010, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b110, 0b001, 0b010, 0b100, 0b111], // 2
+    [0b110, 0b001, 0b010, 0b001, 0b110], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b110, 0b001, 0b110], // 5
+    [0b011, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b010, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b110], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b110, 0b001, 0b010, 0b000, 0b010], // ?
+    [0b010, 0b101, 0b111, 0b100, 0b011], // @
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b011, 0b010, 0b010, 0b010, 0b011], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // \
+    [0b110, 0b010, 0b010, 0b010, 0b110], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111], // _
+    [0b100, 0b010, 0b000, 0b000, 0b000], // `
+    [0b000, 0b011, 0b101, 0b101, 0b011], // a
+    [0b100, 0b110, 0b101, 0b101, 0b110], // b
+    [0b000, 0b011, 0b100, 0b100, 0b011], // c
+    [0b001, 0b011, 0b101, 0b101, 0b011], // d
+    [0b000, 0b010, 0b111, 0b100, 0b011], // e
+    [0b011, 0b100, 0b110, 0b100, 0b100], // f
+    [0b000, 0b011, 0b101, 0b011, 0b110], // g
+    [0b100, 0b110, 0b101, 0b101, 0b101], // h
+    [0b010, 0b000, 0b010, 0b010, 0b010], // i
+    [0b001, 0b000, 0b001, 0b101, 0b010], // j
+    [0b100, 0b101, 0b110, 0b110, 0b101], // k
+    [0b110, 0b010, 0b010, 0b010, 0b111], // l
+    [0b000, 0b111, 0b111, 0b101, 0b101], // m
+    [0b000, 0b110, 0b101, 0b101, 0b101], // n
+    [0b000, 0b010, 0b101, 0b101, 0b010], // o
+    [0b000, 0b110, 0b101, 0b110, 0b100], // p
+    [0b000, 0b011, 0b101, 0b011, 0b001], // q
+    [0b000, 0b011, 0b100, 0b100, 0b100], // r
+    [0b000, 0b011, 0b110, 0b011, 0b110], // s
+    [0b010, 0b111, 0b010, 0b010, 0b011], // t
+    [0b000, 0b101, 0b101, 0b101, 0b011], // u
+    [0b000, 0b101, 0b101, 0b101, 0b010], // v
+    [0b000, 0b101, 0b101, 0b111, 0b111], // w
+    [0b000, 0b101, 0b010, 0b010, 0b101], // x
+    [0b000, 0b101, 0b011, 0b001, 0b110], // y
+    [0b000, 0b111, 0b011, 0b110, 0b111], // z
+    [0b011, 0b010, 0b100, 0b010, 0b011], // {
+    [0b010, 0b010, 0b010, 0b010, 0b010], // |
+    [0b110, 0b010, 0b001, 0b010, 0b110], // }
+    [0b000, 0b000, 0b011, 0b110, 0b000], // ~
+];
+
+/// The glyph of the characters without one (outside of printable ASCII) - a filled box.
+const UNKNOWN_GLYPH: [u8; 5] = [0b111; 5];
+
+/// Draws a character large, for the low-vision mode - its 3x5 pixel glyph, two pixels a cell
+/// with the half-block characters. Returns the rows of the character, `BIG_CHAR_WIDTH` cells
+/// wide (the last one is the space between the characters).
+pub fn big_char(c: char) -> [String; BIG_CHAR_HEIGHT] {
+    let glyph = match c {
+        ' '..='~' => GLYPHS[c as usize - ' ' as usize],
+        _ => UNKNOWN_GLYPH,
+    };
+    let pixel = |row: usize, column: usize| glyph.get(row).is_some_and(|bits| bits & (0b100 >> column) != 0);
+    std::array::from_fn(|row| {
+        let mut cells: String = (0..3)
+            .map(|column| match (pixel(row * 2, column), pixel(row * 2 + 1, column)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            })
+            .collect();
+        cells.push(' ');
+        cells
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_char() {
+        assert_eq!(big_char('A'), ["▄▀▄ ", "█▀█ ", "▀ ▀ "]);
+        assert_eq!(big_char(' '), ["    "; 3]);
+        assert_eq!(big_char('é'), ["███ ", "███ ", "▀▀▀ "]);
+        assert!(big_char('~').iter().all(|row| row.chars().count() == BIG_CHAR_WIDTH));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::checkpoint::CHECKPOINT_FILE;
+use crate::crash::CRASH_DIR;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{fs::{self, File}, io, path::Path};
+
+/// Writes a gzipped tar archive of the whole config directory (config, word and
+/// text files, stats of all the profiles) to the given path.
+///
+/// The transient files (the crash reports, the running timed test) are left out, and so is
+/// the bundle itself, if it's written into the config directory.
+///
+/// Returns the number of files in the bundle.
+pub fn export_bundle(config_dir: &Path, output: &Path) -> io::Result<usize> {
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(output)?, Compression::default()));
+    let output = fs::canonicalize(output)?;
+    let count = append_dir(&mut builder, config_dir, Path::new(""), &output)?;
+    builder.into_inner()?.finish()?;
+    Ok(count)
+}
+
+/// Whether a file (or directory) of the config directory, at the given path of the archive,
+/// is left out of the bundle.
+fn is_transient(archive_path: &Path) -> bool {
+    archive_path == Path::new(CRASH_DIR) || archive_path.file_name().is_some_and(|name| name == CHECKPOINT_FILE)
+}
+
+/// Adds the files of a directory (recursively) to the archive, under the given path -
+/// except the transient ones and the archive itself (`output`).
+fn append_dir<W: io::Write>(builder: &mut tar::Builder<W>, dir: &Path, archive_path: &Path, output: &Path) -> io::Result<usize> {
+    let mut count = 0;
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = archive_path.join(entry.file_name());
+        if is_transient(&name) || fs::canonicalize(&path).is_ok_and(|path| path == output) {
+            continue;
+        }
+        if path.is_dir() {
+            count += append_dir(builder, &path, &name, output)?;
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, &name)?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Restores a bundle made by `export_bundle` into the config directory.
+///
+/// Files in the bundle overwrite the existing ones, other files are left as they are.
+/// Returns the number of restored files.
+pub fn import_bundle(input: &Path, config_dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(config_dir)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(input)?));
+
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        // `unpack_in` skips the entries that would end up outside of the config directory
+        if entry.header().entry_type().is_file() && entry.unpack_in(config_dir)? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_and_import_bundle() {
+        // A config directory with a profile
+        let config_dir = tempdir().unwrap();
+        fs::write(config_dir.path().join("config"), "first_boot = false").unwrap();
+        fs::write(config_dir.path().join("words.txt"), "some words").unwrap();
+        fs::create_dir_all(config_dir.path().join("profiles/kid")).unwrap();
+        fs::write(config_dir.path().join("profiles/kid/stats"), "sessions = []").unwrap();
+
+        let bundle_dir = tempdir().unwrap();
+        let bundle = bundle_dir.path().join("backup.tar.gz");
+        assert_eq!(export_bundle(config_dir.path(), &bundle).unwrap(), 3);
+
+        // --- Restoring into an empty directory ---
+        let restored = tempdir().unwrap();
+        assert_eq!(import_bundle(&bundle, restored.path()).unwrap(), 3);
+        assert_eq!(fs::read_to_string(restored.path().join("words.txt")).unwrap(), "some words");
+        assert_eq!(fs::read_to_string(restored.path().join("profiles/kid/stats")).unwrap(), "sessions = []");
+
+        // --- Written into the config directory - without itself, or the transient files ---
+        fs::create_dir_all(config_dir.path().join(CRASH_DIR)).unwrap();
+        fs::write(config_dir.path().join(CRASH_DIR).join("crash-20260101-120000.txt"), "panic").unwrap();
+        fs::write(config_dir.path().join(CHECKPOINT_FILE), "test_secs = 30").unwrap();
+        let inside = config_dir.path().join("backup.tar.gz");
+        assert_eq!(export_bundle(config_dir.path(), &inside).unwrap(), 3);
+        let listed = tempdir().unwrap();
+        assert_eq!(import_bundle(&inside, listed.path()).unwrap(), 3);
+        assert!(!listed.path().join("backup.tar.gz").exists() && !listed.path().join(CRASH_DIR).exists());
+
+        // --- Restoring overwrites the bundled files only ---
+        fs::write(restored.path().join("words.txt"), "changed").unwrap();
+        fs::write(restored.path().join("text.txt"), "kept").unwrap();
+        import_bundle(&bundle, restored.path()).unwrap();
+        assert_eq!(fs::read_to_string(restored.path().join("words.txt")).unwrap(), "some words");
+        assert_eq!(fs::read_to_string(restored.path().join("text.txt")).unwrap(), "kept");
+    }
+}
@@ -0,0 +1,151 @@
+use crate::engine::{CharState, TypingEngine};
+use crate::stats::{ClassCount, IntegrityFlag, WordCount};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// The file the running timed test is saved to, in the config directory.
+pub const CHECKPOINT_FILE: &str = "timed_test";
+
+/// A timed test in progress - saved as it's typed, so a test interrupted by a crash (or the
+/// terminal closing) can be resumed at the next launch, instead of being lost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TestCheckpoint {
+    pub typing_option: String,
+    pub test_secs: u64, // The length of the test
+    pub elapsed_secs: f64, // The time of the test used up (including the penalties for errors)
+    pub session_secs: f64, // The time from the first key press of the session to the last one
+    pub started_at: i64, // When the session started, in seconds since the Unix epoch
+    pub chars: String, // The characters of the lines
+    pub lines_len: Vec<usize>,
+    pub typed: String,
+    pub states: Vec<CharState>, // The states of the typed characters
+    pub keystrokes: usize,
+    pub correct: usize,
+    pub errors: usize,
+    pub corrected: usize,
+    pub slips: usize,
+    #[serde(default)]
+    pub uncorrected: usize,
+    pub mistyped_chars: HashMap<String, usize>,
+    pub class_counts: HashMap<String, ClassCount>,
+    #[serde(default)]
+    pub words: HashMap<String, WordCount>,
+    #[serde(default)]
+    pub flags: Vec<IntegrityFlag>, // Why the result might not be from typing it (kept for the resumed session)
+}
+
+impl TestCheckpoint {
+    /// Captures the lines and the session of the engine - `None` if no session is in progress.
+    pub fn capture(engine: &TypingEngine, typing_option: &str, test_secs: u64, elapsed_secs: f64) -> Option<TestCheckpoint> {
+        let session = &engine.session;
+        let (started_at, timer, last_key_press) = (session.started_at?, session.timer?, session.last_key_press?);
+        Some(TestCheckpoint {
+            typing_option: typing_option.to_string(),
+            test_secs,
+            elapsed_secs,
+            session_secs: last_key_press.duration_since(timer).as_secs_f64(),
+            started_at: started_at.timestamp(),
+            chars: engine.charset.iter().collect(),
+            lines_len: engine.lines_len.iter().copied().collect(),
+            typed: engine.input_chars.iter().collect(),
+            states: engine.char_states.iter().take(engine.cursor()).copied().collect(),
+            keystrokes: session.keystrokes,
+            correct: session.correct,
+            errors: session.errors,
+            corrected: session.corrected,
+            slips: session.slips,
+            uncorrected: session.uncorrected,
+            mistyped_chars: session.mistyped_chars.clone(),
+            class_counts: session.class_counts.clone(),
+            words: session.words.clone(),
+            flags: session.flags.clone(),
+        })
+    }
+
+    /// Puts the lines and the session back into the engine. The timer of the session isn't
+    /// restored - the frontend starts it `session_secs` back with the next key press.
+    pub fn restore(&self, engine: &mut TypingEngine) {
+        engine.clear();
+        engine.charset.extend(self.chars.chars());
+        engine.lines_len.extend(&self.lines_len);
+        engine.input_chars.extend(self.typed.chars());
+        engine.char_states.extend(&self.states);
+        engine.char_states.resize(engine.charset.len(), CharState::Untyped);
+
+        let session = &mut engine.session;
+        session.started_at = Local.timestamp_opt(self.started_at, 0).single();
+        session.keystrokes = self.keystrokes;
+        session.correct = self.correct;
+        session.errors = self.errors;
+        session.corrected = self.corrected;
+        session.slips = self.slips;
+        session.uncorrected = self.uncorrected;
+        session.mistyped_chars = self.mistyped_chars.clone();
+        session.class_counts = self.class_counts.clone();
+        session.words = self.words.clone();
+        session.flags = self.flags.clone();
+    }
+
+    /// The seconds of the test left.
+    pub fn remaining_secs(&self) -> u64 {
+        (self.test_secs as f64 - self.elapsed_secs).max(0.0).ceil() as u64
+    }
+}
+
+/// Saves the running timed test to a specified directory.
+pub fn save_checkpoint(checkpoint: &TestCheckpoint, dir: &Path) -> io::Result<()> {
+    let toml_string = toml::to_string(checkpoint).map_err(io::Error::other)?;
+    fs::write(dir.join(CHECKPOINT_FILE), toml_string)
+}
+
+/// Loads the timed test interrupted at the last run from a specified directory, if there's one.
+pub fn load_checkpoint(dir: &Path) -> Option<TestCheckpoint> {
+    toml::from_str(&fs::read_to_string(dir.join(CHECKPOINT_FILE)).ok()?).ok()
+}
+
+/// Removes the saved timed test from a specified directory (once it's finished, or not resumed).
+pub fn remove_checkpoint(dir: &Path) {
+    fs::remove_file(dir.join(CHECKPOINT_FILE)).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_checkpoint() {
+        let mut engine = TypingEngine::new();
+        engine.push_line("abc");
+        engine.push_line("def");
+        for c in ['a', 'x', 'c'] {
+            engine.type_char(c, Instant::now());
+        }
+        engine.session.flag(IntegrityFlag::Paste);
+        assert_eq!(TestCheckpoint::capture(&TypingEngine::new(), "Words", 30, 0.0), None);
+        let checkpoint = TestCheckpoint::capture(&engine, "Words", 30, 12.5).unwrap();
+        assert_eq!(checkpoint.remaining_secs(), 18);
+
+        // Saved and loaded
+        let dir = tempdir().unwrap();
+        assert_eq!(load_checkpoint(dir.path()), None);
+        save_checkpoint(&checkpoint, dir.path()).unwrap();
+        let loaded = load_checkpoint(dir.path()).unwrap();
+        assert_eq!(loaded, checkpoint);
+        remove_checkpoint(dir.path());
+        assert_eq!(load_checkpoint(dir.path()), None);
+
+        // Restored - carrying on where it was left
+        let mut restored = TypingEngine::new();
+        loaded.restore(&mut restored);
+        assert_eq!(restored.cursor(), 3);
+        assert_eq!(restored.char_state(1), CharState::Incorrect);
+        assert_eq!((restored.session.keystrokes, restored.session.errors, restored.session.uncorrected), (3, 1, 1));
+        assert_eq!(restored.session.started_at.map(|at| at.timestamp()), Some(checkpoint.started_at));
+        assert_eq!(restored.session.flags, vec![IntegrityFlag::Paste]); // Still flagged
+        restored.type_char('d', Instant::now());
+        assert_eq!(restored.char_state(3), CharState::Correct);
+    }
+}
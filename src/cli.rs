@@ -0,0 +1,695 @@
+use ttypr::bundle::{export_bundle, import_bundle};
+use ttypr::commits::{commits_text, git_log};
+use ttypr::csv::{parse_csv, CsvDrill};
+use ttypr::drills::generated_drill;
+use ttypr::history::{history_drill, history_files, parse_history};
+use ttypr::export::{html_report, session_json, session_line, svg_wpm_chart, text_certificate, words_anki, words_csv, StatsSummary};
+use ttypr::reminder::{cron_reminder, ical_reminder, parse_days, parse_time};
+use ttypr::replay::{parse_replay, score_replay};
+use ttypr::stats::{load_stats, load_stats_file, problem_words, profile_dir, save_stats, Stats, WordOrder};
+use ttypr::utils::{get_config_dir, load_config, save_config};
+use ttypr::wordlist::{add_words, builtin_word_list, dedupe_words, read_word_list, remove_words, word_list_path, write_word_list};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command, ValueHint};
+use clap_complete::Shell;
+use color_eyre::{eyre::eyre, Result};
+use serde::Serialize;
+use std::{fs, io::{self, Read, Write}, path::{Path, PathBuf}};
+
+/// Without a command, ttypr starts the typing practice - the text of `ttypr help` and of the man page.
+const ABOUT: &str = "\
+Terminal typing practice.
+
+Without a command, starts the typing practice (--compact - a single line fitted
+to a small pane, e.g. a tmux popup). With --json, the commands print their
+result as JSON (except the files printed with -).";
+
+/// A switch (`--name`) of a command.
+fn flag(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).action(ArgAction::SetTrue).help(help)
+}
+
+/// A file argument of a command.
+fn file(name: &'static str, value_name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).value_name(value_name).value_hint(ValueHint::FilePath).help(help)
+}
+
+/// The command line of ttypr - parsed in `main`, and described by the shell completions
+/// (`ttypr completions`) and the man page (`ttypr manpage`).
+pub fn command() -> Command {
+    let words = || Arg::new("words").value_name("WORD").required(true).num_args(1..).help("The words");
+    Command::new("ttypr")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Terminal typing practice")
+        .long_about(ABOUT)
+        .arg(flag("compact", "Fit the TUI to a small pane - only the line being typed").global(true))
+        .arg(flag("json", "Print the result of the command as JSON").global(true))
+        // For reproducible screenshots and recordings
+        .arg(flag("deterministic", "Use a fixed seed and clock").global(true).hide(true))
+        .subcommand(
+            Command::new("drill").about("Practice random lines of the given characters (e.g. ttypr drill \"[]{}()\")").arg(
+                Arg::new("chars").value_name("CHARS").required(true).num_args(1..).help("The characters to drill (after --, if they start with -)"),
+            ),
+        )
+        .subcommand(
+            Command::new("csv")
+                .about("Practice data entry - type the fields of a CSV (or TSV) file one at a time")
+                .arg(file("file", "FILE", "The CSV file").required(true))
+                .arg(flag("header", "The first row names the fields")),
+        )
+        .subcommand(Command::new("demo").about("Play back a demo session on loop, until a key is pressed (nothing is recorded)"))
+        .subcommand(
+            Command::new("run")
+                .about("Type the key presses of a replay file (or of stdin) without the TUI, and print the result")
+                .arg(flag("headless", "Run without the TUI (the only supported run)").required(true))
+                .arg(file("input", "FILE", "The replay file (- or none reads stdin)").long("input")),
+        )
+        .subcommand(Command::new("last").about("Print the result of the last session in a single line (for shell prompts, status lines)"))
+        .subcommand(Command::new("stats").about("Print the totals of the typing history"))
+        .subcommand(
+            Command::new("export-html")
+                .about("Export an HTML report of the typing history")
+                .arg(file("file", "FILE", "The report file").required(true))
+                .arg(flag("last", "Only of the last session")),
+        )
+        .subcommand(
+            Command::new("export-chart").about("Export an SVG chart of the WPM history").arg(file("file", "FILE", "The chart file").required(true)),
+        )
+        .subcommand(
+            Command::new("export-certificate")
+                .about("Export a text certificate of the last session")
+                .arg(file("file", "FILE", "The certificate file (- prints it)").required(true))
+                .arg(Arg::new("name").long("name").value_name("NAME").help("The name it's awarded to (the active profile by default)"))
+                .arg(flag("ansi", "Color it for terminals")),
+        )
+        .subcommand(
+            Command::new("export-words")
+                .about("Export the problem words of the typing history (CSV, or Anki cards)")
+                .arg(file("file", "FILE", "The export file (- prints it)").required(true))
+                .arg(flag("anki", "Export Anki cards (tab-separated) instead"))
+                .arg(flag("slowest", "The slowest words, instead of the most often mistyped"))
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .default_value(DEFAULT_WORDS_LIMIT)
+                        .help("How many words to export"),
+                )
+                .arg(
+                    Arg::new("min-typed")
+                        .long("min-typed")
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .default_value(DEFAULT_MIN_TYPED)
+                        .help("Leave out the words typed fewer times"),
+                ),
+        )
+        .subcommand(
+            Command::new("reminder")
+                .about("Write an iCalendar reminder to practice")
+                .arg(file("file", "FILE", "The iCalendar file (- prints it)").required_unless_present("cron"))
+                .arg(Arg::new("time").long("time").value_name("HH:MM").default_value("18:00").help("The time of the reminder"))
+                .arg(Arg::new("days").long("days").value_name("DAYS").default_value("daily").help("The days (e.g. mon,wed,fri, weekdays or daily)"))
+                .arg(flag("cron", "Print a crontab entry instead")),
+        )
+        .subcommand(
+            Command::new("sync")
+                .about("Merge another stats file (e.g. from another machine) into the local one")
+                .arg(file("stats", "STATS", "The stats file (or its directory)").required(true)),
+        )
+        .subcommand(
+            Command::new("export-bundle")
+                .about("Back up the config, word/text files and the stats of all the profiles (.tar.gz)")
+                .arg(file("file", "FILE", "The bundle file").required(true)),
+        )
+        .subcommand(
+            Command::new("import-bundle")
+                .about("Restore a backup made with export-bundle (overwrites the files it contains)")
+                .arg(file("file", "FILE", "The bundle file").required(true)),
+        )
+        .subcommand(
+            Command::new("import-history")
+                .about("Make a drill of the commands of the shell history")
+                .arg(file("file", "FILE", "The history file (~/.bash_history, ~/.zsh_history and fish's by default)")),
+        )
+        .subcommand(
+            Command::new("import-commits")
+                .about("Make a text of the commit messages of a git repository")
+                .arg(Arg::new("repo").value_name("REPO").required(true).value_hint(ValueHint::DirPath).help("The repository"))
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .default_value(DEFAULT_COMMITS_LIMIT)
+                        .help("How many of the latest commits to take"),
+                )
+                .arg(flag("anonymize", "Replace the names and emails"))
+                .arg(flag("use", "Make it the text typed")),
+        )
+        .subcommand(
+            Command::new("words")
+                .about("List or edit the words of words.txt (or of a named list)")
+                .subcommand_required(true)
+                .arg(Arg::new("list").long("list").value_name("NAME").global(true).help("The named list (words/<NAME>.txt)"))
+                .subcommand(Command::new("list").about("Print the words of the list"))
+                .subcommand(Command::new("add").about("Add words to the list (skipping the ones in it)").arg(words()))
+                .subcommand(Command::new("remove").about("Remove words from the list").arg(words()))
+                .subcommand(Command::new("dedupe").about("Remove the repeated words").arg(flag("sort", "Sort the list as well"))),
+        )
+        .subcommand(Command::new("check-update").about("Check crates.io for a newer version of ttypr"))
+        .subcommand(
+            Command::new("completions")
+                .about("Print the shell completions of ttypr")
+                .arg(Arg::new("shell").value_name("SHELL").required(true).value_parser(value_parser!(Shell)).help("The shell")),
+        )
+        .subcommand(Command::new("manpage").about("Print the man page of ttypr (roff)"))
+}
+
+/// The JSON output of the commands writing (or restoring) files.
+#[derive(Serialize)]
+struct FilesOutput<'a> {
+    path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    files: Option<usize>, // How many files the bundle has
+}
+
+/// The JSON output of `ttypr sync`.
+#[derive(Serialize)]
+struct SyncOutput {
+    added: usize, // The sessions that weren't in the local stats
+    total: usize,
+}
+
+/// The JSON output of `ttypr words`.
+#[derive(Serialize)]
+struct WordsOutput<'a> {
+    path: String,
+    words: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed: Option<usize>, // How many words were added/removed
+}
+
+/// The JSON output of `ttypr import-history`.
+#[derive(Serialize)]
+struct HistoryOutput {
+    path: String,
+    commands: usize, // How many commands the drill has
+}
+
+/// The JSON output of `ttypr export-words`.
+#[derive(Serialize)]
+struct ExportedWordsOutput<'a> {
+    path: &'a str,
+    words: usize, // How many words were exported
+}
+
+/// The JSON output of `ttypr import-commits`.
+#[derive(Serialize)]
+struct CommitsOutput {
+    path: String,
+    commits: usize, // How many commit messages the text has
+}
+
+/// How many of the latest commits `ttypr import-commits` takes by default.
+const DEFAULT_COMMITS_LIMIT: &str = "200";
+
+/// How many words `ttypr export-words` exports by default.
+const DEFAULT_WORDS_LIMIT: &str = "50";
+
+/// How many times a word has to be typed for `ttypr export-words` to export it, by default.
+const DEFAULT_MIN_TYPED: &str = "3";
+
+/// Prints the result of a command - as JSON (with `--json`), or as the given text.
+fn print_output(json: bool, output: &impl Serialize, text: &str) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(output)?);
+    } else {
+        println!("{}", text);
+    }
+    Ok(())
+}
+
+/// Runs the command given on the command line, instead of starting the TUI.
+pub fn run(matches: &ArgMatches) -> Result<()> {
+    let json = matches.get_flag("json");
+    match matches.subcommand() {
+        Some(("run", matches)) => run_headless(matches, json),
+        Some(("last", _)) => last(json),
+        Some(("stats", _)) => stats(json),
+        Some(("export-html", matches)) => export_html(matches, json),
+        Some(("export-chart", matches)) => export_chart(matches, json),
+        Some(("export-certificate", matches)) => export_certificate(matches, json),
+        Some(("export-words", matches)) => export_words(matches, json),
+        Some(("reminder", matches)) => reminder(matches, json),
+        Some(("sync", matches)) => sync(matches, json),
+        Some(("export-bundle", matches)) => export_backup(matches, json),
+        Some(("import-bundle", matches)) => import_backup(matches, json),
+        Some(("import-history", matches)) => import_history(matches, json),
+        Some(("import-commits", matches)) => import_commits(matches, json),
+        Some(("words", matches)) => words(matches, json),
+        Some(("check-update", _)) => check_update(json),
+        Some(("completions", matches)) => {
+            let shell = *matches.get_one::<Shell>("shell").ok_or_else(|| eyre!("Missing the shell"))?;
+            Ok(completions(shell, &mut io::stdout())?)
+        }
+        Some(("manpage", _)) => Ok(manpage(&mut io::stdout())?),
+        Some((command, _)) => Err(eyre!("Unknown command '{}'", command)),
+        None => Err(eyre!("Missing the command")),
+    }
+}
+
+/// Writes the completions of the command line for a shell (for packagers, e.g. to
+/// /usr/share/bash-completion/completions/ttypr).
+fn completions(shell: Shell, out: &mut impl Write) -> io::Result<()> {
+    // Generated into a buffer first, as writing them out directly panics on a closed pipe (`| head`)
+    let mut buf = vec![];
+    clap_complete::generate(shell, &mut command(), "ttypr", &mut buf);
+    out.write_all(&buf)
+}
+
+/// Writes the man page of the command line (roff, e.g. to /usr/share/man/man1/ttypr.1).
+fn manpage(out: &mut impl Write) -> io::Result<()> {
+    clap_mangen::Man::new(command()).render(out)
+}
+
+/// Gets a (required) value of the arguments of a command.
+fn value<'a>(matches: &'a ArgMatches, id: &str) -> Result<&'a String> {
+    matches.get_one::<String>(id).ok_or_else(|| eyre!("Missing the {}", id))
+}
+
+/// The JSON output of `ttypr check-update`.
+#[cfg(feature = "update-check")]
+#[derive(Serialize)]
+struct UpdateOutput<'a> {
+    current: &'a str,
+    latest: &'a str,
+    update_available: bool,
+}
+
+/// Checks crates.io for a newer version of ttypr.
+#[cfg(feature = "update-check")]
+fn check_update(json: bool) -> Result<()> {
+    use ttypr::update::{fetch_latest_version, is_newer, CURRENT_VERSION};
+
+    let latest = fetch_latest_version().map_err(|err| eyre!("Failed to check for updates: {}", err))?;
+    let update_available = is_newer(&latest, CURRENT_VERSION);
+    let text = if update_available {
+        format!("ttypr {} is available (this is {}) - cargo install ttypr", latest, CURRENT_VERSION)
+    } else {
+        format!("ttypr {} is up to date", CURRENT_VERSION)
+    };
+    print_output(json, &UpdateOutput { current: CURRENT_VERSION, latest: &latest, update_available }, &text)
+}
+
+/// Built without the `update-check` feature, there's no update check.
+#[cfg(not(feature = "update-check"))]
+fn check_update(_json: bool) -> Result<()> {
+    Err(eyre!("This build of ttypr has no update check (built without the update-check feature)"))
+}
+
+/// Loads the data entry drill of `ttypr csv <file> [--header]`.
+pub fn csv_drill(matches: &ArgMatches) -> Result<CsvDrill> {
+    let header = matches.get_flag("header");
+    let path = value(matches, "file")?;
+    let content = fs::read_to_string(path).map_err(|err| eyre!("Failed to read {}: {}", path, err))?;
+    let rows = parse_csv(&content);
+    if rows.len() <= header as usize {
+        return Err(eyre!("{} has no rows to type", path));
+    }
+    Ok(CsvDrill::new(rows, header))
+}
+
+/// Gets the characters to drill from the arguments of `ttypr drill` (without duplicates
+/// and whitespace).
+pub fn drill_chars(matches: &ArgMatches) -> Result<Vec<String>> {
+    let args: Vec<&str> = matches.get_many::<String>("chars").into_iter().flatten().map(String::as_str).collect();
+    let mut chars: Vec<char> = vec![];
+    for c in args.concat().chars().filter(|c| !c.is_whitespace() && !c.is_control()) {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+    if chars.is_empty() {
+        return Err(eyre!("Missing the characters to drill"));
+    }
+    Ok(chars.iter().map(char::to_string).collect())
+}
+
+/// Types the key presses of a replay (from `--input`, or stdin) with the typing engine,
+/// and prints the result. Nothing is recorded.
+fn run_headless(matches: &ArgMatches, json: bool) -> Result<()> {
+    let content = match matches.get_one::<String>("input").map(String::as_str) {
+        Some("-") | None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            content
+        }
+        Some(path) => fs::read_to_string(path)?,
+    };
+    let replay = parse_replay(&content)?;
+    let session = score_replay(&replay);
+
+    if json {
+        println!("{}", session_json(&session));
+    } else {
+        println!("{}", session_line(&session));
+        println!(
+            "{} keystrokes, {} errors ({} corrected) in {:.1}s",
+            session.keystrokes, session.errors, session.corrected, session.duration_secs
+        );
+    }
+    Ok(())
+}
+
+/// Prints the WPM and accuracy of the last recorded session in a single line (or as JSON).
+fn last(json: bool) -> Result<()> {
+    let stats = load_active_stats()?;
+    let last = stats.sessions.last().ok_or_else(|| eyre!("No sessions recorded yet"))?;
+
+    if json {
+        println!("{}", session_json(last));
+    } else {
+        println!("{}", session_line(last));
+    }
+    Ok(())
+}
+
+/// Prints the totals of the recorded sessions (or as JSON).
+fn stats(json: bool) -> Result<()> {
+    let summary = StatsSummary::of(&load_active_stats()?);
+    print_output(json, &summary, &summary.to_text())
+}
+
+/// Writes an HTML report of the recorded sessions to the given file.
+fn export_html(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "file")?;
+    let only_last = matches.get_flag("last");
+
+    let stats = load_active_stats()?;
+    let sessions = if only_last {
+        let last = stats.sessions.last().ok_or_else(|| eyre!("No sessions recorded yet"))?;
+        std::slice::from_ref(last)
+    } else {
+        &stats.sessions[..]
+    };
+
+    fs::write(path, html_report(sessions))?;
+    print_output(json, &FilesOutput { path, files: None }, &format!("Report written to {}", path))
+}
+
+/// Writes an SVG chart of the WPM of the recorded sessions to the given file.
+fn export_chart(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "file")?;
+
+    let stats = load_active_stats()?;
+    if stats.sessions.is_empty() {
+        return Err(eyre!("No sessions recorded yet"));
+    }
+
+    fs::write(path, svg_wpm_chart(&stats.sessions))?;
+    print_output(json, &FilesOutput { path, files: None }, &format!("Chart written to {}", path))
+}
+
+/// Writes a text certificate of the last recorded session to the given file (or prints it, for `-`).
+///
+/// The certificate is awarded to the active profile, unless a name is given with `--name`.
+fn export_certificate(matches: &ArgMatches, json: bool) -> Result<()> {
+    let ansi = matches.get_flag("ansi");
+    let path = value(matches, "file")?;
+    let name = matches.get_one::<String>("name");
+
+    let config = load_config(&get_config_dir()?).map_err(|err| eyre!("Failed to load config: {}", err))?;
+    let stats = load_active_stats()?;
+    let last = stats.sessions.last().ok_or_else(|| eyre!("No sessions recorded yet"))?;
+    let certificate = text_certificate(last, name.unwrap_or(&config.active_profile), ansi);
+
+    if path == "-" {
+        print!("{}", certificate);
+    } else {
+        fs::write(path, certificate)?;
+        print_output(json, &FilesOutput { path, files: None }, &format!("Certificate written to {}", path))?;
+    }
+    Ok(())
+}
+
+/// Writes the problem words of the recorded sessions to the given file (or prints them, for `-`) -
+/// as CSV, or as Anki cards with `--anki`. The most often mistyped words, or the slowest ones
+/// with `--slowest`.
+fn export_words(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "file")?;
+    let order = if matches.get_flag("slowest") { WordOrder::Slowest } else { WordOrder::Mistakes };
+    let limit = matches.get_one::<usize>("limit").copied().unwrap_or_default();
+    let min_typed = matches.get_one::<usize>("min-typed").copied().unwrap_or_default();
+
+    let stats = load_active_stats()?;
+    let mut words = problem_words(&stats.sessions, min_typed, order);
+    if words.is_empty() {
+        return Err(eyre!("No problem words recorded yet (words typed at least {} times in the Words or Text option)", min_typed));
+    }
+    words.truncate(limit);
+    let export = if matches.get_flag("anki") { words_anki(&words) } else { words_csv(&words) };
+
+    if path == "-" {
+        print!("{}", export);
+    } else {
+        fs::write(path, export)?;
+        let output = ExportedWordsOutput { path, words: words.len() };
+        print_output(json, &output, &format!("Exported {} words to {}", words.len(), path))?;
+    }
+    Ok(())
+}
+
+/// Writes an iCalendar file with a recurring practice reminder (or prints a crontab entry, with `--cron`).
+///
+/// The reminder is at 18:00 every day, unless set with `--time` and `--days`. It lasts
+/// as long as the daily goal (or 15 minutes, without one).
+fn reminder(matches: &ArgMatches, json: bool) -> Result<()> {
+    let cron = matches.get_flag("cron");
+    let (time, days) = (value(matches, "time")?, value(matches, "days")?);
+    let time = parse_time(time).ok_or_else(|| eyre!("Invalid time '{}' (expected HH:MM, e.g. 18:30)", time))?;
+    let days = parse_days(days).ok_or_else(|| eyre!("Invalid days '{}' (expected e.g. mon,wed,fri, weekdays or daily)", days))?;
+
+    if cron {
+        println!("{}", cron_reminder(time, &days));
+        return Ok(());
+    }
+
+    let path = value(matches, "file")?;
+    let config = load_config(&get_config_dir()?).unwrap_or_default();
+    let minutes = if config.daily_goal_minutes > 0 { config.daily_goal_minutes } else { 15 };
+    let ical = ical_reminder(time, &days, minutes, chrono::Local::now().date_naive(), chrono::Utc::now());
+
+    if path == "-" {
+        print!("{}", ical);
+    } else {
+        fs::write(path, ical)?;
+        print_output(json, &FilesOutput { path, files: None }, &format!("Reminder written to {} (import it into your calendar)", path))?;
+    }
+    Ok(())
+}
+
+/// Merges the sessions of another stats file into the stats of the active profile.
+fn sync(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "stats")?;
+
+    // Accept the directory containing the stats file as well
+    let mut path = Path::new(path).to_path_buf();
+    if path.is_dir() {
+        path = path.join("stats");
+    }
+    let other = load_stats_file(&path).map_err(|err| eyre!("Failed to load {}: {}", path.display(), err))?;
+
+    let stats_dir = active_stats_dir()?;
+    let mut stats = load_stats(&stats_dir).map_err(|err| eyre!("Failed to load stats: {}", err))?;
+    let added = stats.merge(other);
+    save_stats(&stats, &stats_dir).map_err(|err| eyre!("Failed to save stats: {}", err))?;
+
+    let total = stats.sessions.len();
+    print_output(json, &SyncOutput { added, total }, &format!("Merged {} new sessions ({} in total)", added, total))
+}
+
+/// Writes a backup bundle of the whole config directory to the given file.
+fn export_backup(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "file")?;
+    let count = export_bundle(&get_config_dir()?, Path::new(path))?;
+    print_output(json, &FilesOutput { path, files: Some(count) }, &format!("Backed up {} files to {}", count, path))
+}
+
+/// Restores a backup bundle into the config directory.
+fn import_backup(matches: &ArgMatches, json: bool) -> Result<()> {
+    let path = value(matches, "file")?;
+    let config_dir = get_config_dir()?;
+    let count = import_bundle(Path::new(path), &config_dir)?;
+    let text = format!("Restored {} files to {}", count, config_dir.display());
+    print_output(json, &FilesOutput { path: &config_dir.to_string_lossy(), files: Some(count) }, &text)
+}
+
+/// Makes a drill of the commands of the shell history (or of the given history file) -
+/// the `shell-history` word list, one command per line, tagged as a drill.
+fn import_history(matches: &ArgMatches, json: bool) -> Result<()> {
+    let files = match matches.get_one::<String>("file") {
+        Some(path) => vec![PathBuf::from(path)],
+        None => {
+            let home = home::home_dir().ok_or_else(|| eyre!("Home directory not found"))?;
+            history_files(&home)
+        }
+    };
+    if files.is_empty() {
+        return Err(eyre!("No shell history found (~/.bash_history, ~/.zsh_history or ~/.local/share/fish/fish_history)"));
+    }
+
+    let mut commands = vec![];
+    for file in &files {
+        // Zsh histories aren't always valid UTF-8
+        let content = fs::read(file).map_err(|err| eyre!("Failed to read {}: {}", file.display(), err))?;
+        commands.extend(parse_history(&String::from_utf8_lossy(&content)));
+    }
+    let drill = history_drill(&commands);
+    if drill.is_empty() {
+        return Err(eyre!("No commands to practice in the shell history"));
+    }
+
+    let path = word_list_path(&get_config_dir()?, Some("shell-history"));
+    write_word_list(&path, &drill).map_err(|err| eyre!("Failed to write {}: {}", path.display(), err))?;
+    fs::write(path.with_extension("toml"), "name = \"Shell history\"\ncategory = \"drill\"\nlines = true\n")?;
+
+    let text = format!("Made a drill of {} commands ({}) - pick it with D in Menu mode", drill.len(), path.display());
+    print_output(json, &HistoryOutput { path: path.display().to_string(), commands: drill.len() }, &text)
+}
+
+/// Makes a text of the commit messages of a git repository, in the `texts` directory
+/// (and, with `--use`, makes it the text typed with the Text option).
+fn import_commits(matches: &ArgMatches, json: bool) -> Result<()> {
+    let anonymize = matches.get_flag("anonymize");
+    let use_text = matches.get_flag("use");
+    let limit = matches.get_one::<usize>("limit").copied().unwrap_or_default();
+    let repo = PathBuf::from(value(matches, "repo")?);
+
+    let commits = git_log(&repo, limit).map_err(|err| eyre!("Failed to read the commits of {}: {}", repo.display(), err))?;
+    if commits.is_empty() {
+        return Err(eyre!("No commits in {}", repo.display()));
+    }
+    let repo_name = fs::canonicalize(&repo)?.file_name().map_or("repo".to_string(), |name| name.to_string_lossy().into_owned());
+
+    let config_dir = get_config_dir()?;
+    let path = config_dir.join("texts").join(format!("commits-{}.txt", repo_name));
+    let text = commits_text(&commits, anonymize);
+    fs::create_dir_all(config_dir.join("texts"))?;
+    fs::write(&path, &text)?;
+    fs::write(path.with_extension("toml"), format!("name = \"Commits of {}\"\n", repo_name))?;
+
+    if use_text {
+        // The changed text.txt is typed from its beginning
+        fs::write(config_dir.join("text.txt"), &text)?;
+        let mut config = load_config(&config_dir).map_err(|err| eyre!("Failed to load config: {}", err))?;
+        config.use_default_text_set = false;
+        save_config(&config, &config_dir).map_err(|err| eyre!("Failed to save config: {}", err))?;
+    }
+
+    let message = format!("Made a text of {} commit messages ({})", commits.len(), path.display());
+    print_output(json, &CommitsOutput { path: path.display().to_string(), commits: commits.len() }, &message)
+}
+
+/// Lists or edits the words of words.txt (or of a named list, with `--list <name>`).
+fn words(matches: &ArgMatches, json: bool) -> Result<()> {
+    let (action, matches) = matches.subcommand().ok_or_else(|| eyre!("Missing the words action"))?;
+    let sort = action == "dedupe" && matches.get_flag("sort");
+    let list = matches.get_one::<String>("list");
+    let operands = || -> Vec<String> { matches.get_many::<String>("words").into_iter().flatten().cloned().collect() };
+    let config = load_config(&get_config_dir()?).unwrap_or_default();
+    let builtin = list.and_then(|name| builtin_word_list(name).or_else(|| generated_drill(&mut rand::rng(), name, &config)));
+
+    let path = word_list_path(&get_config_dir()?, list.map(String::as_str));
+    let mut words = match builtin.clone() {
+        Some(words) => words,
+        None => read_word_list(&path).map_err(|err| eyre!("Failed to read {}: {}", path.display(), err))?,
+    };
+
+    let changed = match action {
+        "list" if json => {
+            let output = WordsOutput { path: path.display().to_string(), words: &words, changed: None };
+            return print_output(json, &output, "");
+        }
+        "list" => {
+            for word in &words {
+                println!("{}", word);
+            }
+            return Ok(());
+        }
+        _ if builtin.is_some() => return Err(eyre!("'{}' is built into ttypr, it can't be edited", list.unwrap())),
+        // Nothing to remove from a list that doesn't exist (and it isn't created empty)
+        "remove" | "dedupe" if !path.exists() => return Err(eyre!("There's no word list at {}", path.display())),
+        "add" => add_words(&mut words, &operands()),
+        "remove" => remove_words(&mut words, &operands()),
+        "dedupe" => dedupe_words(&mut words, sort),
+        action => return Err(eyre!("Unknown words action '{}'", action)),
+    };
+    let message = match action {
+        "add" => format!("Added {} words", changed),
+        "remove" => format!("Removed {} words", changed),
+        _ => format!("Removed {} repeated words", changed),
+    };
+
+    write_word_list(&path, &words).map_err(|err| eyre!("Failed to write {}: {}", path.display(), err))?;
+    let output = WordsOutput { path: path.display().to_string(), words: &words, changed: Some(changed) };
+    print_output(json, &output, &format!("{} ({} in {})", message, words.len(), path.display()))
+}
+
+/// Gets the stats directory of the profile that is active in the config.
+fn active_stats_dir() -> Result<PathBuf> {
+    let config_dir = get_config_dir()?;
+    let config = load_config(&config_dir).map_err(|err| eyre!("Failed to load config: {}", err))?;
+    Ok(profile_dir(&config_dir, &config.active_profile))
+}
+
+/// Loads the stats of the profile that is active in the config.
+fn load_active_stats() -> Result<Stats> {
+    load_stats(&active_stats_dir()?).map_err(|err| eyre!("Failed to load stats: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        command().debug_assert();
+        let matches = command().try_get_matches_from(["ttypr", "drill", "[]", "{ }", "--compact"]).unwrap();
+        assert!(matches.get_flag("compact"));
+        assert_eq!(drill_chars(matches.subcommand_matches("drill").unwrap()).unwrap(), ["[", "]", "{", "}"]);
+
+        // --json after the command, and the default limit
+        let matches = command().try_get_matches_from(["ttypr", "import-commits", ".", "--json"]).unwrap();
+        assert!(matches.get_flag("json"));
+        assert_eq!(matches.subcommand_matches("import-commits").unwrap().get_one::<usize>("limit"), Some(&200));
+
+        let matches = command().try_get_matches_from(["ttypr", "export-words", "-", "--anki", "--limit", "20"]).unwrap();
+        let export_words = matches.subcommand_matches("export-words").unwrap();
+        assert_eq!((export_words.get_one::<usize>("limit"), export_words.get_one::<usize>("min-typed")), (Some(&20), Some(&3)));
+
+        assert!(command().try_get_matches_from(["ttypr", "run"]).is_err()); // Only headless runs
+        assert!(command().try_get_matches_from(["ttypr", "reminder", "--cron"]).is_ok()); // No file needed
+        assert!(command().try_get_matches_from(["ttypr", "words", "add"]).is_err());
+        assert!(command().try_get_matches_from(["ttypr", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn test_completions_and_manpage() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut out = vec![];
+            completions(shell, &mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            assert!(out.contains("export-certificate") && out.contains("limit"), "{}", shell);
+        }
+
+        // A failed write (e.g. to a closed pipe) is an error, not a panic
+        let mut closed: &mut [u8] = &mut [];
+        assert_eq!(completions(Shell::Bash, &mut closed).unwrap_err().kind(), io::ErrorKind::WriteZero);
+
+        let mut out = vec![];
+        manpage(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(".TH ttypr 1") && out.contains("import\\-commits"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::{io, path::Path, process::Command};
+
+/// Separates the fields of a commit in the `git log` output (the ASCII unit separator).
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// Separates the commits in the `git log` output (the ASCII record separator).
+const COMMIT_SEPARATOR: char = '\x1e';
+
+/// The trailers of commit messages (`Signed-off-by: ...`), which aren't prose.
+const TRAILERS: &[&str] = &["signed-off-by:", "co-authored-by:", "reviewed-by:", "acked-by:", "tested-by:", "reported-by:", "change-id:"];
+
+/// What names and email addresses are replaced with, when anonymizing.
+const ANONYMOUS_NAME: &str = "someone";
+const ANONYMOUS_EMAIL: &str = "someone@example.com";
+
+/// A commit of a git repository - its author and message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub author: String,
+    pub email: String,
+    pub message: String,
+}
+
+/// Gets the latest `limit` commits of a git repository (without the merge commits), by running `git log`.
+pub fn git_log(repo: &Path, limit: usize) -> io::Result<Vec<Commit>> {
+    let format = format!("--format=%an{0}%ae{0}%B{1}", FIELD_SEPARATOR, COMMIT_SEPARATOR);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--no-merges", "-n", &limit.to_string(), &format])
+        .output()?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(message));
+    }
+    Ok(parse_git_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses the output of `git log` with the format used by `git_log`.
+pub fn parse_git_log(output: &str) -> Vec<Commit> {
+    output
+        .split(COMMIT_SEPARATOR)
+        .filter_map(|commit| {
+            let mut fields = commit.trim_start().splitn(3, FIELD_SEPARATOR);
+            Some(Commit {
+                author: fields.next()?.to_string(),
+                email: fields.next()?.to_string(),
+                message: fields.next()?.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Makes a practice text of the commit messages - one paragraph per commit, without the
+/// trailers. With `anonymize`, the names and email addresses of the authors (and any other
+/// email addresses) are replaced with placeholders.
+pub fn commits_text(commits: &[Commit], anonymize: bool) -> String {
+    let mut paragraphs = vec![];
+    for commit in commits {
+        let mut lines: Vec<String> = commit
+            .message
+            .lines()
+            .filter(|line| !TRAILERS.iter().any(|trailer| line.trim_start().to_lowercase().starts_with(trailer)))
+            .map(str::to_string)
+            .collect();
+        if anonymize {
+            lines = lines.iter().map(|line| anonymize_line(line, commits)).collect();
+        }
+        let paragraph = lines.join("\n").trim().to_string();
+        if !paragraph.is_empty() {
+            paragraphs.push(paragraph);
+        }
+    }
+    let mut text = paragraphs.join("\n\n");
+    text.push('\n');
+    text
+}
+
+/// Replaces the names and the email addresses of the authors, and any other email addresses, in a line.
+fn anonymize_line(line: &str, commits: &[Commit]) -> String {
+    let mut line = line.to_string();
+    for commit in commits {
+        if !commit.email.is_empty() {
+            line = line.replace(&commit.email, ANONYMOUS_EMAIL);
+        }
+        if !commit.author.is_empty() {
+            line = line.replace(&commit.author, ANONYMOUS_NAME);
+        }
+    }
+    line.split(' ')
+        .map(|word| {
+            let address = word.trim_matches(|c: char| "<>(),.;:'\"".contains(c));
+            if address.contains('@') && address.contains('.') && !address.starts_with('@') {
+                word.replace(address, ANONYMOUS_EMAIL)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = "Ada Lovelace\x1fada@example.org\x1fFix the parser\n\nIt failed on empty input.\n\x1e\nAlan Turing\x1falan@example.org\x1fAdd tests\n\x1e\n";
+        let commits = parse_git_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author, "Ada Lovelace");
+        assert_eq!(commits[0].message, "Fix the parser\n\nIt failed on empty input.");
+        assert_eq!(commits[1], Commit { author: "Alan Turing".to_string(), email: "alan@example.org".to_string(), message: "Add tests".to_string() });
+    }
+
+    #[test]
+    fn test_commits_text() {
+        let commit = |author: &str, email: &str, message: &str| Commit { author: author.to_string(), email: email.to_string(), message: message.to_string() };
+        let commits = vec![
+            commit("Ada Lovelace", "ada@example.org", "Fix the parser found by Alan Turing\n\nSigned-off-by: Ada Lovelace <ada@example.org>"),
+            commit("Alan Turing", "alan@example.org", "Ask (bob@corp.com) or alan@example.org, not @team"),
+        ];
+        assert_eq!(
+            commits_text(&commits, false),
+            "Fix the parser found by Alan Turing\n\nAsk (bob@corp.com) or alan@example.org, not @team\n"
+        );
+        assert_eq!(
+            commits_text(&commits, true),
+            "Fix the parser found by someone\n\nAsk (someone@example.com) or someone@example.com, not @team\n"
+        );
+    }
+}
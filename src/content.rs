@@ -0,0 +1,101 @@
+use serde::Deserialize;
+use std::{fs, path::Path};
+
+/// The metadata of a word list or a text, from the TOML file next to it
+/// (`english.toml` for `english.txt`). All of it is optional.
+#[derive(Deserialize, Default, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ContentInfo {
+    pub name: Option<String>, // The name displayed instead of the file name
+    pub language: Option<String>,
+    pub category: Option<String>,
+    pub difficulty: Option<String>,
+    pub lines: bool, // (For word lists) - Every line is one item (e.g. a command), instead of every word
+}
+
+impl ContentInfo {
+    /// The tags the content can be filtered by - its language, category and difficulty.
+    pub fn tags(&self) -> Vec<&str> {
+        [&self.language, &self.category, &self.difficulty]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether the content has the given tag (case-insensitive).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().iter().any(|own| own.eq_ignore_ascii_case(tag))
+    }
+
+    /// The rank of the difficulty - 0 for `easy`, 1 for `medium` and 2 for `hard`
+    /// (or `beginner`, `intermediate` and `advanced`). `None` for others.
+    pub fn difficulty_rank(&self) -> Option<usize> {
+        match self.difficulty.as_deref()?.to_lowercase().as_str() {
+            "easy" | "beginner" => Some(0),
+            "medium" | "intermediate" => Some(1),
+            "hard" | "advanced" => Some(2),
+            _ => None,
+        }
+    }
+
+    /// The name and the tags of the content, as displayed on the pickers
+    /// (`English words [en, common]`).
+    pub fn label(&self, file_name: &str) -> String {
+        let name = self.name.as_deref().unwrap_or(file_name);
+        let tags = self.tags();
+        if tags.is_empty() { name.to_string() } else { format!("{} [{}]", name, tags.join(", ")) }
+    }
+}
+
+/// Loads the metadata of a word list or a text file, from the TOML file next to it.
+///
+/// Content without (or with an unreadable) metadata file gets no metadata.
+pub fn load_content_info(path: &Path) -> ContentInfo {
+    fs::read_to_string(path.with_extension("toml"))
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Gets the distinct tags of the given content (lowercase, sorted).
+pub fn content_tags<'a>(infos: impl IntoIterator<Item = &'a ContentInfo>) -> Vec<String> {
+    let mut tags: Vec<String> = infos
+        .into_iter()
+        .flat_map(|info| info.tags())
+        .map(str::to_lowercase)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_content_info() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rust-terms.txt");
+
+        // No metadata file
+        let info = load_content_info(&path);
+        assert_eq!(info, ContentInfo::default());
+        assert_eq!(info.label("rust-terms"), "rust-terms");
+
+        fs::write(dir.path().join("rust-terms.toml"), "name = \"Rust terms\"\ncategory = \"Programming\"\ndifficulty = \"hard\"\n").unwrap();
+        let info = load_content_info(&path);
+        assert_eq!(info.name.as_deref(), Some("Rust terms"));
+        assert_eq!(info.label("rust-terms"), "Rust terms [Programming, hard]");
+        assert!(info.has_tag("programming"));
+        assert!(!info.has_tag("en"));
+        assert_eq!(info.difficulty_rank(), Some(2));
+
+        let english = ContentInfo { language: Some("en".to_string()), difficulty: Some("Hard".to_string()), ..ContentInfo::default() };
+        assert_eq!(content_tags([&info, &english]), vec!["en", "hard", "programming"]);
+        assert_eq!(ContentInfo::default().difficulty_rank(), None);
+        assert!(!info.lines);
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::Local;
+use std::{collections::VecDeque, fs, io, path::{Path, PathBuf}, sync::Mutex};
+
+/// The directory the crash reports are written to, in the config directory.
+pub const CRASH_DIR: &str = "crashes";
+
+/// How many of the latest log lines are kept for a crash report.
+const RECENT_LOG_LINES: usize = 50;
+
+/// The latest log lines - what the app did (and failed to do) right before a crash.
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Logs a line (with the time) for a crash report. Only the latest `RECENT_LOG_LINES` are kept.
+pub fn log(line: &str) {
+    let Ok(mut recent) = RECENT_LOG.lock() else {
+        return;
+    };
+    if recent.len() == RECENT_LOG_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(format!("[{}] {}", Local::now().format("%H:%M:%S"), line));
+}
+
+/// Prints an error, and logs it for a crash report.
+pub fn log_error(line: &str) {
+    eprintln!("{}", line);
+    log(line);
+}
+
+/// The latest log lines, the oldest first.
+pub fn recent_log() -> Vec<String> {
+    RECENT_LOG.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Anonymizes a config file for a crash report - the settings (numbers, switches) are kept, and
+/// every text value (the profile, keyboard and word list names, the playlist...) is replaced,
+/// so nothing personal ends up in a bug report. A config that doesn't parse is left out.
+pub fn anonymize_config(config: &str) -> String {
+    fn anonymize(value: &mut toml::Value) {
+        match value {
+            toml::Value::String(text) if !text.is_empty() => *text = "<redacted>".to_string(),
+            toml::Value::Array(values) => values.iter_mut().for_each(anonymize),
+            toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| anonymize(value)),
+            _ => {}
+        }
+    }
+
+    match config.parse::<toml::Table>() {
+        Ok(mut table) => {
+            table.iter_mut().for_each(|(_, value)| anonymize(value));
+            // The mistyped characters (the keys) are of no use for a bug report
+            table.remove("mistyped_chars");
+            toml::to_string_pretty(&table).unwrap_or_default()
+        }
+        Err(_) => "(the config couldn't be parsed)\n".to_string(),
+    }
+}
+
+/// Builds the text of a crash report - the panic message, the backtrace, the latest log
+/// lines and the (anonymized) config.
+pub fn crash_report(message: &str, backtrace: &str, log: &[String], config: &str) -> String {
+    let mut report = format!(
+        "ttypr {} crashed on {} ({})\n\n## Panic\n\n{}\n\n## Backtrace\n\n{}\n\n## Recent log\n\n",
+        env!("CARGO_PKG_VERSION"),
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        std::env::consts::OS,
+        message,
+        backtrace.trim_end(),
+    );
+    if log.is_empty() {
+        report.push_str("(empty)\n");
+    }
+    for line in log {
+        report.push_str(line);
+        report.push('\n');
+    }
+    report.push_str("\n## Config (anonymized)\n\n");
+    report.push_str(config);
+    report
+}
+
+/// Writes a crash report to a specified directory (`crash-<date>-<time>.txt`), and returns its path.
+pub fn write_crash_bundle(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.txt", Local::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_anonymize_config() {
+        let config = "line_len = 50\nactive_profile = \"alice\"\nword_list = \"\"\nshow_notifications = true\n\n[mistyped_chars]\na = 3\n\n[[playlist]]\noption = \"Words\"\nsecs = 30\n";
+        let anonymized = anonymize_config(config);
+        assert!(anonymized.contains("line_len = 50") && anonymized.contains("show_notifications = true"));
+        assert!(anonymized.contains("active_profile = \"<redacted>\"") && !anonymized.contains("alice"));
+        assert!(anonymized.contains("word_list = \"\"")); // Nothing to hide
+        assert!(!anonymized.contains("mistyped_chars"));
+        assert!(anonymized.contains("secs = 30") && !anonymized.contains("Words"));
+        assert_eq!(anonymize_config("not = = toml"), "(the config couldn't be parsed)\n");
+    }
+
+    #[test]
+    fn test_crash_bundle() {
+        for i in 0..RECENT_LOG_LINES + 5 {
+            log(&format!("line {}", i));
+        }
+        let recent = recent_log();
+        assert_eq!(recent.len(), RECENT_LOG_LINES);
+        assert!(recent.last().is_some_and(|line| line.ends_with(&format!("line {}", RECENT_LOG_LINES + 4))));
+
+        let report = crash_report("index out of bounds", "0: ttypr::main", &recent[..2], "line_len = 50\n");
+        for section in ["## Panic\n\nindex out of bounds", "## Backtrace\n\n0: ttypr::main", "line 5\n", "## Config (anonymized)\n\nline_len = 50"] {
+            assert!(report.contains(section), "{}", section);
+        }
+
+        let dir = tempdir().unwrap();
+        let path = write_crash_bundle(&dir.path().join("crashes"), &report).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), report);
+    }
+}
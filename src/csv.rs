@@ -0,0 +1,167 @@
+/// Parses the rows of a CSV (or TSV) file into their fields.
+///
+/// The fields are separated by tabs if the first line has any, otherwise by commas.
+/// Quoted fields can contain separators and doubled quotes (`"say ""hi"", bye"`).
+/// Empty lines are skipped.
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let separator = if content.lines().next().is_some_and(|line| line.contains('\t')) { '\t' } else { ',' };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_row(line, separator))
+        .collect()
+}
+
+/// Splits a line of a CSV file into its fields.
+fn parse_row(line: &str, separator: char) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted || field.is_empty() => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// A data entry drill - the fields of the rows of a CSV file, typed one at a time.
+///
+/// Tab finishes the field (moving to the next row after the last one) and Enter finishes
+/// the row. A field is correct if it was typed exactly, so the accuracy is field-level,
+/// like in transcription and data entry.
+pub struct CsvDrill {
+    pub header: Option<Vec<String>>, // The names of the fields (the first row of the file, with a header)
+    pub rows: Vec<Vec<String>>,
+    pub row: usize,
+    pub field: usize,
+    pub typed: String, // The characters typed for the current field
+    pub fields_typed: usize,
+    pub fields_correct: usize,
+}
+
+impl CsvDrill {
+    /// Creates a drill of the rows (the first one being the names of the fields, with `header`).
+    pub fn new(mut rows: Vec<Vec<String>>, header: bool) -> CsvDrill {
+        let header = if header && !rows.is_empty() { Some(rows.remove(0)) } else { None };
+        CsvDrill { header, rows, row: 0, field: 0, typed: String::new(), fields_typed: 0, fields_correct: 0 }
+    }
+
+    /// The field to type (`None` if there are no rows).
+    pub fn current_field(&self) -> Option<&str> {
+        self.rows.get(self.row)?.get(self.field).map(String::as_str)
+    }
+
+    /// The name of the field to type - from the header, or its number.
+    pub fn field_name(&self) -> String {
+        match self.header.as_ref().and_then(|header| header.get(self.field)) {
+            Some(name) => name.clone(),
+            None => format!("Field {}", self.field + 1),
+        }
+    }
+
+    /// The number of fields of the row being typed.
+    pub fn row_len(&self) -> usize {
+        self.rows.get(self.row).map_or(0, Vec::len)
+    }
+
+    /// Types a character of the field.
+    pub fn type_char(&mut self, c: char) {
+        self.typed.push(c);
+    }
+
+    /// Erases the last typed character of the field.
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+
+    /// Finishes the field (Tab) and moves to the next one, or to the next row after the last one.
+    pub fn next_field(&mut self) {
+        self.score_field();
+        self.field += 1;
+        if self.field >= self.row_len() {
+            self.start_next_row();
+        }
+    }
+
+    /// Finishes the row (Enter) - its fields that weren't typed count as incorrect.
+    pub fn next_row(&mut self) {
+        self.score_field();
+        self.fields_typed += self.row_len().saturating_sub(self.field + 1);
+        self.start_next_row();
+    }
+
+    /// The share of the fields typed exactly (0-100), `None` before a field is finished.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.fields_typed > 0).then(|| self.fields_correct as f64 / self.fields_typed as f64 * 100.0)
+    }
+
+    /// Counts the typed field, and whether it's correct.
+    fn score_field(&mut self) {
+        if let Some(field) = self.current_field() {
+            self.fields_correct += (self.typed == field) as usize;
+            self.fields_typed += 1;
+        }
+        self.typed.clear();
+    }
+
+    /// Moves to the first field of the next row (starting over after the last row).
+    fn start_next_row(&mut self) {
+        self.field = 0;
+        self.row = if self.row + 1 < self.rows.len() { self.row + 1 } else { 0 };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("name,city,amount\n\"Smith, Anna\",Berlin,\"1,200\"\n\n\"say \"\"hi\"\"\", x ,\n");
+        assert_eq!(rows, vec![
+            vec!["name", "city", "amount"],
+            vec!["Smith, Anna", "Berlin", "1,200"],
+            vec!["say \"hi\"", "x", ""],
+        ]);
+        assert_eq!(parse_csv("a\tb,c\n1\t2"), vec![vec!["a", "b,c"], vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn test_csv_drill() {
+        let rows = parse_csv("name,city\nAnna,Berlin\nBen,Rome\n");
+        let mut drill = CsvDrill::new(rows, true);
+        assert_eq!((drill.current_field(), drill.field_name().as_str()), (Some("Anna"), "name"));
+        assert_eq!(drill.accuracy(), None);
+
+        // Typed exactly
+        "Anna".chars().for_each(|c| drill.type_char(c));
+        drill.next_field();
+        assert_eq!((drill.current_field(), drill.field_name().as_str()), (Some("Berlin"), "city"));
+
+        // Corrected with Backspace - still exact
+        "Berln".chars().for_each(|c| drill.type_char(c));
+        drill.backspace();
+        "in".chars().for_each(|c| drill.type_char(c));
+        drill.next_field();
+        assert_eq!(drill.current_field(), Some("Ben"));
+        assert_eq!(drill.accuracy(), Some(100.0));
+
+        // A typo, then the row is finished early
+        "Bem".chars().for_each(|c| drill.type_char(c));
+        drill.next_row();
+        assert_eq!((drill.fields_typed, drill.fields_correct), (4, 2));
+        assert_eq!(drill.accuracy(), Some(50.0));
+
+        // Starting over after the last row
+        assert_eq!(drill.current_field(), Some("Anna"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::clock::Clock;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The score of a phrase typed from memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DictationScore {
+    pub errors: usize, // The characters missed, added or mistyped (the edit distance to the phrase)
+    pub accuracy: f64, // The share of the characters of the phrase typed right (0-100)
+}
+
+/// A dictation drill - a phrase is shown for a moment, then hidden, and typed from memory.
+///
+/// The typed characters aren't compared as they're typed (there's nothing to compare them
+/// to on the screen) - the phrase is scored once Enter is pressed, and revealed with the score.
+/// The first typed key hides the phrase early.
+pub struct Dictation {
+    pub phrase: String,
+    pub typed: String,
+    pub shown_at: Instant,
+    pub reveal: Duration, // How long the phrase is shown for
+    pub hidden: bool, // Whether the phrase was hidden (and drawn hidden)
+    pub score: Option<DictationScore>, // The score of the typed phrase, once Enter is pressed
+    pub rounds: usize, // The phrases scored
+    pub exact: usize, // The phrases typed exactly
+    pub accuracy_sum: f64,
+    clock: Rc<dyn Clock>,
+}
+
+impl Dictation {
+    /// Starts a drill with the first phrase, shown for `reveal`, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>, phrase: String, reveal: Duration) -> Dictation {
+        Dictation {
+            phrase,
+            typed: String::new(),
+            shown_at: clock.now(),
+            reveal,
+            hidden: false,
+            score: None,
+            rounds: 0,
+            exact: 0,
+            accuracy_sum: 0.0,
+            clock,
+        }
+    }
+
+    /// When the phrase is hidden (`None` once it is).
+    pub fn hides_at(&self) -> Option<Instant> {
+        (!self.hidden).then_some(self.shown_at + self.reveal)
+    }
+
+    /// Whether the phrase is displayed - before it's hidden, and with the score.
+    pub fn shows_phrase(&self) -> bool {
+        !self.hidden || self.score.is_some()
+    }
+
+    /// Returns `true` if the phrase was just hidden, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        if self.hidden || self.clock.now() < self.shown_at + self.reveal {
+            return false;
+        }
+        self.hidden = true;
+        true
+    }
+
+    /// Types a character of the phrase (hiding it, if it's still shown).
+    pub fn type_char(&mut self, c: char) {
+        if self.score.is_none() {
+            self.hidden = true;
+            self.typed.push(c);
+        }
+    }
+
+    /// Erases the last typed character.
+    pub fn backspace(&mut self) {
+        if self.score.is_none() {
+            self.typed.pop();
+        }
+    }
+
+    /// Scores the typed phrase against the hidden one (Enter).
+    pub fn submit(&mut self) {
+        if self.score.is_some() {
+            return;
+        }
+        self.hidden = true;
+        let score = score_phrase(&self.phrase, &self.typed);
+        self.rounds += 1;
+        self.exact += (score.errors == 0) as usize;
+        self.accuracy_sum += score.accuracy;
+        self.score = Some(score);
+    }
+
+    /// Shows the next phrase (after the score).
+    pub fn next_phrase(&mut self, phrase: String) {
+        self.phrase = phrase;
+        self.typed.clear();
+        self.shown_at = self.clock.now();
+        self.hidden = false;
+        self.score = None;
+    }
+
+    /// The average accuracy of the scored phrases (0-100), `None` before one is scored.
+    pub fn average_accuracy(&self) -> Option<f64> {
+        (self.rounds > 0).then(|| self.accuracy_sum / self.rounds as f64)
+    }
+}
+
+/// Scores a phrase typed from memory - by the edit distance, so a skipped or an added
+/// character counts as one error, instead of shifting (and failing) the rest of the phrase.
+pub fn score_phrase(phrase: &str, typed: &str) -> DictationScore {
+    let phrase: Vec<char> = phrase.chars().collect();
+    let typed: Vec<char> = typed.chars().collect();
+
+    // The Levenshtein distance, a row at a time
+    let mut previous: Vec<usize> = (0..=typed.len()).collect();
+    for (i, expected) in phrase.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, c) in typed.iter().enumerate() {
+            let substitution = previous[j] + (expected != c) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    let errors = previous[typed.len()];
+
+    let accuracy = if phrase.is_empty() {
+        if typed.is_empty() { 100.0 } else { 0.0 }
+    } else {
+        phrase.len().saturating_sub(errors) as f64 / phrase.len() as f64 * 100.0
+    };
+    DictationScore { errors, accuracy }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_score_phrase() {
+        assert_eq!(score_phrase("the cat", "the cat"), DictationScore { errors: 0, accuracy: 100.0 });
+        // A skipped character is a single error, not a shifted rest of the phrase
+        assert_eq!(score_phrase("the cat", "th cat").errors, 1);
+        assert_eq!(score_phrase("abcd", "abxd"), DictationScore { errors: 1, accuracy: 75.0 });
+        assert_eq!(score_phrase("abcd", "").accuracy, 0.0);
+        assert_eq!(score_phrase("ab", "abcdef").accuracy, 0.0);
+    }
+
+    #[test]
+    fn test_dictation() {
+        let clock = MockClock::new();
+        let mut dictation = Dictation::with_clock(Rc::new(clock.clone()), "red fox".to_string(), Duration::from_secs(2));
+        assert!(dictation.shows_phrase());
+        assert!(!dictation.on_tick());
+
+        // Hidden once the time is up
+        clock.advance(Duration::from_secs(2));
+        assert!(dictation.on_tick());
+        assert!(!dictation.shows_phrase());
+        assert_eq!(dictation.hides_at(), None);
+
+        "red fix".chars().for_each(|c| dictation.type_char(c));
+        dictation.submit();
+        assert_eq!(dictation.score.map(|score| score.errors), Some(1));
+        assert!(dictation.shows_phrase());
+
+        // Typing early hides the phrase, and the scored phrases are averaged
+        dictation.next_phrase("blue".to_string());
+        assert!(dictation.hides_at().is_some());
+        "blue".chars().for_each(|c| dictation.type_char(c));
+        assert!(!dictation.shows_phrase());
+        dictation.submit();
+        assert_eq!((dictation.rounds, dictation.exact), (2, 1));
+        assert_eq!(dictation.average_accuracy(), Some((6.0 / 7.0 * 100.0 + 100.0) / 2.0));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::clock::Clock;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The color words the distractor prompts show, answered with F1-F4 in this order.
+pub const DISTRACTOR_WORDS: [&str; 4] = ["RED", "GREEN", "BLUE", "YELLOW"];
+
+/// How long a prompt waits to be answered, before it counts as missed.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long after a prompt is answered (or missed) the typing still counts as distracted.
+const RECOVERY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The pauses between key presses longer than this aren't counted as typing time.
+const KEY_PAUSE: Duration = Duration::from_secs(2);
+
+/// A distractor prompt - a color word, drawn in a color of its own (`ink`, the index of one
+/// of the words), answered by the word, not the color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prompt {
+    pub word: usize, // The index of the word in `DISTRACTOR_WORDS`
+    pub ink: usize,
+    pub shown_at: Instant,
+}
+
+/// How the typing held up under the distractor prompts of a session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistractionReport {
+    pub shown: usize,
+    pub acknowledged: usize, // Answered with the right key
+    pub wrong: usize,
+    pub missed: usize, // Not answered within `RESPONSE_TIMEOUT`
+    pub response_ms: Option<f64>, // The average time to answer with the right key
+    pub focused_wpm: Option<f64>, // The speed without a prompt around
+    pub distracted_wpm: Option<f64>, // The speed while a prompt was shown, and right after it
+}
+
+impl DistractionReport {
+    /// How much slower the typing was under the prompts, in percent of the focused speed.
+    pub fn wpm_drop(&self) -> Option<f64> {
+        let (focused, distracted) = (self.focused_wpm?, self.distracted_wpm?);
+        (focused > 0.0).then(|| (focused - distracted) / focused * 100.0)
+    }
+}
+
+/// The distractor drill - every now and then a prompt is shown while typing, to be answered
+/// with a function key, and the speed of the typing around the prompts is compared to the
+/// speed of the rest of it (the cost of task switching).
+///
+/// The prompts are picked and scheduled by the frontend (`schedule` and `show`).
+pub struct Distractions {
+    pub prompt: Option<Prompt>,
+    pub next_at: Option<Instant>, // When the next prompt is due
+    pub answered_at: Option<Instant>, // When the last prompt was answered (or missed)
+    pub last_key_press: Option<Instant>,
+    pub shown: usize,
+    pub acknowledged: usize,
+    pub wrong: usize,
+    pub missed: usize,
+    pub response_secs: f64, // The time taken to answer the acknowledged prompts, summed up
+    pub focused: (usize, f64), // The key presses without a prompt around, and their time
+    pub distracted: (usize, f64), // The key presses under a prompt, and their time
+    clock: Rc<dyn Clock>,
+}
+
+impl Distractions {
+    /// Creates a drill with no prompt scheduled, timed by the given clock.
+    pub fn with_clock(clock: Rc<dyn Clock>) -> Distractions {
+        Distractions {
+            prompt: None,
+            next_at: None,
+            answered_at: None,
+            last_key_press: None,
+            shown: 0,
+            acknowledged: 0,
+            wrong: 0,
+            missed: 0,
+            response_secs: 0.0,
+            focused: (0, 0.0),
+            distracted: (0, 0.0),
+            clock,
+        }
+    }
+
+    /// Schedules the next prompt in `after`, unless one is shown or scheduled already.
+    pub fn schedule(&mut self, after: Duration) {
+        if self.prompt.is_none() && self.next_at.is_none() {
+            self.next_at = Some(self.clock.now() + after);
+        }
+    }
+
+    /// Whether the scheduled prompt is due to be shown.
+    pub fn is_due(&self) -> bool {
+        self.next_at.is_some_and(|next_at| self.clock.now() >= next_at)
+    }
+
+    /// Shows a prompt of the word (drawn in the color of the `ink` word).
+    pub fn show(&mut self, word: usize, ink: usize) {
+        self.prompt = Some(Prompt { word, ink, shown_at: self.clock.now() });
+        self.next_at = None;
+        self.shown += 1;
+    }
+
+    /// Answers the prompt with the word of the pressed key. Returns `false` if there's no prompt.
+    pub fn answer(&mut self, word: usize) -> bool {
+        let Some(prompt) = self.prompt.take() else {
+            return false;
+        };
+        let now = self.clock.now();
+        if prompt.word == word {
+            self.acknowledged += 1;
+            self.response_secs += now.duration_since(prompt.shown_at).as_secs_f64();
+        } else {
+            self.wrong += 1;
+        }
+        self.answered_at = Some(now);
+        true
+    }
+
+    /// When the prompt shown times out, or the next one is due.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.prompt.map(|prompt| prompt.shown_at + RESPONSE_TIMEOUT).or(self.next_at)
+    }
+
+    /// Counts the prompt as missed once it wasn't answered in time.
+    ///
+    /// Returns `true` if it was missed, indicating the UI needs to be updated.
+    pub fn on_tick(&mut self) -> bool {
+        let now = self.clock.now();
+        if self.prompt.is_none_or(|prompt| now.duration_since(prompt.shown_at) < RESPONSE_TIMEOUT) {
+            return false;
+        }
+        self.prompt = None;
+        self.missed += 1;
+        self.answered_at = Some(now);
+        true
+    }
+
+    /// Whether the typing is distracted - a prompt is shown, or was answered a moment ago.
+    pub fn is_distracted(&self) -> bool {
+        self.prompt.is_some()
+            || self.answered_at.is_some_and(|answered_at| self.clock.now().duration_since(answered_at) < RECOVERY_WINDOW)
+    }
+
+    /// Counts a key press (and the time since the previous one) as focused or distracted typing.
+    pub fn on_key_press(&mut self) {
+        let now = self.clock.now();
+        if let Some(last_key_press) = self.last_key_press {
+            let interval = now.duration_since(last_key_press);
+            if interval <= KEY_PAUSE {
+                let counts = if self.is_distracted() { &mut self.distracted } else { &mut self.focused };
+                counts.0 += 1;
+                counts.1 += interval.as_secs_f64();
+            }
+        }
+        self.last_key_press = Some(now);
+    }
+
+    /// Ends the drill of the session - returns its report (`None` if no prompt was shown), and
+    /// starts over with no prompt scheduled.
+    pub fn finish(&mut self) -> Option<DistractionReport> {
+        let wpm = |(keys, secs): (usize, f64)| (secs > 0.0).then(|| keys as f64 / 5.0 / (secs / 60.0));
+        let report = (self.shown > 0).then(|| DistractionReport {
+            shown: self.shown,
+            acknowledged: self.acknowledged,
+            wrong: self.wrong,
+            missed: self.missed,
+            response_ms: (self.acknowledged > 0).then(|| self.response_secs * 1000.0 / self.acknowledged as f64),
+            focused_wpm: wpm(self.focused),
+            distracted_wpm: wpm(self.distracted),
+        });
+        *self = Distractions::with_clock(self.clock.clone());
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn test_distractions() {
+        let clock = MockClock::new();
+        let mut distractions = Distractions::with_clock(Rc::new(clock.clone()));
+        let tick = |distractions: &mut Distractions, millis: u64| {
+            clock.advance(Duration::from_millis(millis));
+            distractions.on_key_press();
+        };
+
+        // Focused typing - a key every 100ms (120 WPM)
+        distractions.schedule(Duration::from_secs(1));
+        (0..10).for_each(|_| tick(&mut distractions, 100));
+        assert!(distractions.is_due());
+
+        // Answered right after 500ms, typing at half the speed meanwhile and right after
+        distractions.show(0, 2);
+        assert!(distractions.is_distracted());
+        (0..2).for_each(|_| tick(&mut distractions, 200));
+        tick(&mut distractions, 100);
+        assert!(distractions.answer(0));
+        assert!(!distractions.answer(0)); // Nothing left to answer
+        (0..10).for_each(|_| tick(&mut distractions, 200)); // The last one after the recovery window
+        assert!(!distractions.is_distracted());
+
+        // Missed
+        distractions.show(1, 1);
+        assert_eq!(distractions.next_deadline(), Some(clock.now() + RESPONSE_TIMEOUT));
+        assert!(!distractions.on_tick());
+        clock.advance(RESPONSE_TIMEOUT);
+        assert!(distractions.on_tick());
+
+        let report = distractions.finish().unwrap();
+        assert_eq!((report.shown, report.acknowledged, report.wrong, report.missed), (2, 1, 0, 1));
+        assert_eq!(report.response_ms, Some(500.0));
+        assert!(report.focused_wpm.is_some_and(|wpm| wpm > 100.0));
+        assert!(report.wpm_drop().is_some_and(|drop| drop > 40.0));
+        assert_eq!(distractions.finish(), None);
+    }
+}
@@ -0,0 +1,445 @@
+use crate::stats::{problem_words, Session, WordOrder};
+use crate::utils::{get_sorted_mistakes, Config};
+use rand::{seq::IndexedRandom, Rng};
+use std::collections::HashMap;
+
+/// How many words a generated drill has - enough for the words not to repeat often.
+const DRILL_WORDS: usize = 300;
+
+/// The longest a generated word can be, so it always fits in a line.
+const MAX_DRILL_WORD_LEN: usize = 40;
+
+/// (Targeted drill) - How many of the most mistyped characters of the session a targeted drill is
+/// about - and of all time, besides them.
+const TARGETED_SESSION_CHARS: usize = 5;
+const TARGETED_LIFETIME_CHARS: usize = 3;
+
+/// (Targeted drill) - How many of the bigrams with the targeted characters in them it drills.
+const TARGETED_BIGRAMS: usize = 8;
+
+/// (Targeted drill) - How many times the mistyped words and the bigrams are repeated in it.
+const TARGETED_REPEATS: usize = 3;
+
+/// (Targeted drill) - How many of the problem words of all time it takes.
+const TARGETED_LIFETIME_WORDS: usize = 10;
+
+/// (Targeted drill) - How many words of the word list (the ones with the most targeted characters
+/// and bigrams in them) it takes.
+const TARGETED_LIST_WORDS: usize = 100;
+
+const FIRST_NAMES: &[&str] = &["anna", "ben", "chloe", "david", "elena", "felix", "grace", "hugo", "iris", "jamal", "kate", "leo", "maria", "nils", "olga", "pablo", "rosa", "sam", "tara", "yuki"];
+
+const LAST_NAMES: &[&str] = &["smith", "garcia", "mueller", "rossi", "kowalski", "nguyen", "okafor", "tanaka", "silva", "novak", "dubois", "jensen", "murphy", "khan", "larsen"];
+
+const DOMAINS: &[&str] = &["example", "mail", "acme-corp", "fastmail", "uni-berlin", "startup", "cloudhost", "my-shop", "news", "devtools", "city-lib", "webmail"];
+
+const SUBDOMAINS: &[&str] = &["www.", "docs.", "api.", "blog.", "shop.", "mail.", ""];
+
+const URL_SEGMENTS: &[&str] = &["api", "v2", "users", "search", "blog", "2024", "posts", "docs", "en-us", "products", "cart", "login", "help", "static", "img", "releases", "latest"];
+
+const URL_QUERIES: &[&str] = &["?id=42", "?q=rust", "?page=3", "?sort=asc&limit=20", "#intro", "?lang=en", "#section-2", "?ref=home"];
+
+const PATH_ROOTS: &[&str] = &["~/", "/", "./", "../", "~/.config/", "/usr/local/", "/var/log/", "/etc/"];
+
+const PATH_DIRS: &[&str] = &["projects", "src", "docs", "build", "tmp", "home", "bin", "lib", "nginx", "backups", "photos", "work", "ttypr", "notes", "release-v2", "node_modules"];
+
+const PATH_FILES: &[&str] = &["main.rs", "index.html", "app.toml", "README.md", "notes.txt", "report-2024.pdf", "backup.tar.gz", "style.css", "config.yaml", "error.log", ".bashrc", "photo_01.jpg"];
+
+const DOUBLE_LETTER_WORDS: &[&str] = &["all", "will", "still", "small", "full", "well", "tell", "call", "ball", "hello", "yellow", "follow", "really", "see", "need", "feel", "keep", "free", "three", "green", "street", "coffee", "good", "book", "look", "food", "room", "soon", "school", "door", "floor", "too", "off", "stuff", "staff", "office", "effort", "different", "less", "class", "miss", "pass", "address", "success", "possible", "lesson", "message", "better", "letter", "little", "pretty", "bottle", "attack", "happy", "apple", "supper", "support", "approve", "sorry", "carry", "arrive", "error", "summer", "common", "comment", "dinner", "annual", "connect", "account", "accept", "odd", "add", "middle", "egg", "bigger", "suggest", "dizzy", "puzzle", "committee", "balloon", "bookkeeper", "mississippi"];
+
+const DOUBLE_LETTERS: &[&str] = &["ll", "ss", "oo", "ee", "tt", "ff", "pp", "rr", "mm", "nn", "cc", "dd", "gg", "zz"];
+
+const VOWELS: &[&str] = &["a", "e", "i", "o", "u"];
+
+/// Letter combinations typed by rolling the fingers of one hand (inwards or outwards).
+const ROLLS: &[&str] = &["was", "ase", "wer", "ert", "rew", "sad", "das", "fast", "ion", "pol", "lop", "oil", "kil", "ill", "uio", "oun", "hop", "pin", "lik", "mon", "ing", "ere", "ast", "ter", "tre", "red", "art", "rat", "ewr", "sed"];
+
+const ROLL_WORDS: &[&str] = &["was", "were", "fast", "last", "past", "asked", "sad", "water", "start", "started", "street", "tree", "dress", "read", "free", "ever", "after", "base", "case", "east", "easter", "tread", "treat", "art", "raw", "saw", "draw", "west", "rest", "best", "nation", "onion", "million", "opinion", "option", "polo", "polite", "pool", "look", "loop", "oil", "boil", "join", "point", "pink", "kill", "hill", "mill", "link", "milk", "monk", "union", "pump", "hook", "only", "poll", "lip", "pin", "nil", "noon", "moon"];
+
+/// The snippets of the symbol pairs drill - `#0` stands for an identifier, `#1` for a number
+/// and `#2` for a key (the braces are typed as they are).
+const SYMBOL_SNIPPETS: &[&str] = &["(#0)", "[#0]", "{#0}", "\"#2\"", "'#2'", "`#2`", "(#0)=>{#0};", "vec![#1;#0]", "#0[\"#2\"]", "#0['#2']", "#0[#1]", "#0[#0][#0]", "#0(#0(#0))", "[#0,#0]", "[#1,#1,#1]", "(#0,(#0,#0))", "{\"#2\":#1}", "{#2:[#1,#1]}", "<#0>", "Vec<Option<#0>>", "&mut[#0]", "|#0|{#0+#1}", "${#0}", "#[derive(#0)]", "println!(\"{}\",#0)", "f\"{#0}\"", "#0.get(&#0)", "if(#0){#0();}", "(#0[#1])", "[#0(#1)]", "#0?.#2", "<#2></#2>", "%(#0)s", "\"{#2}\""];
+
+const IDENTIFIERS: &[&str] = &["x", "y", "n", "i", "a", "b", "key", "val", "item", "args", "self", "data", "list", "map", "user", "err", "ctx", "id"];
+
+const KEYS: &[&str] = &["k", "id", "name", "type", "url", "port", "host", "path", "env", "mode", "user", "tags"];
+
+/// The drills generated by ttypr, as their names (like the built-in word lists) and display names.
+pub const GENERATED_DRILLS: [(&str, &str); 5] = [
+    ("builtin:addresses", "Emails, URLs and paths"),
+    ("builtin:numbers", "Numbers, prices and percentages"),
+    ("builtin:doubles", "Double letters"),
+    ("builtin:rolls", "Rolling combinations"),
+    ("builtin:symbols", "Symbol pairs and snippets"),
+];
+
+/// Generates the words of a drill by its name (`builtin:addresses`), `None` for other names.
+pub fn generated_drill(rng: &mut impl Rng, name: &str, config: &Config) -> Option<Vec<String>> {
+    match name {
+        "builtin:addresses" => Some(address_words(rng, &config.address_schemes, &config.address_tlds)),
+        "builtin:numbers" => Some(number_words(rng, NumberStyle::for_locale(&config.number_locale), &config.number_currencies)),
+        "builtin:doubles" => Some(double_letter_words(rng)),
+        "builtin:rolls" => Some(roll_words(rng)),
+        "builtin:symbols" => Some(symbol_words(rng)),
+        _ => None,
+    }
+}
+
+/// How the numbers are written in a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberStyle {
+    pub group_separator: char, // Between the groups of thousands
+    pub decimal_separator: char,
+    pub currency_after: bool, // `1.234,56€` (instead of `$1,234.56`)
+}
+
+impl NumberStyle {
+    /// The number style of a locale - `en` (`1,234.56`), `de` (`1.234,56`) or `ch` (`1'234.56`).
+    /// Unknown locales are written the English way.
+    pub fn for_locale(locale: &str) -> NumberStyle {
+        match locale.to_lowercase().as_str() {
+            "de" => NumberStyle { group_separator: '.', decimal_separator: ',', currency_after: true },
+            "ch" => NumberStyle { group_separator: '\'', decimal_separator: '.', currency_after: false },
+            _ => NumberStyle { group_separator: ',', decimal_separator: '.', currency_after: false },
+        }
+    }
+
+    /// Formats a number with its thousands grouped, and the given decimal digits (if any).
+    pub fn format(&self, integer: u64, decimals: &str) -> String {
+        let digits = integer.to_string();
+        let mut formatted = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                formatted.push(self.group_separator);
+            }
+            formatted.push(digit);
+        }
+        if !decimals.is_empty() {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(decimals);
+        }
+        formatted
+    }
+}
+
+/// Generates numbers the way they are written in data entry - grouped thousands, decimals,
+/// prices with the given currency symbols and percentages, in the given style.
+pub fn number_words(rng: &mut impl Rng, style: NumberStyle, currencies: &[String]) -> Vec<String> {
+    let default_currencies = ["$".to_string()];
+    let currencies = if currencies.is_empty() { &default_currencies[..] } else { currencies };
+
+    (0..DRILL_WORDS)
+        .map(|i| {
+            // Bigger numbers are less common
+            let integer = match rng.random_range(0..4) {
+                0 => rng.random_range(0..100),
+                1 => rng.random_range(100..10_000),
+                2 => rng.random_range(10_000..1_000_000),
+                _ => rng.random_range(1_000_000..100_000_000),
+            };
+            let cents = format!("{:02}", rng.random_range(0..100));
+            match i % 4 {
+                // A whole number, sometimes negative
+                0 => format!("{}{}", if rng.random_bool(0.2) { "-" } else { "" }, style.format(integer, "")),
+                // A decimal number
+                1 => style.format(integer, &cents[..rng.random_range(1..=2)]),
+                // A price
+                2 => {
+                    let currency = currencies.choose(rng).map_or("$", String::as_str);
+                    let amount = style.format(integer, &cents);
+                    if style.currency_after { format!("{}{}", amount, currency) } else { format!("{}{}", currency, amount) }
+                }
+                // A percentage
+                _ => format!("{}%", style.format(rng.random_range(0..100), &cents[..1])),
+            }
+        })
+        .collect()
+}
+
+/// Generates words with double letters (`coffee`, `still`), with syllables around the
+/// double letters (`ella`, `ossu`) in between - they trip up typists more than random characters.
+pub fn double_letter_words(rng: &mut impl Rng) -> Vec<String> {
+    (0..DRILL_WORDS)
+        .map(|i| match i % 3 {
+            // A vowel, a double letter and a vowel
+            2 => format!("{}{}{}", pick(rng, VOWELS), pick(rng, DOUBLE_LETTERS), pick(rng, VOWELS)),
+            _ => pick(rng, DOUBLE_LETTER_WORDS).to_string(),
+        })
+        .collect()
+}
+
+/// Generates the letter combinations typed by rolling the fingers of one hand (`was`, `pol`,
+/// `ion`), taking turns with the words made of them (`water`, `polite`, `onion`).
+pub fn roll_words(rng: &mut impl Rng) -> Vec<String> {
+    (0..DRILL_WORDS)
+        .map(|i| if i % 2 == 0 { pick(rng, ROLLS) } else { pick(rng, ROLL_WORDS) }.to_string())
+        .collect()
+}
+
+/// Generates balanced bracket and quote pairs, and code snippets made of them (`(x)=>{y};`,
+/// `vec![0;n]`, `a["k"]`) - the pairing sequences developers type constantly.
+pub fn symbol_words(rng: &mut impl Rng) -> Vec<String> {
+    (0..DRILL_WORDS)
+        .map(|_| {
+            let snippet = pick(rng, SYMBOL_SNIPPETS);
+            fill_snippet(snippet, |placeholder| match placeholder {
+                "#0" => pick(rng, IDENTIFIERS).to_string(),
+                "#2" => pick(rng, KEYS).to_string(),
+                _ => rng.random_range(0..100).to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Fills the placeholders of a snippet (`#0`, `#1` and `#2`) one at a time - every one of them
+/// gets its own value.
+fn fill_snippet(snippet: &str, mut value: impl FnMut(&str) -> String) -> String {
+    let mut filled = snippet.to_string();
+    for placeholder in ["#0", "#1", "#2"] {
+        while let Some(at) = filled.find(placeholder) {
+            filled.replace_range(at..at + placeholder.len(), &value(placeholder));
+        }
+    }
+    filled
+}
+
+/// Generates realistic email addresses, URLs and filesystem paths, exercising the
+/// `@`, `/`, `.`, `-` and `~` characters. The URLs and the emails use the given
+/// schemes (`https`) and top-level domains (`com`).
+pub fn address_words(rng: &mut impl Rng, schemes: &[String], tlds: &[String]) -> Vec<String> {
+    let default_tlds = ["com".to_string()];
+    let tlds = if tlds.is_empty() { &default_tlds[..] } else { tlds };
+    let default_schemes = ["https".to_string()];
+    let schemes = if schemes.is_empty() { &default_schemes[..] } else { schemes };
+
+    let mut words = vec![];
+    while words.len() < DRILL_WORDS {
+        let word = match words.len() % 3 {
+            0 => random_email(rng, tlds),
+            1 => random_url(rng, schemes, tlds),
+            _ => random_path(rng),
+        };
+        if word.chars().count() <= MAX_DRILL_WORD_LEN {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Builds a targeted drill from the errors of a session - the words it mistyped, the most mistyped
+/// characters of the session and of all time (`lifetime_mistakes`, and the problem words of the
+/// `history`), the bigrams with those characters in them and the words of the word list richest
+/// in them. Empty if the session had no errors.
+pub fn targeted_drill(session: &Session, history: &[Session], lifetime_mistakes: &HashMap<String, usize>, words: &[String]) -> Vec<String> {
+    let is_targetable = |c: &String| c.chars().next().is_some_and(|c| !c.is_whitespace());
+    let mut chars: Vec<String> =
+        get_sorted_mistakes(&session.mistyped_chars).into_iter().map(|(c, _)| c).filter(|c| is_targetable(c)).take(TARGETED_SESSION_CHARS).cloned().collect();
+    let mistyped_words: Vec<&String> = {
+        let mut mistyped: Vec<_> = session.words.iter().filter(|(_, count)| count.mistyped > 0).collect();
+        mistyped.sort_by(|a, b| b.1.mistyped.cmp(&a.1.mistyped).then(a.0.cmp(b.0)));
+        mistyped.into_iter().map(|(word, _)| word).collect()
+    };
+    if chars.is_empty() && mistyped_words.is_empty() {
+        return vec![];
+    }
+    let lifetime_chars = get_sorted_mistakes(lifetime_mistakes).into_iter().map(|(c, _)| c).filter(|c| is_targetable(c) && !chars.contains(c));
+    chars.extend(lifetime_chars.take(TARGETED_LIFETIME_CHARS).cloned().collect::<Vec<_>>());
+    let lifetime_words: Vec<String> = problem_words(history, 2, WordOrder::Mistakes)
+        .into_iter()
+        .map(|word| word.word)
+        .filter(|word| !mistyped_words.contains(&word))
+        .take(TARGETED_LIFETIME_WORDS)
+        .collect();
+
+    // The bigrams of the mistyped words with a targeted character in them, the most common first
+    let is_targeted = |c: char| chars.iter().any(|target| target.starts_with(c));
+    let mut bigrams: HashMap<String, usize> = HashMap::new();
+    for word in mistyped_words.iter().copied().chain(&lifetime_words) {
+        let word: Vec<char> = word.chars().collect();
+        for pair in word.windows(2).filter(|pair| is_targeted(pair[0]) || is_targeted(pair[1])) {
+            *bigrams.entry(pair.iter().collect()).or_default() += 1;
+        }
+    }
+    let bigrams: Vec<String> = get_sorted_mistakes(&bigrams).into_iter().map(|(bigram, _)| bigram.clone()).take(TARGETED_BIGRAMS).collect();
+
+    // The words of the word list with the most of the targeted characters and bigrams
+    let score = |word: &String| word.chars().filter(|c| is_targeted(*c)).count() + bigrams.iter().filter(|bigram| word.contains(bigram.as_str())).count() * 2;
+    let mut list_words: Vec<(usize, &String)> = words.iter().map(|word| (score(word), word)).filter(|(score, _)| *score > 0).collect();
+    list_words.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+    list_words.dedup_by(|a, b| a.1 == b.1);
+
+    let mut drill = vec![];
+    for word in mistyped_words.iter().copied().chain(&bigrams) {
+        drill.extend(std::iter::repeat_n(word.clone(), TARGETED_REPEATS));
+    }
+    drill.extend(lifetime_words);
+    drill.extend(list_words.into_iter().take(TARGETED_LIST_WORDS).map(|(_, word)| word.clone()));
+    drill.retain(|word| word.chars().count() <= MAX_DRILL_WORD_LEN);
+    drill
+}
+
+/// Picks one of the items.
+fn pick<'a>(rng: &mut impl Rng, items: &[&'a str]) -> &'a str {
+    items.choose(rng).copied().unwrap_or_default()
+}
+
+/// An email address - `anna.smith42@example.com`.
+fn random_email(rng: &mut impl Rng, tlds: &[String]) -> String {
+    let separator = pick(rng, &[".", "_", "-", ""]);
+    let number = if rng.random_bool(0.3) { rng.random_range(1..100).to_string() } else { String::new() };
+    format!(
+        "{}{}{}{}@{}.{}",
+        pick(rng, FIRST_NAMES),
+        separator,
+        pick(rng, LAST_NAMES),
+        number,
+        pick(rng, DOMAINS),
+        tlds.choose(rng).map_or("com", String::as_str),
+    )
+}
+
+/// A URL - `https://docs.example.io/api/v2?id=42`.
+fn random_url(rng: &mut impl Rng, schemes: &[String], tlds: &[String]) -> String {
+    let mut url = format!(
+        "{}://{}{}.{}",
+        schemes.choose(rng).map_or("https", String::as_str),
+        pick(rng, SUBDOMAINS),
+        pick(rng, DOMAINS),
+        tlds.choose(rng).map_or("com", String::as_str),
+    );
+    for _ in 0..rng.random_range(0..3) {
+        url.push('/');
+        url.push_str(pick(rng, URL_SEGMENTS));
+    }
+    if rng.random_bool(0.4) {
+        url.push_str(pick(rng, URL_QUERIES));
+    }
+    url
+}
+
+/// A filesystem path - `~/projects/ttypr/src/main.rs`.
+fn random_path(rng: &mut impl Rng) -> String {
+    let mut path = pick(rng, PATH_ROOTS).to_string();
+    for _ in 0..rng.random_range(1..4) {
+        path.push_str(pick(rng, PATH_DIRS));
+        path.push('/');
+    }
+    if rng.random_bool(0.7) {
+        path.push_str(pick(rng, PATH_FILES));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_address_words() {
+        let words = address_words(&mut rand::rng(), &["ftp".to_string()], &["dev".to_string()]);
+        assert_eq!(words.len(), DRILL_WORDS);
+        assert!(words.iter().all(|word| word.chars().count() <= MAX_DRILL_WORD_LEN && !word.contains(' ')));
+
+        // Emails, URLs and paths take turns
+        assert!(words[0].contains('@') && words[0].ends_with(".dev"));
+        assert!(words[1].starts_with("ftp://"));
+        assert!(words[2].contains('/'));
+
+        // Without configured schemes/top-level domains
+        let words = address_words(&mut rand::rng(), &[], &[]);
+        assert!(words[1].starts_with("https://"));
+
+        assert!(generated_drill(&mut rand::rng(), "builtin:addresses", &Config::default()).is_some());
+        assert!(generated_drill(&mut rand::rng(), "builtin:rust", &Config::default()).is_none());
+    }
+
+    #[test]
+    fn test_double_and_roll_words() {
+        let words = double_letter_words(&mut rand::rng());
+        assert_eq!(words.len(), DRILL_WORDS);
+        let has_double = |word: &String| word.as_bytes().windows(2).any(|pair| pair[0] == pair[1]);
+        assert!(words.iter().all(has_double));
+        assert_eq!(words[2].len(), 4);
+
+        let words = roll_words(&mut rand::rng());
+        assert_eq!(words.len(), DRILL_WORDS);
+        assert!(ROLLS.contains(&words[0].as_str()) && ROLL_WORDS.contains(&words[1].as_str()));
+
+        for (name, _) in GENERATED_DRILLS {
+            assert!(generated_drill(&mut rand::rng(), name, &Config::default()).is_some_and(|words| words.len() == DRILL_WORDS));
+
+            // The same seed generates the same drill
+            let seeded_drill = |seed| generated_drill(&mut StdRng::seed_from_u64(seed), name, &Config::default());
+            assert_eq!(seeded_drill(7), seeded_drill(7));
+        }
+    }
+
+    #[test]
+    fn test_symbol_words() {
+        let words = symbol_words(&mut rand::rng());
+        assert_eq!(words.len(), DRILL_WORDS);
+        for word in &words {
+            assert!(!word.contains(' ') && !word.contains("#0") && !word.contains("#1") && !word.contains("#2"));
+            assert!(!word.contains("{{") && !word.contains("}}"), "{}", word);
+
+            // The brackets are balanced, and closed in order
+            let mut open = vec![];
+            for c in word.chars() {
+                match c {
+                    '(' | '[' | '{' => open.push(c),
+                    ')' => assert_eq!(open.pop(), Some('('), "{}", word),
+                    ']' => assert_eq!(open.pop(), Some('['), "{}", word),
+                    '}' => assert_eq!(open.pop(), Some('{'), "{}", word),
+                    _ => {}
+                }
+            }
+            assert!(open.is_empty(), "{}", word);
+            assert_eq!(word.matches('"').count() % 2, 0, "{}", word);
+        }
+
+        // The braces of the snippets are typed as they are
+        let mut values = ["x", "y"].into_iter().cycle();
+        assert_eq!(fill_snippet("(#0)=>{#0};", |_| values.next().unwrap().to_string()), "(x)=>{y};");
+        assert_eq!(fill_snippet("println!(\"{}\",#0)", |_| "n".to_string()), "println!(\"{}\",n)");
+        assert_eq!(fill_snippet("{\"#2\":#1}", |placeholder| if placeholder == "#1" { "3" } else { "k" }.to_string()), "{\"k\":3}");
+        assert_eq!(fill_snippet("#[derive(#0)]", |_| "Debug".to_string()), "#[derive(Debug)]");
+    }
+
+    #[test]
+    fn test_number_words() {
+        let english = NumberStyle::for_locale("en");
+        assert_eq!(english.format(1_234_567, "89"), "1,234,567.89");
+        assert_eq!(english.format(999, ""), "999");
+        let german = NumberStyle::for_locale("DE");
+        assert_eq!(german.format(1_234, "56"), "1.234,56");
+        assert_eq!(NumberStyle::for_locale("ch").format(12_345, "5"), "12'345.5");
+
+        let words = number_words(&mut rand::rng(), german, &["€".to_string()]);
+        assert_eq!(words.len(), DRILL_WORDS);
+        assert!(words[2].ends_with('€') && words[2].contains(','));
+        assert!(words[3].ends_with('%'));
+        assert!(words.iter().all(|word| !word.contains(' ')));
+
+        let words = number_words(&mut rand::rng(), english, &[]);
+        assert!(words[2].starts_with('$'));
+    }
+    #[test]
+    fn test_targeted_drill() {
+        use crate::stats::{sample_session, WordCount};
+
+        let words: Vec<String> = ["the", "this", "with", "cat", "bath", "dog"].iter().map(|word| word.to_string()).collect();
+        let mut session = sample_session(0, 100, 0, 60.0);
+        assert!(targeted_drill(&session, &[], &HashMap::new(), &words).is_empty()); // No errors
+
+        session.mistyped_chars.insert("h".to_string(), 3);
+        session.mistyped_chars.insert(" ".to_string(), 5); // Not a character to drill
+        session.words.insert("the".to_string(), WordCount { typed: 2, mistyped: 1, millis: 500 });
+        let lifetime_mistakes = HashMap::from([("h".to_string(), 9), ("g".to_string(), 4)]);
+        let drill = targeted_drill(&session, std::slice::from_ref(&session), &lifetime_mistakes, &words);
+
+        // The mistyped word and its bigrams with "h" (repeated), then the words with "h" and "g"
+        assert_eq!(&drill[..9], ["the", "the", "the", "he", "he", "he", "th", "th", "th"]);
+        assert_eq!(&drill[9..], ["the", "bath", "this", "with", "dog"]);
+    }
+}
@@ -0,0 +1,514 @@
+use crate::stats::SessionTracker;
+use rand::distr::{weighted::WeightedIndex, Distribution};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, ops::Range, time::Instant};
+
+/// The state of a character of the lines, which determines its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CharState {
+    #[default]
+    Untyped,
+    Correct,
+    Incorrect,
+    /// Mistyped, then fixed with Backspace and typed correctly.
+    Corrected,
+}
+
+/// A line that was typed and dropped - its characters, the typed characters and their states.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TypedLine {
+    pub chars: Vec<char>,
+    pub typed: Vec<char>,
+    pub states: Vec<CharState>,
+}
+
+/// The typing engine - the lines to type, the typed characters and their scoring.
+///
+/// It doesn't depend on the terminal, so other frontends can embed it. The lines are
+/// pushed by the frontend (see `random_chars_line` and `random_words_line`); once the
+/// second one is typed, the first one is dropped and a new one is pushed, creating a
+/// continuous scrolling effect.
+pub struct TypingEngine {
+    pub charset: VecDeque<char>, // The characters of the lines (['a', 'b', 'c'])
+    pub input_chars: VecDeque<char>, // The characters user typed (its length is the cursor position)
+    pub char_states: VecDeque<CharState>, // The states of the characters, to display them colored
+    pub lines_len: VecDeque<usize>, // Current length of lines in characters
+    pub session: SessionTracker, // The key presses, correct characters and errors of the session
+}
+
+impl Default for TypingEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypingEngine {
+    /// Creates an engine with no lines to type.
+    pub fn new() -> TypingEngine {
+        TypingEngine {
+            charset: VecDeque::new(),
+            input_chars: VecDeque::new(),
+            char_states: VecDeque::new(),
+            lines_len: VecDeque::new(),
+            session: SessionTracker::new(),
+        }
+    }
+
+    /// Appends a line of characters to type.
+    pub fn push_line(&mut self, line: &str) {
+        let len_before = self.charset.len();
+        self.charset.extend(line.chars());
+        let line_len = self.charset.len() - len_before;
+        self.lines_len.push_back(line_len);
+        self.char_states.extend(std::iter::repeat_n(CharState::Untyped, line_len));
+    }
+
+    /// Removes the lines and the typed characters (the session carries on).
+    pub fn clear(&mut self) {
+        self.charset.clear();
+        self.input_chars.clear();
+        self.char_states.clear();
+        self.lines_len.clear();
+    }
+
+    /// The position of the cursor, which is the amount of typed characters.
+    pub fn cursor(&self) -> usize {
+        self.input_chars.len()
+    }
+
+    /// Types a character pressed at `now` - counts the key press, and checks the character.
+    pub fn type_char(&mut self, c: char, now: Instant) -> CharState {
+        self.session.on_key_press(now);
+        self.input_chars.push_back(c);
+        self.check_last_char()
+    }
+
+    /// Checks the last typed character against the expected one, and records it in the session.
+    ///
+    /// A character that matches is correct (or corrected, if it was ever mistyped
+    /// before being erased with Backspace - even if it was retyped correctly since).
+    pub fn check_last_char(&mut self) -> CharState {
+        let pos = self.cursor() - 1;
+
+        let expected = self.charset[pos];
+        let state = if self.input_chars[pos] == expected {
+            self.session.on_correct(expected.encode_utf8(&mut [0; 4]));
+            if self.char_states[pos] == CharState::Incorrect {
+                self.session.on_corrected();
+                CharState::Corrected
+            } else if self.char_states[pos] == CharState::Corrected {
+                CharState::Corrected
+            } else {
+                CharState::Correct
+            }
+        } else {
+            self.session.on_error(expected.encode_utf8(&mut [0; 4]));
+            CharState::Incorrect
+        };
+        self.char_states[pos] = state;
+        self.track_word(pos);
+        state
+    }
+
+    /// (Word stats) - Records the words as they're typed - timed from their first character
+    /// to their last, and mistyped if any of their characters was.
+    fn track_word(&mut self, position: usize) {
+        let Some(word) = self.word_range(position) else {
+            return;
+        };
+        if position == word.start {
+            self.session.on_word_start();
+        }
+        if position + 1 == word.end {
+            let mistyped = self.char_states.range(word.clone()).any(|state| matches!(state, CharState::Incorrect | CharState::Corrected));
+            let text: String = self.charset.range(word).collect();
+            self.session.on_word(&text, mistyped);
+        }
+    }
+
+    /// The range of the charset of the word at a position, within its line, without the ASCII
+    /// punctuation around it (`(the`, `the,` and `the.` are all `the`). `None` on whitespace and
+    /// on the punctuation around a word.
+    fn word_range(&self, position: usize) -> Option<Range<usize>> {
+        if self.charset.get(position)?.is_whitespace() {
+            return None;
+        }
+        let mut line_start = 0;
+        let line = self.lines_len.iter().find_map(|len| {
+            let line = line_start..line_start + len;
+            line_start = line.end;
+            line.contains(&position).then_some(line)
+        })?;
+
+        let is_space = |i: &usize| self.charset[*i].is_whitespace();
+        let start = (line.start..position).rev().find(is_space).map_or(line.start, |i| i + 1);
+        let end = (position..line.end).find(is_space).unwrap_or(line.end);
+
+        let is_word_char = |i: &usize| !self.charset[*i].is_ascii_punctuation();
+        let start = (start..end).find(is_word_char)?;
+        let end = (start..end).rfind(is_word_char)? + 1;
+        (start..end).contains(&position).then_some(start..end)
+    }
+
+    /// Erases the last typed character. Returns false if there was none.
+    ///
+    /// A mistyped character keeps its state, to be marked as corrected once retyped.
+    pub fn backspace(&mut self) -> bool {
+        if self.input_chars.pop_back().is_none() {
+            return false;
+        }
+        let pos = self.cursor();
+        match self.char_states[pos] {
+            CharState::Correct => self.char_states[pos] = CharState::Untyped,
+            CharState::Incorrect => self.session.on_error_erased(),
+            _ => {}
+        }
+        true
+    }
+
+    /// The state of the character at a position of the charset.
+    ///
+    /// Characters after the cursor are untyped, even the mistyped ones erased
+    /// with Backspace (which keep their state, to be marked as corrected).
+    pub fn char_state(&self, position: usize) -> CharState {
+        if position < self.cursor() { self.char_states[position] } else { CharState::Untyped }
+    }
+
+    /// Whether the line after `history_lines` typed lines is typed as well, so the
+    /// first one has to be dropped (see `drop_first_line`) and a new one pushed.
+    pub fn needs_new_line(&self, history_lines: usize) -> bool {
+        self.lines_len.len() > history_lines && self.cursor() == self.lines_len.iter().take(history_lines + 1).sum::<usize>()
+    }
+
+    /// The range of the charset of the line that was just typed - the one the cursor
+    /// is right at the end of (`None` if it's within a line).
+    pub fn typed_line(&self) -> Option<Range<usize>> {
+        let mut line_start = 0;
+        for len in &self.lines_len {
+            let line_end = line_start + len;
+            if line_end == self.cursor() && *len > 0 {
+                return Some(line_start..line_end);
+            }
+            line_start = line_end;
+        }
+        None
+    }
+
+    /// The index of the line being typed (the one the cursor is on).
+    pub fn active_line(&self) -> usize {
+        let mut line_end = 0;
+        let typed_lines = self.lines_len.iter().take_while(|len| {
+            line_end += *len;
+            line_end <= self.cursor()
+        });
+        typed_lines.count().min(self.lines_len.len().saturating_sub(1))
+    }
+
+    /// Removes the first line's characters, typed characters and their states, and returns them.
+    pub fn drop_first_line(&mut self) -> TypedLine {
+        let first_line_len = self.lines_len.pop_front().unwrap_or_default();
+        TypedLine {
+            chars: self.charset.drain(..first_line_len).collect(),
+            typed: self.input_chars.drain(..first_line_len).collect(),
+            states: self.char_states.drain(..first_line_len).collect(),
+        }
+    }
+
+    /// Number of characters from the cursor to the end of the current word.
+    pub fn word_len_at_cursor(&self) -> usize {
+        self.charset.iter().skip(self.cursor()).take_while(|c| **c != ' ').count()
+    }
+
+    /// Returns the word at the cursor (or the one just typed, if the cursor is on a space).
+    pub fn word_at_cursor(&self) -> Option<String> {
+        let mut position = self.cursor().min(self.charset.len());
+        if self.charset.get(position).is_none_or(|c| *c == ' ') {
+            position = position.checked_sub(1)?;
+        }
+
+        let is_space = |i: &usize| self.charset[*i] == ' ';
+        let start = (0..position).rev().find(is_space).map_or(0, |i| i + 1);
+        let end = (position..self.charset.len()).find(is_space).unwrap_or(self.charset.len());
+        let word: String = self.charset.range(start..end).collect();
+        if word.is_empty() { None } else { Some(word) }
+    }
+}
+
+/// Generates a line of `len` random characters from the given set.
+pub fn random_chars_line(rng: &mut impl Rng, chars: &[&str], len: usize) -> String {
+    (0..len).map(|_| chars[rng.random_range(0..chars.len())]).collect()
+}
+
+/// Generates a line of `len` random characters from classes of characters (letters, digits,
+/// symbols) - the class of every character is sampled by its weight, then the character
+/// is picked from the class. Empty classes are never sampled.
+///
+/// If none of the non-empty classes has a weight (e.g. the characters of the only weighted
+/// one are all excluded), the characters of all of them are generated uniformly.
+pub fn weighted_chars_line(rng: &mut impl Rng, classes: &[Vec<&str>], weights: &[u32], len: usize) -> String {
+    let weights = classes.iter().zip(weights).map(|(class, weight)| if class.is_empty() { 0 } else { *weight });
+    let Ok(sampler) = WeightedIndex::new(weights) else {
+        let chars = classes.concat();
+        if chars.is_empty() {
+            return String::new();
+        }
+        return random_chars_line(rng, &chars, len);
+    };
+    (0..len)
+        .map(|_| {
+            let class = &classes[sampler.sample(rng)];
+            class[rng.random_range(0..class.len())]
+        })
+        .collect()
+}
+
+/// Changes the case of the letters of a line, so `percent` of them (0-100) are uppercase on average.
+///
+/// Letters without a single-character uppercase (or lowercase) form are kept as they are, and so
+/// are the letters whose other case is `excluded`.
+pub fn with_capitals(rng: &mut impl Rng, line: &str, percent: u32, excluded: &str) -> String {
+    let probability = f64::from(percent.min(100)) / 100.0;
+    line.chars()
+        .map(|c| {
+            if !c.is_lowercase() && !c.is_uppercase() {
+                return c;
+            }
+            let changed: Vec<char> = if rng.random_bool(probability) { c.to_uppercase().collect() } else { c.to_lowercase().collect() };
+            match changed[..] {
+                [changed] if !excluded.contains(changed) => changed,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+/// Generates a line of random words, sampled by their weights, that fits within `max_len`
+/// characters. The words are separated (and the line ended) with spaces.
+///
+/// # Panics
+///
+/// If all of the weights are 0.
+pub fn random_words_line(rng: &mut impl Rng, words: &[String], weights: &[u32], max_len: usize) -> String {
+    let sampler = WeightedIndex::new(weights).expect("at least one word can be generated");
+
+    let mut line_of_words = vec![];
+    loop {
+        let word = words[sampler.sample(rng)].as_str();
+        line_of_words.push(word);
+
+        let current_line_len = line_of_words.join(" ").chars().count();
+
+        if current_line_len > max_len {
+            line_of_words.pop();
+            let mut current_line = line_of_words.join(" ");
+            if !current_line.is_empty() {
+                current_line.push(' ');
+            }
+            return current_line;
+        };
+    }
+}
+
+/// Splits a word that doesn't fit into the rest of a line (`room` characters), so its first
+/// part fills the line. Returns the first part (ending with a hyphen) and the rest of the word.
+///
+/// Simple syllable rules are used - words are split between two consonants (`num-ber`),
+/// or before a consonant followed by a vowel (`ca-mel`), leaving at least 2 letters
+/// on either side. Returns `None` if the word can't be split to fit.
+pub fn hyphenate(word: &str, room: usize) -> Option<(String, String)> {
+    let chars: Vec<char> = word.chars().collect();
+    let is_letter = |i: usize| chars[i].is_ascii_alphabetic();
+    let is_vowel = |i: usize| "aeiouy".contains(chars[i].to_ascii_lowercase());
+    let is_digraph = |i: usize| {
+        let pair: String = chars[i - 1..=i].iter().collect::<String>().to_ascii_lowercase();
+        ["ch", "ck", "gh", "ng", "ph", "sh", "th", "wh"].contains(&pair.as_str())
+    };
+
+    // The first part is `chars[..i]`, plus the hyphen
+    let split = (2..chars.len().saturating_sub(1))
+        .rev()
+        .filter(|&i| i < room) // The first part fits with the hyphen
+        .filter(|&i| (i - 2..=i + 1).all(is_letter))
+        .find(|&i| {
+            let between_consonants = is_vowel(i - 2) && !is_vowel(i - 1) && !is_vowel(i) && is_vowel(i + 1) && !is_digraph(i);
+            let before_consonant = is_vowel(i - 1) && !is_vowel(i) && is_vowel(i + 1);
+            between_consonants || before_consonant
+        })?;
+
+    let mut first: String = chars[..split].iter().collect();
+    first.push('-');
+    Some((first, chars[split..].iter().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::time::Duration;
+
+    #[test]
+    fn test_engine_typing() {
+        let mut engine = TypingEngine::new();
+        let now = Instant::now();
+        for line in ["ab ", "cd ", "ef "] {
+            engine.push_line(line);
+        }
+        assert_eq!(engine.lines_len, VecDeque::from(vec![3, 3, 3]));
+
+        assert_eq!(engine.type_char('a', now), CharState::Correct);
+        assert_eq!(engine.type_char('x', now), CharState::Incorrect);
+        assert_eq!(engine.word_at_cursor().as_deref(), Some("ab"));
+
+        // Erased and retyped correctly
+        assert!(engine.backspace());
+        assert_eq!(engine.char_state(1), CharState::Untyped);
+        assert_eq!(engine.type_char('b', now), CharState::Corrected);
+        assert_eq!(engine.session.keystrokes, 3);
+        assert_eq!((engine.session.correct, engine.session.errors, engine.session.corrected), (2, 1, 1));
+        assert_eq!(engine.session.uncorrected, 0);
+
+        // The first line is dropped once the second one is typed (with one typed line kept)
+        assert_eq!(engine.typed_line(), None);
+        engine.type_char(' ', now);
+        assert_eq!(engine.typed_line(), Some(0..3));
+        for c in "cd ".chars() {
+            assert!(!engine.needs_new_line(1));
+            engine.type_char(c, now);
+        }
+        assert_eq!(engine.active_line(), 2);
+        assert!(engine.needs_new_line(1));
+        assert!(!engine.needs_new_line(2));
+        let dropped = engine.drop_first_line();
+        assert_eq!(dropped.typed, vec!['a', 'b', ' ']);
+        assert_eq!(dropped.states, vec![CharState::Correct, CharState::Corrected, CharState::Correct]);
+        engine.push_line("gh ");
+        assert_eq!(engine.cursor(), 3);
+        assert_eq!(engine.charset.iter().collect::<String>(), "cd ef gh ");
+
+        // (Word stats) - The words typed, the corrected one counted as mistyped
+        let words = &engine.session.words;
+        assert_eq!((words["ab"].typed, words["ab"].mistyped), (1, 1));
+        assert_eq!((words["cd"].typed, words["cd"].mistyped), (1, 0));
+    }
+
+    #[test]
+    fn test_engine_corrected() {
+        let mut engine = TypingEngine::new();
+        engine.push_line("ab");
+        let now = Instant::now();
+
+        // Mistyped, erased and typed right - corrected
+        engine.type_char('x', now);
+        engine.backspace();
+        assert_eq!(engine.type_char('a', now), CharState::Corrected);
+
+        // Still corrected when erased and typed right again, and counted once
+        engine.backspace();
+        assert_eq!(engine.type_char('a', now), CharState::Corrected);
+        assert_eq!(engine.char_state(0), CharState::Corrected);
+        let session = &engine.session;
+        assert_eq!((session.correct, session.errors, session.corrected), (2, 1, 1));
+    }
+
+    #[test]
+    fn test_engine_uncorrected() {
+        let mut engine = TypingEngine::new();
+        engine.push_line("abc");
+        let now = Instant::now();
+
+        // Mistyped twice, then fixed - 2 errors, 1 corrected, none left
+        engine.type_char('a', now);
+        for c in ['x', 'y'] {
+            engine.type_char(c, now);
+            engine.backspace();
+        }
+        engine.type_char('b', now);
+        let session = &engine.session;
+        assert_eq!((session.errors, session.corrected, session.uncorrected), (2, 1, 0));
+
+        // Left mistyped
+        engine.type_char('x', now);
+        assert_eq!(engine.session.uncorrected, 1);
+    }
+
+    #[test]
+    fn test_engine_word_stats() {
+        let mut engine = TypingEngine::new();
+        engine.push_line("to be");
+        engine.push_line("or");
+
+        // The words end with their line ("beor" isn't a word), and aren't counted again when
+        // their last character is retyped. A key every 100ms - each word is timed from its first
+        // key press to its last one
+        let mut now = Instant::now();
+        let mut type_chars = |engine: &mut TypingEngine, chars: &str| {
+            for c in chars.chars() {
+                engine.type_char(c, now);
+                now += Duration::from_millis(100);
+            }
+        };
+        type_chars(&mut engine, "to bx");
+        engine.backspace();
+        type_chars(&mut engine, "eor");
+        let mut words: Vec<(&str, usize, usize, u64)> = engine.session.words.iter().map(|(word, count)| (word.as_str(), count.typed, count.mistyped, count.millis)).collect();
+        words.sort();
+        assert_eq!(words, vec![("be", 1, 1, 100), ("or", 1, 0, 100), ("to", 1, 0, 100)]);
+
+        // The punctuation around a word isn't part of it, and is left out of its time
+        let mut engine = TypingEngine::new();
+        engine.push_line("(to) to, -- to.");
+        type_chars(&mut engine, "(to) to, -- to.");
+        let words: Vec<(&str, usize, usize, u64)> = engine.session.words.iter().map(|(word, count)| (word.as_str(), count.typed, count.mistyped, count.millis)).collect();
+        assert_eq!(words, vec![("to", 3, 0, 300)]);
+    }
+
+    #[test]
+    fn test_random_lines() {
+        let mut rng = rand::rng();
+        let line = random_chars_line(&mut rng, &["a", "b"], 10);
+        assert_eq!(line.chars().count(), 10);
+        assert!(line.chars().all(|c| c == 'a' || c == 'b'));
+
+        let words = vec!["one".to_string(), "two".to_string()];
+        let line = random_words_line(&mut rng, &words, &[1, 0], 12);
+        assert_eq!(line, "one one one ");
+
+        // Only the classes with a weight (and characters) are sampled
+        let classes = vec![vec!["a", "b"], vec!["1"], vec![]];
+        let line = weighted_chars_line(&mut rng, &classes, &[0, 1, 5], 10);
+        assert_eq!(line, "1111111111");
+        let line = weighted_chars_line(&mut rng, &classes, &[1, 0, 0], 10);
+        assert!(line.chars().all(|c| c == 'a' || c == 'b'));
+
+        // Only an empty class with a weight - all of the characters, uniformly
+        let line = weighted_chars_line(&mut rng, &classes, &[0, 0, 1], 100);
+        assert!(line.contains('1') && line.contains('a'));
+        assert_eq!(weighted_chars_line(&mut rng, &[vec![], vec![]], &[1, 1], 10), "");
+
+        // The share of the capital letters
+        assert_eq!(with_capitals(&mut rng, "abC1 ", 0, ""), "abc1 ");
+        assert_eq!(with_capitals(&mut rng, "abC1 ", 100, ""), "ABC1 ");
+        assert_eq!(with_capitals(&mut rng, "abC1 ", 100, "B"), "AbC1 ");
+        let line = with_capitals(&mut rng, &"a".repeat(1000), 30, "");
+        assert!((200..400).contains(&line.chars().filter(|c| c.is_uppercase()).count()));
+
+        // The same seed generates the same lines
+        let charset = ["a", "b", "c", "d"];
+        let seeded_line = |seed| random_chars_line(&mut StdRng::seed_from_u64(seed), &charset, 20);
+        assert_eq!(seeded_line(7), seeded_line(7));
+    }
+
+    #[test]
+    fn test_hyphenate() {
+        assert_eq!(hyphenate("number", 4), Some(("num-".to_string(), "ber".to_string())));
+        assert_eq!(hyphenate("camel", 3), Some(("ca-".to_string(), "mel".to_string())));
+        // The split furthest into the word that fits is used
+        assert_eq!(hyphenate("remember", 10), Some(("remem-".to_string(), "ber".to_string())));
+        // Digraphs are kept together
+        assert_eq!(hyphenate("ashes", 10), None);
+        assert_eq!(hyphenate("number", 3), None);
+        assert_eq!(hyphenate("the", 10), None);
+        assert_eq!(hyphenate("1234567", 10), None);
+    }
+}
@@ -0,0 +1,501 @@
+use crate::stats::{profile_metrics, recent_wpm_trend, top_mistakes, IntegrityFlag, Session, Stats, WordStat};
+use crate::utils::format_duration;
+use serde::Serialize;
+
+/// Renders a self-contained HTML report of the given sessions.
+///
+/// The report contains a summary, WPM and accuracy charts, the most
+/// mistyped characters, the trend of the recent sessions and a table of
+/// all the sessions. Works for a single session as well as for the whole history.
+pub fn html_report(sessions: &[Session]) -> String {
+    let title = if sessions.len() == 1 { "ttypr session report" } else { "ttypr report" };
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", title));
+    html.push_str("<style>\n");
+    html.push_str("body { font-family: monospace; background: #1e1e1e; color: #ddd; max-width: 900px; margin: 2em auto; }\n");
+    html.push_str("h1, h2 { color: #fff; font-weight: normal; }\n");
+    html.push_str("table { border-collapse: collapse; width: 100%; }\n");
+    html.push_str("td, th { border-bottom: 1px solid #444; padding: 0.3em 0.6em; text-align: right; }\n");
+    html.push_str("th:first-child, td:first-child { text-align: left; }\n");
+    html.push_str("svg { background: #252525; }\n");
+    html.push_str("</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", title));
+
+    if sessions.is_empty() {
+        html.push_str("<p>No sessions recorded yet.</p>\n</body>\n</html>\n");
+        return html;
+    }
+
+    // Summary
+    let total_secs: f64 = sessions.iter().map(|s| s.duration_secs).sum();
+    let wpms: Vec<f64> = sessions.iter().map(Session::wpm).collect();
+    let accuracies: Vec<f64> = sessions.iter().map(Session::accuracy).collect();
+    // (Flagged sessions aren't counted as the best)
+    let best_wpm = sessions.iter().filter(|s| !s.is_flagged()).map(Session::wpm).fold(0.0, f64::max);
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    html.push_str(&format!("<tr><td>Sessions</td><td>{}</td></tr>\n", sessions.len()));
+    html.push_str(&format!("<tr><td>Time typed</td><td>{}</td></tr>\n", format_duration(total_secs)));
+    html.push_str(&format!("<tr><td>Average WPM</td><td>{:.0}</td></tr>\n", average(&wpms)));
+    html.push_str(&format!("<tr><td>Best WPM</td><td>{:.0}</td></tr>\n", best_wpm));
+    html.push_str(&format!("<tr><td>Average accuracy</td><td>{:.1}%</td></tr>\n", average(&accuracies)));
+    html.push_str("</table>\n");
+
+    // Charts (only meaningful with more than one session)
+    if sessions.len() > 1 {
+        html.push_str("<h2>WPM</h2>\n");
+        html.push_str(&svg_line_chart(&wpms, 860, 200, "#5fd75f"));
+        html.push_str("<h2>Accuracy</h2>\n");
+        html.push_str(&svg_line_chart(&accuracies, 860, 200, "#5fafff"));
+    }
+
+    // Trend - the last 10 sessions compared to the 10 before them
+    if let Some((recent_len, recent_wpm, change)) = recent_wpm_trend(sessions) {
+        html.push_str("<h2>Trend</h2>\n");
+        html.push_str(&format!(
+            "<p>Last {} sessions: {:.0} WPM ({:+.1} compared to the {} before)</p>\n",
+            recent_len, recent_wpm, change, recent_len
+        ));
+    }
+
+    // Top mistakes
+    let mistakes = top_mistakes(sessions, 15);
+    if !mistakes.is_empty() {
+        html.push_str("<h2>Most mistyped characters</h2>\n<table>\n");
+        for (character, count) in mistakes {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&display_char(&character)), count
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    // All the sessions, most recent first
+    html.push_str("<h2>Sessions</h2>\n<table>\n");
+    html.push_str("<tr><th>Date</th><th>Option</th><th>Duration</th><th>WPM</th><th>Accuracy</th><th>Errors</th><th>Flagged</th></tr>\n");
+    for session in sessions.iter().rev() {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.0}</td><td>{:.1}%</td><td>{}</td><td>{}</td></tr>\n",
+            session.started_at_local().format("%Y-%m-%d %H:%M"),
+            escape_html(&session.typing_option),
+            format_duration(session.duration_secs),
+            session.wpm(),
+            session.accuracy(),
+            session.errors,
+            session.flags_description(),
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+/// Renders a simple SVG line chart of the given values, scaled to fit the given size.
+pub fn svg_line_chart(values: &[f64], width: u32, height: u32, color: &str) -> String {
+    let padding = 30.0;
+    let max = values.iter().cloned().fold(0.0, f64::max).max(1.0);
+    let step = if values.len() > 1 {
+        (width as f64 - 2.0 * padding) / (values.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let x = padding + i as f64 * step;
+            let y = height as f64 - padding - value / max * (height as f64 - 2.0 * padding);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+    // Axes with the maximum value labeled
+    svg.push_str(&format!(
+        "<line x1=\"{p}\" y1=\"{p}\" x2=\"{p}\" y2=\"{b}\" stroke=\"#888\"/>\n<line x1=\"{p}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#888\"/>\n",
+        p = padding, b = height as f64 - padding, r = width as f64 - padding
+    ));
+    svg.push_str(&format!(
+        "<text x=\"2\" y=\"{}\" font-size=\"10\" fill=\"#888\">{:.0}</text>\n",
+        padding + 4.0, max
+    ));
+    svg.push_str(&format!(
+        "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>\n",
+        color, points.join(" ")
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Renders a standalone SVG chart of the WPM of the given sessions, in order,
+/// labeled with the dates of the first and the last session.
+pub fn svg_wpm_chart(sessions: &[Session]) -> String {
+    let (width, height) = (800, 300);
+    let wpms: Vec<f64> = sessions.iter().map(Session::wpm).collect();
+    let mut svg = svg_line_chart(&wpms, width, height, "#5fd75f");
+
+    let mut labels = format!(
+        "<text x=\"{}\" y=\"18\" font-size=\"14\" fill=\"#888\" text-anchor=\"middle\">WPM history</text>\n",
+        width / 2
+    );
+    if let (Some(first), Some(last)) = (sessions.first(), sessions.last()) {
+        labels.push_str(&format!(
+            "<text x=\"30\" y=\"{y}\" font-size=\"10\" fill=\"#888\">{}</text>\n<text x=\"{}\" y=\"{y}\" font-size=\"10\" fill=\"#888\" text-anchor=\"end\">{}</text>\n",
+            first.started_at_local().format("%Y-%m-%d"),
+            width - 30,
+            last.started_at_local().format("%Y-%m-%d"),
+            y = height - 12,
+        ));
+    }
+
+    // Put a background behind the chart, and the labels on top of it
+    let background = "<rect width=\"100%\" height=\"100%\" fill=\"#252525\"/>\n";
+    let content_start = svg.find('\n').map_or(0, |i| i + 1);
+    svg.insert_str(content_start, background);
+    svg.insert_str(svg.len() - "</svg>\n".len(), &labels);
+    svg
+}
+
+/// Formats the result of a session in a single line (`62 WPM | 96.4% | Words`),
+/// for embedding in shell prompts and status lines.
+pub fn session_line(session: &Session) -> String {
+    let line = format!("{:.0} WPM | {:.1}% | {}", session.wpm(), session.accuracy(), session.typing_option);
+    if session.is_flagged() { format!("{} | flagged: {}", line, session.flags_description()) } else { line }
+}
+
+/// Rounds a value to one decimal place, for the JSON output.
+fn round(value: f64) -> f64 {
+    // Adding 0 turns -0 (e.g. the sum of no values) into 0
+    (value * 10.0).round() / 10.0 + 0.0
+}
+
+/// The result of a session, as printed by `ttypr last --json` (and `ttypr run --headless --json`).
+///
+/// Scripts rely on these fields - new ones can be added, but the existing ones are kept.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SessionResult {
+    pub accuracy: f64,
+    pub duration_secs: f64,
+    pub errors: usize,
+    pub flags: Vec<IntegrityFlag>, // Why the result might not be from typing it ("paste", "fast-keys", "replay")
+    pub gross_wpm: f64,
+    pub kph: f64, // Keystrokes per hour
+    pub net_wpm: f64,
+    pub slips: usize, // Mistakes right away followed by a correct key, not counted in the errors
+    pub started_at: i64, // Seconds since the Unix epoch
+    pub typing_option: String,
+    pub wpm: f64,
+}
+
+impl SessionResult {
+    /// The result of the given session (rounded to one decimal place).
+    pub fn of(session: &Session) -> SessionResult {
+        SessionResult {
+            accuracy: round(session.accuracy()),
+            duration_secs: round(session.duration_secs),
+            errors: session.errors,
+            flags: session.flags.clone(),
+            gross_wpm: round(session.gross_wpm()),
+            kph: round(session.kph()),
+            net_wpm: round(session.net_wpm()),
+            slips: session.slips,
+            started_at: session.started_at,
+            typing_option: session.typing_option.clone(),
+            wpm: round(session.wpm()),
+        }
+    }
+}
+
+/// Formats the result of a session as a single line of JSON.
+pub fn session_json(session: &Session) -> String {
+    serde_json::to_string(&SessionResult::of(session)).unwrap_or_default()
+}
+
+/// The totals of the typing history, as printed by `ttypr stats` (`--json`).
+///
+/// Scripts rely on these fields - new ones can be added, but the existing ones are kept.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StatsSummary {
+    pub sessions: usize,
+    pub keystrokes: usize,
+    pub errors: usize,
+    pub corrected: usize,
+    pub duration_secs: f64, // The time typed
+    pub best_wpm: f64,
+    pub recent_wpm: f64, // Average of the last 10 sessions
+    pub accuracy: f64, // Average of the sessions
+    pub first_session: Option<i64>, // Seconds since the Unix epoch
+    pub top_mistakes: Vec<MistakeCount>,
+}
+
+/// A character and how many times it was mistyped.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MistakeCount {
+    pub character: String,
+    pub count: usize,
+}
+
+impl StatsSummary {
+    /// Sums up the recorded sessions.
+    pub fn of(stats: &Stats) -> StatsSummary {
+        let totals = stats.lifetime_totals();
+        let metrics = profile_metrics(stats);
+        StatsSummary {
+            sessions: totals.sessions,
+            keystrokes: totals.keystrokes,
+            errors: totals.errors,
+            corrected: totals.corrected,
+            duration_secs: round(totals.secs),
+            best_wpm: round(totals.best_wpm),
+            recent_wpm: round(metrics.recent_wpm),
+            accuracy: round(metrics.accuracy),
+            first_session: totals.first_session.map(|first| first.timestamp()),
+            top_mistakes: metrics
+                .top_mistakes
+                .into_iter()
+                .map(|(character, count)| MistakeCount { character, count })
+                .collect(),
+        }
+    }
+
+    /// Formats the totals as lines of text, for the terminal.
+    pub fn to_text(&self) -> String {
+        let mistakes: Vec<String> = self.top_mistakes.iter().map(|mistake| format!("{} ({})", mistake.character, mistake.count)).collect();
+        let mut lines = vec![
+            format!("Sessions:     {}", self.sessions),
+            format!("Time typed:   {}", format_duration(self.duration_secs)),
+            format!("Keystrokes:   {} ({} errors, {} corrected)", self.keystrokes, self.errors, self.corrected),
+            format!("WPM:          {:.0} recently, {:.0} at best", self.recent_wpm, self.best_wpm),
+            format!("Accuracy:     {:.1}%", self.accuracy),
+        ];
+        if !mistakes.is_empty() {
+            lines.push(format!("Top mistakes: {}", mistakes.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The width of the text certificate, between its borders.
+const CERTIFICATE_WIDTH: usize = 40;
+
+/// Renders a plain-text "certificate" of a session - the name, date, typing option, WPM
+/// and accuracy in a box, for pasting or printing.
+///
+/// With `ansi`, the box and the results are colored with ANSI escape codes (for terminals).
+pub fn text_certificate(session: &Session, name: &str, ansi: bool) -> String {
+    let paint = |text: &str, code: &str| {
+        if ansi { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text.to_string() }
+    };
+    // A row of the certificate, padded to the width (before it's colored)
+    let row = |text: &str, centered: bool, code: &str| {
+        let text: String = text.chars().take(CERTIFICATE_WIDTH).collect();
+        let padded = if centered {
+            format!("{:^width$}", text, width = CERTIFICATE_WIDTH)
+        } else {
+            format!("{:<width$}", text, width = CERTIFICATE_WIDTH)
+        };
+        let padded = if code.is_empty() { padded } else { paint(&padded, code) };
+        format!("{}{}{}\n", paint("║", "33"), padded, paint("║", "33"))
+    };
+    let detail = |label: &str, value: String| row(&format!("     {:<10}{}", label, value), false, "");
+
+    let mut certificate = paint(&format!("╔{}╗", "═".repeat(CERTIFICATE_WIDTH)), "33");
+    certificate.push('\n');
+    certificate.push_str(&row("", true, ""));
+    certificate.push_str(&row("TYPING CERTIFICATE", true, "1"));
+    certificate.push_str(&row("", true, ""));
+    certificate.push_str(&row("awarded to", true, ""));
+    certificate.push_str(&row(name, true, "1"));
+    certificate.push_str(&row("", true, ""));
+    certificate.push_str(&detail("Date:", session.started_at_local().format("%Y-%m-%d %H:%M").to_string()));
+    certificate.push_str(&detail("Mode:", session.typing_option.clone()));
+    certificate.push_str(&detail("Duration:", format_duration(session.duration_secs)));
+    certificate.push_str(&row(&format!("     {:<10}{:.0}", "WPM:", session.wpm()), false, "1;32"));
+    certificate.push_str(&row(&format!("     {:<10}{:.1}%", "Accuracy:", session.accuracy()), false, "1;32"));
+    if session.is_flagged() {
+        certificate.push_str(&row(&format!("     {:<10}{}", "Flagged:", session.flags_description()), false, "1;31"));
+    }
+    certificate.push_str(&row("", true, ""));
+    certificate.push_str(&row("ttypr - terminal typing practice", true, "2"));
+    certificate.push_str(&paint(&format!("╚{}╝", "═".repeat(CERTIFICATE_WIDTH)), "33"));
+    certificate.push('\n');
+    certificate
+}
+
+/// (Word stats) - Renders the problem words as CSV - a row for every word, with how often it was
+/// typed and mistyped, its error rate and the time per character (empty for a single character).
+pub fn words_csv(words: &[WordStat]) -> String {
+    let mut csv = String::from("word,typed,mistyped,error_rate,ms_per_char\n");
+    for word in words {
+        let ms_per_char = word.ms_per_char().map_or(String::new(), |ms| format!("{:.0}", ms));
+        csv.push_str(&format!("{},{},{},{:.1},{}\n", csv_field(&word.word), word.typed, word.mistyped, word.error_rate(), ms_per_char));
+    }
+    csv
+}
+
+/// (Word stats) - Renders the problem words as an Anki import file - a card for every word
+/// (the word on the front, how it was typed on the back), tagged `ttypr`.
+pub fn words_anki(words: &[WordStat]) -> String {
+    let mut anki = String::from("#separator:tab\n#html:false\n#tags column:3\n");
+    for word in words {
+        let mut back = format!("Mistyped {} of {} times ({:.0}%)", word.mistyped, word.typed, word.error_rate());
+        if let Some(ms) = word.ms_per_char() {
+            back.push_str(&format!(" - {:.0} ms per character", ms));
+        }
+        anki.push_str(&format!("{}\t{}\tttypr\n", word.word, back));
+    }
+    anki
+}
+
+/// Quotes a CSV field if it has to be (a comma, a quote or a line break in it).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Makes whitespace characters visible in reports.
+fn display_char(character: &str) -> String {
+    if character == " " { "space".to_string() } else { character.to_string() }
+}
+
+/// Escapes the characters that have a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Average of the values, 0 if there are none.
+fn average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::sample_session;
+
+    #[test]
+    fn test_html_report() {
+        // --- No sessions ---
+        let html = html_report(&[]);
+        assert!(html.contains("No sessions recorded yet."));
+
+        // --- A history of sessions ---
+        let mut first = sample_session(1_700_000_000_000, 300, 30, 60.0);
+        first.mistyped_chars.insert("<".to_string(), 3);
+        let second = sample_session(1_700_000_100_000, 400, 0, 60.0);
+        let html = html_report(&[first, second]);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>ttypr report</title>"));
+        assert!(html.contains("<tr><td>Best WPM</td><td>80</td></tr>"));
+        assert!(html.contains("<polyline"));
+        assert!(html.contains("<tr><td>&lt;</td><td>3</td></tr>")); // Escaped
+        assert!(html.contains("(+20.0 compared to the 1 before)"));
+    }
+
+    #[test]
+    fn test_svg_wpm_chart() {
+        let sessions = vec![
+            sample_session(1_700_000_000_000, 300, 0, 60.0),
+            sample_session(1_700_000_100_000, 400, 0, 60.0),
+        ];
+        let svg = svg_wpm_chart(&sessions);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("WPM history"));
+        assert!(svg.contains("<rect width=\"100%\""));
+        // Two points - the highest one at the top of the chart
+        assert!(svg.contains("points=\"30.0,90.0 770.0,30.0\""));
+    }
+
+    #[test]
+    fn test_session_line() {
+        let session = sample_session(1_700_000_000_000, 310, 31, 60.0);
+        assert_eq!(session_line(&session), "62 WPM | 90.0% | Words");
+        let flagged = Session { flags: vec![IntegrityFlag::Paste], ..sample_session(0, 310, 31, 60.0) };
+        assert_eq!(session_line(&flagged), "62 WPM | 90.0% | Words | flagged: pasted text");
+        assert!(session_json(&flagged).contains(r#""flags":["paste"]"#));
+        assert_eq!(
+            session_json(&session),
+            r#"{"accuracy":90.0,"duration_secs":60.0,"errors":31,"flags":[],"gross_wpm":62.0,"kph":18600.0,"net_wpm":31.0,"slips":0,"started_at":1700000000,"typing_option":"Words","wpm":62.0}"#
+        );
+    }
+
+    #[test]
+    fn test_stats_summary() {
+        let mut first = sample_session(1_700_000_000_000, 300, 30, 60.0);
+        first.mistyped_chars.insert("e".to_string(), 4);
+        let stats = Stats { sessions: vec![first, sample_session(1_700_000_100_000, 400, 0, 60.0)], ..Stats::default() };
+
+        let summary = StatsSummary::of(&stats);
+        assert_eq!((summary.sessions, summary.keystrokes, summary.errors), (2, 700, 30));
+        assert_eq!(summary.best_wpm, 80.0);
+        assert_eq!(summary.first_session, Some(1_700_000_000));
+        assert_eq!(summary.top_mistakes, vec![MistakeCount { character: "e".to_string(), count: 4 }]);
+
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.starts_with(r#"{"sessions":2,"keystrokes":700,"errors":30,"#));
+        assert!(json.ends_with(r#""top_mistakes":[{"character":"e","count":4}]}"#));
+        assert!(summary.to_text().contains("Top mistakes: e (4)"));
+
+        // No sessions
+        let empty = StatsSummary::of(&Stats::default());
+        assert_eq!(empty.first_session, None);
+        assert!(serde_json::to_string(&empty).unwrap().contains(r#""duration_secs":0.0,"#));
+        assert!(!empty.to_text().contains("Top mistakes"));
+    }
+
+    #[test]
+    fn test_text_certificate() {
+        let session = sample_session(1_700_000_000_000, 300, 30, 60.0);
+        let certificate = text_certificate(&session, "alice", false);
+
+        let lines: Vec<&str> = certificate.lines().collect();
+        assert_eq!(lines.len(), 15);
+        // Every line is as wide as the box
+        assert!(lines.iter().all(|line| line.chars().count() == CERTIFICATE_WIDTH + 2));
+        assert!(lines.contains(&"║                 alice                  ║"));
+        assert!(certificate.contains("║     WPM:      60"));
+        assert!(certificate.contains("║     Accuracy: 90.0%"));
+        assert!(certificate.contains("║     Mode:     Words"));
+        assert!(!certificate.contains('\x1b'));
+
+        // Colored for terminals
+        let certificate = text_certificate(&session, "alice", true);
+        assert!(certificate.starts_with("\x1b[33m╔"));
+        assert!(certificate.contains("\x1b[1m                 alice                  \x1b[0m"));
+    }
+    #[test]
+    fn test_words_export() {
+        let words = vec![
+            WordStat { word: "their".to_string(), typed: 4, mistyped: 3, millis: 2400 },
+            WordStat { word: "a,\"b".to_string(), typed: 2, mistyped: 1, millis: 1000 },
+            WordStat { word: "I".to_string(), typed: 5, mistyped: 1, millis: 0 },
+        ];
+        assert_eq!(
+            words_csv(&words),
+            "word,typed,mistyped,error_rate,ms_per_char\ntheir,4,3,75.0,150\n\"a,\"\"b\",2,1,50.0,167\nI,5,1,20.0,\n"
+        );
+
+        let anki = words_anki(&words);
+        assert!(anki.starts_with("#separator:tab\n#html:false\n#tags column:3\n"));
+        assert!(anki.contains("their\tMistyped 3 of 4 times (75%) - 150 ms per character\tttypr\n"));
+        assert!(anki.ends_with("I\tMistyped 1 of 5 times (20%)\tttypr\n"));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+/// The history files of the shells, relative to the home directory - bash, zsh and fish.
+const HISTORY_FILES: [&str; 3] = [".bash_history", ".zsh_history", ".local/share/fish/fish_history"];
+
+/// The longest a command can be, so it always fits in a line.
+const MAX_COMMAND_LEN: usize = 40;
+
+/// How many commands the drill has at most - the most often typed ones.
+const MAX_COMMANDS: usize = 500;
+
+/// Commands with these (case-insensitive) are left out, as they might contain secrets.
+const SECRET_MARKERS: &[&str] = &["password", "passwd", "token", "secret", "api_key", "apikey", "authorization", "bearer", "private"];
+
+/// Gets the shell history files that exist in the home directory.
+pub fn history_files(home: &Path) -> Vec<PathBuf> {
+    HISTORY_FILES.iter().map(|file| home.join(file)).filter(|path| path.is_file()).collect()
+}
+
+/// Parses the commands of a shell history - of bash (one command per line, with
+/// optional `#<timestamp>` lines), zsh (`: <timestamp>:<duration>;<command>` with
+/// the extended history) or fish (`- cmd: <command>`).
+pub fn parse_history(content: &str) -> Vec<String> {
+    let mut commands = vec![];
+    let mut continued = String::new();
+    for line in content.lines() {
+        // Multi-line commands (with lines ending with a backslash) are joined into one line
+        // (with single spaces where the lines were split)
+        let line = if continued.is_empty() { line } else { line.trim_start() };
+        if let Some(part) = line.strip_suffix('\\') {
+            continued.push_str(part.trim_end());
+            continued.push(' ');
+            continue;
+        }
+        let line = std::mem::take(&mut continued) + line;
+
+        let command = if let Some(command) = line.strip_prefix("- cmd: ") {
+            command.replace("\\\\", "\\")
+        } else if let Some(entry) = line.strip_prefix(": ")
+            && let Some((_, command)) = entry.split_once(';')
+        {
+            command.to_string()
+        } else if line.starts_with('#') || line.starts_with("  ") {
+            // Bash timestamps, and the other fields of fish entries (`  when: ...`)
+            continue;
+        } else {
+            line.to_string()
+        };
+        commands.push(command);
+    }
+    commands
+}
+
+/// Makes a drill of the commands - cleaned up (with single spaces between the words), without
+/// the ones that are too long to fit in a line or might contain secrets, and without repeats.
+/// The most often typed commands come first.
+pub fn history_drill(commands: &[String]) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut order = vec![];
+    for command in commands {
+        let command = command.split_whitespace().collect::<Vec<_>>().join(" ");
+        let lowercase = command.to_lowercase();
+        if command.is_empty()
+            || command.chars().count() > MAX_COMMAND_LEN
+            || command.chars().any(char::is_control)
+            || SECRET_MARKERS.iter().any(|marker| lowercase.contains(marker))
+        {
+            continue;
+        }
+        let count = counts.entry(command.clone()).or_insert(0);
+        if *count == 0 {
+            order.push(command);
+        }
+        *count += 1;
+    }
+
+    // The most often typed first (the stable sort keeps the ties in the order they were typed)
+    order.sort_by(|a, b| counts[b].cmp(&counts[a]));
+    order.truncate(MAX_COMMANDS);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history() {
+        let bash = "git status\n#1690000000\nls -la\nfor f in *; do \\\n  echo $f; \\\ndone\n";
+        assert_eq!(parse_history(bash), vec!["git status", "ls -la", "for f in *; do echo $f; done"]);
+
+        let zsh = ": 1690000000:0;cargo build\n: 1690000005:2;cd ~/src\n: 1690000009:0;cargo test \\\n  --release\n";
+        assert_eq!(parse_history(zsh), vec!["cargo build", "cd ~/src", "cargo test --release"]);
+
+        let fish = "- cmd: grep -r \\\\d src\n  when: 1690000000\n- cmd: ls\n  when: 1690000001\n  paths:\n    - src\n";
+        assert_eq!(parse_history(fish), vec!["grep -r \\d src", "ls"]);
+    }
+
+    #[test]
+    fn test_history_drill() {
+        let commands: Vec<String> = ["ls", "git  status", "export API_TOKEN=abc", "git status", "cd ..", "", &"x".repeat(41), "ls", "git status"]
+            .iter()
+            .map(|command| command.to_string())
+            .collect();
+        assert_eq!(history_drill(&commands), vec!["git status", "ls", "cd .."]);
+    }
+}
@@ -1,44 +1,255 @@
-use crate::app::{App, CurrentMode, CurrentTypingOption};
-use crate::utils::{default_text, default_words};
-use color_eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crate::app::{App, CurrentMode, CurrentTypingOption, Region};
+use ttypr::replay::{Replay, ReplayKey};
+use ttypr::stats::IntegrityFlag;
+use ttypr::utils::{default_text, default_words};
+use color_eyre::{eyre::eyre, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-/// Reads the terminal events.
-pub fn handle_events(app: &mut App) -> Result<()> {
-    // Only wait for keyboard events for 50ms - otherwise continue the loop iteration
-    if event::poll(std::time::Duration::from_millis(50))? {
-        match event::read()? {
-            Event::Key(key) if key.kind == KeyEventKind::Press => on_key_event(app, key), // Handle keyboard input
-            Event::Mouse(_) => {}
-            Event::Resize(_, _) => {
-                app.needs_redraw = true;
-            } // Re-render if terminal window resized
-            _ => {}
+/// The longest note that can be attached to a session, in characters.
+const MAX_NOTE_LEN: usize = 30;
+
+/// A source of events for the input handling - the terminal, or scripted events in tests.
+pub trait EventSource {
+    /// Waits up to `timeout` for an event, and returns whether one is available.
+    fn poll(&mut self, timeout: Duration) -> Result<bool>;
+    /// Reads the next event, blocking until there is one.
+    fn read(&mut self) -> Result<Event>;
+}
+
+/// The events of the terminal.
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        Ok(event::poll(timeout)?)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        Ok(event::read()?)
+    }
+}
+
+/// The key presses of a replay, played back at their recorded pace (for the demo mode).
+pub struct ReplayEvents<'a> {
+    keys: &'a [(Duration, ReplayKey)],
+    next: usize, // The index of the next key press
+    next_at: Instant, // When the next key press is due
+}
+
+impl<'a> ReplayEvents<'a> {
+    /// Starts playing back the replay.
+    pub fn new(replay: &'a Replay) -> ReplayEvents<'a> {
+        let first_delay = replay.keys.first().map_or(Duration::ZERO, |(delay, _)| *delay);
+        ReplayEvents { keys: &replay.keys, next: 0, next_at: Instant::now() + first_delay }
+    }
+
+    /// Whether all the key presses were played back.
+    pub fn finished(&self) -> bool {
+        self.next >= self.keys.len()
+    }
+}
+
+impl EventSource for ReplayEvents<'_> {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let now = Instant::now();
+        if self.finished() || self.next_at > now + timeout {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        }
+        std::thread::sleep(self.next_at.saturating_duration_since(now));
+        Ok(true)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        let (_, key) = self.keys.get(self.next).ok_or_else(|| eyre!("No more replayed key presses"))?;
+        std::thread::sleep(self.next_at.saturating_duration_since(Instant::now()));
+
+        self.next += 1;
+        let delay = self.keys.get(self.next).map_or(Duration::ZERO, |(delay, _)| *delay);
+        self.next_at = Instant::now() + delay;
+
+        let code = match key {
+            ReplayKey::Char(c) => KeyCode::Char(*c),
+            ReplayKey::Backspace => KeyCode::Backspace,
+        };
+        Ok(Event::Key(KeyEvent::from(code)))
+    }
+}
+
+/// Reads the events (of the terminal, except in tests).
+///
+/// All the events that are already pending are handled at once (e.g. a burst of
+/// fast typing), so they are drawn in a single frame.
+pub fn handle_events(app: &mut App, events: &mut impl EventSource) -> Result<()> {
+    // Only wait for events until something needs updating - otherwise continue the loop
+    // iteration. If nothing does, block until the next event (no idle wake-ups).
+    let has_event = match app.tick_timeout() {
+        Some(timeout) => events.poll(timeout)?,
+        None => true,
+    };
+    if has_event {
+        on_event(app, events.read()?);
+        while app.running && events.poll(Duration::ZERO)? {
+            on_event(app, events.read()?);
         }
     }
     Ok(())
 }
 
+/// Handles a single terminal event.
+fn on_event(app: &mut App, event: Event) {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => on_key_event(app, key), // Handle keyboard input
+        Event::Key(key) if key.kind == KeyEventKind::Release => on_key_release(app, key),
+        Event::Paste(text) => on_paste(app, &text),
+        Event::Mouse(_) => {}
+        Event::Resize(_, _) => {
+            app.damage.add(Region::Screen);
+        } // Re-render if terminal window resized
+        _ => {}
+    }
+
+    // If the user typed - check the typed character before the next event
+    if app.typed {
+        app.update_char_state();
+        app.update_lines();
+        app.typed = false;
+    }
+}
+
+/// Handles pasted text (with bracketed paste) - its characters are typed, as without bracketed
+/// paste, but pasting into the lines flags the typing session.
+fn on_paste(app: &mut App, text: &str) {
+    for c in text.chars() {
+        let code = match c {
+            '\n' | '\r' => KeyCode::Enter,
+            '\t' => KeyCode::Tab,
+            c => KeyCode::Char(c),
+        };
+        on_event(app, Event::Key(KeyEvent::from(code)));
+    }
+    // (After typing them, as a key press after an idle gap starts a new session)
+    if let CurrentMode::Typing = app.current_mode
+        && app.engine.session.keystrokes > 0
+    {
+        app.engine.session.flag(IntegrityFlag::Paste);
+    }
+}
+
 /// Handles keyboard input.
 fn on_key_event(app: &mut App, key: KeyEvent) {
     // First boot page input (if toggled takes all input)
     // If Enter key is pressed sets first_boot to false in the config file
     if app.config.first_boot {
+        if key.code == KeyCode::Enter {
+            app.config.first_boot = false;
+            if let Ok(config_dir) = ttypr::utils::get_config_dir() {
+                ttypr::utils::save_config(&app.config, &config_dir).unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to save config: {}", err));
+                });
+            }
+            app.damage.add(Region::Screen);
+        } else {
+            scroll_help(app, key.code);
+        }
+        return;
+    }
+
+    // (`ttypr csv`) - Data entry drill page input (takes all input, until Esc)
+    if let Some(drill) = app.csv.as_mut() {
+        match key.code {
+            KeyCode::Esc => {
+                app.csv = None;
+                app.damage.add(Region::Screen);
+            }
+            KeyCode::Tab => drill.next_field(),
+            KeyCode::Enter => drill.next_row(),
+            KeyCode::Backspace => drill.backspace(),
+            KeyCode::Char(c) => drill.type_char(c),
+            _ => return,
+        }
+        app.damage.add(Region::Typing);
+        return;
+    }
+
+    // Dictation drill page input (takes all input, until Esc)
+    if let Some(dictation) = app.dictation.as_mut() {
         match key.code {
+            KeyCode::Esc => {
+                app.dictation = None;
+                app.damage.add(Region::Screen);
+            }
+            // Score the typed phrase, then move on to the next one
+            KeyCode::Enter if dictation.score.is_some() => app.next_dictation_phrase(),
+            KeyCode::Enter => dictation.submit(),
+            KeyCode::Backspace => dictation.backspace(),
+            KeyCode::Char(c) => dictation.type_char(c),
+            _ => return,
+        }
+        app.damage.add(Region::Typing);
+        return;
+    }
+
+    // Text completion page input (if toggled takes all input)
+    if app.completed_text.is_some() {
+        match key.code {
+            KeyCode::Up => app.text_choice = app.text_choice.saturating_sub(1),
+            KeyCode::Down => app.text_choice = (app.text_choice + 1).min(app.text_choices.len()),
             KeyCode::Enter => {
-                app.config.first_boot = false;
-                if let Ok(config_dir) = crate::utils::get_config_dir() {
-                    crate::utils::save_config(&app.config, &config_dir).unwrap_or_else(|err| {
-                        eprintln!("Failed to save config: {}", err);
-                    });
-                }
-                app.needs_clear = true;
-                app.needs_redraw = true;
+                app.pick_text_choice().unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to load the text: {}", err));
+                });
             }
+            _ => return,
+        }
+        app.damage.add(Region::Typing);
+        return; // Stop here
+    }
+
+    // Interrupted timed test prompt input (if shown takes all input)
+    if app.interrupted_test.is_some() {
+        match key.code {
+            KeyCode::Enter => app.resume_interrupted_test(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.discard_interrupted_test(),
             _ => {}
         }
-        return;
+        return; // Stop here
+    }
+
+    // Resume prompt input (if shown takes all input)
+    if app.show_resume_prompt {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => {}
+            KeyCode::Char('n') | KeyCode::Char('N') => app.restart_text(),
+            _ => return,
+        }
+        app.show_resume_prompt = false;
+        app.damage.add(Region::Screen);
+        return; // Stop here
+    }
+
+    // "Go to" prompt input (if open takes all input)
+    if let Some(goto_input) = app.goto_input.as_mut() {
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '%' => goto_input.push(c),
+            KeyCode::Backspace => {
+                goto_input.pop();
+            }
+            KeyCode::Enter => {
+                let target = app.goto_input.take().unwrap_or_default();
+                app.seek_text(&target);
+                app.damage.add(Region::Screen);
+            }
+            KeyCode::Esc => {
+                app.goto_input = None;
+                app.damage.add(Region::Screen);
+            }
+            _ => return,
+        }
+        app.damage.add(Region::Typing);
+        return; // Stop here
     }
 
     // Help page input (if toggled takes all input)
@@ -46,78 +257,370 @@ fn on_key_event(app: &mut App, key: KeyEvent) {
         match key.code {
             KeyCode::Enter | KeyCode::Char('h') => {
                 app.show_help = false;
-                app.needs_clear = true;
-                app.needs_redraw = true;
+                app.damage.add(Region::Screen);
             }
-            _ => {}
+            _ => scroll_help(app, key.code),
         }
         return; // Stop here
     }
 
+    // Playlist summary page input (if shown takes all input)
+    if app.playlist_summary.is_some() {
+        if key.code == KeyCode::Enter {
+            app.playlist_summary = None;
+            app.damage.add(Region::Screen);
+        }
+        return;
+    }
+
     // Most mistyped page input (if toggled takes all input)
     if app.show_mistyped {
         match key.code {
             KeyCode::Enter | KeyCode::Char('w') => {
                 app.show_mistyped = false;
-                app.needs_clear = true;
-                app.needs_redraw = true;
+                app.damage.add(Region::Screen);
             }
             _ => {}
         }
         return;
     }
 
+    // Profile page input (if toggled takes all input)
+    if app.show_profile {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('p') => {
+                app.show_profile = false;
+                app.compared_profile = None;
+            }
+            // Switch the active profile
+            KeyCode::Char('u') => app.switch_profile(),
+            // Toggle comparing with another profile
+            KeyCode::Char('c') => {
+                if app.compared_profile.is_some() {
+                    app.compared_profile = None;
+                } else {
+                    app.switch_compared_profile(true);
+                }
+            }
+            // Compare with the previous/next other profile
+            KeyCode::Left | KeyCode::Right if app.compared_profile.is_some() => {
+                app.switch_compared_profile(key.code == KeyCode::Right);
+            }
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Quit prompt input (if open takes all input)
+    if app.show_quit_prompt {
+        match key.code {
+            // Finish and save - the unfinished timed test is kept
+            KeyCode::Enter => app.quit(),
+            KeyCode::Char('a') if app.unfinished_test.is_some() => {
+                app.abandon_unfinished_test().unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to abandon the session: {}", err));
+                });
+                app.quit();
+            }
+            KeyCode::Esc => app.show_quit_prompt = false,
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Note of the last session, typed on the results page (if open takes all input)
+    if let Some(note_input) = app.note_input.as_mut() {
+        match key.code {
+            KeyCode::Char(c) if note_input.chars().count() < MAX_NOTE_LEN => note_input.push(c),
+            KeyCode::Backspace => {
+                note_input.pop();
+            }
+            KeyCode::Enter => {
+                let note = app.note_input.take().unwrap_or_default();
+                app.save_session_note(&note).unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to save the note: {}", err));
+                });
+            }
+            KeyCode::Esc => app.note_input = None,
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Repeat mode attempts page input (if toggled takes all input)
+    if app.show_attempts {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('Y') => app.show_attempts = false,
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Last session results page input (if toggled takes all input)
+    if app.show_results {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('R') => app.show_results = false,
+            // Attach a note to the session (editing the current one)
+            KeyCode::Char('n') => {
+                if let Some(session) = app.stats.sessions.last() {
+                    app.note_input = Some(session.note.clone().unwrap_or_default());
+                }
+            }
+            // (Targeted drill) - Make a drill of the errors of the session
+            KeyCode::Char('g') if app.targeted_drill.is_none() => {
+                app.targeted_drill = Some(match app.save_targeted_drill() {
+                    Ok(Some(_)) => "Drill saved - pick it with D".to_string(),
+                    Ok(None) => "No errors to drill".to_string(),
+                    Err(err) => {
+                        ttypr::crash::log_error(&format!("Failed to save the targeted drill: {}", err));
+                        "Failed to save the drill".to_string()
+                    }
+                });
+            }
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Word list picker page input (if open takes all input)
+    if app.show_word_lists {
+        match key.code {
+            KeyCode::Up => app.word_list_choice = app.word_list_choice.saturating_sub(1),
+            KeyCode::Down => app.word_list_choice = (app.word_list_choice + 1).min(app.shown_word_lists().len()),
+            // Filter the lists by the next/previous tag
+            KeyCode::Left | KeyCode::Right => app.switch_word_list_tag(key.code == KeyCode::Right),
+            KeyCode::Enter => {
+                app.pick_word_list().unwrap_or_else(|err| {
+                    ttypr::crash::log_error(&format!("Failed to load the word list: {}", err));
+                });
+            }
+            KeyCode::Esc => app.show_word_lists = false,
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Typed lines review page input (if toggled takes all input)
+    if app.show_scrollback {
+        let oldest = app.scrollback.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('e') => {
+                app.show_scrollback = false;
+            }
+            // Scroll back to older lines/forward to newer ones
+            KeyCode::Up | KeyCode::Char('k') => app.scrollback_offset = (app.scrollback_offset + 1).min(oldest),
+            KeyCode::Down | KeyCode::Char('j') => app.scrollback_offset = app.scrollback_offset.saturating_sub(1),
+            KeyCode::PageUp => app.scrollback_offset = (app.scrollback_offset + 10).min(oldest),
+            KeyCode::PageDown => app.scrollback_offset = app.scrollback_offset.saturating_sub(10),
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
+    // Summary page input (if toggled takes all input)
+    if app.show_summary {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('s') => {
+                app.show_summary = false;
+            }
+            // Browse to older/newer periods
+            KeyCode::Left => {
+                let periods = app.summaries().len();
+                if app.summary_offset + 1 < periods {
+                    app.summary_offset += 1;
+                }
+            }
+            KeyCode::Right => {
+                app.summary_offset = app.summary_offset.saturating_sub(1);
+            }
+            // Switch between weeks and months
+            KeyCode::Char('m') => {
+                app.summary_monthly = !app.summary_monthly;
+                app.summary_offset = 0;
+            }
+            // Filter by the next keyboard
+            KeyCode::Char('k') => app.switch_summary_keyboard(),
+            _ => return,
+        }
+        app.damage.add(Region::Screen);
+        return;
+    }
+
     match app.current_mode {
         // Menu mode input
         CurrentMode::Menu => {
             match key.code {
                 // Exit the application
+                KeyCode::Char('q') if app.config.confirm_quit => {
+                    app.show_quit_prompt = true;
+                    app.damage.add(Region::Typing);
+                }
                 KeyCode::Char('q') => app.quit(),
 
                 // Toggle wpm notification
                 KeyCode::Char('a') => {
                     app.config.show_wpm_notification = !app.config.show_wpm_notification;
                     app.notifications.show_display_wpm();
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Notifications);
                 }
 
                 // Reset mistyped characters count
                 KeyCode::Char('r') => {
                     app.config.mistyped_chars = HashMap::new();
                     app.notifications.show_clear_mistyped();
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Notifications);
                 }
 
                 // Show most mistyped page
                 KeyCode::Char('w') => {
                     app.show_mistyped = true;
-                    app.needs_clear = true;
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Show weekly/monthly summary page
+                KeyCode::Char('s') => {
+                    app.show_summary = true;
+                    app.summary_offset = 0;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Show profile page (lifetime stats)
+                KeyCode::Char('p') => {
+                    app.show_profile = true;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Show the results page of the last session
+                KeyCode::Char('R') => {
+                    app.show_results = true;
+                    app.targeted_drill = None;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Toggle repeat mode (the same material on every attempt)
+                KeyCode::Char('y') => {
+                    app.toggle_repeat_mode();
+                    app.notifications.show_repeat();
+                    app.damage.add(Region::Screen);
+                }
+
+                // Show the attempts at the material of repeat mode
+                KeyCode::Char('Y') => {
+                    app.show_attempts = true;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Open the word list picker page
+                KeyCode::Char('W') => app.open_word_list_picker(),
+
+                // Open the drills submenu (the word list picker, filtered to the generated drills)
+                KeyCode::Char('D') => app.open_drill_picker(),
+
+                // Open the dictation drill page (phrases typed from memory)
+                KeyCode::Char('M') => app.open_dictation(),
+
+                // Start the playlist (the segments set in the config, typed back-to-back)
+                KeyCode::Char('P') => app.start_playlist(),
+
+                // Show the typed lines review page (the latest lines at the bottom)
+                KeyCode::Char('e') => {
+                    app.show_scrollback = true;
+                    app.scrollback_offset = 0;
+                    app.damage.add(Region::Screen);
                 }
 
                 // Toggle counting mistyped characters
                 KeyCode::Char('c') => {
                     app.config.save_mistyped = !app.config.save_mistyped;
                     app.notifications.show_mistyped();
-                    app.needs_clear = true;
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Screen);
                 }
 
                 // Toggle displaying notifications
                 KeyCode::Char('n') => {
                     app.config.show_notifications = !app.config.show_notifications;
                     app.notifications.show_toggle();
-                    app.needs_clear = true;
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Toggle the key-hint footer
+                KeyCode::Char('f') => {
+                    app.config.show_key_hints = !app.config.show_key_hints;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Toggle focus mode (dimming the lines other than the one being typed)
+                KeyCode::Char('d') => {
+                    app.config.focus_mode = !app.config.focus_mode;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Toggle large type (displaying only the line being typed, with large characters)
+                KeyCode::Char('L') => {
+                    app.config.large_type = !app.config.large_type;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Toggle single-line mode (displaying only the line being typed)
+                KeyCode::Char('l') => {
+                    app.config.single_line = !app.config.single_line;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Switch the keyboard the sessions are tagged with
+                KeyCode::Char('K') => {
+                    app.switch_keyboard();
+                    app.notifications.show_keyboard();
+                    app.damage.add(Region::Screen);
+                }
+
+                // Switch the timed test duration
+                KeyCode::Char('t') => {
+                    app.cycle_test_duration();
+                    app.notifications.show_timed_test();
+                    app.damage.add(Region::Screen);
                 }
 
                 // Show help page
                 KeyCode::Char('h') => {
                     app.show_help = true;
-                    app.needs_clear = true;
-                    app.needs_redraw = true;
+                    app.help_scroll = 0;
+                    app.damage.add(Region::Screen);
+                }
+
+                // Jump to the previous/next chapter (Text option)
+                KeyCode::Char('[') | KeyCode::Char(']') => {
+                    if let CurrentTypingOption::Text = app.current_typing_option {
+                        app.jump_to_chapter(key.code == KeyCode::Char(']'));
+                    }
                 }
 
+                // Ban the word at the cursor (Words option)
+                KeyCode::Char('b') => app.ban_word_at_cursor(),
+
+                // Star/unstar the word at the cursor (Words option)
+                KeyCode::Char('*') => app.toggle_favorite_word_at_cursor(),
+
+                // Open the "go to" prompt, to jump to a position in the text (Text option)
+                KeyCode::Char('g') => {
+                    if let CurrentTypingOption::Text = app.current_typing_option
+                        && !app.text.is_empty()
+                    {
+                        app.goto_input = Some(String::new());
+                        app.damage.add(Region::Typing);
+                    }
+                }
+
+                // Practice the clipboard contents (as an ad-hoc Text option session)
+                KeyCode::Char('v') => app.practice_clipboard(ttypr::utils::read_clipboard()),
+
                 // Typing option switch (ASCII, Words, Text)
                 KeyCode::Char('o') => app.switch_typing_option(),
 
@@ -127,74 +630,52 @@ fn on_key_event(app: &mut App, key: KeyEvent) {
                     // to prevent being able to switch to Typing mode
                     // in info page if no words/text file was provided
                     match app.current_typing_option {
-                        CurrentTypingOption::Words => {
-                            if app.words.len() == 0 {
-                                return;
-                            }
-                        }
-                        CurrentTypingOption::Text => {
-                            if app.text.len() == 0 {
-                                return;
-                            }
-                        }
+                        CurrentTypingOption::Words if app.words.is_empty() => return,
+                        CurrentTypingOption::Text if app.text.is_empty() => return,
                         _ => {}
                     }
 
+                    // Every attempt of repeat mode starts with the same material
+                    app.start_repeat_attempt();
+
                     app.current_mode = CurrentMode::Typing;
                     app.notifications.show_mode();
-                    app.needs_redraw = true;
+                    app.damage.add(Region::Typing);
                 }
 
                 // If Enter is pressed in the Words/Text typing options,
                 // with no words/text file provided - use the default set.
                 KeyCode::Enter => {
                     match app.current_typing_option {
-                        CurrentTypingOption::Words => {
-                            if app.words.is_empty() {
-                                // Get the default words set
-                                app.words = default_words();
-
-                                // Generate three lines worth of words (characters) and ids.
-                                // Keep track of the length of those lines in characters.
-                                for _ in 0..3 {
-                                    let one_line = app.gen_one_line_of_words();
-                                    app.populate_charset_from_line(one_line);
-                                }
-
-                                // Remember to use the default word set
-                                app.config.use_default_word_set = true;
-
-                                app.needs_redraw = true;
+                        CurrentTypingOption::Words if app.words.is_empty() => {
+                            // Get the default words set
+                            app.words = default_words();
+
+                            // Generate the displayed lines worth of words (characters) and their states.
+                            // Keep track of the length of those lines in characters.
+                            for _ in 0..app.lines_shown() {
+                                let one_line = app.gen_one_line_of_words();
+                                app.engine.push_line(&one_line);
                             }
+
+                            // Remember to use the default word set
+                            app.config.use_default_word_set = true;
+
+                            app.damage.add(Region::Typing);
                         }
-                        CurrentTypingOption::Text => {
-                            // Only generate the lines if the text file was provided or the default text was chosen
-                            if app.text.is_empty() {
-                                // Get the default sentences
-                                app.text = default_text();
-
-                                // Generate three lines worth of words (characters) and ids.
-                                // Keep track of the length of those lines in characters.
-                                for _ in 0..3 {
-                                    let one_line = app.get_one_line_of_text();
-
-                                    // Count for how many "words" there were on the first three lines
-                                    // to keep position on option switch and exit.
-                                    // Otherwise would always skip 3 lines down.
-                                    let first_text_gen_len: Vec<String> = one_line
-                                        .split_whitespace()
-                                        .map(String::from)
-                                        .collect();
-                                    app.first_text_gen_len += first_text_gen_len.len();
-
-                                    app.populate_charset_from_line(one_line);
-                                }
-
-                                // Remember to use the default text set
-                                app.config.use_default_text_set = true;
-
-                                app.needs_redraw = true;
-                            }
+                        // Only generate the lines if the text file was provided or the default text was chosen
+                        CurrentTypingOption::Text if app.text.is_empty() => {
+                            // Get the default sentences
+                            app.text = default_text();
+
+                            // Generate the displayed lines worth of words (characters) and their states.
+                            // Keep track of the length of those lines in characters.
+                            app.populate_text_lines();
+
+                            // Remember to use the default text set
+                            app.config.use_default_text_set = true;
+
+                            app.damage.add(Region::Typing);
                         }
                         _ => {}
                     }
@@ -208,29 +689,429 @@ fn on_key_event(app: &mut App, key: KeyEvent) {
             match key.code {
                 KeyCode::Esc => {
                     // Switch to Menu mode if ESC pressed
+                    let test_running = app.test_started_at.is_some();
+                    let recorded = app.stats.sessions.len();
+                    app.composition.clear();
+                    app.test_started_at = None;
+                    app.playlist = None;
                     app.current_mode = CurrentMode::Menu;
-                    app.notifications.show_mode();
-                    app.needs_redraw = true;
-                }
-                KeyCode::Char(c) => {
-                    // Add to input characters
-                    app.input_chars.push_back(c.to_string());
-                    app.needs_redraw = true;
-                    app.typed = true;
-                    app.wpm.on_key_press();
-                }
-                KeyCode::Backspace => {
-                    // Remove from input characters
-                    let position = app.input_chars.len();
-                    if position > 0 {
-                        // If there are no input characters - don't do anything
-                        app.input_chars.pop_back();
-                        app.ids[position - 1] = 0;
-                        app.needs_redraw = true;
+                    app.finish_session();
+                    // A timed test left before the time is up - kept, unless abandoned when quitting
+                    if test_running && let Some(session) = app.stats.sessions.get(recorded) {
+                        app.unfinished_test = Some(session.id);
                     }
+                    app.notifications.show_mode();
+                    app.damage.add(Region::Typing);
+                }
+                // Ban the word at the cursor (Words option)
+                KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => app.ban_word_at_cursor(),
+                // Star/unstar the word at the cursor (Words option)
+                KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_favorite_word_at_cursor(),
+                // (Distractor drill) - Answer the prompt shown with the word of F1-F4
+                KeyCode::F(n @ 1..=4) => app.answer_distractor(n as usize - 1),
+                // (Forced pause) - The input is locked for a moment after a burst of errors
+                KeyCode::Char(_) if app.slow_down.is_locked() => {}
+                // (Accessibility) - The same character typed again too soon (tremor, key chatter) is ignored
+                KeyCode::Char(c) if app.is_bounce(c) => {}
+                // (Accessibility) - With a minimum hold time, characters are typed once their keys are released
+                KeyCode::Char(c) if app.holds_keys() => app.key_filter.on_press(c),
+                KeyCode::Char(c) => type_char(app, c),
+                // (IME composition) - Edit the composition of the current word
+                KeyCode::Backspace if !app.composition.is_empty() => {
+                    app.composition.pop();
+                    app.damage.add(Region::Typing);
+                }
+                // (IME composition) - Commit the composition of the current word (without a space)
+                KeyCode::Enter if !app.composition.is_empty() => app.commit_composition(),
+                // Remove from input characters (if there are none - don't do anything)
+                KeyCode::Backspace if app.engine.backspace() => {
+                    app.damage.add(Region::Typing);
                 }
                 _ => {}
             }
         }
     }
 }
+
+/// Handles a key release - (accessibility) with a minimum hold time, the character of the
+/// key is typed if it was held long enough.
+fn on_key_release(app: &mut App, key: KeyEvent) {
+    if !matches!(app.current_mode, CurrentMode::Typing) || !app.holds_keys() || app.slow_down.is_locked() {
+        return;
+    }
+    if let KeyCode::Char(c) = key.code
+        && let Some(c) = app.key_filter.on_release(c, Duration::from_millis(app.config.min_hold_ms))
+    {
+        type_char(app, c);
+    }
+}
+
+/// Types a character in Typing mode.
+fn type_char(app: &mut App, c: char) {
+    // (Speed governor) - Keys faster than `max_wpm` are ignored
+    if !app.governor.allows(app.config.max_wpm) {
+        app.damage.add(Region::Typing);
+        return;
+    }
+
+    // (IME composition) - Characters of a word are compared once it's composed
+    if app.ime_composition_enabled() && app.compose(c) {
+        app.damage.add(Region::Typing);
+        app.on_key_press();
+        return;
+    }
+
+    // Add to input characters
+    app.engine.input_chars.push_back(c);
+    app.damage.add(Region::Typing);
+    app.typed = true;
+    app.on_key_press();
+}
+
+/// Scrolls the help popup up/down, if it doesn't fit in the terminal.
+fn scroll_help(app: &mut App, key_code: KeyCode) {
+    let (_, rows) = crossterm::terminal::size().unwrap_or((0, u16::MAX));
+    let max_scroll = crate::ui::help_max_scroll(rows);
+
+    match key_code {
+        KeyCode::Up | KeyCode::Char('k') => app.help_scroll = app.help_scroll.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => app.help_scroll = (app.help_scroll + 1).min(max_scroll),
+        _ => return,
+    }
+    app.damage.add(Region::Typing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::render;
+    use ttypr::bigtext::big_char;
+    use ttypr::replay::parse_replay;
+    use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+    use std::collections::VecDeque;
+    use ttypr::CharState;
+
+    /// Scripted events, fed through the input handling as if they were typed.
+    struct ScriptedEvents(VecDeque<Event>);
+
+    impl ScriptedEvents {
+        fn keys(codes: &[KeyCode]) -> ScriptedEvents {
+            ScriptedEvents(codes.iter().map(|code| Event::Key(KeyEvent::from(*code))).collect())
+        }
+    }
+
+    impl EventSource for ScriptedEvents {
+        fn poll(&mut self, _timeout: Duration) -> Result<bool> {
+            Ok(!self.0.is_empty())
+        }
+
+        fn read(&mut self) -> Result<Event> {
+            self.0.pop_front().ok_or_else(|| eyre!("No more scripted events"))
+        }
+    }
+
+    /// An app past the first boot page, with known lines to type.
+    fn scripted_app() -> App {
+        let mut app = App::new();
+        app.config.first_boot = false;
+        for line in ["abc def ", "ghi ", "jkl "] {
+            app.engine.push_line(line);
+        }
+        app
+    }
+
+    /// Renders the app in a terminal of the given size.
+    fn render_buffer(app: &App, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| render(frame, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// The rows of a rendered buffer, as text.
+    fn buffer_rows(buffer: &Buffer) -> Vec<String> {
+        let area = buffer.area;
+        (area.top()..area.bottom()).map(|y| (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+    }
+
+    /// Renders the app in a terminal of the given size, as the text of its rows.
+    fn render_rows(app: &App, width: u16, height: u16) -> Vec<String> {
+        buffer_rows(&render_buffer(app, width, height))
+    }
+
+    #[test]
+    fn test_scripted_typing() {
+        let mut app = scripted_app();
+        let mut events = ScriptedEvents::keys(&[
+            KeyCode::Char('i'), // Typing mode
+            KeyCode::Char('a'),
+            KeyCode::Char('x'),
+            KeyCode::Backspace,
+            KeyCode::Char('b'),
+            KeyCode::Char('x'),
+        ]);
+        handle_events(&mut app, &mut events).unwrap();
+        assert!(events.0.is_empty()); // All the pending events are handled at once
+
+        assert!(matches!(app.current_mode, CurrentMode::Typing));
+        assert_eq!(app.engine.cursor(), 3);
+        let states: Vec<CharState> = (0..3).map(|i| app.engine.char_state(i)).collect();
+        assert_eq!(states, vec![CharState::Correct, CharState::Corrected, CharState::Incorrect]);
+
+        // The lines are drawn with the typed characters
+        let buffer = render_buffer(&app, 40, 12);
+        let rows = buffer_rows(&buffer);
+        let y = rows.iter().position(|row| row.trim() == "abc def").unwrap();
+        let x = rows[y].find('a').unwrap();
+        assert_eq!(buffer[(x as u16, y as u16)].fg, ratatui::style::Color::Indexed(10)); // Correct
+    }
+
+    #[test]
+    fn test_scripted_show_typed_chars() {
+        let mut app = scripted_app();
+        app.config.show_typed_chars = true;
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Char('x')])).unwrap();
+
+        // The mistyped character is drawn as it was typed
+        let rows = render_rows(&app, 40, 12);
+        assert!(rows.iter().any(|row| row.trim() == "abx def"));
+        assert!(!rows.iter().any(|row| row.trim() == "abc def"));
+    }
+
+    #[test]
+    fn test_scripted_stacked_errors() {
+        let mut app = scripted_app();
+        app.config.stacked_errors = true;
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Char('x')])).unwrap();
+
+        // The typed character is drawn beneath the expected one
+        let buffer = render_buffer(&app, 40, 12);
+        let y = buffer_rows(&buffer).iter().position(|row| row.trim() == "abc def").unwrap() as u16;
+        let x = (0..40).find(|&x| buffer[(x, y)].symbol() == "c").unwrap();
+        assert_eq!(buffer[(x, y + 1)].symbol(), "x");
+        assert_eq!(buffer[(x, y + 1)].fg, ratatui::style::Color::Indexed(9));
+    }
+
+    #[test]
+    fn test_span_cache() {
+        let mut app = scripted_app();
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|frame| render(frame, &app)).unwrap();
+        assert_eq!(app.span_cache.borrow().rebuilt, 3);
+
+        // Only the line with the typed character is rebuilt
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('x')])).unwrap();
+        terminal.draw(|frame| render(frame, &app)).unwrap();
+        assert_eq!(app.span_cache.borrow().rebuilt, 1);
+        let rows = buffer_rows(terminal.backend().buffer());
+        assert!(rows.iter().any(|row| row.trim() == "abc def") && rows.iter().any(|row| row.trim() == "ghi"));
+        terminal.draw(|frame| render(frame, &app)).unwrap();
+        assert_eq!(app.span_cache.borrow().rebuilt, 0);
+
+        // All of them once the lines scroll
+        handle_events(&mut app, &mut ScriptedEvents::keys(&"bc def ghi ".chars().map(KeyCode::Char).collect::<Vec<_>>())).unwrap();
+        terminal.draw(|frame| render(frame, &app)).unwrap();
+        assert_eq!(app.span_cache.borrow().rebuilt, 3);
+    }
+
+    #[test]
+    fn test_scripted_compact() {
+        let mut app = scripted_app();
+        app.set_compact(24);
+        assert_eq!(app.line_len, 22);
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('a')])).unwrap();
+        assert!(!app.notifications.mode); // Muted
+
+        // Only the line being typed, without the key hints
+        let rows = render_rows(&app, 24, 5);
+        let text: Vec<&str> = rows.iter().map(|row| row.trim()).filter(|row| !row.is_empty()).collect();
+        assert_eq!(text, vec!["abc def"]);
+    }
+
+    #[test]
+    fn test_scripted_menu_keys() {
+        let mut app = scripted_app();
+        let mut events = ScriptedEvents::keys(&[
+            KeyCode::Char('i'),
+            KeyCode::Char('a'),
+            KeyCode::Esc, // Back to Menu mode
+            KeyCode::Char('q'),
+            KeyCode::Char('i'), // Not handled - the app quit
+        ]);
+        handle_events(&mut app, &mut events).unwrap();
+
+        assert!(matches!(app.current_mode, CurrentMode::Menu));
+        assert!(!app.running);
+        assert_eq!(events.0.len(), 1);
+    }
+
+    #[test]
+    fn test_scripted_confirm_quit() {
+        let mut app = scripted_app();
+        app.config.confirm_quit = true;
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('q')])).unwrap();
+        assert!(app.running && app.show_quit_prompt);
+
+        let rows = render_rows(&app, 60, 20);
+        assert!(rows.iter().any(|row| row.contains("Quit ttypr?")));
+        assert!(!rows.iter().any(|row| row.contains("abandon"))); // No unfinished test
+
+        // Cancelled, then confirmed
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Esc, KeyCode::Char('i')])).unwrap();
+        assert!(app.running && !app.show_quit_prompt);
+        assert!(matches!(app.current_mode, CurrentMode::Typing));
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Esc, KeyCode::Char('q'), KeyCode::Enter])).unwrap();
+        assert!(!app.running);
+    }
+
+    #[test]
+    fn test_scripted_scrollback_review() {
+        let mut app = scripted_app();
+        let mut typing = vec![KeyCode::Char('i')];
+        typing.extend("abx def ghi ".chars().map(KeyCode::Char));
+        handle_events(&mut app, &mut ScriptedEvents::keys(&typing)).unwrap();
+
+        // The first line scrolled away, and is kept for the review
+        assert_eq!(app.scrollback.len(), 1);
+        assert_eq!(app.scrollback[0].states[2], CharState::Incorrect);
+
+        app.current_mode = CurrentMode::Menu;
+        let mut events = ScriptedEvents::keys(&[KeyCode::Char('e'), KeyCode::Up, KeyCode::Up]);
+        handle_events(&mut app, &mut events).unwrap();
+        assert!(app.show_scrollback);
+        assert_eq!(app.scrollback_offset, 0); // There are no older lines to scroll back to
+
+        let buffer = render_buffer(&app, 60, 20);
+        let rows = buffer_rows(&buffer);
+        let y = rows.iter().position(|row| row.trim() == "abc def").unwrap();
+        let x = rows[y].find('c').unwrap();
+        assert_eq!(buffer[(x as u16, y as u16)].fg, ratatui::style::Color::Indexed(9)); // Incorrect
+
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('e')])).unwrap();
+        assert!(!app.show_scrollback);
+    }
+
+    #[test]
+    fn test_scripted_paste() {
+        let mut app = scripted_app();
+        let mut events = ScriptedEvents::keys(&[KeyCode::Char('i')]);
+        events.0.push_back(Event::Paste("abc".to_string()));
+        handle_events(&mut app, &mut events).unwrap();
+
+        // Typed, but the session is flagged
+        assert_eq!(app.engine.input_chars.iter().collect::<String>(), "abc");
+        assert_eq!(app.engine.session.flags, vec![IntegrityFlag::Paste]);
+    }
+
+    #[test]
+    fn test_scripted_large_type() {
+        let mut app = scripted_app();
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('L'), KeyCode::Char('i'), KeyCode::Char('a')])).unwrap();
+        assert!(app.config.large_type);
+
+        let buffer = render_buffer(&app, 60, 20);
+        let rows = buffer_rows(&buffer);
+        // The line being typed, drawn large
+        let top: String = "abc def ".chars().map(|c| big_char(c)[0].clone()).collect();
+        let y = rows.iter().position(|row| row.contains(top.trim_end())).unwrap();
+        let x = rows[y][..rows[y].find(top.trim_end()).unwrap()].chars().count();
+        let bottom: String = "abc def ".chars().map(|c| big_char(c)[2].clone()).collect();
+        assert!(rows[y + 2].contains(bottom.trim_end()));
+        assert_eq!(buffer[(x as u16, y as u16 + 1)].fg, ratatui::style::Color::Indexed(10)); // Correct
+    }
+
+    #[test]
+    fn test_scripted_live_wpm() {
+        let mut app = scripted_app();
+        app.config.live_wpm = true;
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('a')])).unwrap();
+
+        let corner = |app: &App| render_rows(app, 60, 20).iter().any(|row| row.trim() == "- wpm");
+        assert!(corner(&app));
+
+        // Hidden with the WPM display toggle of the menu
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Esc, KeyCode::Char('a')])).unwrap();
+        assert!(render_rows(&app, 60, 20).iter().any(|row| row.trim() == "Display wpm off"));
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i')])).unwrap();
+        assert!(!app.config.show_wpm_notification);
+        assert!(!corner(&app));
+        app.config.live_wpm = false;
+        app.config.show_wpm_notification = true;
+        assert!(!corner(&app));
+    }
+
+    #[test]
+    fn test_scripted_clear_mistyped() {
+        let mut app = scripted_app();
+        app.config.mistyped_chars.insert("a".to_string(), 3);
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('r')])).unwrap();
+        assert!(app.config.mistyped_chars.is_empty());
+
+        assert!(render_rows(&app, 60, 20).iter().any(|row| row.trim() == "Cleared mistyped characters count"));
+    }
+
+    #[test]
+    fn test_scripted_key_hints() {
+        let mut app = scripted_app();
+        // Off by default
+        assert!(!render_rows(&app, 80, 20)[19].contains("i type"));
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('f')])).unwrap();
+        assert!(app.config.show_key_hints);
+        assert!(render_rows(&app, 80, 20)[19].contains("i type · o option"));
+    }
+
+    #[test]
+    fn test_scripted_silent_errors() {
+        let mut app = scripted_app();
+        app.config.silent_errors = true;
+        app.config.error_flash = true;
+        handle_events(&mut app, &mut ScriptedEvents::keys(&[KeyCode::Char('i'), KeyCode::Char('x')])).unwrap();
+        assert!(app.error_flash.is_none());
+
+        let buffer = render_buffer(&app, 60, 20);
+        let rows = buffer_rows(&buffer);
+        // Not marked in the line, only counted in the corner
+        let y = rows.iter().position(|row| row.trim() == "abc def").unwrap();
+        let x = rows[y].find('a').unwrap();
+        assert_eq!(buffer[(x as u16, y as u16)].fg, ratatui::style::Color::Indexed(10));
+        assert_eq!(rows[19].trim(), "● 1");
+    }
+
+    #[test]
+    fn test_replay_events() {
+        let replay = parse_replay("text ab cd\n0 a\n0 x\n0 backspace\n0 b\n0 space\n").unwrap();
+        let mut app = App::new();
+        app.start_demo(&replay.text);
+        assert!(app.demo && matches!(app.current_mode, CurrentMode::Typing));
+
+        let mut events = ReplayEvents::new(&replay);
+        while !events.finished() {
+            handle_events(&mut app, &mut events).unwrap();
+        }
+        assert!(events.read().is_err());
+        assert_eq!(app.engine.char_state(1), CharState::Corrected);
+        assert_eq!(app.engine.cursor(), 3);
+    }
+
+    #[test]
+    fn test_scripted_key_filters() {
+        let mut app = scripted_app();
+        app.config.bounce_ms = 60_000; // Every repeated character is a bounce
+        let mut typing = vec![KeyCode::Char('i')];
+        typing.extend("aabbc".chars().map(KeyCode::Char));
+        handle_events(&mut app, &mut ScriptedEvents::keys(&typing)).unwrap();
+        assert_eq!(app.engine.input_chars.iter().collect::<String>(), "abc");
+
+        // With a minimum hold time, the characters are typed once they're released
+        app.config.bounce_ms = 0;
+        app.config.min_hold_ms = 1;
+        app.key_releases = true;
+        let release = |c| Event::Key(KeyEvent::new_with_kind(KeyCode::Char(c), KeyModifiers::NONE, KeyEventKind::Release));
+        let mut events = ScriptedEvents::keys(&[KeyCode::Char(' ')]);
+        handle_events(&mut app, &mut events).unwrap();
+        assert_eq!(app.engine.cursor(), 3);
+        std::thread::sleep(Duration::from_millis(2));
+        events.0.push_back(release(' '));
+        handle_events(&mut app, &mut events).unwrap();
+        assert_eq!(app.engine.cursor(), 4);
+    }
+}
@@ -0,0 +1,28 @@
+//! The typing engine of ttypr, without the terminal user interface.
+//!
+//! [`TypingEngine`] keeps the lines to type, checks the typed characters and scores the
+//! session; [`stats`] records the sessions. Other frontends can embed them, the way
+//! the ttypr TUI does.
+
+pub mod bigtext;
+pub mod bundle;
+pub mod checkpoint;
+pub mod clock;
+pub mod commits;
+pub mod content;
+pub mod crash;
+pub mod csv;
+pub mod dictation;
+pub mod distraction;
+pub mod drills;
+pub mod engine;
+pub mod export;
+pub mod history;
+pub mod reminder;
+pub mod replay;
+pub mod stats;
+pub mod update;
+pub mod utils;
+pub mod wordlist;
+
+pub use engine::{CharState, TypedLine, TypingEngine};
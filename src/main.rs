@@ -1,24 +1,69 @@
 use color_eyre::Result;
+use crossterm::event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags};
+use crossterm::{execute, style::Print, terminal::{supports_keyboard_enhancement, SetTitle}};
+use std::io::stdout;
 use ratatui::DefaultTerminal;
+use std::time::Duration;
+use ttypr::replay::{parse_replay, DEMO_REPLAY};
 
 mod app;
+mod cli;
 mod input;
 mod ui;
-mod utils;
 use crate::{
-    app::App,
-    input::handle_events,
+    app::{App, Damage, Region},
+    input::{handle_events, ReplayEvents, TerminalEvents},
     ui::{draw_on_clear, render},
 };
 
+/// Saves the title of the terminal (on its stack of titles), to restore it on exit.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Restores the title of the terminal saved by `PUSH_TITLE`.
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// (Demo mode) - How long the end of the demo stays displayed before it starts over.
+const DEMO_RESTART_PAUSE: Duration = Duration::from_secs(3);
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
+
+    // Run a command instead of the TUI if one was given
+    // (except `ttypr drill <chars>`, which starts the TUI with a drill of the characters,
+    // `ttypr csv <file>`, which starts it with a data entry drill of the file,
+    // and `ttypr demo`, which plays back the demo session)
+    // (`--deterministic` is hidden from the help - it's for reproducible screenshots and recordings,
+    // `--compact` fits the TUI to a small pane, e.g. a tmux popup)
+    let matches = cli::command().get_matches();
+    let deterministic = matches.get_flag("deterministic");
+    let compact = matches.get_flag("compact");
+    let demo = matches.subcommand_name() == Some("demo");
+    let (drill_chars, csv) = match matches.subcommand() {
+        Some(("drill", matches)) => (Some(cli::drill_chars(matches)?), None),
+        Some(("csv", matches)) => (None, Some(cli::csv_drill(matches)?)),
+        Some(("demo", _)) | None => (None, None),
+        Some(_) => return cli::run(&matches),
+    };
+
     let terminal = ratatui::init();
-    let mut app = App::new();
-    let result = run(terminal, &mut app);
+    install_crash_hook();
+    let mut app = if deterministic { App::deterministic() } else { App::new() };
+    app.drill_chars = drill_chars;
+    app.csv = csv;
+    if compact {
+        app.set_compact(terminal.size()?.width);
+    }
+    let result = if demo { run_demo(terminal, &mut app) } else { run(terminal, &mut app) };
 
     app.on_exit();
+    execute!(stdout(), DisableBracketedPaste)?;
+    if app.key_releases {
+        execute!(stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    // Give the terminal its own title back
+    if app.terminal_title.is_some() {
+        execute!(stdout(), Print(POP_TITLE))?;
+    }
 
     // Restore the terminal and return the result from run()
     ratatui::restore();
@@ -28,33 +73,124 @@ fn main() -> color_eyre::Result<()> {
 fn run(mut terminal: DefaultTerminal, app: &mut App) -> Result<()> {
     app.setup()?;
 
+    // Pasted text arrives as a single event, so pasting into the lines can flag the session
+    execute!(stdout(), EnableBracketedPaste)?;
+
+    // (Accessibility) - The minimum hold time needs the key releases, which only
+    // some terminals report (once asked to)
+    if app.config.min_hold_ms > 0 && supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(stdout(), PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+        app.key_releases = true;
+    }
+
     // Main application loop
     while app.running {
         app.on_tick();
+        draw(&mut terminal, app)?;
 
-        // If the user typed
-        if app.typed {
-            app.update_id_field();
-            app.update_lines();
-            app.typed = false;
+        // Read terminal events
+        handle_events(app, &mut TerminalEvents)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a crash report (the backtrace, the latest log lines and the anonymized config) to
+/// `~/.config/ttypr/crashes` on a panic, after the terminal is restored (by the hook of ratatui,
+/// which prints the panic), and prints its path - to be attached to a bug report.
+fn install_crash_hook() {
+    use std::backtrace::Backtrace;
+    use ttypr::crash::{anonymize_config, crash_report, recent_log, write_crash_bundle, CRASH_DIR};
+    use ttypr::utils::get_config_dir;
+
+    let restore_and_print = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_and_print(info);
+        let backtrace = Backtrace::force_capture().to_string();
+        let Ok(config_dir) = get_config_dir() else {
+            return;
+        };
+        let config = std::fs::read_to_string(config_dir.join("config")).unwrap_or_default();
+        let report = crash_report(&info.to_string(), &backtrace, &recent_log(), &anonymize_config(&config));
+        match write_crash_bundle(&config_dir.join(CRASH_DIR), &report) {
+            Ok(path) => eprintln!("\nA crash report was written to {} - please attach it to the bug report", path.display()),
+            Err(err) => eprintln!("\nFailed to write the crash report: {}", err),
         }
+    }));
+}
 
-        // Clear the entire area
-        if app.needs_clear { 
-            terminal.draw(|frame| draw_on_clear(frame))?;
-            app.needs_clear = false;
-            app.needs_redraw = true;
+/// Plays back the bundled demo session on loop, until a key is pressed (for kiosk screens,
+/// screenshots and recordings). The user's config and stats aren't loaded or changed.
+fn run_demo(mut terminal: DefaultTerminal, app: &mut App) -> Result<()> {
+    let replay = parse_replay(DEMO_REPLAY)?;
+
+    while app.running {
+        app.start_demo(&replay.text);
+        let mut events = ReplayEvents::new(&replay);
+        while app.running && !events.finished() {
+            app.on_tick();
+            draw(&mut terminal, app)?;
+
+            // Any key pressed by the user ends the demo
+            if event::poll(Duration::ZERO)? && matches!(event::read()?, Event::Key(_)) {
+                app.running = false;
+                break;
+            }
+            handle_events(app, &mut events)?;
         }
 
-        // Draw/Redraw the ui
-        if app.needs_redraw {
-            terminal.draw(|frame| render(frame, app))?;
-            app.needs_redraw = false;
+        // Show the end of the demo for a moment before starting over
+        app.finish_session();
+        draw(&mut terminal, app)?;
+        if event::poll(DEMO_RESTART_PAUSE)? && matches!(event::read()?, Event::Key(_)) {
+            app.running = false;
         }
+    }
 
-        // Read terminal events
-        handle_events(app)?;
+    Ok(())
+}
+
+/// Clears the screen and draws the ui, if needed (and the frame budget allows it).
+fn draw(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    // (Frame budget) - With `max_fps`, the frame is held back until it's due
+    if !app.frame_due() {
+        return Ok(());
+    }
+
+    // Clear the entire area (except in the low-bandwidth mode, where only
+    // the changed cells are redrawn, to avoid flicker over slow connections)
+    if app.damage.contains(Region::Screen) && !app.config.low_bandwidth {
+        terminal.draw(draw_on_clear)?;
+    }
+
+    // Draw/Redraw the changed regions of the ui (only the changed cells are written)
+    if !app.damage.is_empty() {
+        terminal.draw(|frame| render(frame, app))?;
+        app.damage = Damage::default();
+        app.mark_drawn();
+    }
+
+    // (Practice schedule) - The bell at the end of a practice block or a rest
+    if app.ring_bell {
+        execute!(stdout(), Print('\x07'))?;
+        app.ring_bell = false;
     }
 
+    // The session info in the terminal title, visible with the terminal in the background
+    if app.config.terminal_title {
+        let title = app.title();
+        if app.terminal_title.as_ref() != Some(&title) {
+            if app.terminal_title.is_none() {
+                execute!(stdout(), Print(PUSH_TITLE))?;
+            }
+            execute!(stdout(), SetTitle(&title))?;
+            app.terminal_title = Some(title);
+        }
+    }
+
+    // A desktop notification (OSC 9 - supported by iTerm2, kitty, WezTerm, Windows Terminal and others)
+    if let Some(message) = app.desktop_notification.take() {
+        execute!(stdout(), Print(format!("\x1b]9;{}\x07", message)))?;
+    }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday};
+
+/// The title of the reminder.
+const REMINDER_TITLE: &str = "Typing practice (ttypr)";
+
+const ALL_DAYS: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+/// Parses the days to practice on - `daily`, `weekdays`, or the days separated
+/// with commas (`mon,wed,fri`). Returns `None` if a day isn't valid.
+pub fn parse_days(days: &str) -> Option<Vec<Weekday>> {
+    match days.to_lowercase().as_str() {
+        "daily" => return Some(ALL_DAYS.to_vec()),
+        "weekdays" => return Some(ALL_DAYS[..5].to_vec()),
+        _ => {}
+    }
+    let mut parsed: Vec<Weekday> = vec![];
+    for day in days.split(',') {
+        let day: Weekday = day.trim().parse().ok()?;
+        if !parsed.contains(&day) {
+            parsed.push(day);
+        }
+    }
+    parsed.sort_by_key(|day| day.num_days_from_monday());
+    Some(parsed)
+}
+
+/// Parses the time to practice at (`18:30`).
+pub fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+/// Renders an iCalendar file with a recurring event (with an alarm) on the given days at
+/// the given time, lasting `minutes`. It starts on the first of the days from `start` on.
+///
+/// The time is floating - at that time in whatever timezone the calendar is in.
+pub fn ical_reminder(time: NaiveTime, days: &[Weekday], minutes: usize, start: NaiveDate, created_at: DateTime<Utc>) -> String {
+    let first_day = start.iter_days().take(7).find(|day| days.contains(&day.weekday())).unwrap_or(start);
+    let by_day: Vec<String> = days.iter().map(|day| day.to_string()[..2].to_uppercase()).collect();
+
+    let lines = [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//ttypr//practice reminder//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        "UID:practice-reminder@ttypr".to_string(),
+        format!("DTSTAMP:{}", created_at.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}", first_day.and_time(time).format("%Y%m%dT%H%M%S")),
+        format!("DURATION:PT{}M", minutes),
+        format!("RRULE:FREQ=WEEKLY;BYDAY={}", by_day.join(",")),
+        format!("SUMMARY:{}", REMINDER_TITLE),
+        "DESCRIPTION:Time to practice typing - run ttypr".to_string(),
+        "BEGIN:VALARM".to_string(),
+        "ACTION:DISPLAY".to_string(),
+        format!("DESCRIPTION:{}", REMINDER_TITLE),
+        "TRIGGER:PT0M".to_string(),
+        "END:VALARM".to_string(),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ];
+    // iCalendar lines end with CRLF
+    lines.iter().map(|line| format!("{}\r\n", line)).collect()
+}
+
+/// Renders a crontab entry showing a desktop notification on the given days at the given time.
+pub fn cron_reminder(time: NaiveTime, days: &[Weekday]) -> String {
+    use chrono::Timelike;
+
+    let days = if ALL_DAYS.iter().all(|day| days.contains(day)) {
+        "*".to_string()
+    } else {
+        days.iter().map(|day| day.num_days_from_sunday().to_string()).collect::<Vec<_>>().join(",")
+    };
+    format!("{} {} * * {} notify-send \"{}\" \"Time to practice typing\"", time.minute(), time.hour(), days, REMINDER_TITLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reminders() {
+        assert_eq!(parse_days("fri, Mon,wed,mon"), Some(vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]));
+        assert_eq!(parse_days("weekdays").unwrap().len(), 5);
+        assert_eq!(parse_days("mon,someday"), None);
+        assert_eq!(parse_time("18:30"), NaiveTime::from_hms_opt(18, 30, 0));
+        assert_eq!(parse_time("6pm"), None);
+
+        let time = NaiveTime::from_hms_opt(18, 30, 0).unwrap();
+        let days = [Weekday::Mon, Weekday::Wed];
+        // 2024-01-11 is a Thursday - the first reminder is on the next Monday
+        let start = NaiveDate::from_ymd_opt(2024, 1, 11).unwrap();
+        let ical = ical_reminder(time, &days, 15, start, Utc.with_ymd_and_hms(2024, 1, 11, 9, 0, 0).unwrap());
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.contains("DTSTART:20240115T183000\r\n"));
+        assert!(ical.contains("RRULE:FREQ=WEEKLY;BYDAY=MO,WE\r\n"));
+        assert!(ical.contains("DURATION:PT15M\r\n"));
+        assert!(ical.contains("DTSTAMP:20240111T090000Z\r\n"));
+
+        assert_eq!(cron_reminder(time, &days), "30 18 * * 1,3 notify-send \"Typing practice (ttypr)\" \"Time to practice typing\"");
+        assert!(cron_reminder(time, &ALL_DAYS).starts_with("30 18 * * * "));
+    }
+}
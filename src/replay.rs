@@ -0,0 +1,164 @@
+use crate::engine::TypingEngine;
+use crate::stats::{IntegrityFlag, Session};
+use chrono::Local;
+use std::{io, time::{Duration, Instant}};
+
+/// The demo session bundled with ttypr, played back by `ttypr demo`.
+pub const DEMO_REPLAY: &str = include_str!("../assets/demo.replay");
+
+/// A key pressed in a replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayKey {
+    Char(char),
+    Backspace,
+}
+
+/// A recorded typing session that can be played back - the text that was typed,
+/// and the key presses with the time since the previous one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    pub text: String,
+    pub keys: Vec<(Duration, ReplayKey)>,
+}
+
+/// Parses a replay - a `text <the text>` line, then a line for every key press with the
+/// milliseconds since the previous one and the key (a character, `space` or `backspace`):
+///
+/// ```text
+/// text The quick brown fox
+/// 120 T
+/// 95 h
+/// ```
+///
+/// Empty lines and lines starting with `#` are skipped.
+pub fn parse_replay(content: &str) -> io::Result<Replay> {
+    let invalid = |line_number: usize, message: &str| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line_number + 1, message))
+    };
+
+    let mut text = None;
+    let mut keys = vec![];
+    for (line_number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(line_text) = line.strip_prefix("text ") {
+            text = Some(line_text.to_string());
+            continue;
+        }
+
+        let (millis, key) = line.split_once(' ').ok_or_else(|| invalid(line_number, "expected the milliseconds and the key"))?;
+        let millis: u64 = millis.parse().map_err(|_| invalid(line_number, "invalid milliseconds"))?;
+        let key = match key {
+            "space" => ReplayKey::Char(' '),
+            "backspace" => ReplayKey::Backspace,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => ReplayKey::Char(c),
+                    _ => return Err(invalid(line_number, "expected a single character, space or backspace")),
+                }
+            }
+        };
+        keys.push((Duration::from_millis(millis), key));
+    }
+
+    let text = text.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing the text line"))?;
+    Ok(Replay { text, keys })
+}
+
+/// Types the key presses of a replay with the typing engine, and scores them as a session
+/// (timed by the recorded delays, not by when it's scored). Key presses past the end of the
+/// text are ignored.
+pub fn score_replay(replay: &Replay) -> Session {
+    let mut engine = TypingEngine::new();
+    engine.push_line(&replay.text);
+
+    // When the first and the last typed characters were typed, since the start of the replay
+    let start = Instant::now();
+    let mut elapsed = Duration::ZERO;
+    let mut typed_between = None;
+    for (delay, key) in &replay.keys {
+        elapsed += *delay;
+        match key {
+            ReplayKey::Char(c) if engine.cursor() < engine.charset.len() => {
+                engine.type_char(*c, start + elapsed);
+                typed_between = Some((typed_between.map_or(elapsed, |(first, _)| first), elapsed));
+            }
+            ReplayKey::Char(_) => {}
+            ReplayKey::Backspace => {
+                engine.backspace();
+            }
+        }
+    }
+
+    let duration = typed_between.map_or(Duration::ZERO, |(first, last)| last - first);
+    let started_at = Local::now();
+    let tracker = std::mem::take(&mut engine.session);
+    Session {
+        id: started_at.timestamp_millis() as u64,
+        started_at: started_at.timestamp(),
+        duration_secs: duration.as_secs_f64(),
+        typing_option: "Text".to_string(),
+        keystrokes: tracker.keystrokes,
+        correct: tracker.correct,
+        errors: tracker.errors,
+        corrected: tracker.corrected,
+        slips: tracker.slips,
+        uncorrected: Some(tracker.uncorrected),
+        mistyped_chars: tracker.mistyped_chars,
+        class_counts: tracker.class_counts,
+        words: tracker.words,
+        note: None,
+        keyboard: None,
+        repeat_seed: None,
+        // (The tracker flags the key presses as too fast - they're played back at once)
+        flags: vec![IntegrityFlag::Replay],
+        practice_block: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replay() {
+        let replay = parse_replay("# A replay\ntext ab c\n120 a\n80 x\n300 backspace\n90 b\n\n100 space\n").unwrap();
+        assert_eq!(replay.text, "ab c");
+        assert_eq!(replay.keys.len(), 5);
+        assert_eq!(replay.keys[0], (Duration::from_millis(120), ReplayKey::Char('a')));
+        assert_eq!(replay.keys[2].1, ReplayKey::Backspace);
+        assert_eq!(replay.keys[4].1, ReplayKey::Char(' '));
+
+        assert!(parse_replay("120 a\n").is_err()); // No text
+        assert!(parse_replay("text ab\nfast a\n").unwrap_err().to_string().contains("line 2"));
+        assert!(parse_replay("text ab\n120 ab\n").is_err());
+
+        // The bundled demo types its text (the typos are erased)
+        let demo = parse_replay(DEMO_REPLAY).unwrap();
+        let mut typed = String::new();
+        for (_, key) in &demo.keys {
+            match key {
+                ReplayKey::Char(c) => typed.push(*c),
+                ReplayKey::Backspace => {
+                    typed.pop();
+                }
+            }
+        }
+        assert_eq!(typed, format!("{} ", demo.text));
+    }
+
+    #[test]
+    fn test_score_replay() {
+        // The last key presses are past the end of the text
+        let replay = parse_replay("text abc de\n200 a\n500 x\n500 backspace\n500 b\n500 c\n500 space\n500 d\n500 e\n500 f\n1000 g\n").unwrap();
+        let session = score_replay(&replay);
+        assert_eq!(session.keystrokes, 7); // Backspace and the ignored key presses aren't counted
+        assert_eq!((session.correct, session.errors, session.corrected), (6, 1, 1));
+        assert_eq!(session.mistyped_chars.get("b"), Some(&1));
+        assert_eq!(session.duration_secs, 3.5);
+        assert_eq!(session.wpm().round(), 24.0);
+        assert_eq!(session.flags, vec![IntegrityFlag::Replay]);
+    }
+}
@@ -0,0 +1,1323 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+/// Sessions with fewer key presses than this are not recorded.
+const MIN_SESSION_KEYSTROKES: usize = 10;
+
+/// Key presses closer together than this are faster than anyone types.
+const MIN_KEY_INTERVAL: Duration = Duration::from_millis(15);
+
+/// How many key presses in a row have to be faster than `MIN_KEY_INTERVAL` for the session to be
+/// flagged (a few can arrive at once, e.g. over a lagging SSH connection).
+const FAST_KEYS_RUN: usize = 20;
+
+/// XP every recorded session earns, besides the XP for the typed characters.
+const SESSION_XP: u64 = 10;
+
+/// A finished typing session, as stored in the stats file.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Session {
+    pub id: u64, // Start time in milliseconds since the Unix epoch
+    pub started_at: i64, // Start time in seconds since the Unix epoch
+    pub duration_secs: f64, // Time from the first to the last key press
+    pub typing_option: String, // "Ascii", "Words" or "Text"
+    pub keystrokes: usize,
+    pub correct: usize,
+    pub errors: usize,
+    #[serde(default)]
+    pub corrected: usize, // Errors fixed with Backspace and typed correctly
+    #[serde(default)]
+    pub slips: usize, // Mistakes right away followed by a correct key (motor slips), not counted in `errors`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uncorrected: Option<usize>, // The characters left mistyped at the end (`None` - recorded before they were counted)
+    pub mistyped_chars: HashMap<String, usize>,
+    #[serde(default)]
+    pub class_counts: HashMap<String, ClassCount>, // By the name of the character class ("digits")
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub words: HashMap<String, WordCount>, // (Word stats) - By the word
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>, // Attached by the user afterwards ("new keyboard")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyboard: Option<String>, // The keyboard the session was typed on ("ergo split")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat_seed: Option<u64>, // (Repeat mode) - The same for the attempts at the same material
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<IntegrityFlag>, // Why the result might not be from typing it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub practice_block: Option<PracticeBlock>, // (Practice schedule) - The block the session was typed in
+}
+
+/// (Practice schedule) - A practice block of a schedule of practice blocks and rests.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PracticeBlock {
+    pub schedule: i64, // When the first block of the schedule started (seconds since the Unix epoch)
+    pub block: u32, // The number of the block in the schedule (from 1)
+}
+
+/// Why the result of a session might not be from typing it - the session is flagged
+/// on the results page and in the exports, and its WPM isn't counted as the best one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum IntegrityFlag {
+    Paste, // Text was pasted while typing
+    FastKeys, // Sustained key presses faster than anyone types (injected by a tool)
+    Replay, // Scored from recorded key presses (`ttypr run --headless`)
+}
+
+impl IntegrityFlag {
+    /// What the flag means, as displayed.
+    pub fn description(self) -> &'static str {
+        match self {
+            IntegrityFlag::Paste => "pasted text",
+            IntegrityFlag::FastKeys => "inhumanly fast keys",
+            IntegrityFlag::Replay => "replayed keys",
+        }
+    }
+}
+
+/// The classes of characters the accuracy is broken down by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Letters, // Lowercase (and caseless) letters
+    Capitals,
+    Digits,
+    Symbols,
+    Spaces,
+}
+
+impl CharClass {
+    /// All of the classes, in the order they're displayed in.
+    pub const ALL: [CharClass; 5] = [CharClass::Letters, CharClass::Capitals, CharClass::Digits, CharClass::Symbols, CharClass::Spaces];
+
+    /// The class of a character.
+    pub fn of(c: char) -> CharClass {
+        if c.is_whitespace() {
+            CharClass::Spaces
+        } else if c.is_uppercase() {
+            CharClass::Capitals
+        } else if c.is_alphabetic() {
+            CharClass::Letters
+        } else if c.is_numeric() {
+            CharClass::Digits
+        } else {
+            CharClass::Symbols
+        }
+    }
+
+    /// The name of the class, as displayed and stored in the stats.
+    pub fn name(self) -> &'static str {
+        match self {
+            CharClass::Letters => "letters",
+            CharClass::Capitals => "capitals",
+            CharClass::Digits => "digits",
+            CharClass::Symbols => "symbols",
+            CharClass::Spaces => "spaces",
+        }
+    }
+}
+
+/// The correctly typed and the mistyped characters of a character class.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct ClassCount {
+    pub correct: usize,
+    pub errors: usize,
+}
+
+impl ClassCount {
+    /// Percentage of correctly typed characters of the class.
+    pub fn accuracy(&self) -> f64 {
+        let typed = self.correct + self.errors;
+        if typed == 0 {
+            return 100.0;
+        }
+        self.correct as f64 * 100.0 / typed as f64
+    }
+}
+
+/// (Word stats) - How often a word was typed in a session, how often it was mistyped, and the time it took.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct WordCount {
+    pub typed: usize,
+    pub mistyped: usize, // Typed with an error (corrected or not)
+    pub millis: u64, // The time from its first character to its last, summed up
+}
+
+/// (Word stats) - A word as typed across sessions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordStat {
+    pub word: String,
+    pub typed: usize,
+    pub mistyped: usize,
+    pub millis: u64,
+}
+
+impl WordStat {
+    /// Percentage of the times the word was typed with an error.
+    pub fn error_rate(&self) -> f64 {
+        if self.typed == 0 {
+            return 0.0;
+        }
+        self.mistyped as f64 * 100.0 / self.typed as f64
+    }
+
+    /// The average time from one character of the word to the next, in milliseconds
+    /// (`None` for a single character).
+    pub fn ms_per_char(&self) -> Option<f64> {
+        let intervals = self.typed * self.word.chars().count().saturating_sub(1);
+        (intervals > 0).then(|| self.millis as f64 / intervals as f64)
+    }
+}
+
+/// (Word stats) - Which of the words are the problem words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordOrder {
+    Mistakes, // The most often mistyped (by the error rate)
+    Slowest, // The slowest to type (by the time per character)
+}
+
+impl Session {
+    /// Words per minute, a "word" being 5 key presses.
+    pub fn wpm(&self) -> f64 {
+        if self.duration_secs <= 0.0 {
+            return 0.0;
+        }
+        (self.keystrokes as f64 / 5.0) / (self.duration_secs / 60.0)
+    }
+
+    /// Percentage of correctly typed characters.
+    pub fn accuracy(&self) -> f64 {
+        let typed = self.correct + self.errors;
+        if typed == 0 {
+            return 100.0;
+        }
+        self.correct as f64 * 100.0 / typed as f64
+    }
+
+    /// Whether the result might not be from typing it (see `IntegrityFlag`).
+    pub fn is_flagged(&self) -> bool {
+        !self.flags.is_empty()
+    }
+
+    /// The descriptions of the flags of the session ("pasted text, replayed keys").
+    pub fn flags_description(&self) -> String {
+        self.flags.iter().map(|flag| flag.description()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Gross WPM, by the typing test standard - all of the typed characters (right or wrong)
+    /// over 5, per minute. Unlike `wpm`, the key presses that don't type a character
+    /// (Backspace) aren't counted.
+    pub fn gross_wpm(&self) -> f64 {
+        if self.duration_secs <= 0.0 {
+            return 0.0;
+        }
+        ((self.correct + self.errors) as f64 / 5.0) / (self.duration_secs / 60.0)
+    }
+
+    /// Net WPM, by the typing test standard - the gross WPM less the uncorrected errors per minute
+    /// (never below 0). The sessions recorded before the characters left mistyped were counted
+    /// estimate them as the errors less the corrected ones.
+    pub fn net_wpm(&self) -> f64 {
+        if self.duration_secs <= 0.0 {
+            return 0.0;
+        }
+        let uncorrected = self.uncorrected.unwrap_or(self.errors.saturating_sub(self.corrected)) as f64;
+        (self.gross_wpm() - uncorrected / (self.duration_secs / 60.0)).max(0.0)
+    }
+
+    /// Keystrokes per hour (KPH), as asked for by clerical and data entry jobs - all of the key presses.
+    pub fn kph(&self) -> f64 {
+        if self.duration_secs <= 0.0 {
+            return 0.0;
+        }
+        self.keystrokes as f64 / (self.duration_secs / 3600.0)
+    }
+
+    /// XP earned by the session - the correctly typed characters weighted
+    /// by the accuracy (squared), plus a bonus for the session itself.
+    pub fn xp(&self) -> u64 {
+        (self.correct as f64 * (self.accuracy() / 100.0).powi(2)) as u64 + SESSION_XP
+    }
+
+    /// The start time of the session in the local timezone.
+    pub fn started_at_local(&self) -> DateTime<Local> {
+        Local
+            .timestamp_opt(self.started_at, 0)
+            .single()
+            .unwrap_or_else(Local::now)
+    }
+}
+
+/// Keeps track of the session currently being typed.
+///
+/// A session starts with the first key press in Typing mode and
+/// is finished when leaving Typing mode.
+pub struct SessionTracker {
+    pub started_at: Option<DateTime<Local>>,
+    pub timer: Option<Instant>,
+    pub last_key_press: Option<Instant>,
+    pub keystrokes: usize,
+    pub correct: usize,
+    pub errors: usize,
+    pub corrected: usize,
+    pub slips: usize,
+    pub uncorrected: usize, // The typed characters that are mistyped (not erased, or fixed)
+    pub mistyped_chars: HashMap<String, usize>,
+    pub class_counts: HashMap<String, ClassCount>,
+    pub words: HashMap<String, WordCount>,
+    pub word_started_at: Option<Instant>, // (Word stats) - When the first character of the word being typed was
+    pub fast_keys: usize, // The key presses in a row faster than `MIN_KEY_INTERVAL`
+    pub flags: Vec<IntegrityFlag>,
+}
+
+impl Default for SessionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionTracker {
+    /// Creates a new `SessionTracker` with no session in progress.
+    pub fn new() -> SessionTracker {
+        SessionTracker {
+            started_at: None,
+            timer: None,
+            last_key_press: None,
+            keystrokes: 0,
+            correct: 0,
+            errors: 0,
+            corrected: 0,
+            slips: 0,
+            uncorrected: 0,
+            mistyped_chars: HashMap::new(),
+            class_counts: HashMap::new(),
+            words: HashMap::new(),
+            word_started_at: None,
+            fast_keys: 0,
+            flags: vec![],
+        }
+    }
+
+    /// Starts the session on the first key press and counts the key presses (pressed at `now`,
+    /// by the clock of the frontend).
+    ///
+    /// Flags the session once `FAST_KEYS_RUN` key presses in a row are faster than anyone types.
+    pub fn on_key_press(&mut self, now: Instant) {
+        if self.timer.is_none() {
+            self.started_at = Some(Local::now());
+            self.timer = Some(now);
+        }
+        match self.last_key_press {
+            Some(last_key_press) if now.duration_since(last_key_press) < MIN_KEY_INTERVAL => self.fast_keys += 1,
+            _ => self.fast_keys = 0,
+        }
+        if self.fast_keys >= FAST_KEYS_RUN {
+            self.flag(IntegrityFlag::FastKeys);
+        }
+        self.last_key_press = Some(now);
+        self.keystrokes += 1;
+    }
+
+    /// Flags the session (once for every flag).
+    pub fn flag(&mut self, flag: IntegrityFlag) {
+        if !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        }
+    }
+
+    /// How long before `now` the last key of the session was pressed (`None` if no session is in progress).
+    pub fn idle_time(&self, now: Instant) -> Option<Duration> {
+        self.last_key_press.map(|last_key_press| now.saturating_duration_since(last_key_press))
+    }
+
+    /// Records a correctly typed character (the one that was expected).
+    pub fn on_correct(&mut self, expected: &str) {
+        self.correct += 1;
+        self.class_count(expected).correct += 1;
+    }
+
+    /// Records a mistyped character that was erased and then typed correctly.
+    pub fn on_corrected(&mut self) {
+        self.corrected += 1;
+    }
+
+    /// Records a mistyped character (the one that was expected).
+    pub fn on_error(&mut self, expected: &str) {
+        self.errors += 1;
+        self.uncorrected += 1;
+        *self.mistyped_chars.entry(expected.to_string()).or_insert(0) += 1;
+        self.class_count(expected).errors += 1;
+    }
+
+    /// Records a mistyped character erased with Backspace - it's no longer left mistyped.
+    pub fn on_error_erased(&mut self) {
+        self.uncorrected = self.uncorrected.saturating_sub(1);
+    }
+
+    /// Recounts the last mistyped character (the one that was expected) as a slip - the
+    /// finger hit the wrong key, but the next one was right - instead of an error.
+    pub fn on_slip(&mut self, expected: &str) {
+        self.errors = self.errors.saturating_sub(1);
+        self.slips += 1;
+        crate::utils::remove_mistake(&mut self.mistyped_chars, expected);
+        let count = self.class_count(expected);
+        count.errors = count.errors.saturating_sub(1);
+    }
+
+    /// (Word stats) - Starts timing a word, with its first character typed (at the last key press).
+    pub fn on_word_start(&mut self) {
+        self.word_started_at = self.last_key_press;
+    }
+
+    /// (Word stats) - Records a word, with its last character typed at the last key press
+    /// (`mistyped` - with an error on the way). A word finished again after Backspace, without
+    /// retyping its start, isn't counted twice.
+    pub fn on_word(&mut self, word: &str, mistyped: bool) {
+        let Some(started_at) = self.word_started_at.take() else {
+            return;
+        };
+        let finished_at = self.last_key_press.unwrap_or(started_at);
+        let count = self.words.entry(word.to_string()).or_default();
+        count.typed += 1;
+        count.mistyped += mistyped as usize;
+        count.millis += finished_at.saturating_duration_since(started_at).as_millis() as u64;
+    }
+
+    /// The counts of the class of the expected character.
+    fn class_count(&mut self, expected: &str) -> &mut ClassCount {
+        let class = CharClass::of(expected.chars().next().unwrap_or(' '));
+        self.class_counts.entry(class.name().to_string()).or_default()
+    }
+
+    /// Ends the current session and resets the tracker.
+    ///
+    /// Returns the finished session, or `None` if too little was typed to be worth recording.
+    pub fn finish(&mut self, typing_option: &str) -> Option<Session> {
+        let tracker = std::mem::take(self);
+
+        let (started_at, timer, last_key_press) =
+            (tracker.started_at?, tracker.timer?, tracker.last_key_press?);
+        if tracker.keystrokes < MIN_SESSION_KEYSTROKES {
+            return None;
+        }
+
+        Some(Session {
+            id: started_at.timestamp_millis() as u64,
+            started_at: started_at.timestamp(),
+            duration_secs: last_key_press.duration_since(timer).as_secs_f64(),
+            typing_option: typing_option.to_string(),
+            keystrokes: tracker.keystrokes,
+            correct: tracker.correct,
+            errors: tracker.errors,
+            corrected: tracker.corrected,
+            slips: tracker.slips,
+            uncorrected: Some(tracker.uncorrected),
+            mistyped_chars: tracker.mistyped_chars,
+            class_counts: tracker.class_counts,
+            words: tracker.words,
+            note: None,
+            keyboard: None,
+            repeat_seed: None,
+            flags: tracker.flags,
+            practice_block: None,
+        })
+    }
+}
+
+/// Sums up the mistyped characters of the given sessions and returns the most frequent ones.
+pub fn top_mistakes(sessions: &[Session], limit: usize) -> Vec<(String, usize)> {
+    let mut mistakes: HashMap<String, usize> = HashMap::new();
+    for session in sessions {
+        for (character, count) in &session.mistyped_chars {
+            *mistakes.entry(character.clone()).or_insert(0) += count;
+        }
+    }
+    crate::utils::get_sorted_mistakes(&mistakes)
+        .into_iter()
+        .take(limit)
+        .map(|(character, count)| (character.clone(), *count))
+        .collect()
+}
+
+/// (Word stats) - Sums up the words of the given sessions typed at least `min_typed` times,
+/// and returns them the problem words first - the most often mistyped ones (the never mistyped
+/// left out) or the slowest ones.
+pub fn problem_words(sessions: &[Session], min_typed: usize, order: WordOrder) -> Vec<WordStat> {
+    let mut words: HashMap<&str, WordCount> = HashMap::new();
+    for session in sessions {
+        for (word, count) in &session.words {
+            let total = words.entry(word).or_default();
+            total.typed += count.typed;
+            total.mistyped += count.mistyped;
+            total.millis += count.millis;
+        }
+    }
+
+    let mut words: Vec<WordStat> = words
+        .into_iter()
+        .filter(|(_, count)| count.typed >= min_typed.max(1))
+        .map(|(word, count)| WordStat { word: word.to_string(), typed: count.typed, mistyped: count.mistyped, millis: count.millis })
+        .collect();
+    match order {
+        WordOrder::Mistakes => {
+            words.retain(|word| word.mistyped > 0);
+            words.sort_by(|a, b| b.error_rate().total_cmp(&a.error_rate()).then(b.mistyped.cmp(&a.mistyped)).then(a.word.cmp(&b.word)));
+        }
+        WordOrder::Slowest => {
+            words.retain(|word| word.ms_per_char().is_some());
+            words.sort_by(|a, b| b.ms_per_char().unwrap_or(0.0).total_cmp(&a.ms_per_char().unwrap_or(0.0)).then(a.word.cmp(&b.word)));
+        }
+    }
+    words
+}
+
+/// Average WPM of the last (up to 10) sessions and its change compared to the same number
+/// of sessions before them, as `(number of sessions, average WPM, change)`.
+///
+/// Returns `None` if there are less than two sessions to compare.
+pub fn recent_wpm_trend(sessions: &[Session]) -> Option<(usize, f64, f64)> {
+    if sessions.len() < 2 {
+        return None;
+    }
+    let recent_len = (sessions.len() / 2).min(10);
+    let average = |sessions: &[Session]| sessions.iter().map(Session::wpm).sum::<f64>() / sessions.len() as f64;
+
+    let recent = average(&sessions[sessions.len() - recent_len..]);
+    let previous = average(&sessions[sessions.len() - 2 * recent_len..sessions.len() - recent_len]);
+    Some((recent_len, recent, recent - previous))
+}
+
+/// The accuracy of the last `count` sessions taken together, or `None` if there are fewer sessions.
+pub fn recent_accuracy(sessions: &[Session], count: usize) -> Option<f64> {
+    if count == 0 || sessions.len() < count {
+        return None;
+    }
+    let recent = &sessions[sessions.len() - count..];
+    let correct: usize = recent.iter().map(|session| session.correct).sum();
+    let typed: usize = recent.iter().map(|session| session.correct + session.errors).sum();
+    Some(if typed == 0 { 100.0 } else { correct as f64 * 100.0 / typed as f64 })
+}
+
+/// The distinct keyboards the sessions were typed on, sorted.
+pub fn session_keyboards(sessions: &[Session]) -> Vec<String> {
+    let mut keyboards: Vec<String> = sessions.iter().filter_map(|s| s.keyboard.clone()).collect();
+    keyboards.sort();
+    keyboards.dedup();
+    keyboards
+}
+
+/// The performance on every keyboard the sessions were typed on, as
+/// `(keyboard, number of sessions, average WPM, average accuracy)`, sorted by the keyboard.
+pub fn keyboard_metrics(sessions: &[Session]) -> Vec<(String, usize, f64, f64)> {
+    session_keyboards(sessions)
+        .into_iter()
+        .map(|keyboard| {
+            let typed_on: Vec<&Session> = sessions.iter().filter(|s| s.keyboard.as_ref() == Some(&keyboard)).collect();
+            let count = typed_on.len() as f64;
+            let average_wpm = typed_on.iter().map(|s| s.wpm()).sum::<f64>() / count;
+            let average_accuracy = typed_on.iter().map(|s| s.accuracy()).sum::<f64>() / count;
+            (keyboard, typed_on.len(), average_wpm, average_accuracy)
+        })
+        .collect()
+}
+
+/// The attempts at the material of repeat mode - the sessions typed with the given
+/// seed in the given typing option, oldest first.
+pub fn repeat_attempts<'a>(sessions: &'a [Session], seed: u64, typing_option: &str) -> Vec<&'a Session> {
+    sessions
+        .iter()
+        .filter(|s| s.repeat_seed == Some(seed) && s.typing_option == typing_option)
+        .collect()
+}
+
+/// A summary of the sessions typed during one week or month.
+pub struct PeriodSummary {
+    pub label: String, // "Week 7, 2025" or "February 2025"
+    pub sessions: usize,
+    pub total_secs: f64,
+    pub average_wpm: f64,
+    pub best_wpm: f64,
+    pub average_accuracy: f64,
+    pub accuracy_change: Option<f64>, // Compared to the previous period with sessions
+    pub most_improved: Vec<(String, f64)>, // Characters with the biggest drop in mistakes per 100 key presses
+    pub notes: Vec<String>, // The notes of the sessions, with their dates and WPM ("Jan 11 (72 WPM): tired")
+}
+
+/// Groups the sessions into weeks (or months, if `monthly`) and summarizes every period.
+///
+/// The periods are returned in chronological order, only the ones with sessions are included.
+pub fn summarize_periods(sessions: &[Session], monthly: bool) -> Vec<PeriodSummary> {
+    // Group the sessions by period, keeping the order they were recorded in
+    let mut periods: Vec<((i32, u32), Vec<&Session>)> = vec![];
+    for session in sessions {
+        let date = session.started_at_local();
+        let key = if monthly {
+            (date.year(), date.month())
+        } else {
+            (date.iso_week().year(), date.iso_week().week())
+        };
+        match periods.iter_mut().find(|(period, _)| *period == key) {
+            Some((_, period_sessions)) => period_sessions.push(session),
+            None => periods.push((key, vec![session])),
+        }
+    }
+    periods.sort_by_key(|(key, _)| *key);
+
+    let mut summaries: Vec<PeriodSummary> = vec![];
+    let mut previous_error_rates: Option<HashMap<String, f64>> = None;
+    for ((year, number), period_sessions) in periods {
+        let label = if monthly {
+            let month = chrono::NaiveDate::from_ymd_opt(year, number, 1)
+                .map(|date| date.format("%B").to_string())
+                .unwrap_or_default();
+            format!("{} {}", month, year)
+        } else {
+            format!("Week {}, {}", number, year)
+        };
+
+        let count = period_sessions.len() as f64;
+        let average_accuracy = period_sessions.iter().map(|s| s.accuracy()).sum::<f64>() / count;
+        let error_rates = error_rates(&period_sessions);
+
+        // Characters that got mistyped less often than in the previous period
+        let mut most_improved: Vec<(String, f64)> = vec![];
+        if let Some(previous) = &previous_error_rates {
+            for (character, previous_rate) in previous {
+                let rate = error_rates.get(character).copied().unwrap_or(0.0);
+                if rate < *previous_rate {
+                    most_improved.push((character.clone(), previous_rate - rate));
+                }
+            }
+            most_improved.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            most_improved.truncate(3);
+        }
+
+        summaries.push(PeriodSummary {
+            label,
+            sessions: period_sessions.len(),
+            total_secs: period_sessions.iter().map(|s| s.duration_secs).sum(),
+            average_wpm: period_sessions.iter().map(|s| s.wpm()).sum::<f64>() / count,
+            best_wpm: period_sessions.iter().filter(|s| !s.is_flagged()).map(|s| s.wpm()).fold(0.0, f64::max),
+            average_accuracy,
+            accuracy_change: summaries.last().map(|previous| average_accuracy - previous.average_accuracy),
+            most_improved,
+            notes: period_sessions
+                .iter()
+                .filter_map(|s| {
+                    let note = s.note.as_ref()?;
+                    Some(format!("{} ({:.0} WPM): {}", s.started_at_local().format("%b %-d"), s.wpm(), note))
+                })
+                .collect(),
+        });
+        previous_error_rates = Some(error_rates);
+    }
+
+    summaries
+}
+
+/// Mistakes per 100 key presses for every mistyped character of the given sessions.
+fn error_rates(sessions: &[&Session]) -> HashMap<String, f64> {
+    let keystrokes: usize = sessions.iter().map(|s| s.keystrokes).sum();
+    let mut rates: HashMap<String, f64> = HashMap::new();
+    if keystrokes == 0 {
+        return rates;
+    }
+    for session in sessions {
+        for (character, count) in &session.mistyped_chars {
+            *rates.entry(character.clone()).or_insert(0.0) += *count as f64 * 100.0 / keystrokes as f64;
+        }
+    }
+    rates
+}
+
+/// All of the recorded typing history, stored in the stats file.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Stats {
+    pub sessions: Vec<Session>,
+    pub goal_completed_days: Vec<String>, // Days ("YYYY-MM-DD") the daily goal was reached on
+    pub level_milestones: Vec<LevelMilestone>,
+}
+
+/// A level reached, as stored in the stats file.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LevelMilestone {
+    pub level: u32,
+    pub reached_at: i64, // Seconds since the Unix epoch
+}
+
+/// Totals over the whole recorded typing history.
+pub struct LifetimeTotals {
+    pub sessions: usize,
+    pub keystrokes: usize,
+    pub errors: usize,
+    pub corrected: usize,
+    pub secs: f64,
+    pub best_wpm: f64,
+    pub first_session: Option<DateTime<Local>>,
+}
+
+impl LifetimeTotals {
+    /// Words per minute over all the summed up sessions.
+    pub fn wpm(&self) -> f64 {
+        if self.secs <= 0.0 {
+            return 0.0;
+        }
+        (self.keystrokes as f64 / 5.0) / (self.secs / 60.0)
+    }
+
+    /// Percentage of the key presses that weren't errors.
+    pub fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+        100.0 - self.errors as f64 * 100.0 / self.keystrokes as f64
+    }
+}
+
+impl Stats {
+    /// Merges another stats (e.g. from another machine) into these ones.
+    ///
+    /// Sessions are de-duplicated by their id and kept in chronological order.
+    /// Returns the number of sessions that were added.
+    pub fn merge(&mut self, other: Stats) -> usize {
+        let before = self.sessions.len();
+        for session in other.sessions {
+            if !self.sessions.iter().any(|existing| existing.id == session.id) {
+                self.sessions.push(session);
+            }
+        }
+        self.sessions.sort_by_key(|session| session.id);
+
+        for day in other.goal_completed_days {
+            if !self.goal_completed_days.contains(&day) {
+                self.goal_completed_days.push(day);
+            }
+        }
+        self.goal_completed_days.sort();
+
+        for milestone in other.level_milestones {
+            match self.level_milestones.iter_mut().find(|existing| existing.level == milestone.level) {
+                Some(existing) => existing.reached_at = existing.reached_at.min(milestone.reached_at),
+                None => self.level_milestones.push(milestone),
+            }
+        }
+        self.level_milestones.sort_by_key(|milestone| milestone.level);
+
+        self.sessions.len() - before
+    }
+
+    /// Sums up the key presses, errors and time typed over all the sessions.
+    pub fn lifetime_totals(&self) -> LifetimeTotals {
+        totals(&self.sessions.iter().collect::<Vec<_>>())
+    }
+
+    /// Sums up the Text option sessions started at or after the given time
+    /// (in seconds since the Unix epoch) - the ones spent typing the current text.
+    pub fn text_totals_since(&self, since: i64) -> LifetimeTotals {
+        let sessions: Vec<&Session> = self
+            .sessions
+            .iter()
+            .filter(|s| s.typing_option == "Text" && s.started_at >= since)
+            .collect();
+        totals(&sessions)
+    }
+
+    /// Time typed (in seconds) and the number of sessions on the given day.
+    pub fn practiced_on(&self, day: NaiveDate) -> (f64, usize) {
+        let sessions: Vec<&Session> = self
+            .sessions
+            .iter()
+            .filter(|session| session.started_at_local().date_naive() == day)
+            .collect();
+        (sessions.iter().map(|s| s.duration_secs).sum(), sessions.len())
+    }
+
+    /// Number of days since the last session (0 - practiced today), `None` if nothing was recorded.
+    pub fn days_since_practice(&self, today: NaiveDate) -> Option<i64> {
+        let last = self.sessions.iter().map(|session| session.started_at).max()?;
+        let last_day = Local.timestamp_opt(last, 0).single()?.date_naive();
+        Some((today - last_day).num_days())
+    }
+
+    /// Records that the daily goal was reached on the given day.
+    /// Returns `false` if it was already recorded.
+    pub fn record_goal_completion(&mut self, day: NaiveDate) -> bool {
+        let day = day.format("%Y-%m-%d").to_string();
+        if self.goal_completed_days.contains(&day) {
+            return false;
+        }
+        self.goal_completed_days.push(day);
+        true
+    }
+
+    /// Whether the last session is a personal best - faster than every earlier (unflagged)
+    /// session of its typing option. The first session of an option isn't one.
+    pub fn is_personal_best(&self) -> bool {
+        let Some((last, earlier)) = self.sessions.split_last() else {
+            return false;
+        };
+        let mut earlier = earlier
+            .iter()
+            .filter(|session| session.typing_option == last.typing_option && !session.is_flagged())
+            .peekable();
+        !last.is_flagged() && earlier.peek().is_some() && earlier.all(|session| last.wpm() > session.wpm())
+    }
+
+    /// All of the XP earned, over all the sessions (of every typing option).
+    pub fn total_xp(&self) -> u64 {
+        self.sessions.iter().map(Session::xp).sum()
+    }
+
+    /// Records reaching a new level, at the given time (in seconds since the Unix epoch).
+    /// Returns the new level, or `None` if no new level was reached.
+    pub fn record_level(&mut self, now: i64) -> Option<u32> {
+        let (level, _, _) = level_progress(self.total_xp());
+        let highest = self.level_milestones.iter().map(|milestone| milestone.level).max().unwrap_or(1);
+        if level <= highest {
+            return None;
+        }
+        self.level_milestones.push(LevelMilestone { level, reached_at: now });
+        Some(level)
+    }
+
+    /// Number of consecutive days the daily goal was reached on, up to the given day.
+    ///
+    /// The given day not being done yet doesn't break the streak.
+    pub fn goal_streak(&self, today: NaiveDate) -> usize {
+        let completed = |day: NaiveDate| self.goal_completed_days.contains(&day.format("%Y-%m-%d").to_string());
+
+        let mut day = if completed(today) { today } else { today.pred_opt().unwrap_or(today) };
+        let mut streak = 0;
+        while completed(day) {
+            streak += 1;
+            match day.pred_opt() {
+                Some(previous) => day = previous,
+                None => break,
+            }
+        }
+        streak
+    }
+}
+
+/// The level reached with the given XP, the XP earned into that level
+/// and the XP needed to reach the next one.
+///
+/// Every level needs 100 XP more than the previous one (100 for level 2, 200 more for level 3...).
+pub fn level_progress(xp: u64) -> (u32, u64, u64) {
+    let xp_to_reach = |level: u64| 50 * level * (level - 1);
+    let mut level = 1;
+    while xp_to_reach(level + 1) <= xp {
+        level += 1;
+    }
+    (level as u32, xp - xp_to_reach(level), xp_to_reach(level + 1) - xp_to_reach(level))
+}
+
+/// Sums up the key presses, errors and time typed over the given sessions.
+pub fn totals(sessions: &[&Session]) -> LifetimeTotals {
+    LifetimeTotals {
+        sessions: sessions.len(),
+        keystrokes: sessions.iter().map(|s| s.keystrokes).sum(),
+        errors: sessions.iter().map(|s| s.errors).sum(),
+        corrected: sessions.iter().map(|s| s.corrected).sum(),
+        secs: sessions.iter().map(|s| s.duration_secs).sum(),
+        best_wpm: sessions.iter().filter(|s| !s.is_flagged()).map(|s| s.wpm()).fold(0.0, f64::max),
+        first_session: sessions.iter().map(|s| s.started_at).min().map(|started_at| {
+            Local.timestamp_opt(started_at, 0).single().unwrap_or_else(Local::now)
+        }),
+    }
+}
+
+/// Whether the daily goal is reached, with `goal_minutes`/`goal_sessions` of 0 meaning no goal.
+pub fn goal_reached(goal_minutes: usize, goal_sessions: usize, secs: f64, sessions: usize) -> bool {
+    if goal_minutes == 0 && goal_sessions == 0 {
+        return false;
+    }
+    secs >= goal_minutes as f64 * 60.0 && sessions >= goal_sessions
+}
+
+/// Key metrics of a profile, displayed on the profile comparison page.
+pub struct ProfileMetrics {
+    pub sessions: usize,
+    pub recent_wpm: f64, // Average of the last 10 sessions
+    pub wpm_change: Option<f64>, // Compared to the 10 sessions before them
+    pub accuracy: f64,
+    pub top_mistakes: Vec<(String, usize)>,
+}
+
+/// Computes the key metrics of a profile from its stats.
+pub fn profile_metrics(stats: &Stats) -> ProfileMetrics {
+    let sessions = &stats.sessions;
+    let count = sessions.len().max(1) as f64;
+
+    let (recent_wpm, wpm_change) = match recent_wpm_trend(sessions) {
+        Some((_, recent_wpm, change)) => (recent_wpm, Some(change)),
+        None => (sessions.last().map(Session::wpm).unwrap_or(0.0), None),
+    };
+
+    ProfileMetrics {
+        sessions: sessions.len(),
+        recent_wpm,
+        wpm_change,
+        accuracy: if sessions.is_empty() { 0.0 } else { sessions.iter().map(Session::accuracy).sum::<f64>() / count },
+        top_mistakes: top_mistakes(sessions, 3),
+    }
+}
+
+/// Gets the directory the stats of a profile are stored in.
+///
+/// The "default" profile uses the config directory itself, the other
+/// profiles use `profiles/<name>` inside of it.
+pub fn profile_dir(config_dir: &Path, profile: &str) -> PathBuf {
+    if profile.is_empty() || profile == "default" {
+        config_dir.to_path_buf()
+    } else {
+        config_dir.join("profiles").join(profile)
+    }
+}
+
+/// Loads the stats from a specified directory.
+/// If the stats file doesn't exist yet, returns empty stats.
+pub fn load_stats(dir: &Path) -> Result<Stats, Box<dyn std::error::Error>> {
+    let stats_path = dir.join("stats");
+    if !stats_path.exists() {
+        return Ok(Stats::default());
+    }
+
+    load_stats_file(&stats_path)
+}
+
+/// Loads the stats from a specified stats file.
+pub fn load_stats_file(path: &Path) -> Result<Stats, Box<dyn std::error::Error>> {
+    let stats_string = fs::read_to_string(path)?;
+    let stats: Stats = toml::from_str(&stats_string)?;
+    Ok(stats)
+}
+
+/// Saves the stats to a specified directory.
+pub fn save_stats(stats: &Stats, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let toml_string = toml::to_string_pretty(stats)?;
+    fs::write(dir.join("stats"), toml_string)?;
+    Ok(())
+}
+
+#[cfg(test)]
+/// Creates a session with the given numbers, for tests.
+pub(crate) fn sample_session(id: u64, keystrokes: usize, errors: usize, duration_secs: f64) -> Session {
+    Session {
+        id,
+        started_at: (id / 1000) as i64,
+        duration_secs,
+        typing_option: "Words".to_string(),
+        keystrokes,
+        correct: keystrokes - errors,
+        errors,
+        ..Session::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_session_wpm_and_accuracy() {
+        // 300 key presses in a minute - 60 words
+        let session = sample_session(0, 300, 30, 60.0);
+        assert_eq!(session.wpm(), 60.0);
+        assert_eq!(session.accuracy(), 90.0);
+
+        // Nothing typed
+        let session = sample_session(0, 0, 0, 0.0);
+        assert_eq!(session.wpm(), 0.0);
+        assert_eq!(session.accuracy(), 100.0);
+        assert_eq!((session.gross_wpm(), session.net_wpm(), session.kph()), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_session_standard_report() {
+        // 330 key presses in 2 minutes - 300 characters typed, 30 of them wrong (10 corrected)
+        let mut session = sample_session(0, 330, 30, 120.0);
+        session.correct = 270;
+        session.corrected = 10;
+        assert_eq!(session.gross_wpm(), 30.0);
+        assert_eq!(session.net_wpm(), 20.0);
+        assert_eq!(session.kph(), 9900.0);
+
+        // Only the characters left mistyped count - not a position mistyped twice before it was fixed
+        session.uncorrected = Some(0);
+        assert_eq!(session.net_wpm(), 30.0);
+        session.uncorrected = None;
+
+        // Never below 0
+        session.corrected = 0;
+        session.errors = 1000;
+        assert_eq!(session.net_wpm(), 0.0);
+    }
+
+    #[test]
+    fn test_session_tracker_fast_keys() {
+        // Key presses a human could make
+        let mut tracker = SessionTracker::new();
+        let mut now = Instant::now();
+        for _ in 0..FAST_KEYS_RUN * 2 {
+            now += MIN_KEY_INTERVAL;
+            tracker.on_key_press(now);
+        }
+        assert!(tracker.flags.is_empty());
+
+        // A run of key presses injected at once
+        for _ in 0..FAST_KEYS_RUN {
+            tracker.on_key_press(now);
+        }
+        let session = tracker.finish("Ascii").unwrap();
+        assert_eq!(session.flags, vec![IntegrityFlag::FastKeys]);
+        assert_eq!(session.flags_description(), "inhumanly fast keys");
+
+        // Flagged sessions aren't the best
+        let typed = sample_session(0, 300, 0, 60.0);
+        assert_eq!(totals(&[&typed, &session]).best_wpm, 60.0);
+    }
+
+    #[test]
+    fn test_session_tracker_finish() {
+        let mut tracker = SessionTracker::new();
+
+        // --- Nothing typed - nothing to record ---
+        assert!(tracker.finish("Ascii").is_none());
+
+        // --- Too few key presses ---
+        tracker.on_key_press(Instant::now());
+        tracker.on_correct("a");
+        assert!(tracker.finish("Ascii").is_none());
+        assert_eq!(tracker.keystrokes, 0); // Reset after finishing
+
+        // --- A recorded session ---
+        for _ in 0..12 {
+            tracker.on_key_press(Instant::now());
+            tracker.on_correct("a");
+        }
+        tracker.on_key_press(Instant::now());
+        tracker.on_error("7");
+        tracker.on_corrected();
+        let session = tracker.finish("Words").unwrap();
+        assert_eq!(session.typing_option, "Words");
+        assert_eq!(session.keystrokes, 13);
+        assert_eq!(session.correct, 12);
+        assert_eq!(session.errors, 1);
+        assert_eq!(session.corrected, 1);
+        assert_eq!(session.mistyped_chars.get("7"), Some(&1));
+        assert_eq!(session.class_counts.get("letters"), Some(&ClassCount { correct: 12, errors: 0 }));
+        assert_eq!(session.class_counts.get("digits"), Some(&ClassCount { correct: 0, errors: 1 }));
+        assert!(tracker.timer.is_none());
+    }
+
+    #[test]
+    fn test_char_classes() {
+        let classes: Vec<CharClass> = "aZ7; é".chars().map(CharClass::of).collect();
+        assert_eq!(
+            classes,
+            vec![CharClass::Letters, CharClass::Capitals, CharClass::Digits, CharClass::Symbols, CharClass::Spaces, CharClass::Letters]
+        );
+        assert_eq!(ClassCount { correct: 9, errors: 1 }.accuracy(), 90.0);
+        assert_eq!(ClassCount::default().accuracy(), 100.0);
+    }
+
+    #[test]
+    fn test_summarize_periods() {
+        // Two sessions in one month and one in the next (timestamps at noon UTC,
+        // so the local date is the same in every timezone)
+        let mut first = sample_session(1_704_974_400_000, 300, 30, 60.0); // 2024-01-11
+        first.mistyped_chars.insert("a".to_string(), 20);
+        first.mistyped_chars.insert("b".to_string(), 10);
+        let second = sample_session(1_705_320_000_000, 600, 0, 60.0); // 2024-01-15
+        let mut third = sample_session(1_707_825_600_000, 400, 4, 60.0); // 2024-02-13
+        third.mistyped_chars.insert("a".to_string(), 4);
+        third.note = Some("new keyboard".to_string());
+        let sessions = vec![first, second, third];
+
+        // --- Monthly ---
+        let months = summarize_periods(&sessions, true);
+        assert_eq!(months.len(), 2);
+        assert_eq!(months[0].label, "January 2024");
+        assert_eq!(months[0].sessions, 2);
+        assert_eq!(months[0].total_secs, 120.0);
+        assert_eq!(months[0].average_wpm, 90.0);
+        assert_eq!(months[0].best_wpm, 120.0);
+        assert_eq!(months[0].average_accuracy, 95.0);
+        assert!(months[0].accuracy_change.is_none());
+        assert!(months[0].most_improved.is_empty());
+
+        assert_eq!(months[1].label, "February 2024");
+        assert_eq!(months[1].accuracy_change, Some(4.0));
+        // "a": 20 per 900 key presses before, 4 per 400 now; "b" not mistyped anymore
+        let improved: Vec<&str> = months[1].most_improved.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(improved, vec!["a", "b"]);
+        assert!(months[0].notes.is_empty());
+        assert_eq!(months[1].notes, vec!["Feb 13 (80 WPM): new keyboard"]);
+
+        // --- Weekly ---
+        let weeks = summarize_periods(&sessions, false);
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[0].label, "Week 2, 2024");
+        assert_eq!(weeks[1].label, "Week 3, 2024");
+    }
+
+    #[test]
+    fn test_stats_merge() {
+        let mut local = Stats {
+            sessions: vec![sample_session(1, 100, 0, 10.0), sample_session(3, 100, 0, 10.0)],
+            goal_completed_days: vec!["2024-01-02".to_string()],
+            level_milestones: vec![LevelMilestone { level: 2, reached_at: 200 }],
+        };
+        let other = Stats {
+            sessions: vec![sample_session(2, 50, 0, 10.0), sample_session(3, 100, 0, 10.0)],
+            goal_completed_days: vec!["2024-01-01".to_string(), "2024-01-02".to_string()],
+            level_milestones: vec![LevelMilestone { level: 2, reached_at: 100 }],
+        };
+
+        // Session 3 is in both, only session 2 is new
+        assert_eq!(local.merge(other), 1);
+        let ids: Vec<u64> = local.sessions.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(local.goal_completed_days, vec!["2024-01-01", "2024-01-02"]);
+        assert_eq!(local.lifetime_totals().keystrokes, 250);
+        // The earliest time a level was reached is kept
+        assert_eq!(local.level_milestones.len(), 1);
+        assert_eq!(local.level_milestones[0].reached_at, 100);
+    }
+
+    #[test]
+    fn test_levels() {
+        // --- Level progress ---
+        assert_eq!(level_progress(0), (1, 0, 100));
+        assert_eq!(level_progress(99), (1, 99, 100));
+        assert_eq!(level_progress(100), (2, 0, 200));
+        assert_eq!(level_progress(350), (3, 50, 300));
+
+        // --- XP weighted by accuracy ---
+        assert_eq!(sample_session(1, 100, 0, 60.0).xp(), 110);
+        assert_eq!(sample_session(1, 100, 50, 60.0).xp(), 22); // 50 correct * 0.25 + 10
+
+        // --- Level milestones ---
+        let mut stats = Stats::default();
+        stats.sessions.push(sample_session(1, 80, 0, 60.0));
+        assert_eq!(stats.record_level(1_700_000_000), None);
+        stats.sessions.push(sample_session(2, 100, 0, 60.0));
+        assert_eq!(stats.record_level(1_700_000_000), Some(2));
+        assert_eq!(stats.record_level(1_700_000_000), None);
+        assert_eq!(stats.level_milestones.len(), 1);
+    }
+
+    #[test]
+    fn test_lifetime_totals() {
+        // --- Nothing recorded ---
+        let totals = Stats::default().lifetime_totals();
+        assert_eq!(totals.sessions, 0);
+        assert_eq!(totals.keystrokes, 0);
+        assert!(totals.first_session.is_none());
+
+        // --- Sums over all the sessions ---
+        let stats = Stats {
+            sessions: vec![
+                sample_session(1_700_000_100_000, 300, 10, 60.0),
+                sample_session(1_700_000_000_000, 200, 5, 20.0),
+            ],
+            ..Default::default()
+        };
+        let totals = stats.lifetime_totals();
+        assert_eq!(totals.sessions, 2);
+        assert_eq!(totals.keystrokes, 500);
+        assert_eq!(totals.errors, 15);
+        assert_eq!(totals.secs, 80.0);
+        assert_eq!(totals.best_wpm, 120.0);
+        assert_eq!(totals.first_session.unwrap().timestamp(), 1_700_000_000);
+
+        // --- Only the Text option sessions since the text was started ---
+        let mut stats = stats;
+        stats.sessions[0].typing_option = "Text".to_string();
+        stats.sessions.push(Session { typing_option: "Text".to_string(), ..sample_session(1_600_000_000_000, 100, 0, 10.0) });
+        let totals = stats.text_totals_since(1_700_000_000);
+        assert_eq!(totals.sessions, 1);
+        assert_eq!(totals.keystrokes, 300);
+    }
+
+    #[test]
+    fn test_personal_best() {
+        let mut stats = Stats::default();
+        assert!(!stats.is_personal_best());
+        stats.sessions.push(sample_session(0, 300, 0, 60.0)); // 60 WPM, the first one
+        assert!(!stats.is_personal_best());
+        stats.sessions.push(sample_session(1, 250, 0, 60.0));
+        assert!(!stats.is_personal_best());
+        stats.sessions.push(sample_session(2, 350, 0, 60.0));
+        assert!(stats.is_personal_best());
+
+        // Only the sessions of the same option count, and not the flagged ones
+        stats.sessions.push(Session { typing_option: "ASCII".to_string(), ..sample_session(3, 400, 0, 60.0) });
+        assert!(!stats.is_personal_best());
+        stats.sessions.push(Session { flags: vec![IntegrityFlag::Paste], ..sample_session(4, 500, 0, 60.0) });
+        assert!(!stats.is_personal_best());
+        stats.sessions.push(sample_session(5, 400, 0, 60.0));
+        assert!(stats.is_personal_best());
+    }
+
+    #[test]
+    fn test_top_mistakes() {
+        let mut first = sample_session(0, 10, 2, 1.0);
+        first.mistyped_chars.insert("a".to_string(), 2);
+        let mut second = sample_session(0, 10, 3, 1.0);
+        second.mistyped_chars.insert("a".to_string(), 1);
+        second.mistyped_chars.insert("b".to_string(), 2);
+
+        let mistakes = top_mistakes(&[first, second], 1);
+        assert_eq!(mistakes, vec![("a".to_string(), 3)]);
+    }
+
+    #[test]
+    fn test_problem_words() {
+        let word_count = |typed, mistyped, millis| WordCount { typed, mistyped, millis };
+        let mut first = sample_session(0, 10, 2, 1.0);
+        first.words.insert("their".to_string(), word_count(2, 1, 1200));
+        first.words.insert("the".to_string(), word_count(5, 0, 1000));
+        first.words.insert("rhythm".to_string(), word_count(1, 1, 3000));
+        let mut second = sample_session(0, 10, 3, 1.0);
+        second.words.insert("their".to_string(), word_count(2, 2, 1200));
+        second.words.insert("which".to_string(), word_count(3, 1, 600));
+
+        // Summed up - "rhythm" typed too few times, "the" never mistyped
+        let words = problem_words(&[first.clone(), second.clone()], 2, WordOrder::Mistakes);
+        assert_eq!(words.iter().map(|word| word.word.as_str()).collect::<Vec<_>>(), vec!["their", "which"]);
+        assert_eq!((words[0].typed, words[0].mistyped, words[0].millis), (4, 3, 2400));
+        assert_eq!(words[0].error_rate(), 75.0);
+        assert_eq!(words[0].ms_per_char(), Some(150.0));
+
+        let words = problem_words(&[first, second], 2, WordOrder::Slowest);
+        assert_eq!(words.iter().map(|word| word.word.as_str()).collect::<Vec<_>>(), vec!["their", "the", "which"]);
+    }
+
+    #[test]
+    fn test_recent_accuracy() {
+        let sessions = vec![sample_session(0, 100, 50, 1.0), sample_session(0, 100, 10, 1.0), sample_session(0, 100, 0, 1.0)];
+        assert_eq!(recent_accuracy(&sessions, 4), None);
+        assert_eq!(recent_accuracy(&sessions, 0), None);
+        assert_eq!(recent_accuracy(&sessions, 1), Some(100.0));
+        // 190 correct of 200 typed
+        assert_eq!(recent_accuracy(&sessions, 2), Some(95.0));
+    }
+
+    #[test]
+    fn test_keyboard_metrics() {
+        let on = |keyboard: Option<&str>, keystrokes: usize, errors: usize| Session {
+            keyboard: keyboard.map(String::from),
+            ..sample_session(0, keystrokes, errors, 60.0)
+        };
+        let sessions = vec![on(Some("laptop"), 300, 30), on(None, 100, 0), on(Some("ergo"), 400, 0), on(Some("laptop"), 500, 0)];
+
+        assert_eq!(session_keyboards(&sessions), vec!["ergo", "laptop"]);
+        assert_eq!(
+            keyboard_metrics(&sessions),
+            vec![("ergo".to_string(), 1, 80.0, 100.0), ("laptop".to_string(), 2, 80.0, 95.0)]
+        );
+    }
+
+    #[test]
+    fn test_repeat_attempts() {
+        let attempt = |seed: Option<u64>, typing_option: &str, keystrokes: usize| Session {
+            repeat_seed: seed,
+            typing_option: typing_option.to_string(),
+            ..sample_session(0, keystrokes, 0, 60.0)
+        };
+        let sessions = vec![attempt(Some(7), "Words", 300), attempt(None, "Words", 100), attempt(Some(7), "Ascii", 200), attempt(Some(7), "Words", 400)];
+
+        let attempts = repeat_attempts(&sessions, 7, "Words");
+        assert_eq!(attempts.iter().map(|s| s.keystrokes).collect::<Vec<_>>(), vec![300, 400]);
+        assert!(repeat_attempts(&sessions, 8, "Words").is_empty());
+    }
+
+    #[test]
+    fn test_profile_metrics() {
+        // --- Nothing recorded ---
+        let metrics = profile_metrics(&Stats::default());
+        assert_eq!(metrics.sessions, 0);
+        assert_eq!(metrics.recent_wpm, 0.0);
+        assert!(metrics.wpm_change.is_none());
+
+        // --- Trend over the sessions ---
+        let mut first = sample_session(0, 300, 30, 60.0);
+        first.mistyped_chars.insert("a".to_string(), 30);
+        let stats = Stats {
+            sessions: vec![first, sample_session(0, 400, 0, 60.0)],
+            ..Default::default()
+        };
+        let metrics = profile_metrics(&stats);
+        assert_eq!(metrics.sessions, 2);
+        assert_eq!(metrics.recent_wpm, 80.0);
+        assert_eq!(metrics.wpm_change, Some(20.0));
+        assert_eq!(metrics.accuracy, 95.0);
+        assert_eq!(metrics.top_mistakes, vec![("a".to_string(), 30)]);
+    }
+
+    #[test]
+    fn test_profile_dir() {
+        let config_dir = Path::new("/config");
+        assert_eq!(profile_dir(config_dir, "default"), PathBuf::from("/config"));
+        assert_eq!(profile_dir(config_dir, ""), PathBuf::from("/config"));
+        assert_eq!(profile_dir(config_dir, "kid"), PathBuf::from("/config/profiles/kid"));
+    }
+
+    #[test]
+    fn test_daily_goal() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut stats = Stats::default();
+
+        // --- Practice on a day ---
+        let mut session = sample_session(0, 100, 0, 300.0);
+        session.started_at = Local.from_local_datetime(&day(10).and_hms_opt(12, 0, 0).unwrap()).unwrap().timestamp();
+        stats.sessions.push(session.clone());
+        stats.sessions.push(session);
+        assert_eq!(stats.practiced_on(day(10)), (600.0, 2));
+        assert_eq!(stats.practiced_on(day(11)), (0.0, 0));
+        assert_eq!(stats.days_since_practice(day(10)), Some(0));
+        assert_eq!(stats.days_since_practice(day(13)), Some(3));
+        assert_eq!(Stats::default().days_since_practice(day(13)), None);
+
+        // --- Goals ---
+        assert!(!goal_reached(0, 0, 600.0, 2)); // No goal set
+        assert!(goal_reached(10, 0, 600.0, 2));
+        assert!(!goal_reached(10, 3, 600.0, 2));
+
+        // --- Streak ---
+        assert!(stats.record_goal_completion(day(9)));
+        assert!(stats.record_goal_completion(day(10)));
+        assert!(!stats.record_goal_completion(day(10))); // Already recorded
+        assert_eq!(stats.goal_streak(day(10)), 2);
+        assert_eq!(stats.goal_streak(day(11)), 2); // Today not done yet
+        assert_eq!(stats.goal_streak(day(12)), 0);
+    }
+
+    #[test]
+    fn test_save_and_load_stats() {
+        let dir = tempdir().unwrap();
+
+        // --- Missing file loads empty stats ---
+        let stats = load_stats(dir.path()).unwrap();
+        assert!(stats.sessions.is_empty());
+
+        // --- Saved sessions load back ---
+        let mut session = sample_session(1_700_000_000_000, 100, 5, 30.0);
+        session.mistyped_chars.insert("\"".to_string(), 2);
+        session.class_counts.insert("symbols".to_string(), ClassCount { correct: 8, errors: 2 });
+        let stats = Stats { sessions: vec![session], ..Default::default() };
+        save_stats(&stats, dir.path()).unwrap();
+
+        let loaded = load_stats(dir.path()).unwrap();
+        assert_eq!(loaded.sessions.len(), 1);
+        assert_eq!(loaded.sessions[0].id, 1_700_000_000_000);
+        assert_eq!(loaded.sessions[0].errors, 5);
+        assert_eq!(loaded.sessions[0].mistyped_chars.get("\""), Some(&2));
+        assert_eq!(loaded.sessions[0].class_counts.get("symbols"), Some(&ClassCount { correct: 8, errors: 2 }));
+    }
+}
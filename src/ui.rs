@@ -1,21 +1,79 @@
-use crate::app::{App, CurrentMode, CurrentTypingOption};
+use crate::app::{App, ClipboardLoad, CurrentMode, CurrentTypingOption, Region, SchedulePhase};
 use ratatui::{
     layout::{Alignment, Direction, Flex}, 
     prelude::{Constraint, Layout, Rect}, 
-    style::{Color, Style}, 
+    style::{Color, Modifier, Style}, 
     text::{Line, Span}, 
-    widgets::{Clear, List, ListItem}, 
+    widgets::{Block, Clear, LineGauge, List, ListItem, Paragraph}, 
     Frame
 };
-use crate::utils::{get_sorted_mistakes};
+use std::ops::Range;
+use ttypr::{CharState, TypedLine};
+use ttypr::bigtext::{big_char, BIG_CHAR_HEIGHT, BIG_CHAR_WIDTH};
+use ttypr::checkpoint::TestCheckpoint;
+use ttypr::content::{load_content_info, ContentInfo};
+use ttypr::csv::CsvDrill;
+use ttypr::dictation::Dictation;
+use ttypr::distraction::DISTRACTOR_WORDS;
+use ttypr::stats::{goal_reached, keyboard_metrics, level_progress, profile_metrics, repeat_attempts, totals, CharClass, LifetimeTotals, ProfileMetrics, Session};
+use ttypr::utils::{format_duration, get_sorted_mistakes};
 
 /// Renders the entire user interface based on the application's current state.
 ///
 /// This function acts as a dispatcher, determining which screen to render based on the app's
-/// state flags like `first_boot`, `show_help`, and `show_mistyped`.
+/// state flags like `first_boot`, `show_help`, and `show_mistyped`. The help page is
+/// rendered as a popup over the main user interface.
 pub fn render(frame: &mut Frame, app: &App) {
+    // Rendered first, so the pages and the help popup can be drawn over it
+    render_key_hints(frame, app);
+
     if app.config.first_boot || app.show_help {
-        render_help_screen(frame);
+        render_main_ui(frame, app);
+        render_help_popup(frame, app);
+        return;
+    }
+
+    if let Some(drill) = &app.csv {
+        render_csv_screen(frame, drill);
+        return;
+    }
+
+    if let Some(dictation) = &app.dictation {
+        render_dictation_screen(frame, dictation);
+        return;
+    }
+
+    if let Some(checkpoint) = &app.interrupted_test {
+        render_main_ui(frame, app);
+        render_interrupted_test_prompt(frame, checkpoint);
+        return;
+    }
+
+    if app.show_quit_prompt {
+        render_main_ui(frame, app);
+        render_quit_prompt(frame, app);
+        return;
+    }
+
+    if app.show_resume_prompt {
+        render_main_ui(frame, app);
+        render_resume_prompt(frame, app);
+        return;
+    }
+
+    if let Some(goto_input) = &app.goto_input {
+        render_main_ui(frame, app);
+        render_goto_prompt(frame, goto_input);
+        return;
+    }
+
+    if let Some(totals) = &app.completed_text {
+        render_text_complete_screen(frame, app, totals);
+        return;
+    }
+
+    if let Some(sessions) = &app.playlist_summary {
+        render_playlist_summary_screen(frame, sessions);
         return;
     }
 
@@ -24,36 +82,439 @@ pub fn render(frame: &mut Frame, app: &App) {
         return;
     }
 
+    if app.show_summary {
+        render_summary_screen(frame, app);
+        return;
+    }
+
+    if app.show_profile {
+        render_profile_screen(frame, app);
+        return;
+    }
+
+    if app.show_results {
+        render_results_screen(frame, app);
+        return;
+    }
+
+    if app.show_attempts {
+        render_attempts_screen(frame, app);
+        return;
+    }
+
+    if app.show_scrollback {
+        render_scrollback_screen(frame, app);
+        return;
+    }
+
+    if app.show_word_lists {
+        render_word_lists_screen(frame, app);
+        return;
+    }
+
     render_main_ui(frame, app);
 }
 
 /// Renders the main user interface, including the typing area and notifications.
 fn render_main_ui(frame: &mut Frame, app: &App) {
+    // Height, with spaces between the lines (1 - only the current line displayed)
+    let lines_height = if app.single_line() { 1 } else { (app.lines_shown() * 2 - 1) as u16 };
+    // (Stacked errors) - The row beneath the line being typed has to fit, even below the last line
+    let lines_height = lines_height + app.config.stacked_errors as u16;
+    // (Large type) - Only the line being typed, with large characters
+    let lines_height = if app.config.large_type { BIG_CHAR_HEIGHT as u16 } else { lines_height };
+
+    // (Two columns) - The typed text is displayed in a column of its own, next to the lines
+    let width = if app.config.two_columns { app.line_len * 2 + COLUMN_GAP as usize } else { app.line_len };
+    let width = if app.config.large_type { frame.area().width as usize } else { width };
+
     // Where to display the lines
     let area = center(
         frame.area(), // The area of the entire frame
-        Constraint::Length(app.line_len as u16), // Width depending on set line length
-        Constraint::Length(5), // Height, 5 - because spaces between them
+        Constraint::Length(width as u16), // Width depending on set line length
+        Constraint::Length(lines_height),
     );
 
+    // (Smooth line advance) - Draw the lines lower while they move into place after scrolling
+    let offset = app.line_advance_offset();
+    let typing_area = Rect { y: area.y + offset, ..area }.intersection(frame.area());
+
     render_notifications(frame, app);
-    render_typing_area(frame, app, area);
+    render_typing_area(frame, app, typing_area);
+    render_test_progress(frame, app);
+    render_schedule_phase(frame, app);
+    render_silent_errors(frame, app);
+    render_live_wpm(frame, app);
+    render_metronome(frame, app);
+    render_combo(frame, app, typing_area);
+    render_slow_down(frame, app, typing_area);
+    render_governor_cue(frame, app, typing_area);
+    render_distractor_prompt(frame, app, area);
+    render_book_progress(frame, app, area);
+
+    if let CurrentMode::Menu = app.current_mode {
+        render_daily_goal(frame, app);
+        render_level(frame, app);
+        render_lapse_warning(frame, app);
+        render_update_notice(frame, app);
+    }
 }
 
-/// Renders the help screen, which displays keybindings and instructions.
+/// Renders the progress toward the daily goal at the bottom of the screen.
 ///
-/// This screen is shown on the first boot or when the user explicitly requests it.
-fn render_help_screen(frame: &mut Frame) {
-    let first_boot_message_area = center(
-        frame.area(),
-        Constraint::Length(65),
-        Constraint::Length(32),
+/// Only shown if a daily goal is set in the config.
+fn render_daily_goal(frame: &mut Frame, app: &App) {
+    let (goal_minutes, goal_sessions) = (app.config.daily_goal_minutes, app.config.daily_goal_sessions);
+    if goal_minutes == 0 && goal_sessions == 0 {
+        return;
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let (secs, sessions) = app.stats.practiced_on(today);
+
+    let mut parts = vec![];
+    if goal_minutes > 0 {
+        parts.push(format!("{}/{} min", (secs / 60.0) as usize, goal_minutes));
+    }
+    if goal_sessions > 0 {
+        parts.push(format!("{}/{} sessions", sessions, goal_sessions));
+    }
+    let streak = app.stats.goal_streak(today);
+    if streak > 0 {
+        parts.push(format!("{} day streak", streak));
+    }
+
+    let color = if goal_reached(goal_minutes, goal_sessions, secs, sessions) {
+        Color::Green
+    } else {
+        Color::Indexed(8)
+    };
+
+    let goal_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ]).split(frame.area());
+
+    let goal = Line::from(format!("Daily goal: {}", parts.join(" · ")))
+        .style(Style::new().fg(color))
+        .alignment(Alignment::Center);
+    frame.render_widget(goal, goal_area[1]);
+}
+
+/// Returns the keys relevant to the current mode/page, with what they do.
+pub fn key_hints(app: &App) -> Vec<(&'static str, &'static str)> {
+    if app.demo {
+        vec![("any key", "exit the demo")]
+    } else if app.config.first_boot || app.show_help {
+        vec![("Enter", "close"), ("↑/↓", "scroll")]
+    } else if app.csv.is_some() {
+        vec![("Tab", "next field"), ("Enter", "next row"), ("Esc", "quit the drill")]
+    } else if let Some(dictation) = &app.dictation {
+        if dictation.score.is_some() {
+            vec![("Enter", "next phrase"), ("Esc", "quit the drill")]
+        } else {
+            vec![("Enter", "check"), ("Esc", "quit the drill")]
+        }
+    } else if app.interrupted_test.is_some() {
+        vec![("Enter", "resume"), ("n", "discard")]
+    } else if app.show_quit_prompt && app.unfinished_test.is_some() {
+        vec![("Enter", "finish and save"), ("a", "abandon the test"), ("Esc", "cancel")]
+    } else if app.show_quit_prompt {
+        vec![("Enter", "quit"), ("Esc", "cancel")]
+    } else if app.show_resume_prompt {
+        vec![("Enter", "continue"), ("n", "start over")]
+    } else if app.goto_input.is_some() {
+        vec![("Enter", "go"), ("Esc", "cancel")]
+    } else if app.completed_text.is_some() {
+        if app.text_choices.is_empty() {
+            vec![("Enter", "start over")]
+        } else {
+            vec![("Enter", "pick"), ("↑/↓", "select")]
+        }
+    } else if app.playlist_summary.is_some() || app.show_mistyped {
+        vec![("Enter", "close")]
+    } else if app.show_summary {
+        vec![("Enter", "close"), ("←/→", "browse"), ("m", "weeks/months"), ("k", "keyboard")]
+    } else if app.show_profile {
+        vec![("Enter", "close"), ("u", "switch profile"), ("c", "compare"), ("←/→", "compared profile")]
+    } else if app.note_input.is_some() {
+        vec![("Enter", "save"), ("Esc", "cancel")]
+    } else if app.show_results {
+        vec![("Enter", "close"), ("n", "note")]
+    } else if app.show_attempts {
+        vec![("Enter", "close")]
+    } else if app.show_word_lists {
+        vec![("Enter", "pick"), ("↑/↓", "select"), ("←/→", "tag"), ("Esc", "cancel")]
+    } else if app.show_scrollback {
+        vec![("Enter", "close"), ("↑/↓", "scroll"), ("PgUp/PgDn", "scroll a page")]
+    } else if let CurrentMode::Typing = app.current_mode {
+        if app.distractions.prompt.is_some() {
+            vec![("F1-F4", "red/green/blue/yellow"), ("ESC", "menu"), ("Backspace", "delete")]
+        } else {
+            vec![("ESC", "menu"), ("Backspace", "delete")]
+        }
+    } else {
+        let mut hints = vec![("i", "type"), ("o", "option")];
+        if let CurrentTypingOption::Text = app.current_typing_option {
+            hints.extend([("[/]", "chapter"), ("g", "go to")]);
+        }
+        hints.extend([("s", "summary"), ("p", "profile"), ("h", "help"), ("q", "quit")]);
+        hints
+    }
+}
+
+/// Renders a single-line footer with the keys relevant to the current mode/page.
+///
+/// Turned on with `show_key_hints` in the config (or with `f` in Menu mode).
+fn render_key_hints(frame: &mut Frame, app: &App) {
+    if !app.config.show_key_hints || app.compact || frame.area().height == 0 {
+        return;
+    }
+
+    let hints: Vec<String> = key_hints(app)
+        .iter()
+        .map(|(key, action)| format!("{} {}", key, action))
+        .collect();
+    let footer_area = Rect { y: frame.area().bottom() - 1, height: 1, ..frame.area() };
+    let footer = Line::from(hints.join(" · "))
+        .style(Style::new().fg(Color::Indexed(8)))
+        .alignment(Alignment::Center);
+    frame.render_widget(footer, footer_area);
+}
+
+/// Renders a thin progress bar of the running timed test across the top of the screen.
+fn render_test_progress(frame: &mut Frame, app: &App) {
+    let Some((elapsed, remaining)) = app.test_progress() else {
+        return;
+    };
+
+    let label = if app.test_penalty_secs > 0.0 {
+        format!("{:>3}s (+{:.0}s penalty) ", remaining, app.test_penalty_secs)
+    } else {
+        format!("{:>3}s ", remaining)
+    };
+    let progress_area = Rect { height: 1, ..frame.area() };
+    let progress = LineGauge::default()
+        .ratio(elapsed)
+        .label(label)
+        .filled_style(Style::new().fg(Color::Indexed(10)))
+        .unfilled_style(Style::new().fg(Color::Indexed(8)));
+    frame.render_widget(progress, progress_area);
+}
+
+/// (Practice schedule) - Renders the phase of the schedule and its remaining time, at the top
+/// of the screen (below the timed test progress).
+fn render_schedule_phase(frame: &mut Frame, app: &App) {
+    if app.config.practice_minutes == 0 {
+        return;
+    }
+    let (practice, rest) = app.schedule_lengths();
+    let (text, color) = match app.schedule.remaining(practice, rest) {
+        Some((SchedulePhase::Practice, remaining)) => {
+            (format!("Practice block {} · {} left", app.schedule.block, format_duration(remaining.as_secs_f64().ceil())), Color::Indexed(10))
+        }
+        Some((SchedulePhase::Rest, remaining)) => (format!("Rest · {} left", format_duration(remaining.as_secs_f64().ceil())), Color::Indexed(12)),
+        None => (format!("Practice block {} starts with the first key", app.schedule.block + 1), Color::Indexed(8)),
+    };
+    let phase_area = Rect { y: frame.area().y + 1, height: 1, ..frame.area() };
+    frame.render_widget(Line::from(text).style(Style::new().fg(color)).alignment(Alignment::Center), phase_area);
+}
+
+/// (Silent errors) - Renders the count of the errors of the session in the bottom right corner
+/// (above the key hints), instead of marking them in the lines.
+fn render_silent_errors(frame: &mut Frame, app: &App) {
+    if !app.config.silent_errors || !matches!(app.current_mode, CurrentMode::Typing) {
+        return;
+    }
+    let rows_above_bottom = 1 + (app.config.show_key_hints && !app.compact) as u16;
+    let Some(y) = frame.area().bottom().checked_sub(rows_above_bottom) else {
+        return;
+    };
+    let errors = app.engine.session.errors;
+    let color = if errors > 0 { Color::Indexed(9) } else { Color::Indexed(8) };
+    let indicator_area = Rect { y, height: 1, ..frame.area() };
+    let indicator = Line::from(format!("● {}  ", errors)).style(Style::new().fg(color)).alignment(Alignment::Right);
+    frame.render_widget(indicator, indicator_area);
+}
+
+/// (Live WPM) - Renders the WPM of the last few seconds of typing in the bottom left corner
+/// (above the key hints), dimmed while the typing is paused.
+fn render_live_wpm(frame: &mut Frame, app: &App) {
+    if !app.live_wpm_shown() || !matches!(app.current_mode, CurrentMode::Typing) {
+        return;
+    }
+    let rows_above_bottom = 1 + (app.config.show_key_hints && !app.compact) as u16;
+    let Some(y) = frame.area().bottom().checked_sub(rows_above_bottom) else {
+        return;
+    };
+    let wpm = if app.wpm.wpm > 0 { app.wpm.wpm.to_string() } else { "-".to_string() };
+    let style = if app.wpm.is_measuring() { Style::new() } else { Style::new().fg(Color::Indexed(8)) };
+    let indicator_area = Rect { y, height: 1, ..frame.area() };
+    frame.render_widget(Line::from(format!("  {} wpm", wpm)).style(style), indicator_area);
+}
+
+/// (Metronome) - Renders the dot of the metronome in the top left corner (below the timed test
+/// progress), lit for a moment at every beat.
+fn render_metronome(frame: &mut Frame, app: &App) {
+    if !app.metronome_active() || frame.area().height < 2 || frame.area().width < 3 {
+        return;
+    }
+    let color = if app.metronome.is_lit(app.config.metronome_bpm) { Color::Indexed(12) } else { Color::Indexed(8) };
+    let dot_area = Rect { x: frame.area().x + 2, y: frame.area().y + 1, width: 1, height: 1 };
+    frame.render_widget(Span::styled("●", Style::new().fg(color)), dot_area);
+}
+
+/// Renders the count of consecutive correctly typed characters below the typing area,
+/// highlighted for a moment at the milestones.
+fn render_combo(frame: &mut Frame, app: &App, area: Rect) {
+    if !app.config.show_combo || app.combo.current == 0 {
+        return;
+    }
+
+    // Below the last line, with a row of space between them
+    let combo_area = Rect { y: area.bottom() + 1, height: 1, ..area };
+    if combo_area.bottom() > frame.area().bottom() {
+        return;
+    }
+
+    let combo = if app.combo.at_milestone() {
+        Line::from(format!("{} combo!", app.combo.current))
+            .style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+    } else {
+        Line::from(format!("{} combo", app.combo.current)).style(Style::new().fg(Color::Indexed(8)))
+    };
+    frame.render_widget(combo.alignment(Alignment::Center), combo_area);
+}
+
+/// (Forced pause) - Renders the "slow down" indicator below the typing area, while the input is locked.
+fn render_slow_down(frame: &mut Frame, app: &App, area: Rect) {
+    if !app.slow_down.is_locked() {
+        return;
+    }
+
+    // In place of the combo (which is broken by the errors anyway)
+    let indicator_area = Rect { y: area.bottom() + 1, height: 1, ..area };
+    if indicator_area.bottom() > frame.area().bottom() {
+        return;
+    }
+
+    let indicator = Line::from("Slow down...").style(Style::new().fg(Color::Red).add_modifier(Modifier::BOLD));
+    frame.render_widget(indicator.alignment(Alignment::Center), indicator_area);
+}
+
+/// (Speed governor) - Renders a cue below the lines for a moment, when a key typed faster
+/// than `max_wpm` was ignored.
+fn render_governor_cue(frame: &mut Frame, app: &App, area: Rect) {
+    if !app.governor.shows_cue() || app.slow_down.is_locked() {
+        return;
+    }
+
+    // In place of the combo
+    let cue_area = Rect { y: area.bottom() + 1, height: 1, ..area };
+    if cue_area.bottom() > frame.area().bottom() {
+        return;
+    }
+
+    let cue = Line::from(format!("Too fast - {} WPM max", app.config.max_wpm)).style(Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    frame.render_widget(Clear, cue_area);
+    frame.render_widget(cue.alignment(Alignment::Center), cue_area);
+}
+
+/// (Distractor drill) - Renders the prompt to answer above the lines - a color word, drawn in
+/// a color of its own (answered by the word).
+fn render_distractor_prompt(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(prompt) = app.distractions.prompt else {
+        return;
+    };
+
+    let ink = [Color::Red, Color::Green, Color::Blue, Color::Yellow][prompt.ink];
+    let prompt_area = center(
+        Rect { y: frame.area().y, height: area.y.saturating_sub(frame.area().y), ..frame.area() },
+        Constraint::Length(16),
+        Constraint::Length(3),
     );
+    if prompt_area.height < 3 {
+        return;
+    }
+
+    let word = Paragraph::new(Line::from(DISTRACTOR_WORDS[prompt.word]).style(Style::new().fg(ink).add_modifier(Modifier::BOLD)))
+        .alignment(Alignment::Center)
+        .block(Block::bordered());
+    frame.render_widget(Clear, prompt_area);
+    frame.render_widget(word, prompt_area);
+}
+
+/// Renders a warning in the top left corner when the last practice was `lapse_warning_days`
+/// (or more) days ago.
+fn render_lapse_warning(frame: &mut Frame, app: &App) {
+    if app.config.lapse_warning_days == 0 {
+        return;
+    }
+    let today = chrono::Local::now().date_naive();
+    let Some(days) = app.stats.days_since_practice(today) else {
+        return;
+    };
+    if days < app.config.lapse_warning_days as i64 {
+        return;
+    }
 
-    let first_boot_message = vec![
+    let warning_area = Rect { height: 1, ..frame.area() };
+    let warning = Line::from(format!(" Last practiced {} days ago", days)).style(Style::new().fg(Color::Yellow));
+    frame.render_widget(warning, warning_area);
+}
+
+/// (Update check) - Renders a notice of a newer version at the top of the screen.
+fn render_update_notice(frame: &mut Frame, app: &App) {
+    let Some(version) = &app.available_update else {
+        return;
+    };
+    let notice_area = Rect { height: 1, ..frame.area() };
+    let notice = Line::from(format!("v{} available", version.trim_start_matches('v')))
+        .style(Style::new().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    frame.render_widget(notice, notice_area);
+}
+
+/// Renders the level and the XP earned towards the next one, in the top right corner.
+fn render_level(frame: &mut Frame, app: &App) {
+    let (level, xp, next_level_xp) = level_progress(app.stats.total_xp());
+    let level_area = Rect { height: 1, ..frame.area() };
+    let level = Line::from(format!("Level {} · {}/{} XP ", level, xp, next_level_xp))
+        .style(Style::new().fg(Color::Indexed(8)))
+        .alignment(Alignment::Right);
+    frame.render_widget(level, level_area);
+}
+
+/// Renders the current chapter and the position in the text above the typing area.
+///
+/// Only shown in the Text option, once there is text to type.
+fn render_book_progress(frame: &mut Frame, app: &App, area: Rect) {
+    if !matches!(app.current_typing_option, CurrentTypingOption::Text) || app.text.is_empty() {
+        return;
+    }
+
+    // Two rows above the first line
+    if area.y < 2 {
+        return;
+    }
+    let progress_area = Rect { y: area.y - 2, height: 1, ..area };
+
+    let (chapter, percent) = app.book_progress();
+    let progress = Line::from(format!("Chapter {} — {}%", chapter, percent))
+        .style(Style::new().fg(Color::Indexed(8)))
+        .alignment(Alignment::Center);
+    frame.render_widget(progress, progress_area);
+}
+
+/// Returns the lines of the help page, which displays keybindings and instructions.
+pub fn help_lines() -> Vec<Line<'static>> {
+    vec![
         Line::from("The application starts in the Menu mode.").alignment(Alignment::Center),
         Line::from(""),
-        Line::from("For larger font - increase the terminal font size.").alignment(Alignment::Center),
+        Line::from("For larger font - press L (large type), or increase the terminal font size.").alignment(Alignment::Center),
         Line::from(""),
         Line::from(""),
         Line::from("Menu mode:").alignment(Alignment::Center),
@@ -63,68 +524,758 @@ fn render_help_screen(frame: &mut Frame) {
         Line::from("            i - switch to Typing mode"),
         Line::from("            o - switch Typing option (ASCII, Words, Text)"),
         Line::from("            n - toggle notifications"),
+        Line::from("            f - toggle the key-hint footer"),
+        Line::from("            d - toggle focus mode (dim other lines)"),
+        Line::from("            l - toggle single-line mode"),
+        Line::from("            L - toggle large type (low vision)"),
+        Line::from("            t - switch the timed test duration"),
         Line::from("            c - toggle counting mistyped characters"),
         Line::from("            w - display top mistyped characters"),
         Line::from("            r - clear mistyped characters count"),
         Line::from("            a - toggle displaying WPM"),
+        Line::from("            [ / ] - previous/next chapter (Text option)"),
+        Line::from("            g - go to a position in the text (Text option)"),
+        Line::from("            b - ban the word at the cursor (Words option)"),
+        Line::from("            * - star the word at the cursor (Words option)"),
+        Line::from("            v - practice the clipboard contents"),
+        Line::from("            s - display weekly/monthly summary"),
+        Line::from("            p - display lifetime stats (profile)"),
+        Line::from("            K - switch the keyboard sessions are tagged with"),
+        Line::from("            R - display the results of the last session"),
+        Line::from("            y - toggle repeat mode (the same material every time)"),
+        Line::from("            Y - display the attempts of repeat mode"),
+        Line::from("            e - review the typed lines"),
+        Line::from("            W - pick the word list (Words option)"),
+        Line::from("            D - pick a drill (Words option)"),
+        Line::from("            M - dictation - type phrases from memory"),
+        Line::from("            P - type the playlist (set in the config)"),
         Line::from(""),
         Line::from(""),
         Line::from("Typing mode:").alignment(Alignment::Center),
         Line::from(""),
-        Line::from("            ESC - switch to Menu mode"),
-        Line::from("            Character keys - Type the corresponding characters"),
-        Line::from("            Backspace - Remove characters"),
+        Line::from("            ESC - switch to Menu mode"),
+        Line::from("            Character keys - Type the corresponding characters"),
+        Line::from("            Backspace - Remove characters"),
+    ]
+}
+
+/// How far the help popup can be scrolled down in a terminal of the given height.
+pub fn help_max_scroll(frame_height: u16) -> usize {
+    // The borders of the popup take two rows
+    help_lines().len().saturating_sub(frame_height.saturating_sub(2) as usize)
+}
+
+/// Renders the help page in a popup over the current screen.
+///
+/// This popup is shown on the first boot or when the user explicitly requests it.
+/// If the terminal is too short to fit it, the popup can be scrolled.
+fn render_help_popup(frame: &mut Frame, app: &App) {
+    let help_lines = help_lines();
+    let height = (help_lines.len() as u16 + 2).min(frame.area().height);
+    let help_area = center(frame.area(), Constraint::Length(67), Constraint::Length(height));
+
+    let scroll = app.help_scroll.min(help_max_scroll(frame.area().height));
+    let mut hints = vec![
+        Span::from(" "),
+        Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black)),
+        Span::from(" "),
+    ];
+    if help_max_scroll(frame.area().height) > 0 {
+        hints.push(Span::from("↑/↓ scroll "));
+    }
+
+    let block = Block::bordered()
+        .title(Line::from(" Help ").alignment(Alignment::Center))
+        .title_bottom(Line::from(hints).alignment(Alignment::Center));
+    let help = Paragraph::new(help_lines).block(block).scroll((scroll as u16, 0));
+
+    frame.render_widget(Clear, help_area);
+    frame.render_widget(help, help_area);
+}
+
+/// Renders the screen displaying the user's most frequently mistyped characters.
+fn render_mistakes_screen(frame: &mut Frame, app: &App) {
+    let sorted_mistakes = get_sorted_mistakes(&app.config.mistyped_chars);
+    // Limit the display to the top 15 most frequent mistakes.
+    let sorted_mistakes: Vec<(String, usize)> = sorted_mistakes.iter().take(15).map(|(k, v)| (k.to_string(), **v)).collect();
+
+    let mut mistake_lines: Vec<ListItem> = vec![];
+
+    let mistyped_title = vec![
+        ListItem::new(Line::from("Most mistyped characters")),
+        ListItem::new(Line::from("")),
+        ListItem::new(Line::from("")),
+    ];
+    for item in mistyped_title { mistake_lines.push(item) }
+
+    for (mistake, count) in sorted_mistakes {
+        let line = Line::from(format!("{}: {}", mistake, count)).alignment(Alignment::Center);
+        mistake_lines.push(ListItem::new(line));
+    }
+
+    let enter_button = vec![
+        ListItem::new(Line::from("")),
+        ListItem::new(Line::from("")),
+        ListItem::new(Line::from("")),
+        ListItem::new(Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center)),
+    ];
+    for item in enter_button { mistake_lines.push(item) }
+
+    let mistakes_area = center(
+        frame.area(),
+        Constraint::Length(25),
+        Constraint::Length(25),
+    );
+
+    let list = List::new(mistake_lines);
+    frame.render_widget(list, mistakes_area);
+}
+
+/// Renders the weekly/monthly summary page of the recorded sessions.
+///
+/// Shows one period at a time, the latest one first.
+fn render_summary_screen(frame: &mut Frame, app: &App) {
+    let summaries = app.summaries();
+
+    let title = if app.summary_monthly { "Monthly summary" } else { "Weekly summary" };
+    let mut summary_lines = vec![
+        Line::from(title).alignment(Alignment::Center),
+        Line::from(""),
+    ];
+    if let Some(keyboard) = &app.summary_keyboard {
+        summary_lines.push(Line::from(format!("Keyboard: {}", keyboard)).alignment(Alignment::Center));
+        summary_lines.push(Line::from(""));
+    }
+
+    let index = summaries.len().checked_sub(app.summary_offset + 1);
+    match index.map(|index| &summaries[index]) {
+        None => {
+            summary_lines.push(Line::from("No sessions recorded yet").alignment(Alignment::Center));
+        }
+        Some(summary) => {
+            // Arrows showing whether there are older/newer periods to browse to
+            let older = if index > Some(0) { "< " } else { "  " };
+            let newer = if app.summary_offset > 0 { " >" } else { "  " };
+            summary_lines.push(Line::from(format!("{}{}{}", older, summary.label, newer)).alignment(Alignment::Center));
+            summary_lines.push(Line::from(""));
+
+            let accuracy_change = match summary.accuracy_change {
+                Some(change) => format!(" ({:+.1})", change),
+                None => String::new(),
+            };
+            let most_improved = if summary.most_improved.is_empty() {
+                "-".to_string()
+            } else {
+                summary.most_improved.iter().map(|(c, _)| c.as_str()).collect::<Vec<_>>().join(" ")
+            };
+
+            summary_lines.extend(vec![
+                Line::from(format!("Sessions: {}", summary.sessions)),
+                Line::from(format!("Time typed: {}", format_duration(summary.total_secs))),
+                Line::from(format!("Average WPM: {:.0}", summary.average_wpm)),
+                Line::from(format!("Best WPM: {:.0}", summary.best_wpm)),
+                Line::from(format!("Average accuracy: {:.1}%{}", summary.average_accuracy, accuracy_change)),
+                Line::from(format!("Most improved: {}", most_improved)),
+            ]);
+
+            // The notes attached to the sessions, explaining the outliers
+            if !summary.notes.is_empty() {
+                summary_lines.extend([Line::from(""), Line::from("Notes:")]);
+                for note in &summary.notes {
+                    summary_lines.push(Line::from(format!("  {}", note)).style(Style::new().fg(Color::Indexed(8))));
+                }
+            }
+        }
+    }
+
+    summary_lines.extend(vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from("←/→ - browse, m - weeks/months, k - keyboard").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let summary_area = center(
+        frame.area(),
+        Constraint::Length(52),
+        Constraint::Length(summary_lines.len() as u16),
+    );
+
+    let list = List::new(summary_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, summary_area);
+}
+
+/// Renders the results page of the last session - its WPM and accuracy, and the accuracy
+/// broken down by character class (with the share of the misses every class has).
+fn render_results_screen(frame: &mut Frame, app: &App) {
+    let mut results_lines = vec![Line::from("Last session").alignment(Alignment::Center), Line::from(""), Line::from("")];
+
+    match app.stats.sessions.last() {
+        Some(session) => {
+            results_lines.extend([
+                Line::from(format!("Option: {}", session.typing_option)),
+                Line::from(format!("WPM: {:.0}", session.wpm())),
+                Line::from(format!("Accuracy: {:.1}% ({} errors)", session.accuracy(), session.errors)),
+                // The typing test standard figures (for job applications)
+                Line::from(format!("Gross WPM: {:.0}, net WPM: {:.0}", session.gross_wpm(), session.net_wpm())),
+                Line::from(format!("KPH: {:.0}", session.kph())),
+            ]);
+            if session.slips > 0 {
+                results_lines.push(Line::from(format!("Slips: {} (not counted as errors)", session.slips)));
+            }
+            if let Some(report) = &app.distraction_report {
+                let response = report.response_ms.map(|ms| format!(" in {:.0} ms on average", ms)).unwrap_or_default();
+                results_lines.push(Line::from(format!(
+                    "Distractors: {}/{} answered{}, {} missed, {} wrong",
+                    report.acknowledged, report.shown, response, report.missed, report.wrong
+                )));
+                if let (Some(focused), Some(distracted)) = (report.focused_wpm, report.distracted_wpm) {
+                    let drop = report.wpm_drop().map(|drop| format!(" ({:+.0}%)", -drop)).unwrap_or_default();
+                    results_lines.push(Line::from(format!("WPM focused: {:.0}, distracted: {:.0}{}", focused, distracted, drop)));
+                }
+            }
+            if let Some(practice_block) = session.practice_block {
+                results_lines.push(Line::from(format!("Practice block: {}", practice_block.block)));
+            }
+            if session.is_flagged() {
+                results_lines.push(Line::from(format!("Flagged: {}", session.flags_description())).style(Style::new().fg(Color::Red)));
+            }
+            match (&app.note_input, &session.note) {
+                (Some(note_input), _) => results_lines.push(Line::from(vec![
+                    Span::from(format!("Note: {}", note_input)),
+                    Span::styled(" ", Style::new().bg(Color::White)),
+                ])),
+                (None, Some(note)) => results_lines.push(Line::from(format!("Note: {}", note))),
+                (None, None) => {}
+            }
+            results_lines.extend([Line::from(""), Line::from("")]);
+
+            // Sessions recorded before the classes were counted have no breakdown
+            if session.class_counts.is_empty() {
+                results_lines.push(Line::from("No breakdown by character class").style(Style::new().fg(Color::Indexed(8))));
+            } else {
+                results_lines.push(Line::from(format!("{:<10}{:>8}{:>11}{:>10}", "", "Typed", "Accuracy", "Misses")));
+                results_lines.push(Line::from(""));
+                for class in CharClass::ALL {
+                    let Some(count) = session.class_counts.get(class.name()) else {
+                        continue;
+                    };
+                    let misses = if session.errors > 0 { count.errors as f64 * 100.0 / session.errors as f64 } else { 0.0 };
+                    let line = Line::from(format!(
+                        "{:<10}{:>8}{:>10.1}%{:>9.0}%",
+                        class.name(),
+                        count.correct + count.errors,
+                        count.accuracy(),
+                        misses,
+                    ));
+                    // The class with the most misses stands out
+                    let most_missed = session.class_counts.values().all(|other| other.errors <= count.errors);
+                    results_lines.push(if count.errors > 0 && most_missed { line.style(Style::new().fg(Color::Red)) } else { line });
+                }
+            }
+        }
+        None => results_lines.push(Line::from("No sessions recorded yet").style(Style::new().fg(Color::Indexed(8)))),
+    }
+
+    let hint = match (&app.note_input, &app.targeted_drill) {
+        (Some(_), _) => "Enter - save the note, Esc - cancel",
+        (None, Some(drill)) => drill,
+        (None, None) => "n - add a note, g - drill the errors",
+    };
+    results_lines.extend([
+        Line::from(""),
+        Line::from(""),
+        Line::from(hint).style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let results_area = center(
+        frame.area(),
+        Constraint::Length(40),
+        Constraint::Length(results_lines.len() as u16),
+    );
+
+    let list = List::new(results_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, results_area);
+}
+
+/// The most attempts displayed on the attempts page (the latest ones).
+const MAX_ATTEMPTS_SHOWN: usize = 10;
+
+/// The width of the WPM bars on the attempts page, for the best attempt.
+const ATTEMPT_BAR_WIDTH: f64 = 16.0;
+
+/// Renders the attempts page of repeat mode - the WPM and accuracy of every attempt at the
+/// repeated material, to see the improvement on it.
+fn render_attempts_screen(frame: &mut Frame, app: &App) {
+    let mut attempts_lines = vec![Line::from("Repeat attempts").alignment(Alignment::Center), Line::from(""), Line::from("")];
+
+    match app.config.repeat_seed {
+        Some(seed) => {
+            let attempts = repeat_attempts(&app.stats.sessions, seed, app.current_typing_option.name());
+            attempts_lines.push(Line::from(format!("Option: {}", app.current_typing_option.name())));
+            attempts_lines.push(Line::from(""));
+
+            if attempts.is_empty() {
+                attempts_lines.push(Line::from("No attempts yet").style(Style::new().fg(Color::Indexed(8))));
+            }
+
+            let best_wpm = attempts.iter().map(|s| s.wpm()).fold(0.0, f64::max);
+            let first_shown = attempts.len().saturating_sub(MAX_ATTEMPTS_SHOWN);
+            for (i, attempt) in attempts.iter().enumerate().skip(first_shown) {
+                let bar_len = if best_wpm > 0.0 { (attempt.wpm() / best_wpm * ATTEMPT_BAR_WIDTH).round() as usize } else { 0 };
+                attempts_lines.push(Line::from(vec![
+                    Span::from(format!("#{:<4}{:>4.0} WPM{:>7.1}%  ", i + 1, attempt.wpm(), attempt.accuracy())),
+                    Span::styled("█".repeat(bar_len), Style::new().fg(Color::Green)),
+                ]));
+            }
+
+            // The improvement since the first attempt
+            if let (Some(first), Some(last)) = (attempts.first(), attempts.last())
+                && attempts.len() > 1
+            {
+                let wpm_change = last.wpm() - first.wpm();
+                let accuracy_change = last.accuracy() - first.accuracy();
+                let color = if wpm_change >= 0.0 { Color::Green } else { Color::Red };
+                attempts_lines.extend([
+                    Line::from(""),
+                    Line::from(format!("Since the first: {:+.0} WPM, {:+.1}%", wpm_change, accuracy_change)).style(Style::new().fg(color)),
+                ]);
+            }
+        }
+        None => attempts_lines.push(Line::from("Repeat mode is off (y - turn it on)").style(Style::new().fg(Color::Indexed(8)))),
+    }
+
+    attempts_lines.extend([
+        Line::from(""),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let attempts_area = center(
+        frame.area(),
+        Constraint::Length(40),
+        Constraint::Length(attempts_lines.len() as u16),
+    );
+
+    let list = List::new(attempts_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, attempts_area);
+}
+
+/// Renders the data entry drill page (`ttypr csv`) - the field being typed, colored as it's
+/// typed, with its name and row, and the share of the fields typed exactly.
+fn render_csv_screen(frame: &mut Frame, drill: &CsvDrill) {
+    let mut csv_lines = vec![Line::from("Data entry").alignment(Alignment::Center), Line::from(""), Line::from("")];
+
+    match drill.current_field() {
+        Some(field) => {
+            let position = format!("{} - row {} of {}", drill.field_name(), drill.row + 1, drill.rows.len());
+            csv_lines.push(Line::from(position).style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center));
+            csv_lines.push(Line::from(""));
+
+            // The expected characters, colored by the typed ones (the extra typed ones in red)
+            let expected: Vec<char> = field.chars().collect();
+            let typed: Vec<char> = drill.typed.chars().collect();
+            let mut spans: Vec<Span> = vec![];
+            for i in 0..expected.len().max(typed.len()) {
+                let (c, state) = match (expected.get(i), typed.get(i)) {
+                    (Some(&c), Some(&typed)) if typed == c => (c, CharState::Correct),
+                    (Some(&c), Some(_)) => (c, CharState::Incorrect),
+                    (Some(&c), None) => (c, CharState::Untyped),
+                    (None, Some(&typed)) => (typed, CharState::Incorrect),
+                    (None, None) => break,
+                };
+                spans.push(Span::styled(c.to_string(), char_state_style(state)));
+            }
+            // An empty field (with nothing typed over it) still waits for Tab or Enter - mark it as such
+            if spans.is_empty() {
+                spans.push(Span::styled("(empty)", Style::new().fg(Color::Indexed(8))));
+            }
+            csv_lines.push(Line::from(spans).alignment(Alignment::Center));
+            csv_lines.push(Line::from(""));
+
+            let accuracy = match drill.accuracy() {
+                Some(accuracy) => format!("Fields: {} typed, {:.1}% exact", drill.fields_typed, accuracy),
+                None => "Fields: none typed yet".to_string(),
+            };
+            csv_lines.push(Line::from(accuracy).alignment(Alignment::Center));
+        }
+        None => csv_lines.push(Line::from("The file has no rows").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center)),
+    }
+
+    csv_lines.extend([
+        Line::from(""),
+        Line::from(""),
+        Line::from("Tab - next field, Enter - next row, Esc - quit").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+    ]);
+
+    let csv_area = center(
+        frame.area(),
+        Constraint::Length(60),
+        Constraint::Length(csv_lines.len() as u16),
+    );
+
+    let list = List::new(csv_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, csv_area);
+}
+
+/// Renders the dictation drill page - the phrase while it's shown, then only what's typed,
+/// and the score once Enter is pressed (with the phrase revealed).
+fn render_dictation_screen(frame: &mut Frame, dictation: &Dictation) {
+    let grey = Style::new().fg(Color::Indexed(8));
+    let mut dictation_lines = vec![Line::from("Dictation").alignment(Alignment::Center), Line::from(""), Line::from("")];
+
+    if dictation.shows_phrase() {
+        dictation_lines.push(Line::from(dictation.phrase.as_str()).style(Style::new().add_modifier(Modifier::BOLD)).alignment(Alignment::Center));
+    } else {
+        dictation_lines.push(Line::from("(type the phrase from memory)").style(grey).alignment(Alignment::Center));
+    }
+    dictation_lines.push(Line::from(""));
+
+    // The typed phrase, with a cursor until it's checked
+    let typed = match dictation.score {
+        Some(score) if score.errors == 0 => Line::from(dictation.typed.as_str()).style(char_state_style(CharState::Correct)),
+        Some(_) => Line::from(dictation.typed.as_str()).style(char_state_style(CharState::Incorrect)),
+        None => Line::from(vec![Span::from(dictation.typed.as_str()), Span::styled(" ", Style::new().bg(Color::White))]),
+    };
+    dictation_lines.push(typed.alignment(Alignment::Center));
+    dictation_lines.push(Line::from(""));
+
+    match dictation.score {
+        Some(score) => {
+            let result = format!("{} errors - {:.1}% accurate", score.errors, score.accuracy);
+            dictation_lines.push(Line::from(result).alignment(Alignment::Center));
+        }
+        None => dictation_lines.push(Line::from("")),
+    }
+    if let Some(average) = dictation.average_accuracy() {
+        let totals = format!("Phrases: {} ({} exact), {:.1}% accurate on average", dictation.rounds, dictation.exact, average);
+        dictation_lines.push(Line::from(totals).style(grey).alignment(Alignment::Center));
+    }
+
+    let dictation_area = center(
+        frame.area(),
+        Constraint::Length(70),
+        Constraint::Length(dictation_lines.len() as u16),
+    );
+
+    let list = List::new(dictation_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, dictation_area);
+}
+
+/// Renders the word list picker page - words.txt and the named lists in `~/.config/ttypr/words/`.
+fn render_word_lists_screen(frame: &mut Frame, app: &App) {
+    let mut picker_lines = vec![
+        Line::from("Word lists").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(format!("Tag: < {} >", app.word_list_tag.as_deref().unwrap_or("all"))).alignment(Alignment::Center),
+        Line::from(""),
+    ];
+
+    // The accuracy needed to unlock the harder lists
+    if let Some(accuracy) = app.progression_accuracy() {
+        let unlocked = accuracy >= app.config.progression_accuracy;
+        picker_lines.push(
+            Line::from(format!(
+                "Harder lists: {:.1}% / {:.1}% over {} sessions",
+                accuracy,
+                app.config.progression_accuracy,
+                app.config.progression_sessions,
+            ))
+            .style(Style::new().fg(if unlocked { Color::Green } else { Color::Indexed(8) }))
+            .alignment(Alignment::Center),
+        );
+        picker_lines.push(Line::from(""));
+    }
+
+    let shown = app.shown_word_lists();
+    let label = |(name, info): &&(String, ContentInfo)| {
+        if app.word_list_locked(info) { format!("{} (locked)", info.label(name)) } else { info.label(name) }
+    };
+    let names = std::iter::once("words.txt".to_string()).chain(shown.iter().map(label));
+    for (i, name) in names.enumerate() {
+        if i == app.word_list_choice {
+            picker_lines.push(Line::from(Span::styled(format!("> {}", name), Style::new().fg(Color::White))));
+        } else {
+            picker_lines.push(Line::from(format!("  {}", name)).style(Style::new().fg(Color::Indexed(8))));
+        }
+    }
+
+    picker_lines.extend(vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from("↑/↓ - select, ←/→ - tag, Esc - cancel").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let picker_area = center(
+        frame.area(),
+        Constraint::Length(50),
+        Constraint::Length(picker_lines.len() as u16),
+    );
+
+    let list = List::new(picker_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, picker_area);
+}
+
+/// Renders the profile page with the lifetime totals of the typing history.
+fn render_profile_screen(frame: &mut Frame, app: &App) {
+    if let Some((compared_name, compared_stats)) = &app.compared_profile {
+        render_profile_comparison(frame, app, compared_name, &profile_metrics(compared_stats));
+        return;
+    }
+
+    let totals = app.stats.lifetime_totals();
+
+    let accuracy = if totals.keystrokes > 0 {
+        100.0 - totals.errors as f64 * 100.0 / totals.keystrokes as f64
+    } else {
+        100.0
+    };
+    let practicing_since = match totals.first_session {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "-".to_string(),
+    };
+
+    let mut profile_lines = vec![
+        Line::from(format!("Profile: {}", app.config.active_profile)).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(""),
+        Line::from(format!("Practicing since: {}", practicing_since)),
+        Line::from(format!("Sessions: {}", totals.sessions)),
+        Line::from(format!("Time typed: {}", format_duration(totals.secs))),
+        Line::from(format!("Total keystrokes: {}", totals.keystrokes)),
+        Line::from(format!("Total errors: {} ({} corrected)", totals.errors, totals.corrected)),
+        Line::from(format!("Overall accuracy: {:.1}%", accuracy)),
+        Line::from(format!("Best WPM: {:.0}", totals.best_wpm)),
+        Line::from(format!("Level: {} ({} XP)", level_progress(app.stats.total_xp()).0, app.stats.total_xp())),
+    ];
+
+    // The performance on every keyboard the sessions were tagged with, to compare them
+    let keyboards = keyboard_metrics(&app.stats.sessions);
+    if !keyboards.is_empty() {
+        profile_lines.extend([Line::from(""), Line::from("By keyboard:")]);
+        for (keyboard, sessions, average_wpm, average_accuracy) in keyboards {
+            profile_lines.push(Line::from(format!("  {}: {:.0} WPM, {:.1}% ({})", keyboard, average_wpm, average_accuracy, sessions)));
+        }
+    }
+
+    profile_lines.extend([
+        Line::from(""),
+        Line::from(""),
+        Line::from("u - switch profile, c - compare").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let profile_area = center(
+        frame.area(),
+        Constraint::Length(40),
+        Constraint::Length(profile_lines.len() as u16),
+    );
+
+    let list = List::new(profile_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, profile_area);
+}
+
+/// Renders the prompt asking whether to continue the text at the saved position, over the current screen.
+fn render_resume_prompt(frame: &mut Frame, app: &App) {
+    let (_, percent) = app.book_progress();
+    let question = format!("Continue '{}' at {}%?", app.text_name(), percent);
+    let width = (question.chars().count() as u16 + 4).max(34);
+    let prompt_area = center(frame.area(), Constraint::Length(width), Constraint::Length(4));
+
+    let prompt_lines = vec![
+        Line::from(question).alignment(Alignment::Center),
+        Line::from(vec![
+            Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black)),
+            Span::from(" continue  "),
+            Span::styled("<N>", Style::new().bg(Color::White).fg(Color::Black)),
+            Span::from(" new"),
+        ]).alignment(Alignment::Center),
+    ];
+
+    frame.render_widget(Clear, prompt_area);
+    frame.render_widget(Paragraph::new(prompt_lines).block(Block::bordered()), prompt_area);
+}
+
+/// Renders the prompt asking whether to resume the timed test interrupted at the last run.
+fn render_interrupted_test_prompt(frame: &mut Frame, checkpoint: &TestCheckpoint) {
+    let key = |key: &'static str| Span::styled(key, Style::new().bg(Color::White).fg(Color::Black));
+    let question = format!("Resume the timed test? ({}, {}s left)", checkpoint.typing_option, checkpoint.remaining_secs());
+    let width = (question.chars().count() as u16 + 4).max(34);
+    let prompt_area = center(frame.area(), Constraint::Length(width), Constraint::Length(4));
+
+    let prompt_lines = vec![
+        Line::from(question).alignment(Alignment::Center),
+        Line::from(vec![key("<Enter>"), Span::from(" resume  "), key("<N>"), Span::from(" discard")]).alignment(Alignment::Center),
+    ];
+
+    frame.render_widget(Clear, prompt_area);
+    frame.render_widget(Paragraph::new(prompt_lines).block(Block::bordered()), prompt_area);
+}
+
+/// Renders the prompt asking whether to quit (with `confirm_quit`) - and, with a timed test left
+/// unfinished, whether to keep it (finish and save) or abandon it.
+fn render_quit_prompt(frame: &mut Frame, app: &App) {
+    let key = |key: &'static str| Span::styled(key, Style::new().bg(Color::White).fg(Color::Black));
+    let mut prompt_lines = vec![Line::from("Quit ttypr?").alignment(Alignment::Center)];
+    if app.unfinished_test.is_some() {
+        prompt_lines.push(Line::from("The last timed test is unfinished").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center));
+        prompt_lines.push(Line::from(vec![key("<Enter>"), Span::from(" save  "), key("<A>"), Span::from(" abandon")]).alignment(Alignment::Center));
+    } else {
+        prompt_lines.push(Line::from(vec![key("<Enter>"), Span::from(" quit  "), key("<Esc>"), Span::from(" cancel")]).alignment(Alignment::Center));
+    }
+    let prompt_area = center(frame.area(), Constraint::Length(38), Constraint::Length(prompt_lines.len() as u16 + 2));
+
+    frame.render_widget(Clear, prompt_area);
+    frame.render_widget(Paragraph::new(prompt_lines).block(Block::bordered()), prompt_area);
+}
+
+/// Renders the "go to" prompt, for jumping to a position in the text, over the current screen.
+fn render_goto_prompt(frame: &mut Frame, goto_input: &str) {
+    let prompt_area = center(frame.area(), Constraint::Length(34), Constraint::Length(4));
+
+    let prompt_lines = vec![
+        Line::from(format!("{}_", goto_input)).alignment(Alignment::Center),
+        Line::from("37% or a word index").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+    ];
+    let block = Block::bordered().title(Line::from(" Go to ").alignment(Alignment::Center));
+
+    frame.render_widget(Clear, prompt_area);
+    frame.render_widget(Paragraph::new(prompt_lines).block(block), prompt_area);
+}
+
+/// Renders the page shown once the whole text was typed, with the stats of the whole text
+/// and (with `end_of_text = "prompt"`) the texts that can be picked as the next one.
+fn render_text_complete_screen(frame: &mut Frame, app: &App, totals: &LifetimeTotals) {
+    let mut complete_lines = vec![
+        Line::from("You finished the text!").alignment(Alignment::Center),
+        Line::from(""),
         Line::from(""),
+        Line::from(format!("Sessions: {}", totals.sessions)),
+        Line::from(format!("Time typed: {}", format_duration(totals.secs))),
+        Line::from(format!("Average WPM: {:.0}", totals.wpm())),
+        Line::from(format!("Best WPM: {:.0}", totals.best_wpm)),
+        Line::from(format!("Accuracy: {:.1}% ({} of {} errors corrected)", totals.accuracy(), totals.corrected, totals.errors)),
         Line::from(""),
         Line::from(""),
-        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center)
     ];
 
-    let first_boot_message: Vec<_> = first_boot_message
-        .into_iter()
-        .map(ListItem::new)
-        .collect();
+    // The texts to pick the next one from
+    if !app.text_choices.is_empty() {
+        complete_lines.push(Line::from("Pick the next text:"));
+        complete_lines.push(Line::from(""));
+        let names = std::iter::once("Start over".to_string()).chain(app.text_choices.iter().map(|path| {
+            let file_name = path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+            load_content_info(path).label(&file_name)
+        }));
+        for (i, name) in names.enumerate() {
+            if i == app.text_choice {
+                complete_lines.push(Line::from(Span::styled(format!("> {}", name), Style::new().fg(Color::White))));
+            } else {
+                complete_lines.push(Line::from(format!("  {}", name)).style(Style::new().fg(Color::Indexed(8))));
+            }
+        }
+        complete_lines.push(Line::from(""));
+        complete_lines.push(Line::from(""));
+    }
 
-    let first_boot_message = List::new(first_boot_message);
-    frame.render_widget(first_boot_message, first_boot_message_area);
-}
+    complete_lines.push(Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center));
 
-/// Renders the screen displaying the user's most frequently mistyped characters.
-fn render_mistakes_screen(frame: &mut Frame, app: &App) {
-    let sorted_mistakes = get_sorted_mistakes(&app.config.mistyped_chars);
-    // Limit the display to the top 15 most frequent mistakes.
-    let sorted_mistakes: Vec<(String, usize)> = sorted_mistakes.iter().take(15).map(|(k, v)| (k.to_string(), **v)).collect();
+    let complete_area = center(
+        frame.area(),
+        Constraint::Length(50),
+        Constraint::Length(complete_lines.len() as u16),
+    );
 
-    let mut mistake_lines: Vec<ListItem> = vec![];
+    let list = List::new(complete_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, complete_area);
+}
 
-    let mistyped_title = vec![
-        ListItem::new(Line::from("Most mistyped characters")),
-        ListItem::new(Line::from("")),
-        ListItem::new(Line::from("")),
+/// Renders the summary page of a finished playlist - the WPM and accuracy of every segment,
+/// and of all of them together.
+fn render_playlist_summary_screen(frame: &mut Frame, sessions: &[Session]) {
+    let grey = Style::new().fg(Color::Indexed(8));
+    let mut summary_lines = vec![
+        Line::from("Playlist finished!").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(""),
     ];
-    for item in mistyped_title { mistake_lines.push(item) }
-
-    for (mistake, count) in sorted_mistakes {
-        let line = Line::from(format!("{}: {}", mistake, count)).alignment(Alignment::Center);
-        mistake_lines.push(ListItem::new(line));
+    for (i, session) in sessions.iter().enumerate() {
+        summary_lines.push(Line::from(vec![
+            Span::from(format!("{}. {:<6}", i + 1, session.typing_option)),
+            Span::styled(format!("{:>8}", format_duration(session.duration_secs)), grey),
+            Span::from(format!("{:>9.0} WPM", session.wpm())),
+            Span::from(format!("{:>8.1}%", session.accuracy())),
+        ]));
+    }
+    if sessions.is_empty() {
+        summary_lines.push(Line::from("(nothing was typed)").style(grey).alignment(Alignment::Center));
     }
 
-    let enter_button = vec![
-        ListItem::new(Line::from("")),
-        ListItem::new(Line::from("")),
-        ListItem::new(Line::from("")),
-        ListItem::new(Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center)),
+    let combined = totals(&sessions.iter().collect::<Vec<_>>());
+    summary_lines.extend([
+        Line::from(""),
+        Line::from(format!("Time typed: {}", format_duration(combined.secs))),
+        Line::from(format!("WPM: {:.0}", combined.wpm())),
+        Line::from(format!("Accuracy: {:.1}%", combined.accuracy())),
+        Line::from(""),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let summary_area = center(
+        frame.area(),
+        Constraint::Length(40),
+        Constraint::Length(summary_lines.len() as u16),
+    );
+    frame.render_widget(Paragraph::new(summary_lines), summary_area);
+}
+
+/// Renders the key metrics of the active profile and another profile side by side.
+fn render_profile_comparison(frame: &mut Frame, app: &App, compared_name: &str, compared: &ProfileMetrics) {
+    let active = profile_metrics(&app.stats);
+
+    let wpm_change = |metrics: &ProfileMetrics| match metrics.wpm_change {
+        Some(change) => format!("{:+.1}", change),
+        None => "-".to_string(),
+    };
+    let top_mistakes = |metrics: &ProfileMetrics| {
+        let mistakes: Vec<&str> = metrics.top_mistakes.iter().map(|(c, _)| c.as_str()).collect();
+        if mistakes.is_empty() { "-".to_string() } else { mistakes.join(" ") }
+    };
+    let row = |label: &str, left: String, right: String| Line::from(format!("{:<14}{:>12}{:>12}", label, left, right));
+
+    let comparison_lines = vec![
+        Line::from("Profile comparison").alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(""),
+        row("", app.config.active_profile.clone(), format!("< {} >", compared_name)),
+        Line::from(""),
+        row("Sessions", active.sessions.to_string(), compared.sessions.to_string()),
+        row("Recent WPM", format!("{:.0}", active.recent_wpm), format!("{:.0}", compared.recent_wpm)),
+        row("WPM trend", wpm_change(&active), wpm_change(compared)),
+        row("Accuracy", format!("{:.1}%", active.accuracy), format!("{:.1}%", compared.accuracy)),
+        row("Top mistakes", top_mistakes(&active), top_mistakes(compared)),
+        Line::from(""),
+        Line::from(""),
+        Line::from("←/→ - other profile, c - back").style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
     ];
-    for item in enter_button { mistake_lines.push(item) }
 
-    let mistakes_area = center(
+    let comparison_area = center(
         frame.area(),
-        Constraint::Length(25),
-        Constraint::Length(25),
+        Constraint::Length(38),
+        Constraint::Length(comparison_lines.len() as u16),
     );
 
-    let list = List::new(mistake_lines);
-    frame.render_widget(list, mistakes_area);
+    let list = List::new(comparison_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, comparison_area);
 }
 
 /// Renders transient notifications at various positions on the screen.
@@ -191,6 +1342,161 @@ fn render_notifications(frame: &mut Frame, app: &App) {
         frame.render_widget(Line::from("Cleared mistyped characters count").alignment(Alignment::Center), clear_mistyped_notification_area[1]);
     }
 
+    // Clipboard contents loading display
+    if let Some(load) = app.notifications.clipboard
+        && app.config.show_notifications
+    {
+        let clipboard_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let message = match load {
+            ClipboardLoad::Loaded => Line::from("Practicing the clipboard contents"),
+            ClipboardLoad::Empty => Line::from(vec![Span::from("Clipboard is "), Span::styled("empty", Style::new().fg(Color::Red))]),
+            ClipboardLoad::Failed => Line::from(vec![Span::from("Clipboard "), Span::styled("can't be read", Style::new().fg(Color::Red))]),
+        };
+        frame.render_widget(message.alignment(Alignment::Center), clipboard_notification_area[1]);
+    }
+
+    // Daily goal reached display
+    if app.notifications.goal && app.config.show_notifications {
+        let goal_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let goal_reached = Line::from(Span::styled("Daily goal reached!", Style::new().fg(Color::Green)));
+        frame.render_widget(goal_reached.alignment(Alignment::Center), goal_notification_area[1]);
+    }
+
+    // Keyboard the sessions are tagged with display
+    if app.notifications.keyboard && app.config.show_notifications {
+        let keyboard_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let keyboard = match &app.config.active_keyboard {
+            Some(keyboard) => Line::from(vec![Span::from("Keyboard: "), Span::styled(keyboard.clone(), Style::new().fg(Color::Green))]),
+            None => Line::from(vec![Span::from("Keyboard: "), Span::styled("untagged", Style::new().fg(Color::Red))]),
+        };
+        frame.render_widget(keyboard.alignment(Alignment::Center), keyboard_notification_area[1]);
+    }
+
+    // Repeat mode toggled display
+    if app.notifications.repeat && app.config.show_notifications {
+        let repeat_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let repeat = match app.config.repeat_seed {
+            Some(_) => Line::from(vec![Span::from("Repeat mode: "), Span::styled("on", Style::new().fg(Color::Green))]),
+            None => Line::from(vec![Span::from("Repeat mode: "), Span::styled("off", Style::new().fg(Color::Red))]),
+        };
+        frame.render_widget(repeat.alignment(Alignment::Center), repeat_notification_area[1]);
+    }
+
+    // Timed test duration display
+    if app.notifications.timed_test && app.config.show_notifications {
+        let timed_test_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let timed_test = match app.config.test_duration_secs {
+            0 => Line::from(vec![Span::from("Timed test "), Span::styled("off", Style::new().fg(Color::Red))]),
+            secs => Line::from(vec![Span::from("Timed test "), Span::styled(format!("{}s", secs), Style::new().fg(Color::Green))]),
+        };
+        frame.render_widget(timed_test.alignment(Alignment::Center), timed_test_notification_area[1]);
+    }
+
+    // Banned word display
+    if app.notifications.banned && app.config.show_notifications {
+        let banned_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let word = app.config.banned_words.last().map_or("", String::as_str);
+        let banned = Line::from(vec![Span::from("Banned "), Span::styled(format!("'{}'", word), Style::new().fg(Color::Red))]);
+        frame.render_widget(banned.alignment(Alignment::Center), banned_notification_area[1]);
+    }
+
+    // Starred word display
+    if app.notifications.favorite && app.config.show_notifications {
+        let favorite_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let word = app.engine.word_at_cursor().unwrap_or_default();
+        let favorite = if app.config.favorite_words.contains(&word) {
+            Line::from(vec![Span::from("Starred "), Span::styled(format!("'{}'", word), Style::new().fg(Color::Green))])
+        } else {
+            Line::from(format!("Unstarred '{}'", word))
+        };
+        frame.render_widget(favorite.alignment(Alignment::Center), favorite_notification_area[1]);
+    }
+
+    // Level reached display
+    if app.notifications.level_up && app.config.show_notifications {
+        let level_up_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let (level, _, _) = level_progress(app.stats.total_xp());
+        let level_up = Line::from(Span::styled(format!("Level {}!", level), Style::new().fg(Color::Yellow)));
+        frame.render_widget(level_up.alignment(Alignment::Center), level_up_notification_area[1]);
+    }
+
+    // Timed test over display
+    if app.notifications.time_up && app.config.show_notifications {
+        let time_up_notification_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Percentage(65),
+                Constraint::Percentage(10),
+                Constraint::Percentage(25),
+            ]).split(frame.area());
+
+        let time_up = if app.test_penalty_secs > 0.0 {
+            Line::from(vec![
+                Span::from("Time's up! Errors cost "),
+                Span::styled(format!("{:.0}s", app.test_penalty_secs), Style::new().fg(Color::Red)),
+            ])
+        } else {
+            Line::from("Time's up!")
+        }.alignment(Alignment::Center);
+        frame.render_widget(time_up, time_up_notification_area[1]);
+    }
+
     // Mistyped characters count toggle display
     if app.notifications.mistyped && app.config.show_notifications {
         let mistyped_chars_area = Layout::default()
@@ -302,44 +1608,27 @@ fn render_notifications(frame: &mut Frame, app: &App) {
 /// This function handles the display of the character set, user input, and messages for
 /// missing word/text files.
 fn render_typing_area(frame: &mut Frame, app: &App, area: Rect) {
-    // A vector of colored characters
-    let span: Vec<Span> = app.charset.iter().enumerate().map(|(i, c)| {
-        match app.ids[i] {
-            1 => { // Correct
-                Span::styled(c.to_string(), Style::new().fg(Color::Indexed(10)))
-            }
-            2 => { // Incorrect
-                // Render incorrect spaces as underscores for better visibility.
-                let char_to_render = if app.input_chars[i] == " " || c == " " {
-                    "_"
-                } else {
-                    c
-                };
-                Span::styled(char_to_render.to_string(), Style::new().fg(Color::Indexed(9)))
-            }
-            _ => { // Untyped
-                Span::styled(c.to_string(), Style::new().fg(Color::Indexed(8)))
-            }
-        }
-    }).collect();
-
     // Draw the typing area itself
     match app.current_typing_option {
         CurrentTypingOption::Ascii => {
-            render_typing_lines(frame, app, area, span);
+            render_typing_lines(frame, app, area);
         }
         CurrentTypingOption::Words => {
             if app.words.is_empty() {
-                render_file_not_found_message(frame, "Words", "~/.config/ttypr/words.txt", Some("The formatting is just words separated by spaces"));
+                let path = match &app.config.word_list {
+                    Some(name) => format!("~/.config/ttypr/words/{}.txt", name),
+                    None => "~/.config/ttypr/words.txt".to_string(),
+                };
+                render_file_not_found_message(frame, "Words", &path, Some("The formatting is just words separated by spaces"));
             } else {
-                render_typing_lines(frame, app, area, span);
+                render_typing_lines(frame, app, area);
             }
         }
         CurrentTypingOption::Text => {
             if app.text.is_empty() {
                 render_file_not_found_message(frame, "Text", "~/.config/ttypr/text.txt", None);
             } else {
-                render_typing_lines(frame, app, area, span);
+                render_typing_lines(frame, app, area);
             }        
         }
     } 
@@ -389,33 +1678,391 @@ fn render_file_not_found_message(frame: &mut Frame, option_name: &str, file_path
     frame.render_widget(list, area);
 }
 
+/// Returns the color of a character of the given state.
+fn char_state_style(state: CharState) -> Style {
+    match state {
+        CharState::Correct => Style::new().fg(Color::Indexed(10)),
+        CharState::Corrected => Style::new().fg(Color::Indexed(11)),
+        CharState::Incorrect => Style::new().fg(Color::Indexed(9)),
+        CharState::Untyped => Style::new().fg(Color::Indexed(8)),
+    }
+}
+
+/// Returns the character to display for a mistyped one - the expected character, or
+/// the typed one (with `show_typed_chars`).
+///
+/// Incorrect spaces are rendered as underscores for better visibility.
+fn incorrect_char(expected: char, typed: char, show_typed: bool) -> char {
+    match (show_typed, expected, typed) {
+        (true, _, ' ') => '_',
+        (true, _, typed) => typed,
+        (false, ' ', _) | (false, _, ' ') => '_',
+        (false, expected, _) => expected,
+    }
+}
+
+/// Returns the character to display at a position of the charset, and its style.
+fn typing_char_style(app: &App, i: usize, composed_end: usize, flashed: Option<usize>) -> (char, Style) {
+    let c = app.engine.charset[i];
+    // (Stacked errors) - The typed characters are displayed beneath the line instead
+    let show_typed = app.config.show_typed_chars && !app.config.stacked_errors;
+    let incorrect = || incorrect_char(c, app.engine.input_chars[i], show_typed);
+    match app.engine.char_state(i) {
+        // (Silent errors) - The errors are only counted in a corner
+        CharState::Incorrect | CharState::Corrected if app.config.silent_errors => (c, char_state_style(CharState::Correct)),
+        CharState::Incorrect if flashed == Some(i) => (incorrect(), Style::new().bg(Color::Red).fg(Color::White)), // Just mistyped
+        CharState::Incorrect => (incorrect(), char_state_style(CharState::Incorrect)),
+        CharState::Untyped if i < composed_end => (c, Style::new().fg(Color::White).add_modifier(Modifier::UNDERLINED)), // Being composed (IME composition)
+        state => (c, char_state_style(state)),
+    }
+}
+
+/// Builds a typed line out of runs of equally styled characters, colored by their states.
+fn typed_line_spans(line: &TypedLine, show_typed: bool) -> Line<'static> {
+    let mut spans: Vec<Span> = vec![];
+    for ((&c, &typed), &state) in line.chars.iter().zip(&line.typed).zip(&line.states) {
+        let c = if state == CharState::Incorrect { incorrect_char(c, typed, show_typed) } else { c };
+        let style = char_state_style(state);
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line::from(spans)
+}
+
+/// Renders the review page of the lines typed and scrolled away, the latest ones at the bottom.
+///
+/// It's read-only - the page is scrolled back through the older lines.
+fn render_scrollback_screen(frame: &mut Frame, app: &App) {
+    let mut review_lines = vec![
+        Line::from("Typed lines").alignment(Alignment::Center),
+        Line::from(""),
+    ];
+
+    // The rows left for the typed lines, below the title and above the hints
+    let rows = (frame.area().height as usize).saturating_sub(9).max(1);
+    let end = app.scrollback.len().saturating_sub(app.scrollback_offset);
+    let start = end.saturating_sub(rows);
+    if app.scrollback.is_empty() {
+        review_lines.push(Line::from("No lines typed yet").alignment(Alignment::Center));
+    }
+    for line in app.scrollback.range(start..end) {
+        review_lines.push(typed_line_spans(line, app.config.show_typed_chars).alignment(Alignment::Center));
+    }
+
+    let position = if app.scrollback_offset > 0 {
+        format!("{} lines back - ", app.scrollback_offset)
+    } else {
+        String::new()
+    };
+    review_lines.extend(vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from(format!("{}↑/↓ - scroll", position)).style(Style::new().fg(Color::Indexed(8))).alignment(Alignment::Center),
+        Line::from(""),
+        Line::from(Span::styled("<Enter>", Style::new().bg(Color::White).fg(Color::Black))).alignment(Alignment::Center),
+    ]);
+
+    let review_area = center(
+        frame.area(),
+        Constraint::Length(app.line_len.max(36) as u16),
+        Constraint::Length(review_lines.len() as u16),
+    );
+
+    let list = List::new(review_lines.into_iter().map(ListItem::new).collect::<Vec<_>>());
+    frame.render_widget(list, review_area);
+}
+
+/// The styled characters of a displayed line, and the runs of equally styled ones it's drawn with.
+#[derive(Default)]
+struct CachedLine {
+    cells: Vec<(char, Style)>,
+    runs: Vec<(String, Style)>,
+}
+
+/// The styled lines of the last frame, kept between the frames (in `App::span_cache`).
+///
+/// The styles of the characters are compared to the cached ones, and only the lines with
+/// a changed character have their runs rebuilt - typing changes a character or two a key
+/// press, so the other lines are drawn from the cache (the spans borrow its runs, instead of
+/// owning copies of them). It makes a difference on very wide terminals.
+#[derive(Default)]
+pub struct SpanCache {
+    lines: Vec<CachedLine>,
+    pub rebuilt: usize, // The lines rebuilt for the last frame
+}
+
+impl SpanCache {
+    /// Updates the cached line of the given index (of the engine's lines) with its styled characters,
+    /// rebuilding its runs if any of them changed.
+    fn update(&mut self, index: usize, cells: impl Iterator<Item = (char, Style)>) {
+        if self.lines.len() <= index {
+            self.lines.resize_with(index + 1, CachedLine::default);
+        }
+        let line = &mut self.lines[index];
+        let mut changed = false;
+        let mut len = 0;
+        for (position, cell) in cells.enumerate() {
+            match line.cells.get_mut(position) {
+                Some(cached) if *cached == cell => {}
+                Some(cached) => {
+                    *cached = cell;
+                    changed = true;
+                }
+                None => {
+                    line.cells.push(cell);
+                    changed = true;
+                }
+            }
+            len = position + 1;
+        }
+        if line.cells.len() != len {
+            line.cells.truncate(len);
+            changed = true;
+        }
+        if !changed {
+            return;
+        }
+
+        line.runs.clear();
+        for &(c, style) in &line.cells {
+            match line.runs.last_mut() {
+                Some((run, run_style)) if *run_style == style => run.push(c),
+                _ => line.runs.push((c.to_string(), style)),
+            }
+        }
+        self.rebuilt += 1;
+    }
+
+    /// The cached line of the given index, borrowing its runs.
+    fn line(&self, index: usize) -> Line<'_> {
+        let runs = self.lines.get(index).map(|line| line.runs.as_slice()).unwrap_or_default();
+        Line::from(runs.iter().map(|(run, style)| Span::styled(run.as_str(), *style)).collect::<Vec<_>>())
+    }
+}
+
 /// Renders the lines of text for the user to type.
 ///
-/// This function takes the application state, a frame, a rendering area, and a vector of styled
-/// characters (`Span`s). It then splits the characters into three lines and displays them
-/// centered in the provided area.
-pub fn render_typing_lines(frame: &mut Frame, app: &App, area: Rect, span: Vec<Span>) {
-    // Separating vector of all the colored characters into vector of 3 lines, each line_len long
-    // and making them List items, to display as a List widget
-    let mut three_lines = vec![];
-    let mut skip_len = 0;
-    // The UI displays three lines of text at a time.
-    for i in 0..3 {
-        // Use `skip()` and `take()` to create a view into the full character buffer for each line.
-        let line_span: Vec<Span> = span.iter().skip(skip_len).take(app.lines_len[i]).map(|c| {
-            c.clone()
-        }).collect();
-        let line = Line::from(line_span).alignment(Alignment::Center);
-        let item = ListItem::new(line);
-        three_lines.push(item);
+/// Every line is built from runs of equally styled characters (one `Span` per run),
+/// cached between the frames (see `SpanCache`), and the lines are displayed centered
+/// in the provided area.
+pub fn render_typing_lines(frame: &mut Frame, app: &App, area: Rect) {
+    if app.config.large_type {
+        render_large_type_line(frame, app, area);
+        return;
+    }
+    if app.config.two_columns {
+        render_two_column_lines(frame, app, area);
+        return;
+    }
+
+    // The characters of the word being composed (with IME composition) are underlined
+    let composed_end = app.engine.input_chars.len() + app.composition.chars().count();
+
+    // (Error flash) - The mistyped character being highlighted
+    let flashed = app.error_flash.and(app.engine.input_chars.len().checked_sub(1));
+
+    // (Pace caret) - The character the target pace is at
+    let pace = app.pace.position();
+
+    // (Line summary) - The row and the width of the line just typed
+    let mut typed_line_row = None;
+
+    // The line the user is typing (the lines scroll once the one after the history lines is finished)
+    let active_line = app.engine.active_line();
+    let line_ranges: Vec<Range<usize>> = app
+        .engine
+        .lines_len
+        .iter()
+        .scan(0, |line_start, &len| {
+            *line_start += len;
+            Some(*line_start - len..*line_start)
+        })
+        .collect();
+
+    // Update the cached lines with the styles of their characters (unless only the other
+    // regions of the screen changed, see `Damage`)
+    let mut cache = app.span_cache.borrow_mut();
+    cache.rebuilt = 0;
+    let restyle = app.damage.contains(Region::Typing) || cache.lines.len() != line_ranges.len();
+    for (i, range) in line_ranges.iter().enumerate().filter(|_| restyle) {
+        // (Single-line mode) - Display only the current line
+        if app.single_line() && i != active_line {
+            continue;
+        }
+
+        // (Focus mode) - Dim the typed lines, and slightly dim the upcoming ones
+        let focus_style = if !app.config.focus_mode || i == active_line {
+            Style::new()
+        } else if i < active_line {
+            Style::new().add_modifier(Modifier::DIM)
+        } else {
+            Style::new().fg(Color::Indexed(240))
+        };
+
+        cache.update(i, range.clone().map(|position| {
+            let (c, style) = typing_char_style(app, position, composed_end, flashed);
+            let style = style.patch(focus_style);
+            if pace == Some(position) { (c, style.bg(Color::Indexed(238))) } else { (c, style) }
+        }));
+    }
+    cache.lines.truncate(line_ranges.len());
+
+    let mut lines = vec![];
+    // The UI displays the typed lines kept above the current one, and the upcoming lines
+    for (i, range) in line_ranges.into_iter().enumerate() {
+        if app.single_line() && i != active_line {
+            continue;
+        }
+        if i + 1 == active_line {
+            typed_line_row = Some((lines.len() as u16, range.len() as u16));
+        }
+        lines.push(ListItem::new(cache.line(i).alignment(Alignment::Center)));
+
+        // (Stacked errors) - The typed characters of the errors, beneath the expected ones
+        if app.config.stacked_errors && !app.config.silent_errors && i == active_line {
+            let annotation: String = range
+                .map(|position| match app.engine.char_state(position) {
+                    CharState::Incorrect => incorrect_char(' ', app.engine.input_chars[position], true),
+                    _ => ' ',
+                })
+                .collect();
+            let annotation = Line::styled(annotation, Style::new().fg(Color::Indexed(9)));
+            lines.push(ListItem::new(annotation.alignment(Alignment::Center)));
+            continue;
+        }
+        // (Error markers) - Where the errors of the line just typed were, beneath it
+        if app.error_markers.is_some() && i + 1 == active_line {
+            let markers: String = range
+                .map(|position| match app.engine.char_state(position) {
+                    CharState::Incorrect | CharState::Corrected => '^',
+                    _ => ' ',
+                })
+                .collect();
+            let markers = Line::styled(markers, Style::new().fg(Color::Red));
+            lines.push(ListItem::new(markers.alignment(Alignment::Center)));
+            continue;
+        }
         // Add an empty `ListItem` to create visual spacing between the lines.
-        three_lines.push(ListItem::new(""));
-        skip_len += app.lines_len[i];
+        lines.push(ListItem::new(""));
     }
 
     // Make a List widget out of list items and render it in the middle
-    let list = List::new(three_lines);
+    let list = List::new(lines);
     frame.render_widget(list, area);
+
+    // (Line summary) - The speed and the errors of the line just typed, at its right edge
+    if let (Some(summary), Some((row, width))) = (&app.line_summary, typed_line_row) {
+        let text = format!("{:.0}wpm ·{}✗", summary.wpm, summary.errors);
+        let x = area.x + area.width.saturating_sub(width) / 2 + width + 1;
+        let summary_area = Rect::new(x, area.y + row, text.chars().count() as u16, 1).intersection(frame.area());
+        frame.render_widget(Paragraph::new(text).style(Style::new().fg(Color::Indexed(8))), summary_area);
+    }
+}
+
+/// (Large type) - Renders the line being typed with large characters (see `big_char`), colored
+/// as the lines are, for low-vision users. Only as much of the line as fits the width is displayed,
+/// a page at a time.
+fn render_large_type_line(frame: &mut Frame, app: &App, area: Rect) {
+    let composed_end = app.engine.input_chars.len() + app.composition.chars().count();
+    let flashed = app.error_flash.and(app.engine.input_chars.len().checked_sub(1));
+    let cursor = app.engine.cursor();
+
+    let active_line = app.engine.active_line();
+    let Some(&line_len) = app.engine.lines_len.get(active_line) else {
+        return;
+    };
+    let line_start: usize = app.engine.lines_len.iter().take(active_line).sum();
+
+    // The page of the line the cursor is on
+    let fit = (area.width as usize / BIG_CHAR_WIDTH).max(1);
+    let page_start = line_start + cursor.saturating_sub(line_start).min(line_len.saturating_sub(1)) / fit * fit;
+    let page_end = (page_start + fit).min(line_start + line_len);
+
+    let mut rows: Vec<Vec<Span>> = vec![vec![]; BIG_CHAR_HEIGHT];
+    for position in page_start..page_end {
+        let (c, mut style) = typing_char_style(app, position, composed_end, flashed);
+        if position == cursor {
+            style = style.bg(Color::Indexed(238));
+        }
+        for (row, cells) in rows.iter_mut().zip(big_char(c)) {
+            row.push(Span::styled(cells, style));
+        }
+    }
+
+    let lines: Vec<ListItem> = rows.into_iter().map(|row| ListItem::new(Line::from(row).alignment(Alignment::Center))).collect();
+    frame.render_widget(List::new(lines), area);
+}
+
+/// (Two columns) - The columns between the lines and the typed text.
+const COLUMN_GAP: u16 = 4;
+
+/// (Two columns) - Renders the lines in the left column, and what was typed of them in the right
+/// column, on the same rows - instead of coloring the lines, as when transcribing a document.
+///
+/// The lines are only marked with the cursor (and the pace caret); the typed characters
+/// are in red where they don't match the line.
+fn render_two_column_lines(frame: &mut Frame, app: &App, area: Rect) {
+    let [source_area, _, typed_area] = Layout::horizontal([
+        Constraint::Length(app.line_len as u16),
+        Constraint::Length(COLUMN_GAP),
+        Constraint::Length(app.line_len as u16),
+    ])
+    .areas(area);
+
+    let cursor = app.engine.cursor();
+    let pace = app.pace.position();
+    let active_line = app.engine.active_line();
+
+    let mut source_lines = vec![];
+    let mut typed_lines = vec![];
+    let mut line_start = 0;
+    for i in 0..app.engine.lines_len.len() {
+        let line_end = line_start + app.engine.lines_len[i];
+        let range = line_start..line_end;
+        line_start = line_end;
+        if app.single_line() && i != active_line {
+            continue;
+        }
+
+        let line_style = if i == active_line { Style::new() } else { Style::new().fg(Color::Indexed(8)) };
+        let source: Vec<Span> = range
+            .clone()
+            .map(|position| {
+                let style = if position == cursor {
+                    Style::new().add_modifier(Modifier::UNDERLINED)
+                } else if pace == Some(position) {
+                    Style::new().bg(Color::Indexed(238))
+                } else {
+                    line_style
+                };
+                Span::styled(app.engine.charset[position].to_string(), style)
+            })
+            .collect();
+
+        let typed: Vec<Span> = range
+            .filter(|&position| position < cursor)
+            .map(|position| {
+                let typed = app.engine.input_chars[position];
+                match app.engine.char_state(position) {
+                    CharState::Incorrect if !app.config.silent_errors => {
+                        Span::styled(incorrect_char(' ', typed, true).to_string(), char_state_style(CharState::Incorrect))
+                    }
+                    _ => Span::styled(typed.to_string(), line_style),
+                }
+            })
+            .collect();
+
+        source_lines.push(ListItem::new(Line::from(source)));
+        typed_lines.push(ListItem::new(Line::from(typed)));
+        // Empty `ListItem`s for the spacing between the lines
+        source_lines.push(ListItem::new(""));
+        typed_lines.push(ListItem::new(""));
+    }
+
+    frame.render_widget(List::new(source_lines), source_area);
+    frame.render_widget(List::new(typed_lines), typed_area);
 }
 
 /// Helper function to center a layout area
@@ -429,4 +2076,4 @@ pub fn center(area: Rect, horizontal: Constraint, vertical: Constraint) -> Rect
 pub fn draw_on_clear(f: &mut Frame) {
     let area = f.area(); // The area of the entire frame
     f.render_widget(Clear, area);
-}
\ No newline at end of file
+}
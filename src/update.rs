@@ -0,0 +1,68 @@
+/// The crates.io API endpoint of the ttypr crate, listing its latest version.
+#[cfg(feature = "update-check")]
+const CRATE_URL: &str = "https://crates.io/api/v1/crates/ttypr";
+
+/// The version of this build.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Gets the latest released version of ttypr from crates.io.
+///
+/// Uses curl (if it's installed), with a timeout - the network code is only built with
+/// the `update-check` feature (on by default).
+#[cfg(feature = "update-check")]
+pub fn fetch_latest_version() -> std::io::Result<String> {
+    use std::{io, process::Command};
+
+    let user_agent = format!("ttypr/{} ({})", CURRENT_VERSION, env!("CARGO_PKG_REPOSITORY"));
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--max-time", "5", "--user-agent", &user_agent, CRATE_URL])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    parse_latest_version(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "No version in the crates.io response"))
+}
+
+/// Parses the latest stable version out of the crates.io API response of a crate.
+pub fn parse_latest_version(response: &str) -> Option<String> {
+    let response: serde_json::Value = serde_json::from_str(response).ok()?;
+    let krate = response.get("crate")?;
+    let version = krate.get("max_stable_version").filter(|version| !version.is_null()).or_else(|| krate.get("max_version"))?;
+    Some(version.as_str()?.to_string())
+}
+
+/// Whether a version is newer than the current one - by its numbers (`0.10.0` is newer than
+/// `0.9.2`); a version that isn't only numbers isn't newer.
+pub fn is_newer(version: &str, current: &str) -> bool {
+    let numbers = |version: &str| -> Option<Vec<u64>> { version.trim_start_matches('v').split('.').map(|n| n.parse().ok()).collect() };
+    match (numbers(version), numbers(current)) {
+        (Some(version), Some(current)) => version > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_latest_version() {
+        let response = r#"{"crate":{"name":"ttypr","max_version":"0.4.0-beta.1","max_stable_version":"0.3.7"}}"#;
+        assert_eq!(parse_latest_version(response), Some("0.3.7".to_string()));
+        let response = r#"{"crate":{"max_version":"0.1.0","max_stable_version":null}}"#;
+        assert_eq!(parse_latest_version(response), Some("0.1.0".to_string()));
+        assert_eq!(parse_latest_version(r#"{"errors":[{"detail":"Not Found"}]}"#), None);
+        assert_eq!(parse_latest_version("<html>"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.3.7", "0.3.6"));
+        assert!(is_newer("0.10.0", "0.9.2"));
+        assert!(is_newer("v1.0.0", "0.3.6"));
+        assert!(!is_newer("0.3.6", "0.3.6"));
+        assert!(!is_newer("0.3.5", "0.3.6"));
+        assert!(!is_newer("0.4.0-beta.1", "0.3.6"));
+    }
+}
@@ -1,20 +1,98 @@
-use std::{collections::HashMap, fs, io, path::{Path, PathBuf}};
+use std::{collections::HashMap, fs, io, path::{Path, PathBuf}, process::Command};
 use serde::{ser::SerializeMap, Serialize, Deserialize, Serializer};
 use sha2::{Sha256, Digest};
+use unicode_normalization::UnicodeNormalization;
+use rand::Rng;
 
 /// Config struct to store all config values, is a part of the App struct
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub first_boot: bool,
     pub show_notifications: bool,
     pub show_wpm_notification: bool,
+    pub wpm_window_secs: u64,
+    pub wpm_update_secs: u64,
+    pub live_wpm: bool,
     #[serde(serialize_with = "serialize_sorted_by_value")]
     pub mistyped_chars: HashMap<String, usize>,
     pub save_mistyped: bool,
     pub skip_len: usize,
     pub use_default_word_set: bool,
+    pub word_list: Option<String>,
+    pub address_schemes: Vec<String>,
+    pub address_tlds: Vec<String>,
+    pub number_locale: String,
+    pub number_currencies: Vec<String>,
     pub use_default_text_set: bool,
     pub last_text_txt_hash: Option<Vec<u8>>,
+    pub chapter_positions: Vec<usize>,
+    pub daily_goal_minutes: usize,
+    pub daily_goal_sessions: usize,
+    pub lapse_warning_days: usize,
+    pub progression_accuracy: f64,
+    pub progression_sessions: usize,
+    pub active_profile: String,
+    pub profiles: Vec<String>,
+    pub keyboards: Vec<String>,
+    pub active_keyboard: Option<String>,
+    pub repeat_seed: Option<u64>,
+    pub show_key_hints: bool,
+    pub confirm_quit: bool,
+    pub ascii_charset: String,
+    pub ascii_letter_weight: u32,
+    pub ascii_digit_weight: u32,
+    pub ascii_symbol_weight: u32,
+    pub capital_letters_percent: Option<u32>,
+    pub excluded_chars: String,
+    pub ime_composition: bool,
+    pub focus_mode: bool,
+    pub single_line: bool,
+    pub history_lines: usize,
+    pub preview_lines: usize,
+    pub smooth_line_advance: bool,
+    pub pace_wpm: u32,
+    pub pace_average: bool,
+    pub metronome_bpm: u32,
+    pub test_duration_secs: u64,
+    pub error_penalty_secs: f64,
+    pub forced_pause_secs: f64,
+    pub slip_window_ms: u64,
+    pub max_wpm: u32,
+    pub distractor_secs: u64,
+    pub terminal_title: bool,
+    pub tick_ms: u64,
+    pub max_fps: u32,
+    pub check_updates: bool,
+    pub desktop_notifications: bool,
+    pub idle_split_minutes: u64,
+    pub practice_minutes: u64,
+    pub rest_minutes: u64,
+    pub schedule_bell: bool,
+    pub min_hold_ms: u64,
+    pub bounce_ms: u64,
+    pub show_combo: bool,
+    pub error_flash: bool,
+    pub silent_errors: bool,
+    pub error_markers: bool,
+    pub line_summary: bool,
+    pub dictation_words: usize,
+    pub dictation_reveal_ms: u64,
+    pub show_typed_chars: bool,
+    pub stacked_errors: bool,
+    pub two_columns: bool,
+    pub large_type: bool,
+    pub low_bandwidth: bool,
+    pub end_of_text: String,
+    pub hyphenate: bool,
+    pub normalize_text: bool,
+    pub dumb_quotes: bool,
+    pub text_started_at: Option<i64>,
+    pub text_name: Option<String>,
+    pub banned_words: Vec<String>,
+    pub favorite_words: Vec<String>,
+    pub favorite_boost: u32,
+    pub playlist: Vec<PlaylistSegment>,
 }
 
 impl Default for Config {
@@ -23,12 +101,108 @@ impl Default for Config {
             first_boot: true, 
             show_notifications: true,
             show_wpm_notification: true,
+            wpm_window_secs: 0, // The rolling window the WPM is calculated over while typing (0 - every burst of typing, after a pause)
+            wpm_update_secs: 1, // (Rolling WPM window) - How often the WPM is recalculated
+            live_wpm: false, // Display the WPM in a corner while typing, instead of in a notification
             mistyped_chars: HashMap::new(),
             save_mistyped: true,
             skip_len: 0, // (For the text option) - To save position in the text
             use_default_word_set: false,
+            word_list: None, // The named word list of the Words option (words/<name>.txt), None - words.txt
+            address_schemes: vec!["https".to_string(), "http".to_string(), "ftp".to_string()], // (Address drill) - The URL schemes
+            address_tlds: vec!["com".to_string(), "org".to_string(), "net".to_string(), "io".to_string(), "dev".to_string()], // (Address drill) - The top-level domains
+            number_locale: "en".to_string(), // (Number drill) - How the numbers are written (en - 1,234.56, de - 1.234,56, ch - 1'234.56)
+            number_currencies: vec!["$".to_string(), "€".to_string(), "£".to_string()], // (Number drill) - The currency symbols of the prices
             use_default_text_set: false,
             last_text_txt_hash: None,
+            chapter_positions: vec![], // (For the text option) - Resume position for every chapter
+            daily_goal_minutes: 0, // 0 - no daily goal for the time typed
+            daily_goal_sessions: 0, // 0 - no daily goal for the number of sessions
+            lapse_warning_days: 2, // The days without practice after which the Menu mode warns about it (0 - never)
+            progression_accuracy: 0.0, // The accuracy needed to pick a harder word list, 0 - no lock
+            progression_sessions: 5, // The number of the last sessions the accuracy is needed over
+            active_profile: "default".to_string(), // The profile the sessions are recorded for
+            profiles: vec![], // Names of the profiles besides the default one
+            keyboards: vec![], // Names of the keyboards the sessions can be tagged with ("laptop", "ergo split")
+            active_keyboard: None, // The keyboard the sessions are tagged with, None - untagged
+            repeat_seed: None, // (Repeat mode) - The seed every attempt's lines are generated from, None - off
+            show_key_hints: false, // The footer with the keys relevant to the current mode/page
+            confirm_quit: false, // Ask before quitting with q (and whether to keep an unfinished timed test)
+            ascii_charset: "latin".to_string(), // The script of the ASCII option (latin, cyrillic, greek)
+            ascii_letter_weight: 0, // (For the ASCII option) - How often letters are generated (all weights 0 - uniformly)
+            ascii_digit_weight: 0, // (For the ASCII option) - How often digits are generated
+            ascii_symbol_weight: 0, // (For the ASCII option) - How often symbols are generated
+            capital_letters_percent: None, // (For the ASCII option) - The share of uppercase letters (0-100), None - as in the script
+            excluded_chars: String::new(), // Characters that are never generated, nor the words with them ("`~")
+            ime_composition: false, // Compare typed words as a whole (for input methods of CJK languages)
+            focus_mode: false, // Dim the lines other than the one being typed
+            single_line: false, // Display only the line being typed
+            history_lines: 1, // The typed lines kept displayed above the line being typed
+            preview_lines: 1, // The upcoming lines displayed below the line being typed (0 - no lookahead)
+            smooth_line_advance: false, // Move the lines into place over a few frames when they scroll
+            pace_wpm: 0, // The speed of the pace caret to race (0 - no pace caret)
+            pace_average: false, // (Pace caret) - Race the average WPM of the recent sessions instead of pace_wpm
+            metronome_bpm: 0, // Pulse a dot in a corner while typing, this many beats a minute (0 - no metronome)
+            test_duration_secs: 0, // 0 - untimed typing, otherwise the duration of the timed test
+            error_penalty_secs: 0.0, // (Timed test) - The time every error uses up (0 - no penalty)
+            forced_pause_secs: 0.0, // How long the input is locked after 3 errors within a second (0 - never)
+            slip_window_ms: 0, // A mistake followed by a correct key within this long is a slip, not an error (0 - off)
+            max_wpm: 0, // (Speed governor) - The keys typed faster than this are ignored (0 - off)
+            distractor_secs: 0, // (Distractor drill) - About how often a prompt to answer is shown while typing (0 - off)
+            terminal_title: false, // The WPM and the time of the session in the terminal title
+            tick_ms: 50, // How often the countdowns and the animations (timed test, pace caret) are updated
+            max_fps: 0, // The most frames drawn a second, the key presses are handled in between (0 - no limit)
+            check_updates: false, // Check crates.io for a newer version at startup (a notice in Menu mode)
+            desktop_notifications: false, // Desktop notifications (OSC 9) when a session is finished
+            idle_split_minutes: 5, // (Untimed typing) - The idle gap after which a key press starts a new session (0 - never)
+            practice_minutes: 0, // (Practice schedule) - The length of the practice blocks (0 - no schedule)
+            rest_minutes: 2, // (Practice schedule) - The length of the rests between the practice blocks
+            schedule_bell: true, // (Practice schedule) - Ring the terminal bell when a practice block or a rest is over
+            min_hold_ms: 0, // How long a key has to be held to be typed, for sticky/slow keys (0 - off)
+            bounce_ms: 0, // How soon a character typed again is ignored, for tremor or key chatter (0 - off)
+            show_combo: false, // Display the count of consecutive correctly typed characters
+            error_flash: false, // Briefly highlight mistyped characters in red
+            silent_errors: false, // Don't mark the errors in the lines, only count them in a corner (for streaming)
+            error_markers: false, // Briefly mark where the errors of a typed line were, beneath it
+            line_summary: false, // Briefly display the WPM and errors of a typed line, next to it
+            dictation_words: 5, // (Dictation) - The words of a phrase to remember (the characters, in the ASCII option)
+            dictation_reveal_ms: 3000, // (Dictation) - How long the phrase is shown before it's hidden
+            show_typed_chars: false, // Display the mistyped characters as they were typed (instead of the expected ones)
+            stacked_errors: false, // Display the typed characters of the errors in a row beneath the line being typed
+            two_columns: false, // Display the lines in a left column and the typed text in a right one, like transcription
+            large_type: false, // (Low vision) - Display only the line being typed, with large characters drawn with blocks
+            low_bandwidth: false, // Redraw as little as possible (for slow SSH connections)
+            end_of_text: "loop".to_string(), // (For the text option) - At the end: loop, stop or prompt (for another text)
+            hyphenate: false, // (For the text option) - Split words that don't fit at the end of a line
+            normalize_text: true, // Compose the characters of the loaded words/text (Unicode NFC)
+            dumb_quotes: false, // Replace curly quotes, dashes and ellipses of the loaded words/text with ASCII
+            text_started_at: None, // (For the text option) - When the text was started (for its completion stats)
+            text_name: None, // (For the text option) - The file the text was picked from, if not text.txt itself
+            banned_words: vec![], // (For the words option) - Words that are never generated
+            favorite_words: vec![], // (For the words option) - Words that are generated more often
+            favorite_boost: 3, // (For the words option) - How many times more often the favorite words are generated
+            playlist: vec![], // (Playlist) - The segments typed back-to-back with P, each in its typing option
+        }
+    }
+}
+
+/// (Playlist) - A segment of the playlist - typed in its typing option until `secs` pass
+/// or `words` are typed (5 characters a word), whichever comes first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlaylistSegment {
+    pub option: String, // "ascii", "words" or "text"
+    #[serde(default)]
+    pub secs: u64, // 0 - not timed
+    #[serde(default)]
+    pub words: usize, // 0 - not limited by the typed words
+}
+
+/// Takes back one count of a mistyped character, removing it once it's at 0.
+pub fn remove_mistake(map: &mut HashMap<String, usize>, c: &str) {
+    if let Some(count) = map.get_mut(c) {
+        *count -= 1;
+        if *count == 0 {
+            map.remove(c);
         }
     }
 }
@@ -97,25 +271,153 @@ pub fn save_config(config: &Config, config_dir: &Path) -> Result<(), Box<dyn std
 }
 
 /// Loads a list of items from a given file in a specified directory.
-fn load_items_from_file(dir: &Path, filename: &str) -> io::Result<Vec<String>> {
+fn load_items_from_file(dir: &Path, filename: &str, config: &Config) -> io::Result<Vec<String>> {
     let file_path = dir.join(filename);
     let content = fs::read_to_string(file_path)?;
-    let items = content
+    Ok(split_items(&content, config))
+}
+
+/// Splits the contents of a words/text file into its words, cleaned up as set in the config.
+pub fn split_items(content: &str, config: &Config) -> Vec<String> {
+    content
         .split_whitespace()
         .filter(|word| word.len() <= 50)
-        .map(String::from)
-        .collect();
-    Ok(items)
+        .map(|word| clean_word(word, config))
+        .collect()
 }
 
-/// Reads the contents of words.txt from a specified directory.
-pub fn read_words_from_file(dir: &Path) -> io::Result<Vec<String>> {
-    load_items_from_file(dir, "words.txt")
+/// Cleans up a loaded word - composes its characters (Unicode NFC, with `normalize_text`),
+/// and replaces the typographic characters that are awkward to type (with `dumb_quotes`).
+pub fn clean_word(word: &str, config: &Config) -> String {
+    let mut word: String = if config.normalize_text { word.nfc().collect() } else { word.to_string() };
+    if config.dumb_quotes {
+        let mut plain = String::with_capacity(word.len());
+        for c in word.chars() {
+            match c {
+                '‘' | '’' | '‚' | '‛' | '′' => plain.push('\''),
+                '“' | '”' | '„' | '‟' | '″' | '«' | '»' => plain.push('"'),
+                '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => plain.push('-'),
+                '…' => plain.push_str("..."),
+                _ => plain.push(c),
+            }
+        }
+        word = plain;
+    }
+    word
+}
+
+/// Reads the contents of the active word list (words.txt, or the named list set
+/// in the config) from a specified directory. Built-in lists aren't read from a file, and the
+/// generated drills are generated with `rng`.
+pub fn read_words_from_file(dir: &Path, config: &Config, rng: &mut impl Rng) -> io::Result<Vec<String>> {
+    if let Some(name) = config.word_list.as_deref()
+        && let Some(words) = crate::wordlist::builtin_word_list(name).or_else(|| crate::drills::generated_drill(rng, name, config))
+    {
+        return Ok(words);
+    }
+    let path = crate::wordlist::word_list_path(dir, config.word_list.as_deref());
+    let content = fs::read_to_string(&path)?;
+    if crate::content::load_content_info(&path).lines {
+        return Ok(crate::wordlist::split_lines(&content).map(|line| clean_word(line, config)).collect());
+    }
+    Ok(split_items(&content, config))
 }
 
 /// Reads the contents of text.txt from a specified directory.
-pub fn read_text_from_file(dir: &Path) -> io::Result<Vec<String>> {
-    load_items_from_file(dir, "text.txt")
+pub fn read_text_from_file(dir: &Path, config: &Config) -> io::Result<Vec<String>> {
+    load_items_from_file(dir, "text.txt", config)
+}
+
+/// Lists the texts (.txt files) in the `texts` subdirectory of a specified directory, sorted by name.
+///
+/// These can be picked as the next text once the current one is finished.
+pub fn list_texts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir.join("texts")) else {
+        return vec![];
+    };
+    let mut texts: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    texts.sort();
+    texts
+}
+
+/// Reads text.txt from a specified directory and detects where its chapters begin.
+pub fn read_text_chapters(dir: &Path) -> io::Result<Vec<usize>> {
+    let content = fs::read_to_string(dir.join("text.txt"))?;
+    Ok(detect_chapters(&content))
+}
+
+/// Finds the word positions at which the chapters of a text begin.
+///
+/// Lines starting with "Chapter" (any case) are treated as chapter headings.
+/// If the text has no headings, paragraphs (separated by blank lines) are used
+/// instead. The returned positions always start with 0.
+pub fn detect_chapters(content: &str) -> Vec<usize> {
+    let mut headings = vec![0];
+    let mut paragraphs = vec![0];
+    let mut word_count = 0;
+    let mut after_blank_line = false;
+
+    for line in content.lines() {
+        let words: Vec<&str> = line
+            .split_whitespace()
+            .filter(|word| word.len() <= 50)
+            .collect();
+
+        if words.is_empty() {
+            after_blank_line = true;
+            continue;
+        }
+
+        if words[0].eq_ignore_ascii_case("chapter") && word_count > 0 {
+            headings.push(word_count);
+        }
+        if after_blank_line && word_count > 0 {
+            paragraphs.push(word_count);
+        }
+        after_blank_line = false;
+        word_count += words.len();
+    }
+
+    if headings.len() > 1 { headings } else { paragraphs }
+}
+
+/// Formats seconds as "1h 02m", "3m 05s" or "42s".
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Reads the contents of the system clipboard.
+///
+/// Uses the first clipboard tool available on the system
+/// (wl-paste, xclip, xsel, pbpaste or PowerShell).
+pub fn read_clipboard() -> io::Result<String> {
+    let commands: &[(&str, &[&str])] = &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+        ("pbpaste", &[]),
+        ("powershell", &["-NoProfile", "-Command", "Get-Clipboard"]),
+    ];
+
+    for (program, args) in commands {
+        if let Ok(output) = Command::new(program).args(*args).output()
+            && output.status.success()
+        {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+
+    Err(io::Error::new(io::ErrorKind::NotFound, "No clipboard tool found"))
 }
 
 /// Just returns the default words set in a vector
@@ -146,6 +448,7 @@ mod tests {
     use tempfile::tempdir;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default, clippy::bool_assert_comparison)]
     fn test_save_and_load_config() {
         // Create a temporary directory to avoid interfering with actual config files.
         let dir = tempdir().unwrap();
@@ -156,6 +459,10 @@ mod tests {
         config_to_save.first_boot = false;
         config_to_save.save_mistyped = false;
         config_to_save.mistyped_chars.insert("a".to_string(), 100);
+        config_to_save.playlist = vec![
+            PlaylistSegment { option: "ascii".to_string(), secs: 120, words: 0 },
+            PlaylistSegment { option: "words".to_string(), secs: 0, words: 25 },
+        ];
 
         // Save the custom config and assert it was successful.
         assert!(save_config(&config_to_save, dir_path).is_ok());
@@ -167,6 +474,7 @@ mod tests {
         assert_eq!(loaded_config.first_boot, false);
         assert_eq!(loaded_config.save_mistyped, false);
         assert_eq!(*loaded_config.mistyped_chars.get("a").unwrap(), 100);
+        assert_eq!(loaded_config.playlist, config_to_save.playlist);
 
         // --- Test loading a config when none exists ---
         // `load_config` should create a default one automatically.
@@ -189,7 +497,7 @@ mod tests {
         let words_content = "hello world from ttypr";
         fs::write(dir_path.join("words.txt"), words_content).unwrap();
         
-        let words = read_words_from_file(dir_path).unwrap();
+        let words = read_words_from_file(dir_path, &Config::default(), &mut rand::rng()).unwrap();
         assert_eq!(words, vec!["hello", "world", "from", "ttypr"]);
 
         // --- Test filtering based on length ---
@@ -198,19 +506,35 @@ mod tests {
         let filter_content = format!("short {} another_short {}", long_word, valid_word);
         fs::write(dir_path.join("filter_test.txt"), filter_content).unwrap();
         
-        let filtered_items = load_items_from_file(dir_path, "filter_test.txt").unwrap();
+        let filtered_items = load_items_from_file(dir_path, "filter_test.txt", &Config::default()).unwrap();
         assert_eq!(filtered_items, vec!["short", "another_short", &valid_word]);
 
         // --- Test reading a standard text.txt file ---
         let text_content = "this is a line of text";
         fs::write(dir_path.join("text.txt"), text_content).unwrap();
 
-        let text = read_text_from_file(dir_path).unwrap();
+        let text = read_text_from_file(dir_path, &Config::default()).unwrap();
         assert_eq!(text, vec!["this", "is", "a", "line", "of", "text"]);
 
         // --- Test error handling for missing files ---
-        assert!(read_words_from_file(dir.path().join("non_existent_dir").as_path()).is_err());
-        assert!(read_text_from_file(dir.path().join("another_fake_dir").as_path()).is_err());
+        assert!(read_words_from_file(dir.path().join("non_existent_dir").as_path(), &Config::default(), &mut rand::rng()).is_err());
+        assert!(read_text_from_file(dir.path().join("another_fake_dir").as_path(), &Config::default()).is_err());
+    }
+
+    #[test]
+    fn test_clean_word() {
+        let mut config = Config::default();
+
+        // Decomposed characters are composed (NFC)
+        assert_eq!(clean_word("cafe\u{301}", &config), "caf\u{e9}");
+        assert_eq!(clean_word("“Well—”", &config), "“Well—”");
+
+        // Typographic characters are replaced with ASCII
+        config.dumb_quotes = true;
+        assert_eq!(clean_word("“Well—it’s…”", &config), "\"Well-it's...\"");
+
+        config.normalize_text = false;
+        assert_eq!(clean_word("cafe\u{301}", &config), "cafe\u{301}");
     }
 
     #[test]
@@ -269,6 +593,28 @@ mod tests {
         assert!(sorted_empty.is_empty());
     }
 
+    #[test]
+    fn test_detect_chapters() {
+        // --- Chapter headings take priority over paragraphs ---
+        let content = "Chapter 1\nIt was a dark night.\n\nThe end.\n\nCHAPTER 2\nMorning came.";
+        assert_eq!(detect_chapters(content), vec![0, 9]);
+
+        // --- Without headings, paragraphs are used ---
+        let content = "one two three\n\nfour five\n\n\nsix";
+        assert_eq!(detect_chapters(content), vec![0, 3, 5]);
+
+        // --- A single block of text is one chapter ---
+        assert_eq!(detect_chapters("just some words"), vec![0]);
+        assert_eq!(detect_chapters(""), vec![0]);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42.4), "42s");
+        assert_eq!(format_duration(185.0), "3m 05s");
+        assert_eq!(format_duration(3720.0), "1h 02m");
+    }
+
     #[test]
     fn test_default_words() {
         let words = default_words();
@@ -0,0 +1,185 @@
+use crate::content::load_content_info;
+use std::{fs, io, path::{Path, PathBuf}, time::SystemTime};
+
+/// Gets the path of a word list - `words.txt`, or a named list in the `words` directory
+/// (`words/<name>.txt`) of the config directory.
+pub fn word_list_path(config_dir: &Path, name: Option<&str>) -> PathBuf {
+    match name {
+        Some(name) => config_dir.join("words").join(format!("{}.txt", name)),
+        None => config_dir.join("words.txt"),
+    }
+}
+
+/// Gets the names of the named word lists (the `.txt` files in the `words` directory), sorted.
+pub fn list_word_lists(config_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(config_dir.join("words")) else {
+        return vec![];
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|path| path.file_stem().map(|name| name.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Reads the words of a word list as they are in the file (a missing file has none).
+///
+/// The items of lists with `lines = true` in their metadata are their lines (see `ContentInfo`).
+pub fn read_word_list(path: &Path) -> io::Result<Vec<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) if load_content_info(path).lines => Ok(split_lines(&content).map(String::from).collect()),
+        Ok(content) => Ok(content.split_whitespace().map(String::from).collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err),
+    }
+}
+
+/// Splits the contents of a list into its non-empty lines, trimmed.
+pub fn split_lines(content: &str) -> impl Iterator<Item = &str> {
+    content.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+/// Writes the words of a word list, one per line.
+///
+/// The words are written to a temporary file first, which then replaces the list,
+/// so the list is never left half-written.
+pub fn write_word_list(path: &Path, words: &[String]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = words.join("\n");
+    content.push('\n');
+
+    let temp_path = path.with_extension("txt.tmp");
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, path)
+}
+
+/// When a word list was last modified (`None` if it doesn't exist).
+pub fn word_list_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Adds the words that aren't in the list yet. Returns how many were added.
+pub fn add_words(words: &mut Vec<String>, new_words: &[String]) -> usize {
+    let len_before = words.len();
+    for word in new_words {
+        if !words.contains(word) {
+            words.push(word.clone());
+        }
+    }
+    words.len() - len_before
+}
+
+/// Removes every occurrence of the given words. Returns how many were removed.
+pub fn remove_words(words: &mut Vec<String>, removed: &[String]) -> usize {
+    let len_before = words.len();
+    words.retain(|word| !removed.contains(word));
+    len_before - words.len()
+}
+
+/// Removes the repeated words (keeping the first occurrence), and sorts the list
+/// alphabetically if `sort` is set. Returns how many words were removed.
+pub fn dedupe_words(words: &mut Vec<String>, sort: bool) -> usize {
+    let len_before = words.len();
+    let mut seen = std::collections::HashSet::new();
+    words.retain(|word| seen.insert(word.clone()));
+    if sort {
+        words.sort();
+    }
+    len_before - words.len()
+}
+
+/// The word lists built into ttypr - the keywords and common identifiers of programming
+/// languages - as their names (set in the config) and display names.
+pub const BUILTIN_WORD_LISTS: [(&str, &str); 4] = [
+    ("builtin:rust", "Rust keywords"),
+    ("builtin:python", "Python keywords"),
+    ("builtin:javascript", "JavaScript keywords"),
+    ("builtin:sql", "SQL keywords"),
+];
+
+/// Returns the words of a built-in word list by its name (`builtin:rust`), `None` for other names.
+pub fn builtin_word_list(name: &str) -> Option<Vec<String>> {
+    let words: &[&str] = match name {
+        "builtin:rust" => RUST_KEYWORDS,
+        "builtin:python" => PYTHON_KEYWORDS,
+        "builtin:javascript" => JAVASCRIPT_KEYWORDS,
+        "builtin:sql" => SQL_KEYWORDS,
+        _ => return None,
+    };
+    Some(words.iter().map(|word| word.to_string()).collect())
+}
+
+const RUST_KEYWORDS: &[&str] = &["as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "Option", "Some", "None", "Result", "Ok", "Err", "Vec", "String", "Box", "Rc", "Arc", "HashMap", "&str", "&mut", "&self", "usize", "u8", "i32", "i64", "f64", "bool", "char", "Default", "Clone", "Debug", "#[derive(Debug)]", "println!()", "format!()", "vec![]", "unwrap()", "clone()", "iter()", "collect()", "into()", "as_str()", "->", "=>", "::", "?;"];
+
+const PYTHON_KEYWORDS: &[&str] = &["False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield", "self", "__init__", "print()", "len()", "range()", "list", "dict", "set", "tuple", "str", "int", "float", "isinstance()", "enumerate()", "zip()", "open()", "append()", "items()", "*args", "**kwargs", "@property", "@staticmethod", "f\"{}\"", "[]", "{}", "->"];
+
+const JAVASCRIPT_KEYWORDS: &[&str] = &["break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do", "else", "export", "extends", "finally", "for", "function", "if", "import", "in", "instanceof", "let", "new", "return", "super", "switch", "this", "throw", "try", "typeof", "var", "void", "while", "yield", "async", "await", "null", "undefined", "true", "false", "console.log()", "document", "window", "=>", "===", "!==", "Promise", "JSON.stringify()", "JSON.parse()", "addEventListener()", "querySelector()", "map()", "filter()", "reduce()", "forEach()", "push()", "Array", "Object.keys()", "require()", "module.exports", "${}", "[]"];
+
+const SQL_KEYWORDS: &[&str] = &["SELECT", "FROM", "WHERE", "INSERT", "INTO", "VALUES", "UPDATE", "SET", "DELETE", "CREATE", "TABLE", "ALTER", "DROP", "INDEX", "VIEW", "JOIN", "LEFT", "RIGHT", "INNER", "OUTER", "ON", "GROUP", "BY", "ORDER", "HAVING", "LIMIT", "OFFSET", "DISTINCT", "AS", "AND", "OR", "NOT", "NULL", "IS", "IN", "BETWEEN", "LIKE", "ASC", "DESC", "COUNT(*)", "SUM()", "AVG()", "MIN()", "MAX()", "PRIMARY", "KEY", "FOREIGN", "REFERENCES", "UNIQUE", "DEFAULT", "VARCHAR(255)", "INTEGER", "BOOLEAN", "TIMESTAMP", "UNION", "CASE", "WHEN", "THEN", "ELSE", "END", "EXISTS", "BEGIN", "COMMIT", "ROLLBACK", "id", "users", "orders", "user_id", "created_at", "=", "<>", ">=", "<=", ";"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn to_words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_word_list() {
+        let mut words = to_words(&["cat", "dog", "cat", "bird"]);
+
+        assert_eq!(add_words(&mut words, &to_words(&["dog", "ant"])), 1);
+        assert_eq!(remove_words(&mut words, &to_words(&["bird", "fish"])), 1);
+        assert_eq!(words, to_words(&["cat", "dog", "cat", "ant"]));
+
+        assert_eq!(dedupe_words(&mut words, false), 1);
+        assert_eq!(words, to_words(&["cat", "dog", "ant"]));
+        assert_eq!(dedupe_words(&mut words, true), 0);
+        assert_eq!(words, to_words(&["ant", "cat", "dog"]));
+    }
+
+    #[test]
+    fn test_builtin_word_lists() {
+        for (name, _) in BUILTIN_WORD_LISTS {
+            let words = builtin_word_list(name).unwrap();
+            assert!(words.len() > 50);
+            assert!(words.iter().all(|word| !word.is_empty() && !word.contains(char::is_whitespace)));
+        }
+        assert!(builtin_word_list("builtin:rust").unwrap().contains(&"impl".to_string()));
+        assert!(builtin_word_list("english").is_none());
+    }
+
+    #[test]
+    fn test_read_and_write_word_list() {
+        let dir = tempdir().unwrap();
+        let path = word_list_path(dir.path(), Some("rust-terms"));
+        assert_eq!(path, dir.path().join("words").join("rust-terms.txt"));
+
+        // A missing list is empty
+        assert!(read_word_list(&path).unwrap().is_empty());
+        assert!(word_list_modified(&path).is_none());
+
+        write_word_list(&path, &to_words(&["fn", "impl"])).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn\nimpl\n");
+        assert_eq!(read_word_list(&path).unwrap(), to_words(&["fn", "impl"]));
+        assert!(word_list_modified(&path).is_some());
+        assert!(!path.with_extension("txt.tmp").exists());
+
+        // The items of the lists with `lines = true` are their lines
+        let commands_path = word_list_path(dir.path(), Some("commands"));
+        write_word_list(&commands_path, &to_words(&["git status", "ls -la"])).unwrap();
+        fs::write(commands_path.with_extension("toml"), "lines = true\n").unwrap();
+        assert_eq!(read_word_list(&commands_path).unwrap(), to_words(&["git status", "ls -la"]));
+        fs::remove_file(&commands_path).unwrap();
+
+        write_word_list(&word_list_path(dir.path(), Some("english")), &to_words(&["the"])).unwrap();
+        fs::write(dir.path().join("words").join("notes.md"), "not a list").unwrap();
+        assert_eq!(list_word_lists(dir.path()), to_words(&["english", "rust-terms"]));
+    }
+}